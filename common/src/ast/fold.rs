@@ -0,0 +1,72 @@
+use crate::ast::types::{BoolOperatorKind, LiteralKind, MathOperatorKind};
+
+/// Follows the instructions of the backend: integers are converted to floats when mixed with them,
+/// and the operations that would trap are left to run
+pub fn fold_math(operation: &MathOperatorKind, left: &LiteralKind, right: &LiteralKind) -> Option<LiteralKind> {
+    match (left, right) {
+        (LiteralKind::Int(left), LiteralKind::Int(right)) => {
+            let result = match operation {
+                MathOperatorKind::Plus => left.wrapping_add(*right),
+                MathOperatorKind::Minus => left.wrapping_sub(*right),
+                MathOperatorKind::Multiply => left.wrapping_mul(*right),
+                MathOperatorKind::Divide => left.checked_div(*right)?,
+                MathOperatorKind::Modulus => left.checked_rem(*right)?,
+                MathOperatorKind::Power => return None,
+            };
+
+            Some(LiteralKind::Int(result))
+        },
+        (left, right) => {
+            let (left, right) = (as_float(left)?, as_float(right)?);
+
+            let result = match operation {
+                MathOperatorKind::Plus => left + right,
+                MathOperatorKind::Minus => left - right,
+                MathOperatorKind::Multiply => left * right,
+                MathOperatorKind::Divide => left / right,
+                MathOperatorKind::Modulus |
+                MathOperatorKind::Power => return None,
+            };
+
+            Some(LiteralKind::Float(result))
+        },
+    }
+}
+
+/// The strings are compared by their address at runtime, so they are never folded
+pub fn fold_compare(operation: &BoolOperatorKind, left: &LiteralKind, right: &LiteralKind) -> Option<LiteralKind> {
+    let result = match (as_int(left), as_int(right)) {
+        (Some(left), Some(right)) => compare(operation, left, right),
+        _ => compare(operation, as_float(left)?, as_float(right)?),
+    };
+
+    Some(LiteralKind::Bool(result))
+}
+
+fn compare<T: PartialOrd>(operation: &BoolOperatorKind, left: T, right: T) -> bool {
+    match operation {
+        BoolOperatorKind::Equal => left == right,
+        BoolOperatorKind::Different => left != right,
+        BoolOperatorKind::Bigger => left > right,
+        BoolOperatorKind::Smaller => left < right,
+        BoolOperatorKind::BiggerEq => left >= right,
+        BoolOperatorKind::SmallerEq => left <= right,
+    }
+}
+
+/// Booleans are compared as the integers holding them
+fn as_int(literal: &LiteralKind) -> Option<i32> {
+    match literal {
+        LiteralKind::Int(i) => Some(*i),
+        LiteralKind::Bool(b) => Some(*b as i32),
+        _ => None,
+    }
+}
+
+fn as_float(literal: &LiteralKind) -> Option<f32> {
+    match literal {
+        LiteralKind::Int(i) => Some(*i as f32),
+        LiteralKind::Float(f) => Some(*f),
+        _ => None,
+    }
+}
@@ -6,6 +6,7 @@ use self::types::{BoolOperatorKind, Function, LiteralKind, MathOperatorKind, Ret
 
 pub mod types;
 pub mod parsing_types;
+pub mod fold;
 
 
 pub enum ElseType {
@@ -22,9 +23,18 @@ pub enum ElseType {
 
 pub type ASTBody = Vec<ASTNode>;
 
+/// Position of a statement inside the source of its module, in characters like the token positions
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
 pub struct ASTNode {
     pub kind: Box<NodeKind>,
     pub eval_type: TypeKind,
+    /// Only the statements of a body have a span
+    pub span: Option<Span>,
 }
 
 impl ASTNode {
@@ -32,6 +42,7 @@ impl ASTNode {
         Self {
             kind: Box::new(kind),
             eval_type,
+            span: None,
         }
     }
 
@@ -41,8 +52,14 @@ impl ASTNode {
                 value: LiteralKind::Nothing,
             }),
             eval_type: TypeKind::Nothing,
+            span: None,
         }
     }
+
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
 }
 
 pub enum NodeKind {
@@ -55,6 +72,7 @@ pub enum NodeKind {
         name: String,
     },
     VariableAsgn {
+        module: ModuleUID,
         name: String,
         value: ASTNode,
     },
@@ -108,6 +126,14 @@ pub enum NodeKind {
     VectorLiteral {
         values: Vec<ASTNode>
     },
+    TupleLiteral {
+        values: ASTBody,
+    },
+    /// `var (<name>, <name>) = <tuple>`, declares a variable for each element of the tuple
+    TupleDecl {
+        names: Vec<String>,
+        value: ASTNode,
+    },
     ObjectLiteral {
         values: Vec<(String, ASTNode)>,
     },
@@ -127,12 +153,22 @@ pub enum NodeKind {
         parameters: ASTBody,
         class_type: Arc<ClassType>,
     },
+    /// `super.<method>(...)`, the method of `class_type` is called on `this` without looking for an override
+    ParentInvok {
+        class_type: Arc<ClassType>,
+        method_name: String,
+        parameters: ASTBody,
+    },
     ConstructEnumVariant {
         value: ASTNode,
         variant_type: TypeKind,
         variant_id: u32,
         enum_type: Arc<EnumType>,
-    }
+    },
+    ResultPropagation {
+        value: ASTNode,
+        result_type: Arc<EnumType>,
+    },
 }
 
 impl NodeKind {
@@ -144,8 +180,8 @@ impl NodeKind {
         NodeKind::VariableRef { module, name }
     }
     
-    pub fn new_variable_asgn(name: String, value: ASTNode) -> NodeKind {
-        NodeKind::VariableAsgn { name, value }
+    pub fn new_variable_asgn(module: ModuleUID, name: String, value: ASTNode) -> NodeKind {
+        NodeKind::VariableAsgn { module, name, value }
     }
     
     pub fn new_function_invok(variable: ASTNode, parameters: ASTBody) -> NodeKind {
@@ -192,6 +228,14 @@ impl NodeKind {
         NodeKind::VectorLiteral { values }
     }
     
+    pub fn new_tuple_literal(values: ASTBody) -> NodeKind {
+        NodeKind::TupleLiteral { values }
+    }
+
+    pub fn new_tuple_decl(names: Vec<String>, value: ASTNode) -> NodeKind {
+        NodeKind::TupleDecl { names, value }
+    }
+
     pub fn new_object_literal(values: Vec<(String, ASTNode)>) -> NodeKind {
         NodeKind::ObjectLiteral { values }
     }
@@ -212,7 +256,15 @@ impl NodeKind {
         NodeKind::ConstructClass { parameters, class_type }
     }
 
+    pub fn new_parent_invok(class_type: Arc<ClassType>, method_name: String, parameters: ASTBody) -> NodeKind {
+        NodeKind::ParentInvok { class_type, method_name, parameters }
+    }
+
     pub fn new_construct_enum_variant(value: ASTNode, variant_type: TypeKind, variant_id: u32, enum_type: Arc<EnumType>) -> NodeKind {
         NodeKind::ConstructEnumVariant { value, variant_type, enum_type, variant_id }
     }
+
+    pub fn new_result_propagation(value: ASTNode, result_type: Arc<EnumType>) -> NodeKind {
+        NodeKind::ResultPropagation { value, result_type }
+    }
 }
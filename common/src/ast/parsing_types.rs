@@ -27,6 +27,8 @@ pub enum ParsableType {
     Nothing,
     Vector(Box<ParsableType>),
     Function(ParsableFunctionType),
+    Result(Box<ParsableType>, Box<ParsableType>),
+    Tuple(Vec<ParsableType>),
     Custom(String),
 }
 
@@ -59,7 +61,13 @@ impl From<&TypeKind> for ParsableType {
                 ))
             }
             TypeKind::Class(class_type) => ParsableType::Custom(class_type.name.to_string()),
-            TypeKind::Enum(enum_type) => ParsableType::Custom(enum_type.name.clone()),
+            TypeKind::Enum(enum_type) => match enum_type.result_types() {
+                Some((ok, err)) => ParsableType::Result(
+                    Box::new(ParsableType::from(&ok)),
+                    Box::new(ParsableType::from(&err))),
+                None => ParsableType::Custom(enum_type.name.clone()),
+            },
+            TypeKind::Tuple(types) => ParsableType::Tuple(types.iter().map(ParsableType::from).collect()),
         }
     }
 }
@@ -1,5 +1,6 @@
 use std::{sync::Arc, fmt::Debug};
 use std::cell::RefCell;
+use crate::constants::{CORE_MODULE_ID, OPERATOR_ADD_METHOD, OPERATOR_CMP_METHOD, OPERATOR_DIV_METHOD, OPERATOR_EQ_METHOD, OPERATOR_MUL_METHOD, OPERATOR_POW_METHOD, OPERATOR_REM_METHOD, OPERATOR_SUB_METHOD, RESULT_ERR_VARIANT, RESULT_OK_VARIANT, RESULT_TYPE_NAME};
 use crate::module::{FunctionDefinition, ModuleUID};
 use crate::tokens::PrimitiveType;
 use super::ASTBody;
@@ -7,32 +8,145 @@ use super::ASTBody;
 #[derive(Clone, Debug, PartialEq)]
 pub enum Attribute {
     Data,
+    Extern,
     Import,
-    Custom(String),
+    /// The feature or member is visible from other modules
+    Public,
+    /// The function of a class or an enum is called on the type, without an object
+    Static,
+    /// `@Name` or `@Name(arg, key = value)` with literal arguments, boxed to keep the tokens small
+    Custom(String, Box<AttributeArgs>),
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AttributeArgs {
+    pub positional: Vec<LiteralKind>,
+    pub named: Vec<(String, LiteralKind)>,
+}
+
+impl AttributeArgs {
+    pub fn is_empty(&self) -> bool {
+        self.positional.is_empty() && self.named.is_empty()
+    }
+
+    pub fn get_named(&self, key: &str) -> Option<&LiteralKind> {
+        self.named
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value)
+    }
 }
 
 impl Attribute {
     pub fn is_custom(&self, value: &str) -> bool {
         match self {
-            Attribute::Custom(v) => v == value,
+            Attribute::Custom(v, _) => v == value,
             _ => false,
         }
     }
+
+    /// Returns the arguments of the custom attribute named `value`
+    pub fn custom_args(&self, value: &str) -> Option<&AttributeArgs> {
+        match self {
+            Attribute::Custom(v, args) if v == value => Some(args.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Returns true if the feature with these attributes is visible from other modules
+pub fn is_public(attributes: &[Attribute]) -> bool {
+    attributes.contains(&Attribute::Public)
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum ClassKind {
     Normal,
     Data,
+    /// An object owned by the host, it has no fields nor methods and is passed around as an `externref`
+    Extern,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct FunctionType(pub Vec<TypeKind>, pub Box<TypeKind>);
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
 pub struct EnumType {
     pub name: String,
+    pub module: ModuleUID,
     pub variants: RefCell<Vec<(String, Box<TypeKind>)>>,
+    /// The methods get the enum value as `this`
+    pub methods: RefCell<Vec<(String, FunctionType)>>,
+    pub static_methods: RefCell<Vec<(String, FunctionType)>>,
+    pub constants: RefCell<Vec<(String, LiteralKind)>>,
+    /// Names of the methods and constants accessible from other modules
+    pub public_members: RefCell<Vec<String>>,
+}
+
+impl EnumType {
+    /// Creates the builtin `Result<T, E>` enum with `Ok` and `Err` as variants, it belongs to the core module
+    pub fn new_result(ok: TypeKind, err: TypeKind) -> Self {
+        Self {
+            name: RESULT_TYPE_NAME.to_string(),
+            module: ModuleUID::from_string(CORE_MODULE_ID.to_string()),
+            variants: RefCell::new(vec![
+                (RESULT_OK_VARIANT.to_string(), Box::new(ok)),
+                (RESULT_ERR_VARIANT.to_string(), Box::new(err)),
+            ]),
+            methods: Default::default(),
+            static_methods: Default::default(),
+            constants: Default::default(),
+            public_members: Default::default(),
+        }
+    }
+
+    pub fn is_public(&self, member: &str) -> bool {
+        self.public_members
+            .borrow()
+            .iter()
+            .any(|name| name == member)
+    }
+
+    pub fn find_method(&self, name: &str) -> Option<FunctionType> {
+        find_member(&self.methods, name)
+    }
+
+    pub fn find_static_method(&self, name: &str) -> Option<FunctionType> {
+        find_member(&self.static_methods, name)
+    }
+
+    pub fn find_constant(&self, name: &str) -> Option<LiteralKind> {
+        find_member(&self.constants, name)
+    }
+
+    /// Returns the `Ok` and `Err` types if this enum is the builtin `Result<T, E>`
+    pub fn result_types(&self) -> Option<(TypeKind, TypeKind)> {
+        if self.name != RESULT_TYPE_NAME {
+            return None
+        }
+
+        let variants = self.variants.borrow();
+        match variants.as_slice() {
+            [(_, ok), (_, err)] => Some((ok.as_ref().clone(), err.as_ref().clone())),
+            _ => None,
+        }
+    }
+
+    pub fn get_variant(&self, name: &str) -> Option<(u32, TypeKind)> {
+        self.variants
+            .borrow()
+            .iter()
+            .enumerate()
+            .find(|(_, (n, _))| n == name)
+            .map(|(i, (_, t))| (i as u32, t.as_ref().clone()))
+    }
+}
+
+// The methods can take or return the enum itself, so only the variants are compared
+impl PartialEq for EnumType {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.module == other.module && self.variants == other.variants
+    }
 }
 
 unsafe impl Send for EnumType {}
@@ -45,6 +159,111 @@ pub struct ClassType {
     pub kind: ClassKind,
     pub fields: RefCell<Vec<(String, TypeKind)>>,
     pub methods: RefCell<Vec<(String, FunctionType)>>,
+    /// The functions called on the class with `<Class>.<name>(...)`, they have no `this`
+    pub static_methods: RefCell<Vec<(String, FunctionType)>>,
+    /// Evaluated when the module is loaded, they are inlined where they are used
+    pub constants: RefCell<Vec<(String, LiteralKind)>>,
+    /// Names of the fields, methods and constants accessible from other modules
+    pub public_members: RefCell<Vec<String>>,
+    /// The class inheriting its fields and methods to this one, the fields and methods
+    /// above only hold the members declared by this class
+    pub parent: RefCell<Option<Arc<ClassType>>>,
+}
+
+impl ClassType {
+    pub fn is_public(&self, member: &str) -> bool {
+        self.public_members
+            .borrow()
+            .iter()
+            .any(|name| name == member)
+    }
+
+    pub fn parent(&self) -> Option<Arc<ClassType>> {
+        self.parent.borrow().clone()
+    }
+
+    /// Returns true if the class is `other` or inherits from it
+    pub fn is_subclass_of(&self, other: &ClassType) -> bool {
+        if self == other {
+            return true
+        }
+
+        match self.parent() {
+            Some(parent) => parent.is_subclass_of(other),
+            None => false,
+        }
+    }
+
+    /// The fields of the parents come first, so that the object of a class is laid out like the ones of its parents
+    pub fn all_fields(&self) -> Vec<(String, TypeKind)> {
+        let mut fields = match self.parent() {
+            Some(parent) => parent.all_fields(),
+            None => Vec::new(),
+        };
+
+        fields.extend(self.fields.borrow().iter().cloned());
+        fields
+    }
+
+    /// Searches a field in the class and then in its parents, returns the class declaring it with its type
+    pub fn find_field(self: &Arc<Self>, name: &str) -> Option<(Arc<ClassType>, TypeKind)> {
+        let field = self.fields
+            .borrow()
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, type_)| type_.clone());
+
+        match field {
+            Some(type_) => Some((self.clone(), type_)),
+            None => self.parent()?.find_field(name),
+        }
+    }
+
+    /// Searches a method in the class and then in its parents, returns the class implementing it with its type
+    pub fn find_method(self: &Arc<Self>, name: &str) -> Option<(Arc<ClassType>, FunctionType)> {
+        let method = self.methods
+            .borrow()
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, func_type)| func_type.clone());
+
+        match method {
+            Some(func_type) => Some((self.clone(), func_type)),
+            None => self.parent()?.find_method(name),
+        }
+    }
+
+    /// Searches a static method in the class and then in its parents, returns the class implementing it with its type
+    pub fn find_static_method(self: &Arc<Self>, name: &str) -> Option<(Arc<ClassType>, FunctionType)> {
+        match find_member(&self.static_methods, name) {
+            Some(func_type) => Some((self.clone(), func_type)),
+            None => self.parent()?.find_static_method(name),
+        }
+    }
+
+    /// Searches a constant in the class and then in its parents, returns the class declaring it with its value
+    pub fn find_constant(self: &Arc<Self>, name: &str) -> Option<(Arc<ClassType>, LiteralKind)> {
+        match find_member(&self.constants, name) {
+            Some(value) => Some((self.clone(), value)),
+            None => self.parent()?.find_constant(name),
+        }
+    }
+
+    /// Returns true if the method is declared by this class, and not only inherited
+    pub fn declares_method(&self, name: &str) -> bool {
+        self.methods
+            .borrow()
+            .iter()
+            .any(|(n, _)| n == name)
+    }
+}
+
+fn find_member<T: Clone>(members: &RefCell<Vec<(String, T)>>, name: &str) -> Option<T> {
+    members
+        .borrow()
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, member)| member.clone())
 }
 
 impl PartialEq for ClassType {
@@ -56,7 +275,7 @@ impl PartialEq for ClassType {
 unsafe impl Send for ClassType {}
 unsafe impl Sync for ClassType {}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum LiteralKind {
     Nothing,
     Int(i32),
@@ -87,6 +306,7 @@ pub enum OperatorKind {
     Dot,
     Colon,
     At,
+    Question,
 }
 
 #[derive(Clone, Debug)]
@@ -99,6 +319,16 @@ pub enum BoolOperatorKind {
     SmallerEq,
 }
 
+impl BoolOperatorKind {
+    /// The method of a class implementing the operator, `!=` uses `eq` too
+    pub fn method_name(&self) -> &'static str {
+        match self {
+            BoolOperatorKind::Equal | BoolOperatorKind::Different => OPERATOR_EQ_METHOD,
+            _ => OPERATOR_CMP_METHOD,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum MathOperatorKind {
     Plus,
@@ -109,6 +339,20 @@ pub enum MathOperatorKind {
     Power,
 }
 
+impl MathOperatorKind {
+    /// The method of a class implementing the operator
+    pub fn method_name(&self) -> &'static str {
+        match self {
+            MathOperatorKind::Plus => OPERATOR_ADD_METHOD,
+            MathOperatorKind::Minus => OPERATOR_SUB_METHOD,
+            MathOperatorKind::Multiply => OPERATOR_MUL_METHOD,
+            MathOperatorKind::Divide => OPERATOR_DIV_METHOD,
+            MathOperatorKind::Modulus => OPERATOR_REM_METHOD,
+            MathOperatorKind::Power => OPERATOR_POW_METHOD,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub enum ReturnKind {
     Return,
@@ -128,6 +372,8 @@ pub enum TypeKind {
     Function(FunctionType),
     Class(Arc<ClassType>),
     Enum(Arc<EnumType>),
+    /// Passed around as its elements one after the other, like a data class
+    Tuple(Vec<TypeKind>),
 }
 
 impl From<&PrimitiveType> for TypeKind {
@@ -143,12 +389,30 @@ impl From<&PrimitiveType> for TypeKind {
 }
 
 impl TypeKind {
+    /// Returns true if a value of this type can be used where `other` is expected,
+    /// the objects of a class can be used as objects of its parents
     pub fn is_compatible(&self, other: &TypeKind) -> bool {
 
         match (self, other) {
             (a, b) if a == b => true,
             (TypeKind::Unknown, _) => true,
             (_, TypeKind::Unknown) => true,
+            (TypeKind::Class(a), TypeKind::Class(b)) => a.is_subclass_of(b),
+            (TypeKind::Enum(a), TypeKind::Enum(b)) if a.name == b.name => {
+                let a = a.variants.borrow();
+                let b = b.variants.borrow();
+
+                a.len() == b.len() && a
+                    .iter()
+                    .zip(b.iter())
+                    .all(|((a_name, a_type), (b_name, b_type))| a_name == b_name && a_type.is_compatible(b_type))
+            },
+            (TypeKind::Tuple(a), TypeKind::Tuple(b)) => {
+                a.len() == b.len() && a
+                    .iter()
+                    .zip(b.iter())
+                    .all(|(a, b)| a.is_compatible(b))
+            },
             _ => false
         }
     }
@@ -175,6 +439,7 @@ impl From<&LiteralKind> for TypeKind {
 
 pub struct Class {
     pub methods: Vec<(String, FunctionDefinition)>,
+    pub field_attributes: Vec<(String, Vec<Attribute>)>,
 }
 
 impl Debug for Class {
@@ -184,8 +449,8 @@ impl Debug for Class {
 }
 
 impl Class {
-    pub fn new(methods: Vec<(String, FunctionDefinition)>) -> Self {
-        Self { methods }
+    pub fn new(methods: Vec<(String, FunctionDefinition)>, field_attributes: Vec<(String, Vec<Attribute>)>) -> Self {
+        Self { methods, field_attributes }
     }
 }
 
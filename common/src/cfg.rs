@@ -0,0 +1,41 @@
+use crate::constants::{CFG_FEATURE_KEY, CFG_TARGET_KEY};
+
+/// Configuration used to evaluate the `@Cfg(key = "value")` attributes,
+/// a declaration or statement is kept only if all the pairs of its `@Cfg` are set
+#[derive(Clone, Debug, Default)]
+pub struct CfgOptions {
+    values: Vec<(String, String)>,
+}
+
+impl CfgOptions {
+    pub fn new() -> Self {
+        Self {
+            values: Vec::new(),
+        }
+    }
+
+    /// Sets a pair, a key can have more than one value (like `feature`)
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        let pair = (key.into(), value.into());
+
+        if !self.values.contains(&pair) {
+            self.values.push(pair);
+        }
+    }
+
+    /// Sets the backend the sources are built for, replacing the previous one
+    pub fn set_target(&mut self, target: impl Into<String>) {
+        self.values.retain(|(key, _)| key != CFG_TARGET_KEY);
+        self.values.push((CFG_TARGET_KEY.to_string(), target.into()));
+    }
+
+    pub fn enable_feature(&mut self, feature: impl Into<String>) {
+        self.set(CFG_FEATURE_KEY, feature);
+    }
+
+    pub fn is_set(&self, key: &str, value: &str) -> bool {
+        self.values
+            .iter()
+            .any(|(k, v)| k == key && v == value)
+    }
+}
@@ -2,11 +2,46 @@ pub const SCOPE_SIZE: usize = 8;
 pub const CLASS_CONSTRUCTOR_NAME: &str = "init";
 pub const FILE_EXTENSION: &str = ".vrs";
 pub const CLASS_SELF_REFERENCE: &str = "this";
+pub const CLASS_PARENT_REFERENCE: &str = "super";
+pub const MAIN_FUNCTION_NAME: &str = "main";
 
 pub const CORE_MODULE_ID: &str = "core";
-pub const INTERNAL_ALLOC_LOCATION: &str = "__internal_alloc_location";
+pub const STD_MODULE_ID: &str = "std";
+pub const IMPORT_ALIAS_KEYWORD: &str = "as";
+pub const IMPORT_FROM_KEYWORD: &str = "from";
 pub const INTERNAL_MEMORY_ALLOC: &str = "__internal_memory_alloc";
 pub const INTERNAL_MEMORY_ALLOC_INC: &str = "__internal_memory_alloc_inc";
+pub const INTERNAL_PANIC: &str = "__internal_panic";
+pub const INTERNAL_PROPAGATION_LOCATION: &str = "__internal_propagation_location";
+
+// Result
+pub const RESULT_TYPE_NAME: &str = "Result";
+pub const RESULT_OK_VARIANT: &str = "Ok";
+pub const RESULT_ERR_VARIANT: &str = "Err";
+
+// Operator overloading, `cmp` returns a negative, zero or positive Int like the comparison of two numbers
+pub const OPERATOR_ADD_METHOD: &str = "add";
+pub const OPERATOR_SUB_METHOD: &str = "sub";
+pub const OPERATOR_MUL_METHOD: &str = "mul";
+pub const OPERATOR_DIV_METHOD: &str = "div";
+pub const OPERATOR_REM_METHOD: &str = "rem";
+pub const OPERATOR_POW_METHOD: &str = "pow";
+pub const OPERATOR_EQ_METHOD: &str = "eq";
+pub const OPERATOR_CMP_METHOD: &str = "cmp";
+
+pub const ASSERT_DEFAULT_MESSAGE: &str = "Assertion failed";
 
 // Attributes
 pub const ATTRIB_STATIC_MEMORY: &str = "AsStaticMemorySize";
+pub const ATTRIB_EXPORT: &str = "Export";
+pub const ATTRIB_CFG: &str = "Cfg";
+
+// Conditional compilation
+pub const CFG_TARGET_KEY: &str = "target";
+pub const CFG_FEATURE_KEY: &str = "feature";
+pub const CFG_TARGET_WASM: &str = "wasm";
+pub const CFG_TARGET_INTERPRETER: &str = "interpreter";
+pub const CFG_TARGET_WASI: &str = "wasi";
+
+// Wasm custom sections
+pub const SOURCE_MAPPING_URL_SECTION: &str = "sourceMappingURL";
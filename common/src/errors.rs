@@ -32,6 +32,15 @@ pub enum ParserErrorKind {
     InvalidArgCount(usize),
     InvalidAttribute(Attribute),
     InvalidEnumVariant(String),
+    InvalidPropagation,
+    FeatureNotFound(String),
+    PrivateFeature(String),
+    PrivateMember(String),
+    ExternConstruct(String),
+    InvalidParent(String),
+    InvalidOverride(String),
+    NotConstant(String),
+    OperatorNotImplemented(String, String),
 }
 
 #[derive(Debug)]
@@ -43,6 +52,9 @@ pub enum BuildErrorKind {
     ModuleNotFound(ModuleUID),
     InvalidStackType, // TODO: Implement types
     InvalidStackSize(usize, usize),
+    InvalidAttributeArgs(String),
+    DuplicateExport(String),
+    InvalidMemoryConfig(String),
 }
 
 #[derive(Debug)]
@@ -56,6 +68,7 @@ pub enum RuntimeErrorKind {
     ValueNotNumber,
     ValueNotFunc,
     ModuleNotFound(ModuleUID),
+    Panic(String),
 }
 
 #[derive(Debug)]
@@ -216,6 +229,15 @@ fn format_parser(source: &String, token: Token, kind: ParserErrorKind) -> String
         ParserErrorKind::InvalidArgCount(expected) => format!("Expected {} parameters", expected),
         ParserErrorKind::InvalidAttribute(attribute) => format!("Invalid attribute ({:?})", attribute),
         ParserErrorKind::InvalidEnumVariant(variant) => format!("Invalid enum variant ({:?})", variant),
+        ParserErrorKind::InvalidPropagation => "The ? operator can only be used on a Result inside a function returning a Result with the same error type".to_string(),
+        ParserErrorKind::FeatureNotFound(name) => format!("Imported module has no feature named {}", name),
+        ParserErrorKind::PrivateFeature(name) => format!("{} is private to its module", name),
+        ParserErrorKind::PrivateMember(name) => format!("Member {} is private to the module of its class", name),
+        ParserErrorKind::ExternConstruct(name) => format!("Objects of the extern class {} can only be created by the host", name),
+        ParserErrorKind::InvalidParent(name) => format!("Class {} can only inherit from a normal class that doesn't inherit from it", name),
+        ParserErrorKind::InvalidOverride(name) => format!("{} hides a member of the parent class, only the methods can be overridden by methods of the same type", name),
+        ParserErrorKind::NotConstant(name) => format!("The value of the constant {} can't be computed at compile time", name),
+        ParserErrorKind::OperatorNotImplemented(class, method) => format!("The class {} has no {} method implementing the operator", class, method),
     };
 
     res + "\n" + &err
@@ -230,6 +252,9 @@ pub fn format_build(kind: BuildErrorKind) -> String {
         BuildErrorKind::ModuleNotFound(uid) => format!("Module not found ({:?})", uid),
         BuildErrorKind::InvalidStackType => "Invalid stack type".to_string(),
         BuildErrorKind::InvalidStackSize(expected, found) => format!("Expected {} items on the stack found {}", expected, found),
+        BuildErrorKind::InvalidAttributeArgs(attribute) => format!("Invalid arguments for the attribute @{}", attribute),
+        BuildErrorKind::DuplicateExport(name) => format!("More than one function or memory is exported as {}", name),
+        BuildErrorKind::InvalidMemoryConfig(reason) => format!("Invalid memory configuration ({})", reason),
     }
 }
 
@@ -253,6 +278,7 @@ pub fn format_runtime(kind: RuntimeErrorKind) -> String {
         RuntimeErrorKind::ValueNotNumber => "Variable is not a number".to_string(),
         RuntimeErrorKind::ValueNotFunc => "Variable is not a function".to_string(),
         RuntimeErrorKind::ModuleNotFound(uid) => format!("Module not found ({:?})", uid),
+        RuntimeErrorKind::Panic(message) => format!("Panic: {}", message),
     }
 }
 
@@ -269,6 +295,11 @@ impl Into<String> for &TypeKind {
             TypeKind::Vector(t) => format!("Vector of {}", Into::<String>::into(t.as_ref())),
             TypeKind::Unknown => "Unknown".to_string(),
             TypeKind::Function(_) => format!("Function"),
+            TypeKind::Tuple(types) => format!("({})", types
+                .iter()
+                .map(Into::<String>::into)
+                .collect::<Vec<String>>()
+                .join(", ")),
         }
     }
 }
\ No newline at end of file
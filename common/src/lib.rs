@@ -8,3 +8,4 @@ pub mod module;
 pub mod tokens;
 pub mod parsable_types;
 pub mod tokens_iterator;
+pub mod cfg;
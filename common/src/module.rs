@@ -20,6 +20,18 @@ impl ModuleUID {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum ModuleKind {
+    /// A module that contains definitions, its imported features are
+    /// imported from a host module with the same name as the module identifier
+    Definition,
+    /// A module that contains only declarations (signatures) of features provided by the host.
+    /// `import_module` is the name of the host module the features are imported from
+    Declaration {
+        import_module: String,
+    },
+}
+
 pub struct FunctionDefinition {
     pub attributes: Vec<Attribute>,
     pub data: Option<Arc<Function>>,
@@ -39,7 +51,17 @@ pub struct ClassDefinition {
 }
 
 pub struct EnumDefinition {
-    pub metadata: EnumType,
+    pub attributes: Vec<Attribute>,
+    /// The static methods have the `Static` attribute
+    pub methods: Vec<(String, FunctionDefinition)>,
+    pub metadata: Arc<EnumType>,
+}
+
+/// Constants are inlined by the parser, they are kept to be listed with the other features
+pub struct ConstantDefinition {
+    pub attributes: Vec<Attribute>,
+    pub data: LiteralKind,
+    pub metadata: TypeKind,
 }
 
 impl ClassDefinition {
@@ -67,19 +89,41 @@ pub enum ModuleFeature {
     Function(FunctionDefinition),
     Variable(VariableDefinition),
     Class(ClassDefinition),
-    Enum(EnumDefinition)
+    Enum(EnumDefinition),
+    Constant(ConstantDefinition),
 }
 
 pub struct Module {
     pub id: ModuleIdentifier,
     pub uid: ModuleUID,
+    pub kind: ModuleKind,
 
     pub imports: Vec<ModuleUID>,
     pub features: HashMap<String, ModuleFeature>,
     pub parsable_module: Arc<ParsableModule>,
 }
 
+impl ModuleFeature {
+    pub fn attributes(&self) -> &Vec<Attribute> {
+        match self {
+            ModuleFeature::Function(def) => &def.attributes,
+            ModuleFeature::Variable(def) => &def.attributes,
+            ModuleFeature::Class(def) => &def.attributes,
+            ModuleFeature::Enum(def) => &def.attributes,
+            ModuleFeature::Constant(def) => &def.attributes,
+        }
+    }
+}
+
 impl Module {
+    /// Returns the name of the host module from which the declarations of this module are imported
+    pub fn import_module_name(&self) -> &str {
+        match &self.kind {
+            ModuleKind::Definition => self.id.0.as_ref(),
+            ModuleKind::Declaration { import_module } => import_module.as_ref(),
+        }
+    }
+
     pub fn get_func_feature(&self, name: &String) -> Option<&FunctionDefinition> {
         self.features
             .get(name)
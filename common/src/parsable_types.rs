@@ -1,7 +1,7 @@
 use crate::ast::parsing_types::{ParsableFunctionType, ParsableType};
 use crate::ast::types::{Attribute, ClassKind};
-use crate::module::{ModuleIdentifier, ModuleUID};
-use crate::tokens::TokenSnapshot;
+use crate::module::{ModuleIdentifier, ModuleKind, ModuleUID};
+use crate::tokens::{Token, TokenSnapshot};
 use crate::tokens_iterator::Tokens;
 
 pub struct ParsableVariable {
@@ -10,6 +10,18 @@ pub struct ParsableVariable {
     pub body: Option<TokenSnapshot>,
 }
 
+/// `const <name> (type) = <value>`, the value is evaluated when the module is loaded
+pub struct ParsableConstant {
+    pub attributes: Vec<Attribute>,
+    pub type_kind: ParsableType,
+    pub body: TokenSnapshot,
+}
+
+pub struct ParsableField {
+    pub attributes: Vec<Attribute>,
+    pub type_kind: ParsableType,
+}
+
 pub struct ParsableFunction {
     pub attributes: Vec<Attribute>,
     pub func_type: ParsableFunctionType,
@@ -22,25 +34,88 @@ pub struct ParsableClass {
     pub kind: ClassKind,
     pub name: String,
     pub module: ModuleUID,
+    /// The class after `:`, it inherits its fields and methods to this one
+    pub parent: Option<ParsableType>,
 
-    pub fields: Vec<(String, ParsableType)>,
-    pub methods: Vec<(String, ParsableFunction)>
+    pub fields: Vec<(String, ParsableField)>,
+    /// The static methods have the `Static` attribute
+    pub methods: Vec<(String, ParsableFunction)>,
+    pub constants: Vec<(String, ParsableConstant)>,
 }
 
 pub struct ParsableEnum {
+    pub attributes: Vec<Attribute>,
     pub name: String,
     pub variants: Vec<(String, ParsableType)>,
+    /// The static methods have the `Static` attribute
+    pub methods: Vec<(String, ParsableFunction)>,
+    pub constants: Vec<(String, ParsableConstant)>,
+}
+
+/// Describes which features of an imported module are added to the scope
+pub enum ImportKind {
+    /// `import "path"` adds every feature
+    All,
+    /// `import "path" as alias` adds the features under `alias.<name>`
+    Alias(String),
+    /// `import { Name, name } from "path"` adds only the listed features
+    Selective(Vec<(String, Token)>),
+}
+
+pub struct ParsableImport {
+    pub id: ModuleIdentifier,
+    pub kind: ImportKind,
 }
 
 /// This represents a module that needs more processing to be parsed
 pub struct ParsableModule {
     pub id: ModuleIdentifier,
     pub uid: ModuleUID,
+    pub kind: ModuleKind,
 
     pub tokens: Tokens,
-    pub imports: Vec<ModuleIdentifier>,
+    pub imports: Vec<ParsableImport>,
     pub variables: Vec<(String, ParsableVariable)>,
     pub functions: Vec<(String, ParsableFunction)>,
     pub classes: Vec<(String, ParsableClass)>,
     pub enums: Vec<(String, ParsableEnum)>,
+    pub constants: Vec<(String, ParsableConstant)>,
+    /// Used to convert the positions of the tokens to lines
+    pub lines: LineIndex,
+    pub source: ModuleSource,
+}
+
+/// Where the source of a module comes from, used by the source maps
+#[derive(Clone, Debug)]
+pub enum ModuleSource {
+    /// A file loaded by the importer, relative to the source directory
+    Path(String),
+    /// A module without a file (like the core lib), its code is embedded in the source maps
+    Code(String),
+}
+
+/// The start position (in characters) of every line of a source
+#[derive(Clone, Debug, Default)]
+pub struct LineIndex(Vec<usize>);
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        let mut starts = vec![0];
+
+        for (i, c) in source.chars().enumerate() {
+            if c == '\n' {
+                starts.push(i + 1);
+            }
+        }
+
+        Self(starts)
+    }
+
+    /// Returns the line (starting from 1) of a position
+    pub fn line(&self, position: usize) -> usize {
+        match self.0.binary_search(&position) {
+            Ok(line) => line + 1,
+            Err(line) => line,
+        }
+    }
 }
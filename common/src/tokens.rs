@@ -27,9 +27,12 @@ pub enum TokenKind {
     Variable,
     Class,
     Enum,
+    Constant,
     Attribute(Attribute),
     Return,
     Break,
+    Panic,
+    Assert,
     If,
     Else,
     For,
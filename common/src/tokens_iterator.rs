@@ -42,6 +42,14 @@ impl Tokens {
         Some(self.tokens[self.current_pos.0].clone())
     }
     
+    /// Returns the last popped token
+    pub fn previous(&self) -> Option<Token> {
+        match self.current_pos.0 {
+            0 => None,
+            pos => self.tokens.get(pos - 1).cloned(),
+        }
+    }
+
     pub fn has_next(&self) -> bool {
         self.current_pos.0 < self.tokens.len()
     }
@@ -3,6 +3,7 @@ use common::errors::LangError;
 use common::module::{Module, ModuleUID};
 use parser::modules::module_importer::ModuleImporter;
 use parser::modules::module_loader::ModuleLoader;
+use parser::modules::attribute_processor::AttributeProcessor;
 use anyhow::Result;
 
 use crate::module::EngineModule;
@@ -16,10 +17,22 @@ where
     type Module: EngineModule<Engine = Self>;
 
     fn load_module(&mut self, identifier: impl Into<String>, importer: &impl ModuleImporter) -> Result<ModuleUID>;
+    /// Loads a declaration module, every feature of it will be provided by the host module `import_module`
+    fn load_declaration_module(&mut self, identifier: impl Into<String>, import_module: impl Into<String>, importer: &impl ModuleImporter) -> Result<ModuleUID>;
     fn insert_module(&mut self, module: Arc<Module>) -> Result<()>;
 
     fn module_loader(&mut self) -> &mut ModuleLoader;
 
+    /// Registers a handler for a custom attribute used by the modules loaded afterwards
+    fn register_attribute_processor(&mut self, processor: impl AttributeProcessor + 'static) {
+        self.module_loader().register_attribute_processor(Box::new(processor));
+    }
+
+    /// Sets a `key = "value"` pair used by the `@Cfg` attributes of the modules loaded afterwards
+    fn set_cfg(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.module_loader().cfg_mut().set(key, value);
+    }
+
     fn new() -> Self;
 }
 
@@ -14,7 +14,7 @@ pub trait ExternalModuleSetFunctionMetadata<Args, R: ExternalType> : ExternalMod
     fn set_function(&mut self, name: &str);
 }
 
-pub trait ExternalModuleSetFunction<Args, R: ExternalType> : ExternalModule {
+pub trait ExternalModuleSetFunction<Args: std::marker::Tuple, R: ExternalType> : ExternalModule {
     fn set_function<F>(&mut self, name: &str, func: F)
         where F: Fn<Args, Output = R> + Send + Sync + 'static;
 }
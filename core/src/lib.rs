@@ -1,5 +1,6 @@
 #![feature(unboxed_closures)]
 #![feature(generic_associated_types)]
+#![feature(tuple_trait)]
 
 pub use common::errors::LangError;
 pub use engine::{Engine, EngineGetFunction, InternalFunction, EngineBuildSource, EngineExternalModule};
@@ -14,6 +15,7 @@ pub mod external_module;
 pub mod parser {
     pub use parser::modules::module_importer::ModuleImporter;
     pub use parser::modules::module_loader::ModuleLoader;
+    pub use parser::modules::attribute_processor::{AttributeContext, AttributeProcessor};
 }
 
 pub mod reexport {
@@ -1,6 +1,7 @@
 use core::LangError;
 use std::{ops::{FromResidual, Try, ControlFlow}, sync::Arc, collections::HashMap};
-use common::{ast::{ASTNode, NodeKind, types::{ReturnKind, MathOperatorKind, BoolOperatorKind}}, errors::RuntimeErrorKind};
+use common::{ast::{ASTNode, NodeKind, types::{ClassType, ReturnKind, MathOperatorKind, BoolOperatorKind}}, errors::RuntimeErrorKind};
+use common::constants::{CLASS_CONSTRUCTOR_NAME, CLASS_SELF_REFERENCE, RESULT_ERR_VARIANT};
 use crate::{lang_value::LangValue, object::LangObject};
 use super::scope::Scope;
 
@@ -58,12 +59,35 @@ impl<'a> Scope<'a> {
                     None => EvalResult::Err(LangError::runtime(RuntimeErrorKind::VarNotFound(name.clone()))),
                 }
             },
-            NodeKind::VariableAsgn { name, value } => {
+            NodeKind::VariableAsgn { module: _, name, value } => {
                 let value = self.evaluate_ast(value)?;
                 self.set_var(name, value);
                 
                 EvalResult::Ok(LangValue::Nothing)
             },
+            // `<object>.<method>(...)`, the object is the first parameter of the method
+            NodeKind::FunctionInvok { variable, parameters } if matches!(variable.kind.as_ref(), NodeKind::FieldAccess { .. }) => {
+                let (object, class_type, method_name) = match variable.kind.as_ref() {
+                    NodeKind::FieldAccess { variable, class_type, field_name } => (variable, class_type, field_name),
+                    _ => unreachable!(),
+                };
+
+                let object = self.evaluate_ast(object)?;
+
+                // An overridden method is the one of the class the object was created from
+                let class_type = match &object {
+                    LangValue::Object(obj) => obj.class_type().unwrap_or(class_type.clone()),
+                    _ => class_type.clone(),
+                };
+                let func = self.get_method(&class_type, method_name)?;
+
+                let mut param_values = vec![object];
+                for param in parameters {
+                    param_values.push(self.evaluate_ast(param)?);
+                }
+
+                self.invoke_function(&func, param_values)
+            },
             NodeKind::FunctionInvok { variable, parameters } => {
                 let func = self.evaluate_ast(variable)?;
 
@@ -108,6 +132,11 @@ impl<'a> Scope<'a> {
                 
                 EvalResult::Ok(LangValue::Bool(value))
             },
+            NodeKind::ReturnStatement { value: Some(value), kind: ReturnKind::Panic } => {
+                let message = self.evaluate_ast(value)?;
+
+                EvalResult::Err(LangError::runtime(RuntimeErrorKind::Panic(message.to_string())))
+            },
             NodeKind::ReturnStatement { value: Some(value ), kind } => EvalResult::Ret(self.evaluate_ast(value)?, kind.clone()),
             NodeKind::ReturnStatement { value: None, kind } => EvalResult::Ret(LangValue::Nothing, kind.clone()),
             NodeKind::IfStatement { condition, body } => {
@@ -162,7 +191,7 @@ impl<'a> Scope<'a> {
 
                 EvalResult::Ok(LangValue::Nothing)
             },
-            NodeKind::FieldAccess { variable, field_name } => {
+            NodeKind::FieldAccess { variable, field_name, .. } => {
                 let value = self.evaluate_ast(variable)?;
                 let result = value.get_field(field_name);
                 
@@ -177,6 +206,25 @@ impl<'a> Scope<'a> {
                 
                 EvalResult::Ok(LangValue::Vector(Arc::new(eval_values)))
             },
+            // A tuple is kept as a vector, the parser checks how its elements are used
+            NodeKind::TupleLiteral { values } => {
+                let mut eval_values = Vec::new();
+
+                for val in values {
+                    eval_values.push(self.evaluate_ast(val)?);
+                }
+
+                EvalResult::Ok(LangValue::Vector(Arc::new(eval_values)))
+            },
+            NodeKind::TupleDecl { names, value } => {
+                let values = expect_some!(self.evaluate_ast(value)?.as_vec(), RuntimeErrorKind::CantConvertValue);
+
+                for (name, value) in names.iter().zip(values.iter()) {
+                    self.declare_var(name.clone(), value.clone());
+                }
+
+                EvalResult::Ok(LangValue::Nothing)
+            },
             NodeKind::ValueFieldAccess { variable, value } => {
                 let variable = self.evaluate_ast(variable)?;
                 let value = self.evaluate_ast(value)?;
@@ -195,9 +243,70 @@ impl<'a> Scope<'a> {
             NodeKind::FunctionLiteral { value } => {
                 EvalResult::Ok(LangValue::Function(value.clone()))
             },
+            NodeKind::ConstructClass { parameters, class_type } => {
+                let object = LangValue::Object(LangObject::from_class(class_type.clone()));
+
+                // A class without a constructor uses the one of its parent
+                if class_type.find_method(CLASS_CONSTRUCTOR_NAME).is_some() {
+                    let constructor = self.get_method(class_type, &CLASS_CONSTRUCTOR_NAME.to_string())?;
+
+                    let mut param_values = vec![object.clone()];
+                    for param in parameters {
+                        param_values.push(self.evaluate_ast(param)?);
+                    }
+
+                    self.invoke_function(&constructor, param_values)?;
+                }
+
+                EvalResult::Ok(object)
+            },
+            // `super.<method>(...)`, the method of the parent is called even if the class overrides it
+            NodeKind::ParentInvok { class_type, method_name, parameters } => {
+                let this = expect_some!(
+                    self.get_var(class_type.module, &CLASS_SELF_REFERENCE.to_string()),
+                    RuntimeErrorKind::VarNotFound(CLASS_SELF_REFERENCE.to_string()));
+                let func = self.get_method(class_type, method_name)?;
+
+                let mut param_values = vec![this];
+                for param in parameters {
+                    param_values.push(self.evaluate_ast(param)?);
+                }
+
+                self.invoke_function(&func, param_values)
+            },
+            NodeKind::ConstructEnumVariant { value, variant_id, .. } => {
+                let value = self.evaluate_ast(value)?;
+
+                EvalResult::Ok(LangValue::Enum(*variant_id, Arc::new(value)))
+            },
+            NodeKind::ResultPropagation { value, result_type } => {
+                let (err_id, _) = expect_some!(
+                    result_type.get_variant(RESULT_ERR_VARIANT),
+                    RuntimeErrorKind::UnexpectedError("Err variant not found".to_string()));
+
+                match self.evaluate_ast(value)? {
+                    LangValue::Enum(id, value) if id == err_id
+                        => EvalResult::Ret(LangValue::Enum(err_id, value), ReturnKind::Return),
+                    LangValue::Enum(_, value) => EvalResult::Ok(value.as_ref().clone()),
+                    _ => EvalResult::Err(LangError::runtime(RuntimeErrorKind::CantConvertValue)),
+                }
+            },
         }
     }
 
+    /// Searches the method in the class and then in its parents, it's the function `<Class>::<method>`
+    /// of the module of the class implementing it
+    fn get_method(&self, class_type: &Arc<ClassType>, name: &String) -> EvalResult {
+        let (owner, _) = expect_some!(
+            class_type.find_method(name),
+            RuntimeErrorKind::VarNotFound(format!("{}.{}", class_type.name, name)));
+
+        let func_name = format!("{}::{}", owner.name, name);
+        let func = expect_some!(self.get_var(owner.module, &func_name), RuntimeErrorKind::VarNotFound(func_name));
+
+        EvalResult::Ok(func)
+    }
+
     pub(crate) fn invoke_function(&self, func: &LangValue, param_values: Vec<LangValue>) -> EvalResult {
         match func {
             LangValue::Function(func) => {
@@ -20,6 +20,7 @@ pub enum LangValue {
     ExtFunction(LangExternalFunction),
     Vector(LangVector),
     Object(LangObject),
+    Enum(u32, Arc<LangValue>),
 }
 
 #[derive(PartialEq, Eq, Hash)]
@@ -33,6 +34,7 @@ pub enum LangValueDiscriminant {
     ExtFunction,
     Vector,
     Object,
+    Enum,
 }
 
 impl From<&LangValue> for LangValueDiscriminant {
@@ -47,6 +49,7 @@ impl From<&LangValue> for LangValueDiscriminant {
             LangValue::ExtFunction(_) => LangValueDiscriminant::ExtFunction,
             LangValue::Vector(_) => LangValueDiscriminant::Vector,
             LangValue::Object(_) => LangValueDiscriminant::Object,
+            LangValue::Enum(_, _) => LangValueDiscriminant::Enum,
         }
     }
 }
@@ -65,6 +68,7 @@ impl LangValue {
             LangValue::ExtFunction(_) => true,
             LangValue::Vector(vec) => vec.len() != 0,
             LangValue::Object(obj) => obj.len() != 0,
+            LangValue::Enum(_, _) => true,
         }
     }
 
@@ -278,6 +282,7 @@ impl LangValue {
             (LangValue::Function(x), LangValue::Function(y)) => {
                 x as *const _ == y as *const _
             },
+            (LangValue::Enum(x_id, x), LangValue::Enum(y_id, y)) => x_id == y_id && x.equals(y),
             
             _ => false,
         }
@@ -344,6 +349,7 @@ impl ToString for LangValue {
             LangValue::ExtFunction(_) => "[External Function]".to_string(),
             LangValue::Vector(_) => "[Vector]".to_string(),
             LangValue::Object(_) => "[Object]".to_string(),
+            LangValue::Enum(id, value) => format!("[Enum {}({})]", id, value.to_string()),
         }
     }
 }
@@ -360,6 +366,7 @@ impl Clone for LangValue {
             Self::ExtFunction(func) => Self::ExtFunction(func.clone()),
             Self::Vector(vec) => Self::Vector(vec.clone()),
             Self::Object(obj) => Self::Object(obj.clone()),
+            Self::Enum(id, value) => Self::Enum(*id, value.clone()),
         }
     }
 }
@@ -12,6 +12,7 @@ use core::module_store::ModuleStore;
 use core::{ExternalType, Engine, EngineGetFunction, InternalFunction};
 use std::marker::PhantomData;
 use std::sync::Arc;
+use common::constants::CFG_TARGET_INTERPRETER;
 use common::errors::LangError;
 use common::errors::LoadErrorKind;
 use common::errors::RuntimeErrorKind;
@@ -98,8 +99,12 @@ impl Engine for InterpreterEngine {
         Ok(uid)
     }
 
-    fn load_def_module(&mut self, _import_identifier: impl Into<String>, _module_id: impl Into<String>, _importer: &impl ModuleImporter) -> Result<ModuleUID> {
-        todo!()
+    fn load_declaration_module(&mut self, identifier: impl Into<String>, import_module: impl Into<String>, importer: &impl ModuleImporter) -> Result<ModuleUID> {
+        let (module, _) = self
+            .module_loader()
+            .load_declaration_module(&ModuleIdentifier(identifier.into()), import_module.into(), importer)?;
+
+        Ok(module.uid)
     }
 
     fn insert_module(&mut self, module: Arc<Module>) -> Result<()> {
@@ -118,8 +123,11 @@ impl Engine for InterpreterEngine {
     }
 
     fn new() -> Self {
+        let mut module_loader = ModuleLoader::new();
+        module_loader.cfg_mut().set_target(CFG_TARGET_INTERPRETER);
+
         Self {
-            module_loader: ModuleLoader::new(),
+            module_loader,
             module_store: Arc::new(RefCell::new(ModuleStore::new())),
         }
     }
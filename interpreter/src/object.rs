@@ -1,11 +1,13 @@
 use std::{collections::HashMap, sync::{Arc, Mutex}};
-
+use common::ast::types::ClassType;
 use crate::{lang_value::LangValue};
 
 
 #[derive(Clone)]
 pub struct LangObject {
     fields: Arc<Mutex<HashMap<String, LangValue>>>,
+    /// The class the object was created from, its methods are looked up from it
+    class_type: Option<Arc<ClassType>>,
 }
 
 #[allow(dead_code)]
@@ -13,15 +15,34 @@ impl LangObject {
     pub fn new() -> Self {
         Self {
             fields: Arc::new(Mutex::new(HashMap::new())),
+            class_type: None,
         }
     }
     
     pub fn from_map(map: HashMap<String, LangValue>) -> Self {
         Self {
-            fields: Arc::new(Mutex::new(map))
+            fields: Arc::new(Mutex::new(map)),
+            class_type: None,
+        }
+    }
+
+    /// An object of `class_type` with every field, including the ones of its parents, set to nothing
+    pub fn from_class(class_type: Arc<ClassType>) -> Self {
+        let fields = class_type.all_fields()
+            .into_iter()
+            .map(|(name, _)| (name, LangValue::Nothing))
+            .collect();
+
+        Self {
+            fields: Arc::new(Mutex::new(fields)),
+            class_type: Some(class_type),
         }
     }
 
+    pub fn class_type(&self) -> Option<Arc<ClassType>> {
+        self.class_type.clone()
+    }
+
     pub(crate) fn get(&self, name: &String) -> LangValue  {
         match self.fields.lock() {
             Ok(map) => 
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+use common::ast::types::{ClassKind, ClassType};
+use common::constants::CLASS_CONSTRUCTOR_NAME;
+use common::module::{Module, ModuleFeature};
+use crate::function::FunctionRef;
+
+/// The normal classes of every module, used to find the methods overridden by the classes inheriting from another
+pub struct ClassHierarchy {
+    classes: Vec<Arc<ClassType>>,
+}
+
+/// The methods of a class dispatched on the class of the object, in the order of their slots
+/// with the function implementing each one. The slots of a parent come first, so that a method
+/// is at the same place in the vtables of all the classes inheriting it
+pub struct VTable {
+    pub methods: Vec<(String, FunctionRef)>,
+}
+
+impl ClassHierarchy {
+    pub fn new(modules: &[Arc<Module>]) -> Self {
+        let classes = modules
+            .iter()
+            .flat_map(|module| module.features.values())
+            .filter_map(|feature| match feature {
+                ModuleFeature::Class(class) if class.metadata.kind == ClassKind::Normal => Some(class.metadata.clone()),
+                _ => None,
+            })
+            .collect();
+
+        Self { classes }
+    }
+
+    /// Returns true if a class inheriting from `class_type` declares the method again,
+    /// the calls on an object of that type have to look for the method in its vtable
+    pub fn is_overridden(&self, class_type: &ClassType, method: &str) -> bool {
+        if method == CLASS_CONSTRUCTOR_NAME {
+            return false
+        }
+
+        self.classes
+            .iter()
+            .any(|class| **class != *class_type && class.is_subclass_of(class_type) && class.declares_method(method))
+    }
+
+    /// Only the overridden methods have a slot, the others are always called directly
+    pub fn vtable(&self, class_type: &Arc<ClassType>) -> VTable {
+        let mut methods = match class_type.parent() {
+            Some(parent) => self.vtable(&parent).methods,
+            None => Vec::new(),
+        };
+
+        for (name, _) in class_type.methods.borrow().iter() {
+            let function = FunctionRef {
+                module: class_type.module,
+                name: format!("{}::{}", class_type.name, name),
+            };
+
+            match methods.iter_mut().find(|(method, _)| method == name) {
+                Some((_, slot)) => *slot = function,
+                None if self.is_overridden(class_type, name) => methods.push((name.clone(), function)),
+                None => (),
+            }
+        }
+
+        VTable { methods }
+    }
+}
+
+impl VTable {
+    pub fn slot(&self, method: &str) -> Option<u32> {
+        self.methods
+            .iter()
+            .position(|(name, _)| name == method)
+            .map(|slot| slot as u32)
+    }
+
+    pub fn functions(&self) -> impl Iterator<Item = &FunctionRef> {
+        self.methods.iter().map(|(_, function)| function)
+    }
+}
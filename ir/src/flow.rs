@@ -0,0 +1,193 @@
+use crate::function::{BlockId, Function};
+
+/// The shape of the control flow graph of a function: the order of its blocks, their predecessors and dominators.
+/// The blocks that can't be reached from the entry are left out
+pub struct ControlFlow {
+    order: Vec<BlockId>,
+    /// Position of every block in `order`
+    positions: Vec<Option<usize>>,
+    predecessors: Vec<Vec<BlockId>>,
+    immediate_dominators: Vec<Option<BlockId>>,
+    /// The blocks immediately dominated by every block, in reverse postorder
+    dominated: Vec<Vec<BlockId>>,
+}
+
+impl ControlFlow {
+    pub fn new(function: &Function) -> Self {
+        let count = function.blocks.len();
+
+        let order = Self::reverse_postorder(function);
+
+        let mut positions = vec![None; count];
+        for (position, block) in order.iter().enumerate() {
+            positions[block.0 as usize] = Some(position);
+        }
+
+        let mut predecessors = vec![Vec::new(); count];
+        for block in &order {
+            for successor in function.block(*block).terminator.successors() {
+                if !predecessors[successor.0 as usize].contains(block) {
+                    predecessors[successor.0 as usize].push(*block);
+                }
+            }
+        }
+
+        let mut flow = Self {
+            order,
+            positions,
+            predecessors,
+            immediate_dominators: vec![None; count],
+            dominated: vec![Vec::new(); count],
+        };
+
+        flow.compute_dominators();
+
+        flow
+    }
+
+    fn reverse_postorder(function: &Function) -> Vec<BlockId> {
+        let mut visited = vec![false; function.blocks.len()];
+        let mut postorder = Vec::with_capacity(function.blocks.len());
+
+        if function.blocks.is_empty() {
+            return postorder
+        }
+
+        // Every entry holds a block and the index of its next successor to visit
+        let mut stack = vec![(BlockId(0), 0usize)];
+        visited[0] = true;
+
+        while let Some((block, next)) = stack.last_mut() {
+            let successors = function.block(*block).terminator.successors();
+
+            match successors.get(*next) {
+                Some(successor) => {
+                    *next += 1;
+
+                    if !visited[successor.0 as usize] {
+                        visited[successor.0 as usize] = true;
+                        stack.push((*successor, 0));
+                    }
+                },
+                None => {
+                    postorder.push(*block);
+                    stack.pop();
+                },
+            }
+        }
+
+        postorder.reverse();
+        postorder
+    }
+
+    /// "A Simple, Fast Dominance Algorithm" by Cooper, Harvey and Kennedy
+    fn compute_dominators(&mut self) {
+        let entry = match self.order.first() {
+            Some(entry) => *entry,
+            None => return,
+        };
+
+        self.immediate_dominators[entry.0 as usize] = Some(entry);
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for block in self.order.iter().skip(1) {
+                let mut new_dominator = None;
+
+                for predecessor in &self.predecessors[block.0 as usize] {
+                    if self.immediate_dominators[predecessor.0 as usize].is_none() { continue }
+
+                    new_dominator = match new_dominator {
+                        None => Some(*predecessor),
+                        Some(dominator) => Some(self.intersect(*predecessor, dominator)),
+                    };
+                }
+
+                if new_dominator != self.immediate_dominators[block.0 as usize] {
+                    self.immediate_dominators[block.0 as usize] = new_dominator;
+                    changed = true;
+                }
+            }
+        }
+
+        for block in self.order.iter().skip(1) {
+            if let Some(dominator) = self.immediate_dominators[block.0 as usize] {
+                self.dominated[dominator.0 as usize].push(*block);
+            }
+        }
+    }
+
+    fn intersect(&self, mut a: BlockId, mut b: BlockId) -> BlockId {
+        while a != b {
+            while self.position(a) > self.position(b) {
+                a = self.immediate_dominators[a.0 as usize].unwrap();
+            }
+
+            while self.position(b) > self.position(a) {
+                b = self.immediate_dominators[b.0 as usize].unwrap();
+            }
+        }
+
+        a
+    }
+
+    /// The reachable blocks in reverse postorder, a block comes before its successors unless the edge closes a loop
+    pub fn order(&self) -> &Vec<BlockId> {
+        &self.order
+    }
+
+    pub fn is_reachable(&self, block: BlockId) -> bool {
+        self.positions[block.0 as usize].is_some()
+    }
+
+    pub fn position(&self, block: BlockId) -> usize {
+        self.positions[block.0 as usize].unwrap_or(usize::MAX)
+    }
+
+    pub fn predecessors(&self, block: BlockId) -> &Vec<BlockId> {
+        &self.predecessors[block.0 as usize]
+    }
+
+    /// The entry block is its own immediate dominator
+    pub fn immediate_dominator(&self, block: BlockId) -> Option<BlockId> {
+        self.immediate_dominators[block.0 as usize]
+    }
+
+    pub fn dominated(&self, block: BlockId) -> &Vec<BlockId> {
+        &self.dominated[block.0 as usize]
+    }
+
+    pub fn dominates(&self, dominator: BlockId, mut block: BlockId) -> bool {
+        loop {
+            if block == dominator {
+                return true
+            }
+
+            match self.immediate_dominators[block.0 as usize] {
+                Some(next) if next != block => block = next,
+                _ => return false,
+            }
+        }
+    }
+
+    /// An edge is backward if it goes to a block that comes earlier in the order, which only happens in loops
+    pub fn is_backward(&self, from: BlockId, to: BlockId) -> bool {
+        self.position(to) <= self.position(from)
+    }
+
+    pub fn is_loop_header(&self, block: BlockId) -> bool {
+        self.predecessors(block)
+            .iter()
+            .any(|predecessor| self.is_backward(*predecessor, block))
+    }
+
+    /// A block reached by more than one forward edge
+    pub fn is_merge(&self, block: BlockId) -> bool {
+        self.predecessors(block)
+            .iter()
+            .filter(|predecessor| !self.is_backward(**predecessor, block))
+            .count() >= 2
+    }
+}
@@ -0,0 +1,431 @@
+use std::sync::Arc;
+use common::ast::Span;
+use common::ast::types::{BoolOperatorKind, ClassType, EnumType, LiteralKind, MathOperatorKind, TypeKind};
+use common::module::ModuleUID;
+
+/// Index of a local in its function, the parameters are the first locals
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct LocalId(pub u32);
+
+/// Index of a block in its function, the entry block is the first one
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct BlockId(pub u32);
+
+/// A function defined or imported by a module, methods are named `Class::method`
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionRef {
+    pub module: ModuleUID,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlobalRef {
+    pub module: ModuleUID,
+    pub name: String,
+}
+
+#[derive(Clone)]
+pub struct Local {
+    /// The temporaries created by the lowering don't have a name
+    pub name: Option<String>,
+    pub type_: TypeKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operand {
+    Local(LocalId),
+    Const(LiteralKind),
+}
+
+#[derive(Clone)]
+pub enum Value {
+    Use(Operand),
+    Math {
+        operation: MathOperatorKind,
+        left: Operand,
+        right: Operand,
+    },
+    Compare {
+        operation: BoolOperatorKind,
+        left: Operand,
+        right: Operand,
+    },
+    /// The object of a method is its first argument
+    Call {
+        function: FunctionRef,
+        args: Vec<Operand>,
+    },
+    /// The method is looked up in the vtable of the object, its first argument. `function` is the
+    /// method found in `class_type`, the class of the object known when it is called
+    CallVirtual {
+        class_type: Arc<ClassType>,
+        method: String,
+        function: FunctionRef,
+        args: Vec<Operand>,
+    },
+    Global(GlobalRef),
+    Field {
+        object: Operand,
+        class_type: Arc<ClassType>,
+        field: String,
+    },
+    Element {
+        vector: Operand,
+        index: Operand,
+    },
+    /// An object with the default value of every field, the constructor is called separately
+    NewObject(Arc<ClassType>),
+    Vector {
+        element: TypeKind,
+        values: Vec<Operand>,
+    },
+    Variant {
+        enum_type: Arc<EnumType>,
+        variant: u32,
+        value: Operand,
+    },
+    /// The elements of a tuple one after the other, a tuple is never stored on the heap
+    Tuple(Vec<Operand>),
+    /// An element of a tuple value, read in place like the payload of an enum
+    TupleElement {
+        tuple: Operand,
+        index: u32,
+    },
+    /// The index of the variant held by an enum value
+    Discriminant(Operand),
+    /// The value of the variant `variant`, which has to be the one held by the enum value
+    Payload {
+        value: Operand,
+        variant: u32,
+    },
+}
+
+#[derive(Clone)]
+pub enum InstructionKind {
+    Assign {
+        local: LocalId,
+        value: Value,
+    },
+    /// The value is only computed for its side effects
+    Eval(Value),
+    SetGlobal {
+        global: GlobalRef,
+        value: Operand,
+    },
+    /// The object of a data class is a value, so it has to be a local which is updated in place
+    SetField {
+        object: Operand,
+        class_type: Arc<ClassType>,
+        field: String,
+        value: Operand,
+    },
+    SetElement {
+        vector: Operand,
+        index: Operand,
+        value: Operand,
+    },
+}
+
+#[derive(Clone)]
+pub struct Instruction {
+    pub kind: InstructionKind,
+    /// The statement the instruction was lowered from
+    pub span: Option<Span>,
+}
+
+#[derive(Clone)]
+pub enum Terminator {
+    Jump(BlockId),
+    Branch {
+        condition: Operand,
+        then: BlockId,
+        else_: BlockId,
+    },
+    Return(Operand),
+    Unreachable,
+}
+
+#[derive(Clone)]
+pub struct Block {
+    pub instructions: Vec<Instruction>,
+    pub terminator: Terminator,
+    pub terminator_span: Option<Span>,
+}
+
+#[derive(Clone)]
+pub struct Function {
+    pub name: String,
+    pub module: ModuleUID,
+
+    /// Methods have the object as their first parameter
+    pub param_count: u32,
+    pub ret: TypeKind,
+
+    pub locals: Vec<Local>,
+    pub blocks: Vec<Block>,
+}
+
+impl Value {
+    pub fn operands(&self) -> Vec<&Operand> {
+        match self {
+            Value::Use(operand) |
+            Value::Discriminant(operand) => vec![operand],
+            Value::Math { left, right, .. } |
+            Value::Compare { left, right, .. } => vec![left, right],
+            Value::Call { args, .. } |
+            Value::CallVirtual { args, .. } => args.iter().collect(),
+            Value::Global(_) |
+            Value::NewObject(_) => Vec::new(),
+            Value::Field { object, .. } => vec![object],
+            Value::Element { vector, index } => vec![vector, index],
+            Value::Vector { values, .. } |
+            Value::Tuple(values) => values.iter().collect(),
+            Value::Variant { value, .. } |
+            Value::TupleElement { tuple: value, .. } |
+            Value::Payload { value, .. } => vec![value],
+        }
+    }
+
+    pub fn operands_mut(&mut self) -> Vec<&mut Operand> {
+        match self {
+            Value::Use(operand) |
+            Value::Discriminant(operand) => vec![operand],
+            Value::Math { left, right, .. } |
+            Value::Compare { left, right, .. } => vec![left, right],
+            Value::Call { args, .. } |
+            Value::CallVirtual { args, .. } => args.iter_mut().collect(),
+            Value::Global(_) |
+            Value::NewObject(_) => Vec::new(),
+            Value::Field { object, .. } => vec![object],
+            Value::Element { vector, index } => vec![vector, index],
+            Value::Vector { values, .. } |
+            Value::Tuple(values) => values.iter_mut().collect(),
+            Value::Variant { value, .. } |
+            Value::TupleElement { tuple: value, .. } |
+            Value::Payload { value, .. } => vec![value],
+        }
+    }
+
+    /// Values that only read locals and constants can be removed or computed again
+    pub fn is_pure(&self) -> bool {
+        match self {
+            Value::Use(_) |
+            Value::Compare { .. } |
+            Value::Variant { .. } |
+            Value::Discriminant(_) |
+            Value::Tuple(_) |
+            Value::TupleElement { .. } |
+            Value::Payload { .. } => true,
+            // Divisions trap on zero
+            Value::Math { operation, .. } => !matches!(operation, MathOperatorKind::Divide | MathOperatorKind::Modulus),
+            // Fields and elements read the memory
+            _ => false,
+        }
+    }
+}
+
+impl InstructionKind {
+    pub fn operands(&self) -> Vec<&Operand> {
+        match self {
+            InstructionKind::Assign { value, .. } |
+            InstructionKind::Eval(value) => value.operands(),
+            InstructionKind::SetGlobal { value, .. } => vec![value],
+            InstructionKind::SetField { object, value, .. } => vec![object, value],
+            InstructionKind::SetElement { vector, index, value } => vec![vector, index, value],
+        }
+    }
+
+    pub fn operands_mut(&mut self) -> Vec<&mut Operand> {
+        match self {
+            InstructionKind::Assign { value, .. } |
+            InstructionKind::Eval(value) => value.operands_mut(),
+            InstructionKind::SetGlobal { value, .. } => vec![value],
+            InstructionKind::SetField { object, value, .. } => vec![object, value],
+            InstructionKind::SetElement { vector, index, value } => vec![vector, index, value],
+        }
+    }
+}
+
+impl Terminator {
+    pub fn successors(&self) -> Vec<BlockId> {
+        match self {
+            Terminator::Jump(target) => vec![*target],
+            Terminator::Branch { then, else_, .. } => vec![*then, *else_],
+            Terminator::Return(_) |
+            Terminator::Unreachable => Vec::new(),
+        }
+    }
+
+    pub fn map_blocks(&mut self, mut map: impl FnMut(BlockId) -> BlockId) {
+        match self {
+            Terminator::Jump(target) => *target = map(*target),
+            Terminator::Branch { then, else_, .. } => {
+                *then = map(*then);
+                *else_ = map(*else_);
+            },
+            Terminator::Return(_) |
+            Terminator::Unreachable => (),
+        }
+    }
+
+    pub fn operands(&self) -> Vec<&Operand> {
+        match self {
+            Terminator::Branch { condition, .. } => vec![condition],
+            Terminator::Return(value) => vec![value],
+            Terminator::Jump(_) |
+            Terminator::Unreachable => Vec::new(),
+        }
+    }
+
+    pub fn operands_mut(&mut self) -> Vec<&mut Operand> {
+        match self {
+            Terminator::Branch { condition, .. } => vec![condition],
+            Terminator::Return(value) => vec![value],
+            Terminator::Jump(_) |
+            Terminator::Unreachable => Vec::new(),
+        }
+    }
+}
+
+impl Function {
+    pub fn local(&self, id: LocalId) -> &Local {
+        &self.locals[id.0 as usize]
+    }
+
+    pub fn block(&self, id: BlockId) -> &Block {
+        &self.blocks[id.0 as usize]
+    }
+
+    pub fn params(&self) -> &[Local] {
+        &self.locals[..self.param_count as usize]
+    }
+
+    pub fn operand_type(&self, operand: &Operand) -> TypeKind {
+        match operand {
+            Operand::Local(id) => self.local(*id).type_.clone(),
+            Operand::Const(literal) => literal.into(),
+        }
+    }
+
+    /// Number of instructions, counting the terminators
+    pub fn size(&self) -> usize {
+        self.blocks
+            .iter()
+            .map(|block| block.instructions.len() + 1)
+            .sum()
+    }
+
+    /// The functions called by this one, once for every call. The overrides of the virtual
+    /// calls are found through the classes of the objects created
+    pub fn callees(&self) -> Vec<&FunctionRef> {
+        self.blocks
+            .iter()
+            .flat_map(|block| &block.instructions)
+            .filter_map(|instruction| match &instruction.kind {
+                InstructionKind::Assign { value: Value::Call { function, .. } | Value::CallVirtual { function, .. }, .. } |
+                InstructionKind::Eval(Value::Call { function, .. } | Value::CallVirtual { function, .. }) => Some(function),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The classes of the objects created by this function
+    pub fn classes(&self) -> Vec<&Arc<ClassType>> {
+        self.blocks
+            .iter()
+            .flat_map(|block| &block.instructions)
+            .filter_map(|instruction| match &instruction.kind {
+                InstructionKind::Assign { value: Value::NewObject(class_type), .. } |
+                InstructionKind::Eval(Value::NewObject(class_type)) => Some(class_type),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The global variables read or written by this function
+    pub fn globals(&self) -> Vec<&GlobalRef> {
+        self.blocks
+            .iter()
+            .flat_map(|block| &block.instructions)
+            .filter_map(|instruction| match &instruction.kind {
+                InstructionKind::Assign { value: Value::Global(global), .. } |
+                InstructionKind::Eval(Value::Global(global)) |
+                InstructionKind::SetGlobal { global, .. } => Some(global),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Number of times every local is read
+    pub fn local_uses(&self) -> Vec<u32> {
+        let mut uses = vec![0; self.locals.len()];
+
+        for block in &self.blocks {
+            let operands = block.instructions
+                .iter()
+                .flat_map(|instruction| instruction.kind.operands())
+                .chain(block.terminator.operands());
+
+            for operand in operands {
+                if let Operand::Local(id) = operand {
+                    uses[id.0 as usize] += 1;
+                }
+            }
+        }
+
+        uses
+    }
+
+    /// Number of times every local is assigned, the parameters are assigned by the caller
+    pub fn local_assignments(&self) -> Vec<u32> {
+        let mut assignments = vec![0; self.locals.len()];
+
+        for id in 0..self.param_count {
+            assignments[id as usize] += 1;
+        }
+
+        for block in &self.blocks {
+            for instruction in &block.instructions {
+                match &instruction.kind {
+                    InstructionKind::Assign { local, .. } |
+                    InstructionKind::SetField { object: Operand::Local(local), .. } => assignments[local.0 as usize] += 1,
+                    _ => (),
+                }
+            }
+        }
+
+        assignments
+    }
+
+    /// Removes the blocks that can't be reached from the entry block, the others are numbered again in the same order
+    pub fn remove_unreachable_blocks(&mut self) {
+        let mut reachable = vec![false; self.blocks.len()];
+        let mut to_visit = vec![0usize];
+
+        while let Some(id) = to_visit.pop() {
+            if reachable[id] { continue }
+            reachable[id] = true;
+
+            to_visit.extend(self.blocks[id].terminator.successors().iter().map(|block| block.0 as usize));
+        }
+
+        let mut new_ids = Vec::with_capacity(self.blocks.len());
+        let mut count = 0;
+        for is_reachable in &reachable {
+            new_ids.push(BlockId(count));
+            if *is_reachable { count += 1 }
+        }
+
+        let blocks = std::mem::take(&mut self.blocks);
+
+        self.blocks = blocks
+            .into_iter()
+            .zip(reachable)
+            .filter(|(_, is_reachable)| *is_reachable)
+            .map(|(mut block, _)| {
+                block.terminator.map_blocks(|target| new_ids[target.0 as usize]);
+                block
+            })
+            .collect();
+    }
+}
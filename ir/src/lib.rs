@@ -0,0 +1,9 @@
+//! A typed intermediate representation between the AST and the backends. The body of every function
+//! is a control flow graph of blocks, whose instructions compute values from locals and constants only
+pub mod function;
+pub mod program;
+pub mod class;
+pub mod flow;
+pub mod liveness;
+pub mod optimize;
+mod lower;
@@ -0,0 +1,177 @@
+use crate::flow::ControlFlow;
+use crate::function::{BlockId, Function, InstructionKind, LocalId, Operand};
+
+/// The pairs of locals that hold a value at the same time, the locals that don't interfere can share their storage
+pub struct Interference {
+    neighbors: Vec<Vec<LocalId>>,
+}
+
+impl Interference {
+    /// Computes the locals live at the end of every block until nothing changes, then walks every block backwards:
+    /// an assigned local interferes with all the locals still needed after the assignment
+    pub fn new(function: &Function, flow: &ControlFlow) -> Self {
+        let count = function.locals.len();
+        let mut live_in = vec![vec![false; count]; function.blocks.len()];
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for block in flow.order().iter().rev() {
+                let mut live = Self::live_out(function, *block, &live_in);
+
+                for instruction in function.block(*block).instructions.iter().rev() {
+                    Self::step(&instruction.kind, &mut live);
+                }
+
+                if live != live_in[block.0 as usize] {
+                    live_in[block.0 as usize] = live;
+                    changed = true;
+                }
+            }
+        }
+
+        let mut interference = Self {
+            neighbors: vec![Vec::new(); count],
+        };
+
+        for block in flow.order() {
+            let mut live = Self::live_out(function, *block, &live_in);
+
+            for instruction in function.block(*block).instructions.iter().rev() {
+                if let InstructionKind::Assign { local, .. } = &instruction.kind {
+                    for (other, is_live) in live.iter().enumerate() {
+                        if *is_live && other != local.0 as usize {
+                            interference.add(*local, LocalId(other as u32));
+                        }
+                    }
+                }
+
+                Self::step(&instruction.kind, &mut live);
+            }
+        }
+
+        // The parameters are all set by the caller when the function starts
+        for a in 0..function.param_count {
+            for b in 0..a {
+                interference.add(LocalId(a), LocalId(b));
+            }
+        }
+
+        interference
+    }
+
+    /// The locals read by the terminator or at the start of a successor
+    fn live_out(function: &Function, block: BlockId, live_in: &[Vec<bool>]) -> Vec<bool> {
+        let block = function.block(block);
+        let mut live = vec![false; function.locals.len()];
+
+        for successor in block.terminator.successors() {
+            for (local, is_live) in live_in[successor.0 as usize].iter().enumerate() {
+                live[local] |= *is_live;
+            }
+        }
+
+        for operand in block.terminator.operands() {
+            if let Operand::Local(local) = operand {
+                live[local.0 as usize] = true;
+            }
+        }
+
+        live
+    }
+
+    /// Goes back over an instruction, the fields set in place keep the rest of the object alive
+    fn step(kind: &InstructionKind, live: &mut [bool]) {
+        if let InstructionKind::Assign { local, .. } = kind {
+            live[local.0 as usize] = false;
+        }
+
+        for operand in kind.operands() {
+            if let Operand::Local(local) = operand {
+                live[local.0 as usize] = true;
+            }
+        }
+    }
+
+    fn add(&mut self, a: LocalId, b: LocalId) {
+        if !self.neighbors[a.0 as usize].contains(&b) {
+            self.neighbors[a.0 as usize].push(b);
+            self.neighbors[b.0 as usize].push(a);
+        }
+    }
+
+    pub fn interferes(&self, a: LocalId, b: LocalId) -> bool {
+        self.neighbors[a.0 as usize].contains(&b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::ast::types::{MathOperatorKind, TypeKind};
+    use crate::flow::ControlFlow;
+    use crate::function::{BlockId, Function, LocalId, Terminator, Value};
+    use crate::optimize::test_utils::{assign, block, int, jump, local, math, new_function};
+    use super::Interference;
+
+    fn interference(function: &Function) -> Interference {
+        Interference::new(function, &ControlFlow::new(function))
+    }
+
+    #[test]
+    fn values_used_together_interfere() {
+        let function = new_function("f", 0, vec![TypeKind::Int; 3], vec![
+            block(vec![
+                assign(0, Value::Use(int(1))),
+                assign(1, Value::Use(int(2))),
+                assign(2, math(MathOperatorKind::Plus, local(0), local(1))),
+            ], Terminator::Return(local(2))),
+        ]);
+        let interference = interference(&function);
+
+        assert!(interference.interferes(LocalId(0), LocalId(1)));
+        assert!(!interference.interferes(LocalId(0), LocalId(2)));
+        assert!(!interference.interferes(LocalId(1), LocalId(2)));
+    }
+
+    #[test]
+    fn values_of_sequential_blocks_share_their_storage() {
+        // Like two variables declared in blocks one after the other
+        let function = new_function("f", 1, vec![TypeKind::Int; 3], vec![
+            block(vec![assign(1, math(MathOperatorKind::Plus, local(0), int(1)))], jump(1)),
+            block(vec![assign(0, math(MathOperatorKind::Plus, local(1), int(1)))], jump(2)),
+            block(vec![assign(2, math(MathOperatorKind::Plus, local(0), int(2)))], Terminator::Return(local(2))),
+        ]);
+        let interference = interference(&function);
+
+        assert!(!interference.interferes(LocalId(1), LocalId(2)));
+        assert!(!interference.interferes(LocalId(0), LocalId(1)));
+    }
+
+    #[test]
+    fn values_live_around_a_loop_interfere() {
+        let function = new_function("f", 0, vec![TypeKind::Int, TypeKind::Int, TypeKind::Bool], vec![
+            block(vec![assign(0, Value::Use(int(0)))], jump(1)),
+            block(vec![
+                assign(1, math(MathOperatorKind::Plus, local(0), int(1))),
+                assign(2, Value::Use(int(1))),
+            ], Terminator::Branch { condition: local(2), then: BlockId(2), else_: BlockId(3) }),
+            block(vec![assign(0, Value::Use(local(1)))], jump(1)),
+            block(vec![], Terminator::Return(local(0))),
+        ]);
+        let interference = interference(&function);
+
+        // The counter is read again after the loop while the others are assigned
+        assert!(interference.interferes(LocalId(0), LocalId(1)));
+        assert!(interference.interferes(LocalId(0), LocalId(2)));
+    }
+
+    #[test]
+    fn parameters_interfere() {
+        let function = new_function("f", 2, vec![TypeKind::Int; 2], vec![
+            block(vec![], Terminator::Return(local(0))),
+        ]);
+
+        assert!(interference(&function).interferes(LocalId(0), LocalId(1)));
+    }
+}
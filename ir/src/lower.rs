@@ -0,0 +1,789 @@
+use std::sync::Arc;
+use common::ast::{ASTBody, ASTNode, ElseType, NodeKind, Span};
+use common::ast::types::{BoolOperatorKind, ClassKind, ClassType, FunctionType, LiteralKind, MathOperatorKind, ReturnKind, TypeKind};
+use common::constants::{CLASS_CONSTRUCTOR_NAME, CLASS_SELF_REFERENCE, CORE_MODULE_ID, INTERNAL_PANIC, RESULT_ERR_VARIANT, RESULT_OK_VARIANT};
+use common::errors::{BuildErrorKind, LangError};
+use common::module::ModuleUID;
+use crate::class::ClassHierarchy;
+use crate::function::{Block, BlockId, Function, FunctionRef, GlobalRef, Instruction, InstructionKind, Local, LocalId, Operand, Terminator, Value};
+
+struct BlockBuilder {
+    instructions: Vec<Instruction>,
+    terminator: Option<(Terminator, Option<Span>)>,
+}
+
+/// A location that can be assigned
+enum Place {
+    Local(LocalId),
+    Global(GlobalRef),
+    /// The field of an object in memory
+    Field {
+        object: Operand,
+        class_type: Arc<ClassType>,
+        field: String,
+    },
+    /// The field of a data class value
+    DataField {
+        object: Box<Place>,
+        class_type: Arc<ClassType>,
+        field: String,
+    },
+    Element {
+        vector: Operand,
+        index: Operand,
+    },
+}
+
+/// Lowers the body of a function to a control flow graph, the expressions are flattened so that
+/// every value is computed from locals and constants
+pub(crate) struct FunctionLowering<'a> {
+    name: String,
+    module: ModuleUID,
+    classes: &'a ClassHierarchy,
+    param_count: u32,
+    ret: TypeKind,
+
+    locals: Vec<Local>,
+    blocks: Vec<BlockBuilder>,
+    /// The code after a terminator is dead, it is placed in a new block only if there is any
+    current: Option<BlockId>,
+
+    /// The variables visible from the current block of code, the last one with a name hides the previous ones
+    variables: Vec<(String, LocalId)>,
+    /// The block after each enclosing loop, the target of `break`
+    loop_exits: Vec<BlockId>,
+    span: Option<Span>,
+}
+
+impl<'a> FunctionLowering<'a> {
+    pub fn new(
+        name: String,
+        module: ModuleUID,
+        func_type: &FunctionType,
+        param_names: &[String],
+        method: Option<TypeKind>,
+        classes: &'a ClassHierarchy,
+    ) -> Self {
+        let mut lowering = Self {
+            name,
+            module,
+            classes,
+            param_count: 0,
+            ret: func_type.1.as_ref().clone(),
+
+            locals: Vec::new(),
+            blocks: Vec::new(),
+            current: None,
+
+            variables: Vec::new(),
+            loop_exits: Vec::new(),
+            span: None,
+        };
+
+        if let Some(method) = method {
+            lowering.declare(CLASS_SELF_REFERENCE.to_string(), method);
+        }
+
+        for (name, type_) in param_names.iter().zip(&func_type.0) {
+            lowering.declare(name.clone(), type_.clone());
+        }
+
+        lowering.param_count = lowering.locals.len() as u32;
+
+        let entry = lowering.new_block();
+        lowering.current = Some(entry);
+
+        lowering
+    }
+
+    pub fn lower(mut self, body: &ASTBody) -> Result<Function, LangError> {
+        self.lower_body(body)?;
+
+        // The parser checks that a function with a result returns on every path
+        match self.ret {
+            TypeKind::Nothing => self.terminate(Terminator::Return(Operand::Const(LiteralKind::Nothing))),
+            _ => self.terminate(Terminator::Unreachable),
+        }
+
+        Ok(self.finish())
+    }
+
+    /// Removes the blocks that can't be reached from the entry block
+    fn finish(self) -> Function {
+        let blocks = self.blocks
+            .into_iter()
+            .map(|block| {
+                let (terminator, terminator_span) = block.terminator.unwrap_or((Terminator::Unreachable, None));
+
+                Block {
+                    instructions: block.instructions,
+                    terminator,
+                    terminator_span,
+                }
+            })
+            .collect();
+
+        let mut function = Function {
+            name: self.name,
+            module: self.module,
+            param_count: self.param_count,
+            ret: self.ret,
+            locals: self.locals,
+            blocks,
+        };
+
+        function.remove_unreachable_blocks();
+
+        function
+    }
+
+    fn declare(&mut self, name: String, type_: TypeKind) -> LocalId {
+        let id = LocalId(self.locals.len() as u32);
+
+        self.locals.push(Local { name: Some(name.clone()), type_ });
+        self.variables.push((name, id));
+
+        id
+    }
+
+    fn new_temp(&mut self, type_: TypeKind) -> LocalId {
+        let id = LocalId(self.locals.len() as u32);
+        self.locals.push(Local { name: None, type_ });
+
+        id
+    }
+
+    fn get_variable(&self, name: &String) -> Option<LocalId> {
+        self.variables
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, id)| *id)
+    }
+
+    fn new_block(&mut self) -> BlockId {
+        self.blocks.push(BlockBuilder {
+            instructions: Vec::new(),
+            terminator: None,
+        });
+
+        BlockId(self.blocks.len() as u32 - 1)
+    }
+
+    fn switch_to(&mut self, block: BlockId) {
+        self.current = Some(block);
+    }
+
+    fn current_block(&mut self) -> BlockId {
+        match self.current {
+            Some(block) => block,
+            None => {
+                let block = self.new_block();
+                self.current = Some(block);
+                block
+            },
+        }
+    }
+
+    fn push(&mut self, kind: InstructionKind) {
+        let block = self.current_block();
+
+        self.blocks[block.0 as usize].instructions.push(Instruction {
+            kind,
+            span: self.span,
+        });
+    }
+
+    fn terminate(&mut self, terminator: Terminator) {
+        if let Some(block) = self.current.take() {
+            self.blocks[block.0 as usize].terminator = Some((terminator, self.span));
+        }
+    }
+
+    fn lower_body(&mut self, body: &ASTBody) -> Result<(), LangError> {
+        for node in body {
+            self.lower_statement(node)?;
+        }
+
+        Ok(())
+    }
+
+    /// The variables declared in the body are dropped at its end, so they stop hiding the previous ones
+    fn lower_scope(&mut self, body: &ASTBody) -> Result<(), LangError> {
+        let depth = self.variables.len();
+
+        self.lower_body(body)?;
+        self.variables.truncate(depth);
+
+        Ok(())
+    }
+
+    fn lower_statement(&mut self, node: &ASTNode) -> Result<(), LangError> {
+        let span = self.span;
+        if node.span.is_some() {
+            self.span = node.span;
+        }
+
+        match node.kind.as_ref() {
+            NodeKind::VariableDecl { name, value: node } => {
+                let value = self.lower_value(node)?;
+                let type_ = self.value_type(node, &value);
+
+                // The variable is visible only after its value
+                let local = self.declare(name.clone(), type_);
+                self.push(InstructionKind::Assign { local, value });
+            },
+            NodeKind::TupleDecl { names, value } => {
+                let types = match &value.eval_type {
+                    TypeKind::Tuple(types) => types.clone(),
+                    _ => return Err(LangError::build(BuildErrorKind::UnexpectedError(
+                        "lower_statement(TupleDecl): The value is not a tuple".to_string()))),
+                };
+
+                let tuple = Operand::Local(self.lower_local(value)?);
+
+                for (index, (name, type_)) in names.iter().zip(types).enumerate() {
+                    let local = self.declare(name.clone(), type_);
+                    self.push(InstructionKind::Assign {
+                        local,
+                        value: Value::TupleElement { tuple: tuple.clone(), index: index as u32 },
+                    });
+                }
+            },
+            NodeKind::VariableAsgn { module, name, value } => {
+                match self.get_variable(name) {
+                    Some(local) => {
+                        let value = self.lower_value(value)?;
+                        self.push(InstructionKind::Assign { local, value });
+                    },
+                    None => {
+                        let value = self.lower_operand(value)?;
+                        self.push(InstructionKind::SetGlobal {
+                            global: GlobalRef { module: *module, name: name.clone() },
+                            value,
+                        });
+                    },
+                }
+            },
+            NodeKind::ReturnStatement { value, kind: ReturnKind::Return } => {
+                let value = match value {
+                    Some(value) => self.lower_operand(value)?,
+                    None => Operand::Const(LiteralKind::Nothing),
+                };
+
+                self.terminate(Terminator::Return(value));
+            },
+            NodeKind::ReturnStatement { kind: ReturnKind::Break, .. } => {
+                let exit = match self.loop_exits.last() {
+                    Some(exit) => *exit,
+                    None => return Err(LangError::build(BuildErrorKind::Unsupported(
+                        "`break` outside of a loop".to_string()))),
+                };
+
+                self.terminate(Terminator::Jump(exit));
+            },
+            NodeKind::ReturnStatement { value, kind: ReturnKind::Panic } => {
+                let message = match value {
+                    Some(value) => self.lower_operand(value)?,
+                    None => return Err(LangError::build(BuildErrorKind::UnexpectedError(
+                        "lower_statement(Panic): Panic without a message".to_string()))),
+                };
+
+                self.push(InstructionKind::Eval(Value::Call {
+                    function: FunctionRef {
+                        module: ModuleUID::from_string(CORE_MODULE_ID.to_string()),
+                        name: INTERNAL_PANIC.to_string(),
+                    },
+                    args: vec![message],
+                }));
+
+                self.terminate(Terminator::Unreachable);
+            },
+            NodeKind::IfStatement { condition, body, else_ } => {
+                self.lower_if_statement(condition, body, else_)?;
+            },
+            NodeKind::ForStatement { left, right, body, iter_name } => {
+                let depth = self.variables.len();
+
+                let start = self.lower_value(left)?;
+                let iter = self.declare(iter_name.clone(), TypeKind::Int);
+                self.push(InstructionKind::Assign { local: iter, value: start });
+
+                let header = self.new_block();
+                let body_block = self.new_block();
+                let exit = self.new_block();
+
+                self.terminate(Terminator::Jump(header));
+                self.switch_to(header);
+
+                // The end of the range is evaluated again before every iteration
+                let end = self.lower_operand(right)?;
+                let condition = self.assign_temp(TypeKind::Bool, Value::Compare {
+                    operation: BoolOperatorKind::Smaller,
+                    left: Operand::Local(iter),
+                    right: end,
+                });
+
+                self.terminate(Terminator::Branch { condition, then: body_block, else_: exit });
+                self.switch_to(body_block);
+
+                self.loop_exits.push(exit);
+                self.lower_scope(body)?;
+                self.loop_exits.pop();
+
+                self.push(InstructionKind::Assign {
+                    local: iter,
+                    value: Value::Math {
+                        operation: MathOperatorKind::Plus,
+                        left: Operand::Local(iter),
+                        right: Operand::Const(LiteralKind::Int(1)),
+                    },
+                });
+
+                self.terminate(Terminator::Jump(header));
+                self.switch_to(exit);
+
+                self.variables.truncate(depth);
+            },
+            NodeKind::WhileStatement { condition, body } => {
+                let header = self.new_block();
+                let body_block = self.new_block();
+                let exit = self.new_block();
+
+                self.terminate(Terminator::Jump(header));
+                self.switch_to(header);
+
+                let condition = self.lower_operand(condition)?;
+
+                self.terminate(Terminator::Branch { condition, then: body_block, else_: exit });
+                self.switch_to(body_block);
+
+                self.loop_exits.push(exit);
+                self.lower_scope(body)?;
+                self.loop_exits.pop();
+
+                self.terminate(Terminator::Jump(header));
+                self.switch_to(exit);
+            },
+            NodeKind::FieldAsgn { variable, class_type, field_name, value } => {
+                let place = self.lower_field_place(variable, class_type, field_name)?;
+                let value = self.lower_operand(value)?;
+
+                self.write_place(place, value);
+            },
+            NodeKind::ValueFieldAssign { variable, offset, asgn_value } => {
+                let vector = self.lower_operand(variable)?;
+                let index = self.lower_operand(offset)?;
+                let value = self.lower_operand(asgn_value)?;
+
+                self.push(InstructionKind::SetElement { vector, index, value });
+            },
+            _ => {
+                // The value of an expression statement is discarded
+                match self.lower_value(node)? {
+                    Value::Use(_) => (),
+                    value => self.push(InstructionKind::Eval(value)),
+                }
+            },
+        }
+
+        self.span = span;
+
+        Ok(())
+    }
+
+    /// The field of an object, the object of a data class is itself a place since it is a value
+    fn lower_field_place(&mut self, variable: &ASTNode, class_type: &Arc<ClassType>, field_name: &str) -> Result<Place, LangError> {
+        Ok(match &class_type.kind {
+            ClassKind::Data => Place::DataField {
+                object: Box::new(self.lower_place(variable)?),
+                class_type: class_type.clone(),
+                field: field_name.to_string(),
+            },
+            _ => Place::Field {
+                object: self.lower_operand(variable)?,
+                class_type: class_type.clone(),
+                field: field_name.to_string(),
+            },
+        })
+    }
+
+    /// The operands of the place are computed once, so that it can be read and then written
+    fn lower_place(&mut self, node: &ASTNode) -> Result<Place, LangError> {
+        Ok(match node.kind.as_ref() {
+            NodeKind::VariableRef { module, name } => match self.get_variable(name) {
+                Some(local) => Place::Local(local),
+                None => Place::Global(GlobalRef { module: *module, name: name.clone() }),
+            },
+            NodeKind::FieldAccess { variable, class_type, field_name } => self.lower_field_place(variable, class_type, field_name)?,
+            NodeKind::ValueFieldAccess { variable, value } => Place::Element {
+                vector: self.lower_operand(variable)?,
+                index: self.lower_operand(value)?,
+            },
+            // A temporary value, the changes are lost with it
+            _ => Place::Local(self.lower_local(node)?),
+        })
+    }
+
+    /// The value of a place in a local, the fields of a data class are read from a local
+    fn read_place(&mut self, place: &Place, type_: TypeKind) -> LocalId {
+        let value = match place {
+            Place::Local(local) => return *local,
+            Place::Global(global) => Value::Global(global.clone()),
+            Place::Field { object, class_type, field } => Value::Field {
+                object: object.clone(),
+                class_type: class_type.clone(),
+                field: field.clone(),
+            },
+            Place::DataField { object, class_type, field } => Value::Field {
+                object: Operand::Local(self.read_place(object, TypeKind::Class(class_type.clone()))),
+                class_type: class_type.clone(),
+                field: field.clone(),
+            },
+            Place::Element { vector, index } => Value::Element {
+                vector: vector.clone(),
+                index: index.clone(),
+            },
+        };
+
+        let local = self.new_temp(type_);
+        self.push(InstructionKind::Assign { local, value });
+
+        local
+    }
+
+    /// Assigning the field of a data class updates a copy of the object, which is written back to its own place
+    fn write_place(&mut self, place: Place, value: Operand) {
+        match place {
+            Place::Local(local) => self.push(InstructionKind::Assign { local, value: Value::Use(value) }),
+            Place::Global(global) => self.push(InstructionKind::SetGlobal { global, value }),
+            Place::Field { object, class_type, field } => self.push(InstructionKind::SetField { object, class_type, field, value }),
+            Place::DataField { object, class_type, field } => {
+                let local = self.read_place(&object, TypeKind::Class(class_type.clone()));
+
+                self.push(InstructionKind::SetField {
+                    object: Operand::Local(local),
+                    class_type,
+                    field,
+                    value,
+                });
+
+                if !matches!(*object, Place::Local(_)) {
+                    self.write_place(*object, Operand::Local(local));
+                }
+            },
+            Place::Element { vector, index } => self.push(InstructionKind::SetElement { vector, index, value }),
+        }
+    }
+
+    fn lower_if_statement(&mut self, condition: &ASTNode, body: &ASTBody, else_: &ElseType) -> Result<(), LangError> {
+        let condition = self.lower_operand(condition)?;
+
+        let then = self.new_block();
+        let merge = self.new_block();
+        let else_block = match else_ {
+            ElseType::None => merge,
+            _ => self.new_block(),
+        };
+
+        self.terminate(Terminator::Branch { condition, then, else_: else_block });
+
+        self.switch_to(then);
+        self.lower_scope(body)?;
+        self.terminate(Terminator::Jump(merge));
+
+        match else_ {
+            ElseType::None => (),
+            ElseType::ElseIf { condition, body, else_ } => {
+                self.switch_to(else_block);
+                self.lower_if_statement(condition, body, else_)?;
+                self.terminate(Terminator::Jump(merge));
+            },
+            ElseType::Else { body } => {
+                self.switch_to(else_block);
+                self.lower_scope(body)?;
+                self.terminate(Terminator::Jump(merge));
+            },
+        }
+
+        self.switch_to(merge);
+
+        Ok(())
+    }
+
+    /// Places a value in a new temporary, values with no type are only evaluated
+    fn assign_temp(&mut self, type_: TypeKind, value: Value) -> Operand {
+        if let Value::Use(operand) = value {
+            return operand
+        }
+
+        if let TypeKind::Nothing = type_ {
+            if !value.is_pure() {
+                self.push(InstructionKind::Eval(value));
+            }
+
+            return Operand::Const(LiteralKind::Nothing)
+        }
+
+        let local = self.new_temp(type_);
+        self.push(InstructionKind::Assign { local, value });
+
+        Operand::Local(local)
+    }
+
+    fn lower_operand(&mut self, node: &ASTNode) -> Result<Operand, LangError> {
+        let value = self.lower_value(node)?;
+        let type_ = self.value_type(node, &value);
+
+        Ok(self.assign_temp(type_, value))
+    }
+
+    /// The parser predicts a float for the division of integers, but the integers are divided as integers
+    fn value_type(&self, node: &ASTNode, value: &Value) -> TypeKind {
+        match value {
+            Value::Math { left, right, .. } => match (self.operand_type(left), self.operand_type(right)) {
+                (TypeKind::Int, TypeKind::Int) => TypeKind::Int,
+                (TypeKind::Int | TypeKind::Float, TypeKind::Int | TypeKind::Float) => TypeKind::Float,
+                _ => node.eval_type.clone(),
+            },
+            _ => node.eval_type.clone(),
+        }
+    }
+
+    fn operand_type(&self, operand: &Operand) -> TypeKind {
+        match operand {
+            Operand::Local(id) => self.locals[id.0 as usize].type_.clone(),
+            Operand::Const(literal) => literal.into(),
+        }
+    }
+
+    /// Like `lower_operand` but the value is always in a local, the enum values are read in place
+    fn lower_local(&mut self, node: &ASTNode) -> Result<LocalId, LangError> {
+        match self.lower_operand(node)? {
+            Operand::Local(local) => Ok(local),
+            operand => {
+                let local = self.new_temp(node.eval_type.clone());
+                self.push(InstructionKind::Assign { local, value: Value::Use(operand) });
+
+                Ok(local)
+            },
+        }
+    }
+
+    fn lower_operands(&mut self, nodes: &ASTBody) -> Result<Vec<Operand>, LangError> {
+        nodes
+            .iter()
+            .map(|node| self.lower_operand(node))
+            .collect()
+    }
+
+    fn lower_value(&mut self, node: &ASTNode) -> Result<Value, LangError> {
+        let value = match node.kind.as_ref() {
+            NodeKind::Literal { value } => Value::Use(Operand::Const(value.clone())),
+            NodeKind::VariableRef { module, name } => match self.get_variable(name) {
+                Some(local) => Value::Use(Operand::Local(local)),
+                None => Value::Global(GlobalRef { module: *module, name: name.clone() }),
+            },
+            NodeKind::MathOperation { operation, left, right } => Value::Math {
+                operation: operation.clone(),
+                left: self.lower_operand(left)?,
+                right: self.lower_operand(right)?,
+            },
+            NodeKind::BoolOperation { operation, left, right } => Value::Compare {
+                operation: operation.clone(),
+                left: self.lower_operand(left)?,
+                right: self.lower_operand(right)?,
+            },
+            NodeKind::FunctionInvok { variable, parameters } => match variable.kind.as_ref() {
+                NodeKind::VariableRef { module, name } => Value::Call {
+                    function: FunctionRef { module: *module, name: name.clone() },
+                    args: self.lower_operands(parameters)?,
+                },
+                NodeKind::FieldAccess { variable, class_type, field_name } => {
+                    let mut args = vec![self.lower_operand(variable)?];
+                    args.extend(self.lower_operands(parameters)?);
+
+                    // The method may be inherited from a parent
+                    let owner = match class_type.find_method(field_name) {
+                        Some((owner, _)) => owner,
+                        None => class_type.clone(),
+                    };
+
+                    let function = FunctionRef {
+                        module: owner.module,
+                        name: format!("{}::{}", owner.name, field_name),
+                    };
+
+                    if class_type.kind == ClassKind::Normal && self.classes.is_overridden(class_type, field_name) {
+                        Value::CallVirtual {
+                            class_type: class_type.clone(),
+                            method: field_name.clone(),
+                            function,
+                            args,
+                        }
+                    } else {
+                        Value::Call { function, args }
+                    }
+                },
+                _ => return Err(LangError::build(BuildErrorKind::Unsupported("Not static function call".to_string()))),
+            },
+            NodeKind::FieldAccess { variable, class_type, field_name } => {
+                let object = match &class_type.kind {
+                    // Only the field is read from the variable
+                    ClassKind::Data => Operand::Local(self.lower_local(variable)?),
+                    _ => self.lower_operand(variable)?,
+                };
+
+                Value::Field {
+                    object,
+                    class_type: class_type.clone(),
+                    field: field_name.clone(),
+                }
+            },
+            NodeKind::VectorLiteral { values } => {
+                let element = match &node.eval_type {
+                    TypeKind::Vector(element) => element.as_ref().clone(),
+                    _ => values.first().map_or(TypeKind::Nothing, |value| value.eval_type.clone()),
+                };
+
+                Value::Vector {
+                    element,
+                    values: self.lower_operands(values)?,
+                }
+            },
+            NodeKind::TupleLiteral { values } => Value::Tuple(self.lower_operands(values)?),
+            NodeKind::ValueFieldAccess { variable, value } => Value::Element {
+                vector: self.lower_operand(variable)?,
+                index: self.lower_operand(value)?,
+            },
+            NodeKind::ConstructClass { parameters, class_type } => {
+                let object = self.new_temp(TypeKind::Class(class_type.clone()));
+                self.push(InstructionKind::Assign { local: object, value: Value::NewObject(class_type.clone()) });
+
+                // A class without a constructor uses the one of its parent
+                let constructor = match class_type.kind {
+                    ClassKind::Normal => class_type.find_method(CLASS_CONSTRUCTOR_NAME),
+                    _ => None,
+                };
+
+                if let Some((owner, _)) = constructor {
+                    let mut args = vec![Operand::Local(object)];
+                    args.extend(self.lower_operands(parameters)?);
+
+                    self.push(InstructionKind::Eval(Value::Call {
+                        function: FunctionRef {
+                            module: owner.module,
+                            name: format!("{}::{}", owner.name, CLASS_CONSTRUCTOR_NAME),
+                        },
+                        args,
+                    }));
+                }
+
+                Value::Use(Operand::Local(object))
+            },
+            NodeKind::ParentInvok { class_type, method_name, parameters } => {
+                let this = match self.get_variable(&CLASS_SELF_REFERENCE.to_string()) {
+                    Some(local) => local,
+                    None => return Err(LangError::build(BuildErrorKind::UnexpectedError(
+                        "lower_value(ParentInvok): `this` not found".to_string()))),
+                };
+
+                let mut args = vec![Operand::Local(this)];
+                args.extend(self.lower_operands(parameters)?);
+
+                let owner = match class_type.find_method(method_name) {
+                    Some((owner, _)) => owner,
+                    None => class_type.clone(),
+                };
+
+                Value::Call {
+                    function: FunctionRef {
+                        module: owner.module,
+                        name: format!("{}::{}", owner.name, method_name),
+                    },
+                    args,
+                }
+            },
+            NodeKind::ConstructEnumVariant { value, variant_id, enum_type, .. } => Value::Variant {
+                enum_type: enum_type.clone(),
+                variant: *variant_id,
+                value: self.lower_operand(value)?,
+            },
+            NodeKind::ResultPropagation { value, result_type } => {
+                let (ok_type, err_type) = match &value.eval_type {
+                    TypeKind::Enum(enum_type) => match enum_type.result_types() {
+                        Some(types) => types,
+                        None => return Err(LangError::build(BuildErrorKind::InvalidStackType)),
+                    },
+                    _ => return Err(LangError::build(BuildErrorKind::InvalidStackType)),
+                };
+
+                let (ok_id, err_id) = match (result_type.get_variant(RESULT_OK_VARIANT), result_type.get_variant(RESULT_ERR_VARIANT)) {
+                    (Some((ok_id, _)), Some((err_id, _))) => (ok_id, err_id),
+                    _ => return Err(LangError::build(BuildErrorKind::UnexpectedError(
+                        "lower_value(ResultPropagation): Result variants not found".to_string()))),
+                };
+
+                let result = Operand::Local(self.lower_local(value)?);
+
+                let discriminant = self.assign_temp(TypeKind::Int, Value::Discriminant(result.clone()));
+                let is_err = self.assign_temp(TypeKind::Bool, Value::Compare {
+                    operation: BoolOperatorKind::Equal,
+                    left: discriminant,
+                    right: Operand::Const(LiteralKind::Int(err_id as i32)),
+                });
+
+                let err_block = self.new_block();
+                let ok_block = self.new_block();
+
+                self.terminate(Terminator::Branch { condition: is_err, then: err_block, else_: ok_block });
+
+                // The error is returned in the result of the function
+                self.switch_to(err_block);
+
+                let err = self.assign_temp(err_type, Value::Payload { value: result.clone(), variant: err_id });
+                let ret = self.assign_temp(TypeKind::Enum(result_type.clone()), Value::Variant {
+                    enum_type: result_type.clone(),
+                    variant: err_id,
+                    value: err,
+                });
+
+                self.terminate(Terminator::Return(ret));
+
+                // Otherwise the value is unwrapped
+                self.switch_to(ok_block);
+
+                match ok_type {
+                    TypeKind::Nothing => Value::Use(Operand::Const(LiteralKind::Nothing)),
+                    _ => Value::Payload { value: result, variant: ok_id },
+                }
+            },
+            NodeKind::FieldAsgn { .. } |
+            NodeKind::ValueFieldAssign { .. } |
+            NodeKind::VariableDecl { .. } |
+            NodeKind::TupleDecl { .. } |
+            NodeKind::VariableAsgn { .. } |
+            NodeKind::ReturnStatement { .. } |
+            NodeKind::IfStatement { .. } |
+            NodeKind::ForStatement { .. } |
+            NodeKind::WhileStatement { .. } => {
+                self.lower_statement(node)?;
+
+                Value::Use(Operand::Const(LiteralKind::Nothing))
+            },
+            NodeKind::ObjectLiteral { .. } => return Err(LangError::build(BuildErrorKind::Unsupported("Object literals".to_string()))),
+            NodeKind::FunctionLiteral { .. } => return Err(LangError::build(BuildErrorKind::Unsupported("Function literals".to_string()))),
+        };
+
+        Ok(value)
+    }
+}
+
+/// The type of a method includes the object as its first parameter
+pub(crate) fn method_type(class_type: &TypeKind, func_type: &FunctionType) -> FunctionType {
+    let mut params = vec![class_type.clone()];
+    params.extend(func_type.0.iter().cloned());
+
+    FunctionType(params, func_type.1.clone())
+}
+
@@ -0,0 +1,414 @@
+use common::ast::fold::{fold_compare, fold_math};
+use common::ast::types::{LiteralKind, TypeKind};
+use crate::function::{BlockId, Function, InstructionKind, Local, LocalId, Operand, Terminator, Value};
+
+/// Computes the operations on constants and replaces the locals that always hold the same constant,
+/// then removes the code that can't be reached anymore
+pub(crate) fn fold_constants(function: &mut Function) {
+    loop {
+        let mut changed = propagate_constants(function);
+        changed |= fold_values(function);
+        changed |= remove_dead_assignments(function);
+        changed |= thread_jumps(function);
+        changed |= fold_branches(function);
+
+        if !changed { break }
+    }
+
+    merge_blocks(function);
+    remove_unused_locals(function);
+}
+
+/// A local assigned only once with a constant holds that constant wherever it is read, since
+/// the variables can't be read before their declaration
+fn propagate_constants(function: &mut Function) -> bool {
+    let assignments = function.local_assignments();
+    let mut constants: Vec<Option<LiteralKind>> = vec![None; function.locals.len()];
+
+    for block in &function.blocks {
+        for instruction in &block.instructions {
+            if let InstructionKind::Assign { local, value: Value::Use(Operand::Const(literal)) } = &instruction.kind {
+                let id = local.0 as usize;
+
+                // Strings are placed in the data every time they are used
+                let same_type = matches!((&function.locals[id].type_, literal),
+                    (TypeKind::Int, LiteralKind::Int(_)) |
+                    (TypeKind::Float, LiteralKind::Float(_)) |
+                    (TypeKind::Bool, LiteralKind::Bool(_)));
+
+                if same_type && assignments[id] == 1 {
+                    constants[id] = Some(literal.clone());
+                }
+            }
+        }
+    }
+
+    let mut changed = false;
+
+    for block in &mut function.blocks {
+        let operands = block.instructions
+            .iter_mut()
+            .flat_map(|instruction| instruction.kind.operands_mut())
+            .chain(block.terminator.operands_mut());
+
+        for operand in operands {
+            if let Operand::Local(local) = operand {
+                if let Some(literal) = &constants[local.0 as usize] {
+                    *operand = Operand::Const(literal.clone());
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    changed
+}
+
+fn fold_values(function: &mut Function) -> bool {
+    let mut changed = false;
+
+    for block in &mut function.blocks {
+        for instruction in &mut block.instructions {
+            let value = match &mut instruction.kind {
+                InstructionKind::Assign { value, .. } |
+                InstructionKind::Eval(value) => value,
+                _ => continue,
+            };
+
+            let literal = match value {
+                Value::Math { operation, left: Operand::Const(left), right: Operand::Const(right) } => fold_math(operation, left, right),
+                Value::Compare { operation, left: Operand::Const(left), right: Operand::Const(right) } => fold_compare(operation, left, right),
+                _ => None,
+            };
+
+            if let Some(literal) = literal {
+                *value = Value::Use(Operand::Const(literal));
+                changed = true;
+            }
+        }
+    }
+
+    changed
+}
+
+/// A branch on a constant or to a single block always goes the same way, the other side is removed if nothing else reaches it
+fn fold_branches(function: &mut Function) -> bool {
+    let mut changed = false;
+
+    for block in &mut function.blocks {
+        let target = match &block.terminator {
+            Terminator::Branch { condition: Operand::Const(LiteralKind::Bool(condition)), then, else_ } => if *condition { *then } else { *else_ },
+            Terminator::Branch { then, else_, .. } if then == else_ => *then,
+            _ => continue,
+        };
+
+        block.terminator = Terminator::Jump(target);
+        changed = true;
+    }
+
+    if changed {
+        function.remove_unreachable_blocks();
+    }
+
+    changed
+}
+
+/// The branches to an empty block that only jumps go directly to the target of the jump
+fn thread_jumps(function: &mut Function) -> bool {
+    let forward: Vec<Option<BlockId>> = function.blocks
+        .iter()
+        .map(|block| match block.terminator {
+            Terminator::Jump(target) if block.instructions.is_empty() => Some(target),
+            _ => None,
+        })
+        .collect();
+
+    // The chain is bounded so that the empty loops are left as they are
+    let resolve = |mut block: BlockId| {
+        for _ in 0..forward.len() {
+            match forward[block.0 as usize] {
+                Some(target) if target != block => block = target,
+                _ => break,
+            }
+        }
+
+        block
+    };
+
+    let mut changed = false;
+
+    for block in &mut function.blocks {
+        block.terminator.map_blocks(|target| {
+            let resolved = resolve(target);
+            changed |= resolved != target;
+            resolved
+        });
+    }
+
+    if changed {
+        function.remove_unreachable_blocks();
+    }
+
+    changed
+}
+
+/// The values assigned to locals never read are only computed for their side effects
+fn remove_dead_assignments(function: &mut Function) -> bool {
+    let uses = function.local_uses();
+    let mut changed = false;
+
+    for block in &mut function.blocks {
+        let instructions = std::mem::take(&mut block.instructions);
+
+        for mut instruction in instructions {
+            instruction.kind = match instruction.kind {
+                InstructionKind::Assign { local, value } if uses[local.0 as usize] == 0 => {
+                    changed = true;
+
+                    if value.is_pure() { continue }
+                    InstructionKind::Eval(value)
+                },
+                InstructionKind::Eval(value) if value.is_pure() => {
+                    changed = true;
+                    continue
+                },
+                kind => kind,
+            };
+
+            block.instructions.push(instruction);
+        }
+    }
+
+    changed
+}
+
+/// A block with a single predecessor that jumps to it is appended to that predecessor
+fn merge_blocks(function: &mut Function) {
+    loop {
+        let mut predecessors = vec![0u32; function.blocks.len()];
+        for block in &function.blocks {
+            for successor in block.terminator.successors() {
+                predecessors[successor.0 as usize] += 1;
+            }
+        }
+
+        let merge = function.blocks
+            .iter()
+            .enumerate()
+            .find_map(|(id, block)| match block.terminator {
+                // The entry block has an implicit predecessor
+                Terminator::Jump(target) if target.0 != 0 && target.0 as usize != id && predecessors[target.0 as usize] == 1 => Some((id, target)),
+                _ => None,
+            });
+
+        let (id, target) = match merge {
+            Some(merge) => merge,
+            None => break,
+        };
+
+        let merged = &mut function.blocks[target.0 as usize];
+        let instructions = std::mem::take(&mut merged.instructions);
+        let terminator = std::mem::replace(&mut merged.terminator, Terminator::Unreachable);
+        let terminator_span = merged.terminator_span;
+
+        let block = &mut function.blocks[id];
+        block.instructions.extend(instructions);
+        block.terminator = terminator;
+        block.terminator_span = terminator_span;
+
+        function.remove_unreachable_blocks();
+    }
+}
+
+/// The locals that are never read nor assigned are removed, the others are numbered again in the same order
+fn remove_unused_locals(function: &mut Function) {
+    let mut used = vec![false; function.locals.len()];
+
+    for id in 0..function.param_count {
+        used[id as usize] = true;
+    }
+
+    for block in &function.blocks {
+        for instruction in &block.instructions {
+            if let InstructionKind::Assign { local, .. } = &instruction.kind {
+                used[local.0 as usize] = true;
+            }
+        }
+    }
+
+    for (id, uses) in function.local_uses().iter().enumerate() {
+        if *uses > 0 { used[id] = true }
+    }
+
+    let mut new_ids = Vec::with_capacity(used.len());
+    let mut count = 0;
+    for is_used in &used {
+        new_ids.push(LocalId(count));
+        if *is_used { count += 1 }
+    }
+
+    let map = |local: &mut LocalId| *local = new_ids[local.0 as usize];
+
+    for block in &mut function.blocks {
+        for instruction in &mut block.instructions {
+            if let InstructionKind::Assign { local, .. } = &mut instruction.kind {
+                map(local);
+            }
+
+            for operand in instruction.kind.operands_mut() {
+                if let Operand::Local(local) = operand { map(local) }
+            }
+        }
+
+        for operand in block.terminator.operands_mut() {
+            if let Operand::Local(local) = operand { map(local) }
+        }
+    }
+
+    let locals: Vec<Local> = std::mem::take(&mut function.locals);
+
+    function.locals = locals
+        .into_iter()
+        .zip(used)
+        .filter(|(_, is_used)| *is_used)
+        .map(|(local, _)| local)
+        .collect();
+}
+
+
+#[cfg(test)]
+mod tests {
+    use common::ast::types::{BoolOperatorKind, LiteralKind, MathOperatorKind, TypeKind};
+    use crate::function::{BlockId, Function, InstructionKind, Operand, Terminator, Value};
+    use crate::optimize::test_utils::{assign, block, int, jump, local, math, new_function};
+    use common::ast::fold::{fold_compare, fold_math};
+    use super::{fold_constants, merge_blocks, thread_jumps};
+
+    fn returned(function: &Function) -> Option<&LiteralKind> {
+        match function.blocks.as_slice() {
+            [block] if block.instructions.is_empty() => match &block.terminator {
+                Terminator::Return(Operand::Const(literal)) => Some(literal),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn wrapping_arithmetic() {
+        let mut function = new_function("f", 0, vec![TypeKind::Int], vec![
+            block(vec![assign(0, math(MathOperatorKind::Plus, int(i32::MAX), int(1)))], Terminator::Return(local(0))),
+        ]);
+        fold_constants(&mut function);
+
+        assert_eq!(returned(&function), Some(&LiteralKind::Int(i32::MIN)));
+        assert!(function.locals.is_empty());
+
+        let fold = |operation, left, right| fold_math(&operation, &LiteralKind::Int(left), &LiteralKind::Int(right));
+        assert_eq!(fold(MathOperatorKind::Minus, i32::MIN, 1), Some(LiteralKind::Int(i32::MAX)));
+        assert_eq!(fold(MathOperatorKind::Multiply, i32::MAX, 2), Some(LiteralKind::Int(-2)));
+        assert_eq!(fold(MathOperatorKind::Power, 2, 3), None);
+    }
+
+    #[test]
+    fn trapping_division_is_left_to_run() {
+        let traps = [
+            (MathOperatorKind::Divide, 1, 0),
+            (MathOperatorKind::Divide, i32::MIN, -1),
+            (MathOperatorKind::Modulus, 1, 0),
+            (MathOperatorKind::Modulus, i32::MIN, -1),
+        ];
+
+        for (operation, left, right) in traps {
+            let mut function = new_function("f", 0, vec![TypeKind::Int], vec![
+                block(vec![assign(0, math(operation, int(left), int(right)))], Terminator::Return(int(0))),
+            ]);
+            fold_constants(&mut function);
+
+            // The result is unused but the trap still happens
+            let instructions = &function.blocks[0].instructions;
+            assert_eq!(instructions.len(), 1);
+            assert!(matches!(instructions[0].kind, InstructionKind::Eval(Value::Math { .. })));
+        }
+
+        // The divisions truncate like the wasm instructions
+        let fold = |operation, left, right| fold_math(&operation, &LiteralKind::Int(left), &LiteralKind::Int(right));
+        assert_eq!(fold(MathOperatorKind::Divide, -7, 2), Some(LiteralKind::Int(-3)));
+        assert_eq!(fold(MathOperatorKind::Modulus, -7, 2), Some(LiteralKind::Int(-1)));
+    }
+
+    #[test]
+    fn float_operations() {
+        let compare = |operation, left, right| fold_compare(&operation, &left, &right);
+
+        // The integers are converted to `f32` when compared to floats
+        assert_eq!(compare(BoolOperatorKind::Equal, LiteralKind::Int(16777217), LiteralKind::Float(16777216.0)), Some(LiteralKind::Bool(true)));
+        assert_eq!(compare(BoolOperatorKind::Smaller, LiteralKind::Float(0.1), LiteralKind::Float(0.2)), Some(LiteralKind::Bool(true)));
+        assert_eq!(compare(BoolOperatorKind::Equal, LiteralKind::Float(f32::NAN), LiteralKind::Float(f32::NAN)), Some(LiteralKind::Bool(false)));
+        assert_eq!(compare(BoolOperatorKind::Different, LiteralKind::Float(f32::NAN), LiteralKind::Float(f32::NAN)), Some(LiteralKind::Bool(true)));
+        assert_eq!(compare(BoolOperatorKind::BiggerEq, LiteralKind::Float(f32::NAN), LiteralKind::Float(1.0)), Some(LiteralKind::Bool(false)));
+
+        assert_eq!(fold_math(&MathOperatorKind::Divide, &LiteralKind::Int(1), &LiteralKind::Float(0.0)), Some(LiteralKind::Float(f32::INFINITY)));
+        assert_eq!(fold_math(&MathOperatorKind::Modulus, &LiteralKind::Float(5.5), &LiteralKind::Float(2.0)), None);
+    }
+
+    #[test]
+    fn constant_branches_are_removed() {
+        let mut function = new_function("f", 0, vec![TypeKind::Int, TypeKind::Bool], vec![
+            block(vec![
+                assign(0, Value::Use(int(2))),
+                assign(1, Value::Compare { operation: BoolOperatorKind::Bigger, left: local(0), right: int(1) }),
+            ], Terminator::Branch { condition: local(1), then: BlockId(1), else_: BlockId(2) }),
+            block(vec![], Terminator::Return(int(10))),
+            block(vec![], Terminator::Return(int(20))),
+        ]);
+        fold_constants(&mut function);
+
+        assert_eq!(returned(&function), Some(&LiteralKind::Int(10)));
+    }
+
+    #[test]
+    fn jumps_are_threaded() {
+        let mut function = new_function("f", 1, vec![TypeKind::Bool, TypeKind::Int], vec![
+            block(vec![], Terminator::Branch { condition: local(0), then: BlockId(1), else_: BlockId(2) }),
+            block(vec![], jump(3)),
+            block(vec![assign(1, Value::Use(int(5)))], jump(3)),
+            block(vec![], Terminator::Return(local(1))),
+        ]);
+
+        assert!(thread_jumps(&mut function));
+
+        // The empty block is removed and the others are numbered again
+        assert_eq!(function.blocks.len(), 3);
+        assert!(matches!(function.blocks[0].terminator, Terminator::Branch { then: BlockId(2), else_: BlockId(1), .. }));
+        assert!(matches!(function.blocks[1].terminator, Terminator::Jump(BlockId(2))));
+
+        // An empty loop is left as it is
+        let mut empty_loop = new_function("f", 0, vec![], vec![
+            block(vec![], jump(1)),
+            block(vec![], jump(1)),
+        ]);
+
+        assert!(!thread_jumps(&mut empty_loop));
+        assert!(matches!(empty_loop.blocks[1].terminator, Terminator::Jump(BlockId(1))));
+    }
+
+    #[test]
+    fn blocks_are_merged() {
+        let mut function = new_function("f", 1, vec![TypeKind::Bool, TypeKind::Int], vec![
+            block(vec![], Terminator::Branch { condition: local(0), then: BlockId(1), else_: BlockId(2) }),
+            block(vec![assign(1, Value::Use(int(1)))], jump(3)),
+            block(vec![assign(1, Value::Use(int(2)))], jump(3)),
+            block(vec![assign(1, math(MathOperatorKind::Plus, local(1), int(1)))], jump(4)),
+            block(vec![], Terminator::Return(local(1))),
+        ]);
+        merge_blocks(&mut function);
+
+        // Only the last block has a single predecessor
+        assert_eq!(function.blocks.len(), 4);
+        assert_eq!(function.blocks[3].instructions.len(), 1);
+        assert!(matches!(function.blocks[3].terminator, Terminator::Return(Operand::Local(_))));
+        assert!(matches!(function.blocks[1].terminator, Terminator::Jump(BlockId(3))));
+        assert!(matches!(function.blocks[2].terminator, Terminator::Jump(BlockId(3))));
+    }
+}
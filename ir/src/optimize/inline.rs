@@ -0,0 +1,278 @@
+use common::ast::types::LiteralKind;
+use crate::function::{Block, BlockId, Function, FunctionRef, Instruction, InstructionKind, Local, LocalId, Operand, Terminator, Value};
+use crate::program::Program;
+
+/// Functions with at most this many instructions, counting the terminators, are inlined
+const INLINE_MAX_SIZE: usize = 12;
+
+/// Replaces the calls to the small functions by their bodies. The bodies are taken before inlining
+/// anything, so the calls brought by an inlined body are left as they are and recursion can't expand forever
+pub(crate) fn inline_small_functions(program: &mut Program) {
+    let mut candidates = Vec::new();
+
+    for table in &program.modules {
+        for symbol in &table.functions {
+            let body = match &symbol.body {
+                Some(body) if body.size() <= INLINE_MAX_SIZE => body,
+                _ => continue,
+            };
+
+            let function = FunctionRef {
+                module: table.uid,
+                name: symbol.name.clone(),
+            };
+
+            if !body.callees().contains(&&function) {
+                candidates.push((function, body.clone()));
+            }
+        }
+    }
+
+    for table in &mut program.modules {
+        for symbol in &mut table.functions {
+            if let Some(body) = &mut symbol.body {
+                let caller = FunctionRef {
+                    module: table.uid,
+                    name: symbol.name.clone(),
+                };
+
+                inline_calls(body, &caller, &candidates);
+            }
+        }
+    }
+}
+
+fn inline_calls(function: &mut Function, caller: &FunctionRef, candidates: &[(FunctionRef, Function)]) {
+    // The blocks holding code of the caller, the inlined blocks are not visited again
+    let mut to_visit: Vec<BlockId> = (0..function.blocks.len() as u32).map(BlockId).collect();
+
+    while let Some(block) = to_visit.pop() {
+        let found = function.block(block).instructions
+            .iter()
+            .enumerate()
+            .find_map(|(index, instruction)| {
+                let (callee, args) = match &instruction.kind {
+                    InstructionKind::Assign { value: Value::Call { function, args }, .. } |
+                    InstructionKind::Eval(Value::Call { function, args }) => (function, args),
+                    _ => return None,
+                };
+
+                if callee == caller {
+                    return None
+                }
+
+                candidates
+                    .iter()
+                    .find(|(function, body)| function == callee && body.param_count as usize == args.len())
+                    .map(|(_, body)| (index, body))
+            });
+
+        if let Some((index, callee)) = found {
+            to_visit.push(inline_call(function, block, index, callee));
+        }
+    }
+}
+
+/// Splits the block at the call, the arguments are assigned to copies of the locals of the callee
+/// and its returns jump to the rest of the block. Returns the block holding the rest
+fn inline_call(function: &mut Function, block: BlockId, index: usize, callee: &Function) -> BlockId {
+    let instructions = &mut function.blocks[block.0 as usize].instructions;
+    let rest = instructions.split_off(index + 1);
+    let call = instructions.pop().unwrap();
+
+    let (destination, args) = match call.kind {
+        InstructionKind::Assign { local, value: Value::Call { args, .. } } => (Some(local), args),
+        InstructionKind::Eval(Value::Call { args, .. }) => (None, args),
+        _ => unreachable!(),
+    };
+
+    // The inlined code is mapped to the line of the call
+    let span = call.span;
+
+    let local_base = function.locals.len() as u32;
+    function.locals.extend(callee.locals
+        .iter()
+        .map(|local| Local { name: None, type_: local.type_.clone() }));
+
+    let rest_block = BlockId(function.blocks.len() as u32);
+    let block_base = rest_block.0 + 1;
+
+    let current = &mut function.blocks[block.0 as usize];
+
+    for (param, arg) in args.into_iter().enumerate() {
+        current.instructions.push(Instruction {
+            kind: InstructionKind::Assign { local: LocalId(local_base + param as u32), value: Value::Use(arg) },
+            span,
+        });
+    }
+
+    let terminator = std::mem::replace(&mut current.terminator, Terminator::Jump(BlockId(block_base)));
+    let terminator_span = std::mem::replace(&mut current.terminator_span, span);
+
+    function.blocks.push(Block {
+        instructions: rest,
+        terminator,
+        terminator_span,
+    });
+
+    for callee_block in &callee.blocks {
+        let mut instructions: Vec<Instruction> = callee_block.instructions
+            .iter()
+            .cloned()
+            .map(|mut instruction| {
+                if let InstructionKind::Assign { local, .. } = &mut instruction.kind {
+                    local.0 += local_base;
+                }
+
+                for operand in instruction.kind.operands_mut() {
+                    if let Operand::Local(local) = operand { local.0 += local_base }
+                }
+
+                instruction.span = span;
+                instruction
+            })
+            .collect();
+
+        let mut terminator = callee_block.terminator.clone();
+        terminator.map_blocks(|target| BlockId(target.0 + block_base));
+
+        for operand in terminator.operands_mut() {
+            if let Operand::Local(local) = operand { local.0 += local_base }
+        }
+
+        let terminator = match terminator {
+            Terminator::Return(value) => {
+                match (destination, value) {
+                    (_, Operand::Const(LiteralKind::Nothing)) |
+                    (None, _) => (),
+                    (Some(local), value) => instructions.push(Instruction {
+                        kind: InstructionKind::Assign { local, value: Value::Use(value) },
+                        span,
+                    }),
+                }
+
+                Terminator::Jump(rest_block)
+            },
+            terminator => terminator,
+        };
+
+        function.blocks.push(Block {
+            instructions,
+            terminator,
+            terminator_span: span,
+        });
+    }
+
+    rest_block
+}
+
+#[cfg(test)]
+mod tests {
+    use common::ast::types::{BoolOperatorKind, MathOperatorKind, TypeKind};
+    use crate::function::{BlockId, Function, FunctionRef, InstructionKind, LocalId, Operand, Terminator, Value};
+    use crate::optimize::test_utils::{assign, block, int, local, math, module, new_function};
+    use super::inline_calls;
+
+    fn reference(name: &str) -> FunctionRef {
+        FunctionRef {
+            module: module(),
+            name: name.to_string(),
+        }
+    }
+
+    fn call(name: &str, args: Vec<Operand>) -> Value {
+        Value::Call { function: reference(name), args }
+    }
+
+    fn calls(function: &Function) -> usize {
+        function.callees().len()
+    }
+
+    /// `abs(a Int) Int`, which has a branch
+    fn abs() -> Function {
+        new_function("abs", 1, vec![TypeKind::Int, TypeKind::Bool, TypeKind::Int], vec![
+            block(vec![
+                assign(1, Value::Compare { operation: BoolOperatorKind::Smaller, left: local(0), right: int(0) }),
+            ], Terminator::Branch { condition: local(1), then: BlockId(1), else_: BlockId(2) }),
+            block(vec![assign(2, math(MathOperatorKind::Minus, int(0), local(0)))], Terminator::Return(local(2))),
+            block(vec![], Terminator::Return(local(0))),
+        ])
+    }
+
+    #[test]
+    fn inlined_locals_and_blocks_are_renumbered() {
+        let mut function = new_function("f", 1, vec![TypeKind::Int, TypeKind::Int], vec![
+            block(vec![
+                assign(1, call("abs", vec![local(0)])),
+                assign(1, math(MathOperatorKind::Plus, local(1), int(1))),
+            ], Terminator::Return(local(1))),
+        ]);
+        inline_calls(&mut function, &reference("f"), &[(reference("abs"), abs())]);
+
+        assert_eq!(calls(&function), 0);
+        assert_eq!(function.locals.len(), 5);
+        assert_eq!(function.blocks.len(), 5);
+
+        // The argument is assigned to the copy of the parameter, then the body is run
+        let entry = &function.blocks[0];
+        assert!(matches!(entry.instructions[0].kind,
+            InstructionKind::Assign { local: LocalId(2), value: Value::Use(Operand::Local(LocalId(0))) }));
+        assert!(matches!(entry.terminator, Terminator::Jump(BlockId(2))));
+
+        // The rest of the caller
+        let rest = &function.blocks[1];
+        assert_eq!(rest.instructions.len(), 1);
+        assert!(matches!(rest.terminator, Terminator::Return(Operand::Local(LocalId(1)))));
+
+        // The body of the callee, with its locals and blocks after the ones of the caller
+        assert!(matches!(function.blocks[2].instructions[0].kind, InstructionKind::Assign { local: LocalId(3), .. }));
+        assert!(matches!(function.blocks[2].terminator,
+            Terminator::Branch { condition: Operand::Local(LocalId(3)), then: BlockId(3), else_: BlockId(4) }));
+
+        // The returns assign the destination of the call and jump to the rest
+        for id in [3, 4] {
+            let returned = function.blocks[id].instructions.last().unwrap();
+            assert!(matches!(returned.kind, InstructionKind::Assign { local: LocalId(1), value: Value::Use(Operand::Local(_)) }));
+            assert!(matches!(function.blocks[id].terminator, Terminator::Jump(BlockId(1))));
+        }
+    }
+
+    #[test]
+    fn every_call_of_a_block_is_inlined() {
+        let one = new_function("one", 0, vec![], vec![
+            block(vec![], Terminator::Return(int(1))),
+        ]);
+        let mut function = new_function("f", 0, vec![TypeKind::Int], vec![
+            block(vec![
+                InstructionKind::Eval(call("one", vec![])),
+                assign(0, call("one", vec![])),
+            ], Terminator::Return(local(0))),
+        ]);
+        inline_calls(&mut function, &reference("f"), &[(reference("one"), one)]);
+
+        assert_eq!(calls(&function), 0);
+        assert_eq!(function.blocks.len(), 5);
+    }
+
+    #[test]
+    fn recursive_calls_are_not_inlined() {
+        let body = new_function("f", 1, vec![TypeKind::Int, TypeKind::Int], vec![
+            block(vec![assign(1, call("f", vec![local(0)]))], Terminator::Return(local(1))),
+        ]);
+        let mut function = body.clone();
+        inline_calls(&mut function, &reference("f"), &[(reference("f"), body)]);
+
+        assert_eq!(calls(&function), 1);
+        assert_eq!(function.blocks.len(), 1);
+    }
+
+    #[test]
+    fn calls_with_other_arguments_are_not_inlined() {
+        let mut function = new_function("f", 0, vec![TypeKind::Int], vec![
+            block(vec![assign(0, call("abs", vec![int(1), int(2)]))], Terminator::Return(local(0))),
+        ]);
+        inline_calls(&mut function, &reference("f"), &[(reference("abs"), abs())]);
+
+        assert_eq!(calls(&function), 1);
+    }
+}
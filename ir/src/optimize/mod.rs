@@ -0,0 +1,129 @@
+use crate::function::{Function, FunctionRef};
+use crate::program::Program;
+
+mod fold;
+mod inline;
+
+/// How much a program is optimized, the levels of `-O`
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd)]
+pub enum OptLevel {
+    /// The functions are translated as they are written
+    #[default]
+    O0,
+    /// The constants are folded and propagated, the unreachable code and the unused functions are removed
+    O1,
+    /// The small functions are also inlined in their callers
+    O2,
+}
+
+impl OptLevel {
+    /// The level of `-O<level>`
+    pub fn from_level(level: u8) -> Option<Self> {
+        match level {
+            0 => Some(OptLevel::O0),
+            1 => Some(OptLevel::O1),
+            2 => Some(OptLevel::O2),
+            _ => None,
+        }
+    }
+}
+
+impl Program {
+    /// Runs the passes of the level on every module, `roots` are the functions used from outside the program,
+    /// the functions they don't call directly or indirectly are removed
+    pub fn optimize(&mut self, level: OptLevel, roots: &[FunctionRef]) {
+        if level == OptLevel::O0 {
+            return
+        }
+
+        self.for_each_body(fold::fold_constants);
+
+        if level >= OptLevel::O2 {
+            inline::inline_small_functions(self);
+
+            // The arguments of the inlined calls are often constants
+            self.for_each_body(fold::fold_constants);
+        }
+
+        self.remove_unused_functions(roots);
+    }
+
+    fn for_each_body(&mut self, mut pass: impl FnMut(&mut Function)) {
+        for table in &mut self.modules {
+            for symbol in &mut table.functions {
+                if let Some(body) = &mut symbol.body {
+                    pass(body);
+                }
+            }
+        }
+    }
+
+    /// The imported functions are kept, they are only declarations
+    fn remove_unused_functions(&mut self, roots: &[FunctionRef]) {
+        let usage = self.usage(roots);
+
+        for table in &mut self.modules {
+            let uid = table.uid;
+
+            table.functions.retain(|symbol| symbol.body.is_none() || usage.uses_function(uid, &symbol.name));
+        }
+    }
+}
+
+/// Builds the small functions checked by the tests of the passes
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use common::ast::types::{LiteralKind, MathOperatorKind, TypeKind};
+    use common::module::ModuleUID;
+    use crate::function::{Block, BlockId, Function, Instruction, InstructionKind, Local, LocalId, Operand, Terminator, Value};
+
+    pub(crate) fn module() -> ModuleUID {
+        ModuleUID::from_string("test".to_string())
+    }
+
+    /// The first `param_count` locals are the parameters
+    pub(crate) fn new_function(name: &str, param_count: u32, locals: Vec<TypeKind>, blocks: Vec<Block>) -> Function {
+        Function {
+            name: name.to_string(),
+            module: module(),
+            param_count,
+            ret: TypeKind::Int,
+            locals: locals
+                .into_iter()
+                .map(|type_| Local { name: None, type_ })
+                .collect(),
+            blocks,
+        }
+    }
+
+    pub(crate) fn block(instructions: Vec<InstructionKind>, terminator: Terminator) -> Block {
+        Block {
+            instructions: instructions
+                .into_iter()
+                .map(|kind| Instruction { kind, span: None })
+                .collect(),
+            terminator,
+            terminator_span: None,
+        }
+    }
+
+    pub(crate) fn assign(local: u32, value: Value) -> InstructionKind {
+        InstructionKind::Assign { local: LocalId(local), value }
+    }
+
+    pub(crate) fn math(operation: MathOperatorKind, left: Operand, right: Operand) -> Value {
+        Value::Math { operation, left, right }
+    }
+
+    pub(crate) fn local(id: u32) -> Operand {
+        Operand::Local(LocalId(id))
+    }
+
+    pub(crate) fn int(value: i32) -> Operand {
+        Operand::Const(LiteralKind::Int(value))
+    }
+
+    pub(crate) fn jump(target: u32) -> Terminator {
+        Terminator::Jump(BlockId(target))
+    }
+}
@@ -0,0 +1,225 @@
+use std::sync::Arc;
+use common::ast::types::{Attribute, FunctionType, LiteralKind, TypeKind};
+use common::errors::LangError;
+use common::module::{FunctionDefinition, Module, ModuleFeature, ModuleUID};
+use crate::class::ClassHierarchy;
+use crate::function::{Function, FunctionRef, GlobalRef};
+use crate::lower::{method_type, FunctionLowering};
+
+pub struct FunctionSymbol {
+    pub name: String,
+    /// The type of a method includes the object as its first parameter
+    pub type_: FunctionType,
+    pub attributes: Vec<Attribute>,
+    /// The functions declared without a body are imported from the host
+    pub body: Option<Function>,
+}
+
+pub struct GlobalSymbol {
+    pub name: String,
+    pub type_: TypeKind,
+    pub attributes: Vec<Attribute>,
+    /// The variables declared without a value are imported from the host
+    pub value: Option<LiteralKind>,
+}
+
+/// The functions and global variables defined by a module, sorted by name
+pub struct SymbolTable {
+    pub uid: ModuleUID,
+    pub functions: Vec<FunctionSymbol>,
+    pub globals: Vec<GlobalSymbol>,
+}
+
+/// The functions and the global variables used by some roots, directly or through the functions they call
+pub struct Usage {
+    pub functions: Vec<FunctionRef>,
+    pub globals: Vec<GlobalRef>,
+}
+
+impl Usage {
+    pub fn uses_function(&self, module: ModuleUID, name: &str) -> bool {
+        self.functions
+            .iter()
+            .any(|function| function.module == module && function.name == name)
+    }
+
+    pub fn uses_global(&self, module: ModuleUID, name: &str) -> bool {
+        self.globals
+            .iter()
+            .any(|global| global.module == module && global.name == name)
+    }
+}
+
+/// Every module lowered to the IR, classes and enums are only types so they have no symbol
+pub struct Program {
+    pub modules: Vec<SymbolTable>,
+    pub classes: ClassHierarchy,
+}
+
+impl Program {
+    pub fn lower(modules: &[Arc<Module>]) -> Result<Program, LangError> {
+        let classes = ClassHierarchy::new(modules);
+        let mut tables = Vec::with_capacity(modules.len());
+
+        for module in modules {
+            tables.push(SymbolTable::lower(module, &classes)?);
+        }
+
+        Ok(Program {
+            modules: tables,
+            classes,
+        })
+    }
+
+    pub fn get_module(&self, uid: ModuleUID) -> Option<&SymbolTable> {
+        self.modules
+            .iter()
+            .find(|table| table.uid == uid)
+    }
+
+    pub fn get_function(&self, function: &FunctionRef) -> Option<&FunctionSymbol> {
+        self.get_module(function.module)?.get_function(&function.name)
+    }
+
+    pub fn get_global(&self, global: &GlobalRef) -> Option<&GlobalSymbol> {
+        self.get_module(global.module)?.get_global(&global.name)
+    }
+
+    /// Follows the calls from the roots, the imported functions are used but have no body to follow.
+    /// The methods in the vtable of a class are used by the functions creating its objects
+    pub fn usage(&self, roots: &[FunctionRef]) -> Usage {
+        let mut usage = Usage {
+            functions: roots.to_vec(),
+            globals: Vec::new(),
+        };
+
+        let mut to_visit = roots.to_vec();
+
+        while let Some(function) = to_visit.pop() {
+            let body = match self.get_function(&function).and_then(|symbol| symbol.body.as_ref()) {
+                Some(body) => body,
+                None => continue,
+            };
+
+            let vtables: Vec<_> = body.classes()
+                .iter()
+                .map(|class_type| self.classes.vtable(class_type))
+                .collect();
+
+            let callees = body.callees()
+                .into_iter()
+                .chain(vtables.iter().flat_map(|vtable| vtable.functions()));
+
+            for callee in callees {
+                if !usage.functions.contains(callee) {
+                    usage.functions.push(callee.clone());
+                    to_visit.push(callee.clone());
+                }
+            }
+
+            for global in body.globals() {
+                if !usage.globals.contains(global) {
+                    usage.globals.push(global.clone());
+                }
+            }
+        }
+
+        usage
+    }
+}
+
+impl SymbolTable {
+    fn lower(module: &Module, classes: &ClassHierarchy) -> Result<SymbolTable, LangError> {
+        let mut functions = Vec::new();
+        let mut globals = Vec::new();
+
+        for (name, feature) in &module.features {
+            match feature {
+                ModuleFeature::Function(func) => {
+                    functions.push(Self::lower_func(module.uid, name.clone(), func.metadata.clone(), func, None, classes)?);
+                },
+                ModuleFeature::Variable(var) => globals.push(GlobalSymbol {
+                    name: name.clone(),
+                    type_: var.metadata.clone(),
+                    attributes: var.attributes.clone(),
+                    value: var.data.clone(),
+                }),
+                ModuleFeature::Class(class) => {
+                    let class_type = TypeKind::Class(class.metadata.clone());
+
+                    for (method_name, method) in &class.data.methods {
+                        let name = format!("{}::{}", class.metadata.name, method_name);
+                        functions.push(Self::lower_method(module.uid, name, &class_type, method, classes)?);
+                    }
+                },
+                ModuleFeature::Enum(enum_def) => {
+                    let enum_type = TypeKind::Enum(enum_def.metadata.clone());
+
+                    for (method_name, method) in &enum_def.methods {
+                        let name = format!("{}::{}", enum_def.metadata.name, method_name);
+                        functions.push(Self::lower_method(module.uid, name, &enum_type, method, classes)?);
+                    }
+                },
+                ModuleFeature::Constant(_) => (),
+            }
+        }
+
+        functions.sort_by(|a, b| a.name.cmp(&b.name));
+        globals.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(SymbolTable {
+            uid: module.uid,
+            functions,
+            globals,
+        })
+    }
+
+    /// The static methods are lowered like plain functions, the others get `this` as their first parameter
+    fn lower_method(
+        module: ModuleUID,
+        name: String,
+        this: &TypeKind,
+        def: &FunctionDefinition,
+        classes: &ClassHierarchy,
+    ) -> Result<FunctionSymbol, LangError> {
+        match def.attributes.contains(&Attribute::Static) {
+            true => Self::lower_func(module, name, def.metadata.clone(), def, None, classes),
+            false => Self::lower_func(module, name, method_type(this, &def.metadata), def, Some(this.clone()), classes),
+        }
+    }
+
+    fn lower_func(
+        module: ModuleUID,
+        name: String,
+        type_: FunctionType,
+        def: &FunctionDefinition,
+        method: Option<TypeKind>,
+        classes: &ClassHierarchy,
+    ) -> Result<FunctionSymbol, LangError> {
+        let body = match &def.data {
+            Some(data) => Some(FunctionLowering::new(name.clone(), module, &def.metadata, &data.parameters, method, classes).lower(&data.body)?),
+            None => None,
+        };
+
+        Ok(FunctionSymbol {
+            name,
+            type_,
+            attributes: def.attributes.clone(),
+            body,
+        })
+    }
+
+    pub fn get_function(&self, name: &String) -> Option<&FunctionSymbol> {
+        self.functions
+            .binary_search_by(|func| func.name.cmp(name))
+            .ok()
+            .map(|i| &self.functions[i])
+    }
+
+    pub fn get_global(&self, name: &String) -> Option<&GlobalSymbol> {
+        self.globals
+            .binary_search_by(|global| global.name.cmp(name))
+            .ok()
+            .map(|i| &self.globals[i])
+    }
+}
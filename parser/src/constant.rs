@@ -0,0 +1,15 @@
+use common::ast::{ASTNode, NodeKind};
+use common::ast::fold::{fold_compare, fold_math};
+use common::ast::types::LiteralKind;
+
+/// Computes the value of the expression of a constant. Only literals, the other constants
+/// (already replaced by their value) and the operators between them are known at compile time.
+/// The operations are folded like the optimizer does, the ones it leaves to run are not constants
+pub(crate) fn eval_constant(node: &ASTNode) -> Option<LiteralKind> {
+    match node.kind.as_ref() {
+        NodeKind::Literal { value } => Some(value.clone()),
+        NodeKind::MathOperation { operation, left, right } => fold_math(operation, &eval_constant(left)?, &eval_constant(right)?),
+        NodeKind::BoolOperation { operation, left, right } => fold_compare(operation, &eval_constant(left)?, &eval_constant(right)?),
+        _ => None,
+    }
+}
@@ -2,4 +2,5 @@ pub mod modules;
 pub mod parser_scope;
 pub mod parser_module_scope;
 mod utils;
+mod constant;
 mod errors;
\ No newline at end of file
@@ -0,0 +1,77 @@
+use common::ast::types::{Attribute, AttributeArgs};
+use common::errors::LangError;
+use common::module::{Module, ModuleFeature, ModuleUID};
+
+/// Information about where a custom attribute is used
+pub struct AttributeContext<'a> {
+    pub module: ModuleUID,
+    /// Name of the feature with the attribute (or of the class of the member with the attribute)
+    pub feature_name: &'a str,
+    /// Name of the class member with the attribute
+    pub member_name: Option<&'a str>,
+    pub args: &'a AttributeArgs,
+}
+
+/// Handles a custom attribute (`@Name(arg, key = value)`).
+/// It is called for every feature and class member marked with the attribute after the module
+/// has been parsed, so it can validate or transform the feature before the module gets built
+pub trait AttributeProcessor {
+    /// The name of the handled attribute without the `@`
+    fn name(&self) -> &str;
+
+    fn process(&self, context: AttributeContext, feature: &mut ModuleFeature) -> Result<(), LangError>;
+}
+
+pub(crate) fn process_attributes(processors: &Vec<Box<dyn AttributeProcessor>>, module: &mut Module) -> Result<(), LangError> {
+    if processors.is_empty() {
+        return Ok(())
+    }
+
+    let uid = module.uid;
+
+    for (name, feature) in module.features.iter_mut() {
+        // The attributes are collected first because the processors can modify the feature
+        let mut attributes: Vec<(Option<String>, Attribute)> = feature.attributes()
+            .iter()
+            .map(|attribute| (None, attribute.clone()))
+            .collect();
+
+        if let ModuleFeature::Class(class) = feature {
+            for (method_name, method) in &class.data.methods {
+                for attribute in &method.attributes {
+                    attributes.push((Some(method_name.clone()), attribute.clone()));
+                }
+            }
+
+            for (field_name, field_attributes) in &class.data.field_attributes {
+                for attribute in field_attributes {
+                    attributes.push((Some(field_name.clone()), attribute.clone()));
+                }
+            }
+        }
+
+        for (member_name, attribute) in &attributes {
+            let (attribute_name, args) = match attribute {
+                Attribute::Custom(attribute_name, args) => (attribute_name, args),
+                _ => continue,
+            };
+
+            for processor in processors {
+                if processor.name() != attribute_name {
+                    continue
+                }
+
+                let context = AttributeContext {
+                    module: uid,
+                    feature_name: name,
+                    member_name: member_name.as_deref(),
+                    args,
+                };
+
+                processor.process(context, feature)?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
 pub mod module_preparser;
 pub mod module_loader;
 pub mod module_importer;
+pub mod attribute_processor;
 mod module_parser;
-mod preparsing_utils;
+pub(crate) mod preparsing_utils;
@@ -10,4 +10,16 @@ pub trait ModuleImporter {
 
     /// Returns the code of a module as a string
     fn load_module(&self, identifier: &ModuleIdentifier) -> Option<String>;
-}
\ No newline at end of file
+
+    /// Returns the code of a declaration module (a module with only signatures) as a string.
+    /// Example: "dom" -> content of "dom.d.rn". By default the importer has no declaration module
+    fn load_declaration_module(&self, _identifier: &ModuleIdentifier) -> Option<String> {
+        None
+    }
+
+    /// Returns the path of the file of a module relative to the source directory, used by the source maps.
+    /// Example: "dom" declaration -> "dom.d.rn". Without a path the code is embedded in the source maps
+    fn get_source_path(&self, _identifier: &ModuleIdentifier, _declaration: bool) -> Option<String> {
+        None
+    }
+}
@@ -4,29 +4,49 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use common::ast::types::ClassType;
 use common::module::{Module, ModuleUID};
-use common::errors::{LoadErrorKind, format_load, LangErrorFormat};
+use common::errors::{LangError, LoadErrorKind, format_load, LangErrorFormat};
 use common::module::ModuleIdentifier;
+use common::cfg::CfgOptions;
 use tokenizer::tokenizer::Tokenizer;
 use crate::modules::module_preparser::ModulePreParser;
 use crate::modules::module_importer::ModuleImporter;
 use crate::modules::module_parser::ModuleParser;
+use crate::modules::attribute_processor::{process_attributes, AttributeProcessor};
 use common::ast::parsing_types::{ParsableFunctionType, ParsableType};
-use common::parsable_types::ParsableModule;
+use common::parsable_types::{LineIndex, ModuleSource, ParsableModule};
 
 // TODO: Move this to the core crate
 
 /// This handles the loading and dependency loading of modules
 pub struct ModuleLoader {
     modules: RefCell<HashMap<ModuleUID, Arc<Module>>>,
+    attribute_processors: Vec<Box<dyn AttributeProcessor>>,
+    cfg: CfgOptions,
 }
 
 impl ModuleLoader {
     pub fn new() -> Self {
         Self {
             modules: RefCell::new(HashMap::new()),
+            attribute_processors: Vec::new(),
+            cfg: CfgOptions::new(),
         }
     }
 
+    /// The configuration used to evaluate the `@Cfg` attributes of the modules loaded afterwards
+    pub fn cfg(&self) -> &CfgOptions {
+        &self.cfg
+    }
+
+    pub fn cfg_mut(&mut self) -> &mut CfgOptions {
+        &mut self.cfg
+    }
+
+    /// Registers a handler for a custom attribute, it will be used for all the modules loaded afterwards
+    pub fn register_attribute_processor(&mut self, processor: Box<dyn AttributeProcessor>) {
+        self.attribute_processors.push(processor);
+    }
+
     pub fn insert_module(&mut self, uid: ModuleUID, module: Arc<Module>) {
         self.modules
             .borrow_mut()
@@ -36,18 +56,34 @@ impl ModuleLoader {
     pub fn load_module_with_source(&mut self, id: ModuleIdentifier, uid: ModuleUID, source: &String, importer: &impl ModuleImporter)
         -> anyhow::Result<(Arc<Module>, Vec<Arc<Module>>)>
     {
-        let tokens = match Tokenizer::tokenize(&source) {
-            Ok(tokens) => tokens,
-            Err(err) => return Err(err.format(&source)),
-        };
-        let parsable_module = match ModulePreParser::prepare_module(tokens, id, uid) {
-            Ok(module) => Arc::new(module),
-            Err(err) => return Err(err.format(&source))
-        };
-        let parser = self.create_parser(parsable_module.clone(), importer)?;
+        let parsable_module = Arc::new(self.prepare_module(id, uid, None, None, source)?);
+
+        self.load_parsable_module(parsable_module, source, importer)
+    }
+
+    /// Loads a module containing only declarations of features provided by the host,
+    /// all of them will be imported from the host module `import_module`
+    pub fn load_declaration_module_with_source(
+        &mut self,
+        id: ModuleIdentifier,
+        uid: ModuleUID,
+        import_module: String,
+        source: &String,
+        importer: &impl ModuleImporter,
+    ) -> anyhow::Result<(Arc<Module>, Vec<Arc<Module>>)> {
+        let parsable_module = Arc::new(self.prepare_module(id, uid, Some(import_module), None, source)?);
+
+        self.load_parsable_module(parsable_module, source, importer)
+    }
+
+    fn load_parsable_module(&mut self, parsable_module: Arc<ParsableModule>, source: &String, importer: &impl ModuleImporter)
+        -> anyhow::Result<(Arc<Module>, Vec<Arc<Module>>)>
+    {
+        let uid = parsable_module.uid;
+        let parser = self.create_parser(parsable_module, importer)?;
 
         // Loading the main module
-        let module = match parser.parse_module(uid, importer) {
+        let module = match self.parse_module(&parser, uid, importer) {
             Ok(module) => Arc::new(module),
             Err(err) => return Err(err.format(&source)),
         };
@@ -60,7 +96,7 @@ impl ModuleLoader {
         let mut dependencies = Vec::new();
 
         for import_uid in &module.imports {
-            let module = match parser.parse_module(*import_uid, importer) {
+            let module = match self.parse_module(&parser, *import_uid, importer) {
                 Ok(module) => Arc::new(module),
                 Err(err) => return Err(err.format(&source)),
             };
@@ -75,6 +111,47 @@ impl ModuleLoader {
         Ok((module, dependencies))
     }
 
+    /// Tokenizes and preparses a source, when `import_module` is set the module is a declaration module.
+    /// `path` is the file loaded by the importer, without it the source is kept for the source maps
+    fn prepare_module(
+        &self,
+        id: ModuleIdentifier,
+        uid: ModuleUID,
+        import_module: Option<String>,
+        path: Option<String>,
+        source: &String,
+    ) -> anyhow::Result<ParsableModule> {
+        let tokens = match Tokenizer::tokenize(source) {
+            Ok(tokens) => tokens,
+            Err(err) => return Err(err.format(source)),
+        };
+
+        let parsable_module = match import_module {
+            Some(import_module) => ModulePreParser::prepare_declaration_module(tokens, id, uid, import_module, &self.cfg),
+            None => ModulePreParser::prepare_module(tokens, id, uid, &self.cfg),
+        };
+
+        match parsable_module {
+            Ok(mut module) => {
+                module.lines = LineIndex::new(source);
+                module.source = match path {
+                    Some(path) => ModuleSource::Path(path),
+                    None => ModuleSource::Code(source.clone()),
+                };
+                Ok(module)
+            },
+            Err(err) => Err(err.format(source)),
+        }
+    }
+
+    fn parse_module(&self, parser: &ModuleParser, uid: ModuleUID, importer: &impl ModuleImporter) -> Result<Module, LangError> {
+        let mut module = parser.parse_module(uid, importer)?;
+
+        process_attributes(&self.attribute_processors, &mut module)?;
+
+        Ok(module)
+    }
+
     pub fn load_module(&mut self, id: &ModuleIdentifier, importer: &impl ModuleImporter) -> anyhow::Result<(Arc<Module>, Vec<Arc<Module>>)> {
         let uid = match importer.get_unique_identifier(id) {
             Some(uid) => uid,
@@ -91,34 +168,38 @@ impl ModuleLoader {
             None => return Err(anyhow!(format_load(LoadErrorKind::LoadModuleError(id.0.clone()))))
         };
 
-        self.load_module_with_source(id.clone(), uid, &source, importer)
-    }
-
-    // pub fn load_declaration_module(
-    //     &mut self,
-    //     id: &ModuleIdentifier,
-    //     module_id: &ModuleIdentifier,
-    //     importer: &impl ModuleImporter
-    // ) -> anyhow::Result<(ModuleUID, Option<Arc<Module>>)> {
-    //     let module_uid = ModuleUID::from_string(module_id.0.clone());
-    //
-    //     // If cached then simply return
-    //     if self.modules.borrow().contains_key(&module_uid) {
-    //         return Ok((module_uid, None))
-    //     }
-    //
-    //     let source = match importer.load_module(id, true) {
-    //         Some(source) => source,
-    //         None => return Err(anyhow!(format_load(LoadErrorKind::LoadModuleError(id.0.clone()))))
-    //     };
-    //
-    //     let res = self.load_declaration_module_with_source(module_id.clone(), module_uid, &source, importer);
-    //
-    //     match res {
-    //         Ok(res) => Ok((module_uid, Some(res))),
-    //         Err(err) => Err(anyhow!(format_error(&source, err))),
-    //     }
-    // }
+        let path = importer.get_source_path(id, false);
+        let parsable_module = Arc::new(self.prepare_module(id.clone(), uid, None, path, &source)?);
+
+        self.load_parsable_module(parsable_module, &source, importer)
+    }
+
+    pub fn load_declaration_module(
+        &mut self,
+        id: &ModuleIdentifier,
+        import_module: String,
+        importer: &impl ModuleImporter,
+    ) -> anyhow::Result<(Arc<Module>, Vec<Arc<Module>>)> {
+        let uid = match importer.get_unique_identifier(id) {
+            Some(uid) => uid,
+            None => return Err(anyhow!(format_load(LoadErrorKind::ModuleNotFound(id.0.clone()))))
+        };
+
+        // If cached then simply return
+        if let Some(module) = self.modules.borrow().get(&uid) {
+            return Ok((module.clone(), Vec::new()))
+        }
+
+        let source = match importer.load_declaration_module(id) {
+            Some(source) => source,
+            None => return Err(anyhow!(format_load(LoadErrorKind::LoadModuleError(id.0.clone()))))
+        };
+
+        let path = importer.get_source_path(id, true);
+        let parsable_module = Arc::new(self.prepare_module(id.clone(), uid, Some(import_module), path, &source)?);
+
+        self.load_parsable_module(parsable_module, &source, importer)
+    }
 
     fn create_parser(&self, module: Arc<ParsableModule>, importer: &impl ModuleImporter) -> anyhow::Result<ModuleParser> {
         let mut modules = Vec::new();
@@ -138,27 +219,45 @@ impl ModuleLoader {
     ) -> anyhow::Result<()> {
 
         for import in &module.imports {
+            let import = &import.id;
             let uid = match importer.get_unique_identifier(import) {
                 Some(uid) => uid,
                 None => return Err(anyhow!(format_load(LoadErrorKind::ModuleNotFound(import.0.clone())))),
             };
 
-            if let Some(module) = self.modules.borrow().get(&uid) {
-                vec.push(module.parsable_module.clone());
+            // Already added by another import
+            if vec.iter().any(|module| module.uid == uid) {
                 continue
             }
 
-            let source = match importer.load_module(&import) {
-                Some(source) => source,
-                None => return Err(anyhow!(format_load(LoadErrorKind::LoadModuleError(import.0.clone()))))
-            };
-            let tokens = Tokenizer::tokenize(&source)?;
+            let cached = self.modules
+                .borrow()
+                .get(&uid)
+                .map(|module| module.parsable_module.clone());
 
-            let parsable_module = match ModulePreParser::prepare_module(tokens, import.clone(), uid) {
-                Ok(module) => Arc::new(module),
-                Err(err) => return Err(err.format(&source))
+            // The imports of a cached module are needed to resolve its types
+            if let Some(module) = cached {
+                vec.push(module.clone());
+                self.load_imports(vec, &module, importer)?;
+                continue
+            }
+
+            // If there is no definition module with this name then search for a declaration module
+            let (source, declaration) = match importer.load_module(import) {
+                Some(source) => (source, false),
+                None => match importer.load_declaration_module(import) {
+                    Some(source) => (source, true),
+                    None => return Err(anyhow!(format_load(LoadErrorKind::LoadModuleError(import.0.clone())))),
+                },
+            };
+            let import_module = match declaration {
+                true => Some(import.0.clone()),
+                false => None,
             };
 
+            let path = importer.get_source_path(import, declaration);
+            let parsable_module = Arc::new(self.prepare_module(import.clone(), uid, import_module, path, &source)?);
+
             self.load_imports(vec, &parsable_module, importer)?;
 
             vec.push(parsable_module);
@@ -1,24 +1,28 @@
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::sync::Arc;
-use common::ast::types::{Class, Function, FunctionType, LiteralKind, ClassType, TypeKind, EnumType};
-use common::constants::CLASS_SELF_REFERENCE;
+use common::ast::parsing_types::ParsableType;
+use common::ast::types::{is_public, Attribute, Class, ClassKind, Function, FunctionType, LiteralKind, ClassType, TypeKind, EnumType};
+use common::constants::{CLASS_CONSTRUCTOR_NAME, CLASS_SELF_REFERENCE};
 use common::errors::{BuildErrorKind, LangError, LoadErrorKind, ParserErrorKind};
-use common::module::{ClassDefinition, FunctionDefinition, Module, ModuleFeature, ModuleUID, VariableDefinition};
+use common::module::{ClassDefinition, ConstantDefinition, EnumDefinition, FunctionDefinition, Module, ModuleFeature, ModuleUID, VariableDefinition};
 use common::tokens::{Token, TokenKind};
 use common::tokens_iterator::Tokens;
 use crate::errors::ParsingErrorHelper;
 use crate::modules::module_importer::ModuleImporter;
 use crate::modules::module_loader::ModuleLoader;
-use common::parsable_types::ParsableModule;
+use common::parsable_types::{ImportKind, ParsableConstant, ParsableFunction, ParsableImport, ParsableModule};
+use crate::constant::eval_constant;
 use crate::parser_scope::ParserScope;
-use crate::parser_module_scope::ModuleParserScope;
+use crate::parser_module_scope::{GlobalKind, ModuleParserScope};
 use crate::utils::TokensExtensions;
 
 pub struct ParsingModule {
     pub classes: HashMap<String, Arc<ClassType>>,
     pub enums: HashMap<String, Arc<EnumType>>,
     pub module: Arc<ParsableModule>,
+    /// The values of the constants declared by the module, computed when the module is loaded
+    pub constants: RefCell<Vec<(String, LiteralKind)>>,
 
     // Indicates whether the module is loaded or not (this means that all the types are already parsed)
     pub loaded: Cell<bool>,
@@ -52,6 +56,10 @@ impl<'a> ModuleParser<'a> {
                     kind: class.kind.clone(),
                     fields: Default::default(),
                     methods: Default::default(),
+                    static_methods: Default::default(),
+                    constants: Default::default(),
+                    public_members: Default::default(),
+                    parent: Default::default(),
                 });
                 types.insert(name.clone(), class_type);
             }
@@ -61,7 +69,12 @@ impl<'a> ModuleParser<'a> {
             for (name, _) in &module.enums {
                 let enum_type = EnumType {
                     name: name.clone(),
+                    module: module.uid,
                     variants: Default::default(),
+                    methods: Default::default(),
+                    static_methods: Default::default(),
+                    constants: Default::default(),
+                    public_members: Default::default(),
                 };
 
                 enums.insert(name.clone(), Arc::new(enum_type));
@@ -72,6 +85,7 @@ impl<'a> ModuleParser<'a> {
                 enums,
 
                 module: module.clone(),
+                constants: Default::default(),
                 loaded: Cell::new(false),
             });
         }
@@ -91,6 +105,23 @@ impl<'a> ModuleParser<'a> {
 
         let mut features = HashMap::new();
 
+        for (name, constant) in &module.module.constants {
+            let data = match module.constants.borrow().iter().find(|(n, _)| n == name) {
+                Some((_, value)) => value.clone(),
+                None => return Err(LangError::build(BuildErrorKind::UnexpectedError(
+                    format!("parse_module: The constant {} is not loaded", name)))),
+            };
+
+            features.insert(
+                name.clone(),
+                ModuleFeature::Constant(ConstantDefinition {
+                    attributes: constant.attributes.clone(),
+                    data,
+                    metadata: module_scope.convert_parsable_type(&constant.type_kind)?,
+                })
+            );
+        }
+
         for (name, var) in &module.module.variables {
             let metadata = module_scope.convert_parsable_type(&var.type_kind)?;
 
@@ -129,7 +160,7 @@ impl<'a> ModuleParser<'a> {
                 Some(body) => {
                     let mut tokens = module.module.tokens.new_clone(body);
 
-                    let scope = module_scope.new_child();
+                    let scope = module_scope.new_function_child(metadata.1.as_ref().clone());
 
                     let token = &tokens.peek().unwrap();
 
@@ -162,60 +193,45 @@ impl<'a> ModuleParser<'a> {
         for (name, class) in &module.module.classes {
             let class_type = module_scope.get_class(name)?;
 
-            let mut methods = Vec::new();
-
-            for (name, method) in &class.methods {
-                let metadata = module_scope.convert_parsable_func_type(&method.func_type)?;
-
-                let data = match method.body {
-                    Some(body) => {
-                        let mut tokens = module.module.tokens.new_clone(body);
-
-                        let scope = module_scope.new_child();
+            Self::check_overrides(&class_type)?;
 
-                        let token = &tokens.peek().unwrap();
+            let methods = Self::parse_methods(module, &module_scope, &class.methods, TypeKind::Class(class_type.clone()))?;
 
-                        let data = Self::parse_function_value(
-                            &mut tokens,
-                            &scope,
-                            &method.params,
-                            metadata.clone(),
-                            Some(class_type.clone()))?;
-
-                        if !scope.eval_type.borrow().is_compatible(&metadata.1) {
-                            return Err(LangError::wrong_type(&token, &metadata.1, &scope.eval_type.into_inner()));
-                        }
-
-                        Some(data)
-                    },
-                    None => None,
-                };
-
-                methods.push((
-                    name.clone(),
-                    FunctionDefinition {
-                        attributes: Vec::new(),
-                        data,
-                        metadata: metadata.clone(),
-                    }
-                ));
-            }
+            let field_attributes = class.fields
+                .iter()
+                .map(|(name, field)| (name.clone(), field.attributes.clone()))
+                .collect();
 
             features.insert(
                 name.clone(),
                 ModuleFeature::Class(ClassDefinition {
                     attributes: class.attributes.clone(),
-                    data: Class::new(methods),
+                    data: Class::new(methods, field_attributes),
                     metadata: class_type,
                 })
             );
         }
 
+        for (name, parsable_enum) in &module.module.enums {
+            let enum_type = module_scope.get_enum(name)?;
+
+            let methods = Self::parse_methods(module, &module_scope, &parsable_enum.methods, TypeKind::Enum(enum_type.clone()))?;
+
+            features.insert(
+                name.clone(),
+                ModuleFeature::Enum(EnumDefinition {
+                    attributes: parsable_enum.attributes.clone(),
+                    methods,
+                    metadata: enum_type,
+                })
+            );
+        }
+
         let mut imports = Vec::new();
         for import in &module.module.imports {
-            let uid = match importer.get_unique_identifier(import) {
+            let uid = match importer.get_unique_identifier(&import.id) {
                 Some(uid) => uid,
-                None => return Err(LangError::load(LoadErrorKind::LoadModuleError(import.0.clone()))),
+                None => return Err(LangError::load(LoadErrorKind::LoadModuleError(import.id.0.clone()))),
             };
 
             imports.push(uid);
@@ -224,6 +240,7 @@ impl<'a> ModuleParser<'a> {
         let module = Module {
             id: module.module.id.clone(),
             uid,
+            kind: module.module.kind.clone(),
 
             imports,
             features,
@@ -233,52 +250,117 @@ impl<'a> ModuleParser<'a> {
         Ok(module)
     }
 
-    fn create_scope(&self, parsing_module: &ParsingModule, uid: ModuleUID, importer: &impl ModuleImporter) -> Result<ModuleParserScope, LangError> {
-        let mut scope = ModuleParserScope::new(uid);
+    /// Parses the methods of a class or an enum, the static methods don't get `this`
+    fn parse_methods(
+        module: &ParsingModule,
+        module_scope: &ModuleParserScope,
+        methods: &Vec<(String, ParsableFunction)>,
+        this: TypeKind,
+    ) -> Result<Vec<(String, FunctionDefinition)>, LangError> {
+        let mut definitions = Vec::new();
+
+        for (name, method) in methods {
+            let metadata = module_scope.convert_parsable_func_type(&method.func_type)?;
+
+            let this = match method.attributes.contains(&Attribute::Static) {
+                true => None,
+                false => Some(this.clone()),
+            };
 
-        let module = &parsing_module.module;
+            let data = match method.body {
+                Some(body) => {
+                    let mut tokens = module.module.tokens.new_clone(body);
 
-        // Adds all the types to the scope (they still don't contain anything)
-        for (name, _) in &module.classes {
-            let class_type = match parsing_module.classes.get(name) {
-                Some(class) => class.clone(),
-                _ => return Err(LangError::build(BuildErrorKind::UnexpectedError("create_scope: variable is not a class".to_string()))),
-            };
+                    let scope = module_scope.new_function_child(metadata.1.as_ref().clone());
 
-            scope.declare_class(name.clone(), class_type);
-        }
+                    let token = &tokens.peek().unwrap();
 
-        for (name, _) in &module.enums {
-            let enum_type = match parsing_module.enums.get(name) {
-                Some(enum_type) => enum_type.clone(),
-                _ => return Err(LangError::build(BuildErrorKind::UnexpectedError("create_scope: variable is not an enum".to_string()))),
+                    let data = Self::parse_function_value(
+                        &mut tokens,
+                        &scope,
+                        &method.params,
+                        metadata.clone(),
+                        this)?;
+
+                    if !scope.eval_type.borrow().is_compatible(&metadata.1) {
+                        return Err(LangError::wrong_type(token, &metadata.1, &scope.eval_type.into_inner()));
+                    }
+
+                    Some(data)
+                },
+                None => None,
             };
 
-            scope.declare_enum(name.clone(), enum_type);
+            definitions.push((
+                name.clone(),
+                FunctionDefinition {
+                    attributes: method.attributes.clone(),
+                    data,
+                    metadata: metadata.clone(),
+                }
+            ));
         }
 
+        Ok(definitions)
+    }
+
+    fn create_scope(&self, parsing_module: &ParsingModule, uid: ModuleUID, importer: &impl ModuleImporter) -> Result<ModuleParserScope, LangError> {
+        let mut scope = self.create_types_scope(parsing_module, uid, importer)?;
+
+        let module = &parsing_module.module;
+
+        // If the module is not loaded the load all the classes in it
+        Self::load_parsing_module(&mut scope, parsing_module)?;
+
         for import in &module.imports {
-            let uid = match importer.get_unique_identifier(import) {
-                Some(uid) => uid,
-                None => return Err(LangError::load(LoadErrorKind::LoadModuleError(import.0.clone()))),
-            };
+            let (import_uid, import_module) = self.get_import(import, importer)?;
 
-            let parsing_module = match self.parsing_modules.get(&uid) {
-                Some(module) => module,
-                None => return Err(LangError::build(BuildErrorKind::UnexpectedError("create_scope: could not found parsing module".to_string()))),
-            };
+            // The types inside the imported module are resolved with the scope of the imported module
+            let mut import_scope = self.create_types_scope(import_module, import_uid, importer)?;
+
+            // If the module is not loaded the load all the classes in it
+            Self::load_parsing_module(&mut import_scope, import_module)?;
 
-            for (name, _) in &parsing_module.module.classes {
-                let class = parsing_module.classes.get(name).unwrap();
+            // Only the public features are visible from other modules
+            for (name, var) in &import_module.module.variables {
+                if !is_public(&var.attributes) { continue }
 
-                scope.declare_external_class(name.clone(), uid, class.clone());
+                let type_ = import_scope.convert_parsable_type(&var.type_kind)?;
+                scope.declare_import(&import.kind, import_uid, name.clone(), GlobalKind::Var(import_uid, type_));
             }
-        }
 
-        // If the module is not loaded the load all the classes in it
-        Self::load_parsing_module(&mut scope, parsing_module)?;
+            for (name, func) in &import_module.module.functions {
+                if !is_public(&func.attributes) { continue }
+
+                let func_type = import_scope.convert_parsable_func_type(&func.func_type)?;
+                scope.declare_import(&import.kind, import_uid, name.clone(), GlobalKind::Func(import_uid, func_type));
+            }
+
+            for (name, value) in import_module.constants.borrow().iter() {
+                if !import_module.module.constants.iter().any(|(n, c)| n == name && is_public(&c.attributes)) { continue }
 
-        // Declaring every type into the scope
+                scope.declare_import(&import.kind, import_uid, name.clone(), GlobalKind::Const(import_uid, value.clone()));
+            }
+
+            // Every selected feature must exist
+            if let ImportKind::Selective(names) = &import.kind {
+                for (name, token) in names {
+                    let feature = import_module.module.variables.iter().find(|(n, _)| n == name).map(|(_, v)| &v.attributes)
+                        .or(import_module.module.functions.iter().find(|(n, _)| n == name).map(|(_, f)| &f.attributes))
+                        .or(import_module.module.classes.iter().find(|(n, _)| n == name).map(|(_, c)| &c.attributes))
+                        .or(import_module.module.enums.iter().find(|(n, _)| n == name).map(|(_, e)| &e.attributes))
+                        .or(import_module.module.constants.iter().find(|(n, _)| n == name).map(|(_, c)| &c.attributes));
+
+                    match feature {
+                        Some(attributes) if is_public(attributes) => (),
+                        Some(_) => return Err(LangError::parser(token, ParserErrorKind::PrivateFeature(name.clone()))),
+                        None => return Err(LangError::parser(token, ParserErrorKind::FeatureNotFound(name.clone()))),
+                    }
+                }
+            }
+        }
+
+        // Declaring every type into the scope, the features of the module shadow the imported ones
         for (name, var) in &module.variables {
             scope.declare_var(name.clone(), scope.convert_parsable_type(&var.type_kind)?);
         }
@@ -287,32 +369,67 @@ impl<'a> ModuleParser<'a> {
             scope.declare_func(name.clone(), scope.convert_parsable_func_type(&func.func_type)?);
         }
 
-        for import in &module.imports {
-            let uid = match importer.get_unique_identifier(import) {
-                Some(uid) => uid,
-                None => return Err(LangError::load(LoadErrorKind::LoadModuleError(import.0.clone()))),
-            };
+        Ok(scope)
+    }
 
-            let parsing_module = match self.parsing_modules.get(&uid) {
-                Some(module) => module,
-                None => return Err(LangError::build(BuildErrorKind::UnexpectedError("create_scope: could not found parsing module".to_string()))),
-            };
+    /// Creates a scope that contains only the types (classes and enums) visible inside a module
+    fn create_types_scope(&self, parsing_module: &ParsingModule, uid: ModuleUID, importer: &impl ModuleImporter) -> Result<ModuleParserScope, LangError> {
+        let mut scope = ModuleParserScope::new(uid);
 
-            // If the module is not loaded the load all the classes in it
-            Self::load_parsing_module(&mut scope, parsing_module)?;
+        let module = &parsing_module.module;
+
+        for import in &module.imports {
+            let (import_uid, import_module) = self.get_import(import, importer)?;
 
-            for (name, var) in &parsing_module.module.variables {
-                scope.declare_external_var(name.clone(), uid, scope.convert_parsable_type(&var.type_kind)?);
+            for (name, parsable_class) in &import_module.module.classes {
+                if !is_public(&parsable_class.attributes) { continue }
+
+                let class = import_module.classes.get(name).unwrap();
+                scope.declare_import(&import.kind, import_uid, name.clone(), GlobalKind::Class(import_uid, class.clone()));
             }
 
-            for (name, func) in &parsing_module.module.functions {
-                scope.declare_external_func(name.clone(), uid, scope.convert_parsable_func_type(&func.func_type)?);
+            for (name, parsable_enum) in &import_module.module.enums {
+                if !is_public(&parsable_enum.attributes) { continue }
+
+                let enum_type = import_module.enums.get(name).unwrap();
+                scope.declare_import(&import.kind, import_uid, name.clone(), GlobalKind::Enum(import_uid, enum_type.clone()));
             }
         }
 
+        // Adds all the types to the scope (they still don't contain anything)
+        for (name, _) in &module.classes {
+            let class_type = match parsing_module.classes.get(name) {
+                Some(class) => class.clone(),
+                _ => return Err(LangError::build(BuildErrorKind::UnexpectedError("create_scope: variable is not a class".to_string()))),
+            };
+
+            scope.declare_class(name.clone(), class_type);
+        }
+
+        for (name, _) in &module.enums {
+            let enum_type = match parsing_module.enums.get(name) {
+                Some(enum_type) => enum_type.clone(),
+                _ => return Err(LangError::build(BuildErrorKind::UnexpectedError("create_scope: variable is not an enum".to_string()))),
+            };
+
+            scope.declare_enum(name.clone(), enum_type);
+        }
+
         Ok(scope)
     }
 
+    fn get_import(&self, import: &ParsableImport, importer: &impl ModuleImporter) -> Result<(ModuleUID, &ParsingModule), LangError> {
+        let uid = match importer.get_unique_identifier(&import.id) {
+            Some(uid) => uid,
+            None => return Err(LangError::load(LoadErrorKind::LoadModuleError(import.id.0.clone()))),
+        };
+
+        match self.parsing_modules.get(&uid) {
+            Some(module) => Ok((uid, module)),
+            None => Err(LangError::build(BuildErrorKind::UnexpectedError("create_scope: could not found parsing module".to_string()))),
+        }
+    }
+
     fn load_parsing_module(scope: &mut ModuleParserScope, parsing_module: &ParsingModule) -> Result<(), LangError> {
         if !parsing_module.loaded.get() {
             // This adds all the values
@@ -321,15 +438,34 @@ impl<'a> ModuleParser<'a> {
 
                 let mut methods = class.methods.borrow_mut();
                 let mut fields = class.fields.borrow_mut();
+                let mut public_members = class.public_members.borrow_mut();
+
+                let mut static_methods = class.static_methods.borrow_mut();
 
                 for (name, method) in &parsable_class.methods {
                     let func = scope.convert_parsable_func_type(&method.func_type)?;
-                    methods.push((name.clone(), func));
+
+                    match method.attributes.contains(&Attribute::Static) {
+                        true => static_methods.push((name.clone(), func)),
+                        false => methods.push((name.clone(), func)),
+                    }
+
+                    if is_public(&method.attributes) {
+                        public_members.push(name.clone());
+                    }
                 }
 
                 for (name, field) in &parsable_class.fields {
-                    let field = scope.convert_parsable_type(&field)?;
-                    fields.push((name.clone(), field));
+                    let field_type = scope.convert_parsable_type(&field.type_kind)?;
+                    fields.push((name.clone(), field_type));
+
+                    if is_public(&field.attributes) {
+                        public_members.push(name.clone());
+                    }
+                }
+
+                if let Some(parent) = &parsable_class.parent {
+                    Self::load_parent(scope, &class, parent)?;
                 }
             }
 
@@ -343,10 +479,153 @@ impl<'a> ModuleParser<'a> {
 
                     variants.push((name.clone(), Box::new(variant)))
                 }
+
+                let mut methods = enum_type.methods.borrow_mut();
+                let mut static_methods = enum_type.static_methods.borrow_mut();
+                let mut public_members = enum_type.public_members.borrow_mut();
+
+                for (name, method) in &parsable_enum.methods {
+                    let func = scope.convert_parsable_func_type(&method.func_type)?;
+
+                    match method.attributes.contains(&Attribute::Static) {
+                        true => static_methods.push((name.clone(), func)),
+                        false => methods.push((name.clone(), func)),
+                    }
+
+                    if is_public(&method.attributes) {
+                        public_members.push(name.clone());
+                    }
+                }
             }
 
+            Self::load_constants(scope, parsing_module)?;
+
             parsing_module.loaded.set(true);
         }
+
+        for (name, value) in parsing_module.constants.borrow().iter() {
+            scope.declare_const(name.clone(), value.clone());
+        }
+
+        Ok(())
+    }
+
+    /// The constants of the module come first, so that the ones of the classes and enums can use them.
+    /// A constant can use the ones declared before it
+    fn load_constants(scope: &mut ModuleParserScope, parsing_module: &ParsingModule) -> Result<(), LangError> {
+        let module = &parsing_module.module;
+
+        for (name, constant) in &module.constants {
+            let value = Self::eval_constant(scope, module, name, constant)?;
+
+            scope.declare_const(name.clone(), value.clone());
+            parsing_module.constants.borrow_mut().push((name.clone(), value));
+        }
+
+        for (name, parsable_class) in &module.classes {
+            let class = scope.get_class(name)?;
+
+            for (name, constant) in &parsable_class.constants {
+                let value = Self::eval_constant(scope, module, name, constant)?;
+
+                class.constants.borrow_mut().push((name.clone(), value));
+
+                if is_public(&constant.attributes) {
+                    class.public_members.borrow_mut().push(name.clone());
+                }
+            }
+        }
+
+        for (name, parsable_enum) in &module.enums {
+            let enum_type = scope.get_enum(name)?;
+
+            for (name, constant) in &parsable_enum.constants {
+                let value = Self::eval_constant(scope, module, name, constant)?;
+
+                enum_type.constants.borrow_mut().push((name.clone(), value));
+
+                if is_public(&constant.attributes) {
+                    enum_type.public_members.borrow_mut().push(name.clone());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn eval_constant(scope: &ModuleParserScope, module: &ParsableModule, name: &str, constant: &ParsableConstant) -> Result<LiteralKind, LangError> {
+        let type_ = scope.convert_parsable_type(&constant.type_kind)?;
+
+        let mut tokens = module.tokens.new_clone(constant.body);
+        let token = tokens.peek().unwrap();
+
+        let node = scope.new_child().parse_statement(&mut tokens)?;
+
+        let value = match eval_constant(&node) {
+            Some(value) => value,
+            None => return Err(LangError::parser(&token, ParserErrorKind::NotConstant(name.to_string()))),
+        };
+
+        let value_type = TypeKind::from(&value);
+
+        if !value_type.is_compatible(&type_) {
+            return Err(LangError::wrong_type(&token, &type_, &value_type));
+        }
+
+        Ok(value)
+    }
+
+    /// Only the normal classes can inherit from each other, a class can't be one of its own parents
+    fn load_parent(scope: &ModuleParserScope, class: &Arc<ClassType>, parent: &ParsableType) -> Result<(), LangError> {
+        let error = || LangError::parser(
+            &Token::new(TokenKind::Symbol(class.name.clone()), 0, 0),
+            ParserErrorKind::InvalidParent(class.name.clone()));
+
+        let parent = match scope.convert_parsable_type(parent)? {
+            TypeKind::Class(parent) if parent.kind == ClassKind::Normal && class.kind == ClassKind::Normal => parent,
+            _ => return Err(error()),
+        };
+
+        if parent.is_subclass_of(class) {
+            return Err(error())
+        }
+
+        class.parent.replace(Some(parent));
+
+        Ok(())
+    }
+
+    /// The members of a class can't hide the ones of its parents, apart from the methods which keep the same type
+    fn check_overrides(class_type: &Arc<ClassType>) -> Result<(), LangError> {
+        let parent = match class_type.parent() {
+            Some(parent) => parent,
+            None => return Ok(()),
+        };
+
+        let error = |name: &String| LangError::parser(
+            &Token::new(TokenKind::Symbol(name.clone()), 0, 0),
+            ParserErrorKind::InvalidOverride(name.clone()));
+
+        for (name, _) in class_type.fields.borrow().iter() {
+            if parent.find_field(name).is_some() || parent.find_method(name).is_some() {
+                return Err(error(name))
+            }
+        }
+
+        for (name, func_type) in class_type.methods.borrow().iter() {
+            // Every class has its own constructor
+            if name == CLASS_CONSTRUCTOR_NAME { continue }
+
+            if parent.find_field(name).is_some() {
+                return Err(error(name))
+            }
+
+            match parent.find_method(name) {
+                Some((_, parent_type)) if parent_type != *func_type => return Err(error(name)),
+                _ => (),
+            }
+        }
+
         Ok(())
     }
 
@@ -366,7 +645,7 @@ impl<'a> ModuleParser<'a> {
         scope: &ParserScope,
         params: &Vec<String>,
         func_type: FunctionType,
-        method: Option<Arc<ClassType>>,
+        this: Option<TypeKind>,
     ) -> Result<Arc<Function>, LangError> {
         if params.len() != func_type.0.len() {
             return Err(
@@ -376,8 +655,13 @@ impl<'a> ModuleParser<'a> {
                         "parse_function_value: different params lenghts".to_string())));
         }
 
-        if let Some(ref method) = method {
-            scope.declare(CLASS_SELF_REFERENCE.to_string(), TypeKind::Class(method.clone()));
+        let method = match &this {
+            Some(TypeKind::Class(class_type)) => Some(class_type.clone()),
+            _ => None,
+        };
+
+        if let Some(this) = this {
+            scope.declare(CLASS_SELF_REFERENCE.to_string(), this);
         }
 
         for i in 0..params.len() {
@@ -1,38 +1,162 @@
-use common::ast::types::{Attribute, ClassKind, LiteralKind, OperatorKind, ParenthesisKind, ParenthesisState};
+use common::ast::types::{Attribute, AttributeArgs, ClassKind, LiteralKind, OperatorKind, ParenthesisKind, ParenthesisState};
 use common::errors::{LangError, ParserErrorKind};
-use common::module::{ModuleIdentifier, ModuleUID};
+use common::module::{ModuleIdentifier, ModuleKind, ModuleUID};
 use common::tokens::{TokenKind, Token};
 use common::tokens_iterator::Tokens;
 use crate::errors::ParsingErrorHelper;
 use crate::{expect_open_body, expect_token};
 use common::ast::parsing_types::{ParsableFunctionType, ParsableType};
-use common::parsable_types::{ParsableClass, ParsableEnum, ParsableFunction, ParsableModule, ParsableVariable};
+use common::parsable_types::{ImportKind, LineIndex, ParsableClass, ParsableConstant, ParsableEnum, ParsableField, ParsableFunction, ParsableImport, ModuleSource, ParsableModule, ParsableVariable};
+use common::constants::{ATTRIB_CFG, CLASS_CONSTRUCTOR_NAME, IMPORT_ALIAS_KEYWORD, IMPORT_FROM_KEYWORD};
+use common::cfg::CfgOptions;
 use crate::modules::preparsing_utils::{preparse_parameter_names, preparse_type_error, preparse_type_option};
 use crate::utils::TokensExtensions;
 
-/// First step of a module parsing, this can either create a `ParsableModule` or a `DeclarationModule`.
-/// `ParsableModule` when the module is a definition module, this will contain
+/// First step of a module parsing, this creates a `ParsableModule`.
+/// When the module is a definition module, this will contain
 /// the declarations with a corresponding token snapshot for later parsing.
-/// `DeclarationModule` when the module is a declaration module, this
-/// will parse all the declarations and directly create a already completely
-/// parsed module.
+/// When the module is a declaration module, every declaration is an import
+/// and none of them has a body.
+/// The declarations and statements disabled by a `@Cfg` are removed before anything else.
 pub struct ModulePreParser;
 
 impl ModulePreParser {
-    pub fn prepare_module(mut tokens: Tokens, id: ModuleIdentifier, uid: ModuleUID) -> Result<ParsableModule, LangError> {
+    pub fn prepare_module(tokens: Tokens, id: ModuleIdentifier, uid: ModuleUID, cfg: &CfgOptions) -> Result<ParsableModule, LangError> {
+        let tokens = Self::apply_cfg(tokens, cfg)?;
+
+        Self::prepare(tokens, id, uid, ModuleKind::Definition)
+    }
+
+    /// Prepares a module that contains only declarations, every feature inside it is
+    /// treated as imported from the host module `import_module`
+    pub fn prepare_declaration_module(
+        tokens: Tokens,
+        id: ModuleIdentifier,
+        uid: ModuleUID,
+        import_module: String,
+        cfg: &CfgOptions,
+    ) -> Result<ParsableModule, LangError> {
+        let tokens = Self::apply_cfg(tokens, cfg)?;
+
+        Self::prepare(tokens, id, uid, ModuleKind::Declaration { import_module })
+    }
+
+    /// Removes the declarations and statements whose `@Cfg` does not match the configuration,
+    /// the `@Cfg` attributes themselves are always removed
+    fn apply_cfg(mut tokens: Tokens, cfg: &CfgOptions) -> Result<Tokens, LangError> {
+        let mut result = Vec::new();
+
+        while let Some(token) = tokens.pop() {
+            // @Cfg
+            let is_cfg = match (&token.kind, tokens.peek()) {
+                (TokenKind::Operator(OperatorKind::At), Some(Token { kind: TokenKind::Symbol(name), .. })) => name == ATTRIB_CFG,
+                _ => false,
+            };
+
+            if !is_cfg {
+                result.push(token);
+                continue
+            }
+
+            let attribute = Self::parse_custom_attribute(&mut tokens)?;
+
+            if !Self::is_cfg_enabled(&token, &attribute, cfg)? {
+                Self::pop_cfg_item(&mut tokens);
+            }
+        }
+
+        Ok(Tokens::from_vec(result))
+    }
+
+    /// A `@Cfg` is enabled when all of its `key = "value"` pairs are set in the configuration
+    fn is_cfg_enabled(token: &Token, attribute: &Attribute, cfg: &CfgOptions) -> Result<bool, LangError> {
+        let args = match attribute.custom_args(ATTRIB_CFG) {
+            Some(args) if args.positional.is_empty() && !args.named.is_empty() => args,
+            _ => return Err(LangError::parser(token, ParserErrorKind::InvalidAttribute(attribute.clone()))),
+        };
+
+        let mut enabled = true;
+
+        for (key, value) in &args.named {
+            match value {
+                LiteralKind::String(value) => enabled &= cfg.is_set(key, value),
+                _ => return Err(LangError::parser(token, ParserErrorKind::InvalidAttribute(attribute.clone()))),
+            }
+        }
+
+        Ok(enabled)
+    }
+
+    /// Pops the declaration or statement following a disabled `@Cfg` together with its other attributes.
+    /// It ends at the first new line outside of any parenthesis or at the end of the enclosing body
+    fn pop_cfg_item(tokens: &mut Tokens) {
+        // The attributes and the empty lines before the item
+        loop {
+            match tokens.peek().map(|token| token.kind) {
+                Some(TokenKind::NewLine) | Some(TokenKind::Attribute(_)) => {
+                    tokens.pop();
+                },
+                // @<attribute name>((<args>))?
+                Some(TokenKind::Operator(OperatorKind::At)) => {
+                    tokens.pop();
+                    tokens.pop();
+
+                    if let Some(TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Open)) = tokens.peek().map(|token| token.kind) {
+                        tokens.pop();
+
+                        loop {
+                            match tokens.pop().map(|token| token.kind) {
+                                Some(TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Close)) | None => break,
+                                Some(_) => (),
+                            }
+                        }
+                    }
+                },
+                _ => break,
+            }
+        }
+
+        let mut open_parenthesis = 0;
+
+        loop {
+            match tokens.peek().map(|token| token.kind) {
+                Some(TokenKind::NewLine) if open_parenthesis == 0 => {
+                    tokens.pop();
+                    break
+                },
+                Some(TokenKind::Parenthesis(_, ParenthesisState::Open)) => open_parenthesis += 1,
+                // The item is the last one of the enclosing body
+                Some(TokenKind::Parenthesis(_, ParenthesisState::Close)) if open_parenthesis == 0 => break,
+                Some(TokenKind::Parenthesis(_, ParenthesisState::Close)) => open_parenthesis -= 1,
+                Some(_) => (),
+                None => break,
+            }
+
+            tokens.pop();
+        }
+    }
+
+    fn prepare(mut tokens: Tokens, id: ModuleIdentifier, uid: ModuleUID, kind: ModuleKind) -> Result<ParsableModule, LangError> {
         let mut imports = Vec::new();
         let mut variables = Vec::new();
         let mut functions = Vec::new();
         let mut classes = Vec::new();
         let mut enums = Vec::new();
+        let mut constants = Vec::new();
 
         loop {
             if !tokens.has_next() { break }
 
-            let result = Self::parse_declaration(&mut tokens, uid, &mut Vec::new());
+            // Every declaration inside a declaration module is a public import
+            let mut attributes = match kind {
+                ModuleKind::Definition => Vec::new(),
+                ModuleKind::Declaration { .. } => vec![Attribute::Import, Attribute::Public],
+            };
+
+            let result = Self::parse_declaration(&mut tokens, uid, &mut attributes);
             match result {
-                Ok(DeclarationParseAction::Import(path)) => {
-                    imports.push(ModuleIdentifier(path));
+                Ok(DeclarationParseAction::Import(import)) => {
+                    imports.push(import);
                 },
                 Ok(DeclarationParseAction::Function(name, func)) => {
                     functions.push((name, func));
@@ -46,6 +170,9 @@ impl ModulePreParser {
                 Ok(DeclarationParseAction::Enum(name, enum_)) => {
                     enums.push((name, enum_));
                 },
+                Ok(DeclarationParseAction::Constant(name, constant)) => {
+                    constants.push((name, constant));
+                },
                 Ok(DeclarationParseAction::Nothing) => (),
                 Err(err) => return Err(err),
             }
@@ -54,6 +181,7 @@ impl ModulePreParser {
         Ok(ParsableModule {
             id,
             uid,
+            kind,
 
             tokens,
             imports,
@@ -61,6 +189,9 @@ impl ModulePreParser {
             functions,
             classes,
             enums,
+            constants,
+            lines: LineIndex::default(),
+            source: ModuleSource::Code(String::new()),
         })
     }
 
@@ -70,15 +201,9 @@ impl ModulePreParser {
 
         match token.kind {
             TokenKind::Operator(OperatorKind::At) => {
-                // @<attribute name>
-
-                let token = tokens.pop_err()?;
-                let name = match token.kind {
-                    TokenKind::Symbol(name) => name,
-                    _ => return Err(LangError::parser(&token, ParserErrorKind::UnexpectedToken)),
-                };
+                // @<attribute name>((<literal>|<name> = <literal>,)*)?
 
-                attributes.push(Attribute::Custom(name));
+                attributes.push(Self::parse_custom_attribute(tokens)?);
 
                 loop {
                     let result = Self::parse_declaration(tokens, module, attributes)?;
@@ -89,20 +214,59 @@ impl ModulePreParser {
                 }
             },
             TokenKind::Import => {
-                // import [path]
+                // import [path] (as <alias>)?
+                // import { (<name>,)* } from [path]
 
                 // Should not have any attributes (at least for now)
                 for attribute in attributes as &Vec<Attribute> {
-                    return match attribute {
-                        _ => Err(LangError::parser(&token, ParserErrorKind::InvalidAttribute(attribute.clone()))),
+                    match attribute {
+                        // Declaration modules mark everything as imported and public
+                        Attribute::Import | Attribute::Public => (),
+                        _ => return Err(LangError::parser(&token, ParserErrorKind::InvalidAttribute(attribute.clone()))),
                     }
                 }
 
-                // [path]
-                let path = match tokens.peek_err()?.kind {
+                let (path, kind) = match tokens.peek_err()?.kind {
+                    // [path] (as <alias>)?
                     TokenKind::Literal(LiteralKind::String(path)) => {
                         tokens.pop();
-                        path
+
+                        let kind = match tokens.peek() {
+                            Some(Token { kind: TokenKind::Symbol(keyword), .. }) if keyword == IMPORT_ALIAS_KEYWORD => {
+                                tokens.pop();
+
+                                let token = tokens.pop_err()?;
+                                match token.kind {
+                                    TokenKind::Symbol(alias) => ImportKind::Alias(alias),
+                                    _ => return Err(LangError::new_parser_unexpected_token(&token)),
+                                }
+                            },
+                            _ => ImportKind::All,
+                        };
+
+                        (path, kind)
+                    },
+                    // { (<name>,)* } from [path]
+                    TokenKind::Parenthesis(ParenthesisKind::Curly, ParenthesisState::Open) => {
+                        tokens.pop();
+
+                        let names = Self::parse_import_names(tokens)?;
+
+                        // from
+                        let token = tokens.pop_err()?;
+                        match token.kind {
+                            TokenKind::Symbol(keyword) if keyword == IMPORT_FROM_KEYWORD => (),
+                            _ => return Err(LangError::new_parser_unexpected_token(&token)),
+                        }
+
+                        // [path]
+                        let token = tokens.pop_err()?;
+                        let path = match token.kind {
+                            TokenKind::Literal(LiteralKind::String(path)) => path,
+                            _ => return Err(LangError::new_parser_unexpected_token(&token)),
+                        };
+
+                        (path, ImportKind::Selective(names))
                     },
                     _ => {
                         attributes.push(Attribute::Import);
@@ -114,23 +278,44 @@ impl ModulePreParser {
                 // new line
                 expect_token!(tokens.pop(), TokenKind::NewLine);
 
-                Ok(DeclarationParseAction::Import(path))
+                Ok(DeclarationParseAction::Import(ParsableImport {
+                    id: ModuleIdentifier(path),
+                    kind,
+                }))
             },
             TokenKind::Variable => {
                 // Definition:  var <name> (type) = [value]
                 // Declaration: var <name> (type)
 
+                let mut import = false;
+
                 for attribute in attributes as &Vec<Attribute> {
                     match attribute {
-                        Attribute::Custom(_) => (),
+                        Attribute::Import => import = true,
+                        Attribute::Public | Attribute::Custom(..) => (),
                         _ => return Err(LangError::parser(&token, ParserErrorKind::InvalidAttribute(attribute.clone()))),
                     }
                 }
 
-                let (name, decl) = Self::parse_variable(tokens, attributes.clone())?;
+                let (name, decl) = Self::parse_variable(tokens, import, attributes.clone())?;
 
                 Ok(DeclarationParseAction::Variable(name, decl))
             },
+            TokenKind::Constant => {
+                // const <name> (type) = [value]
+
+                for attribute in attributes as &Vec<Attribute> {
+                    match attribute {
+                        // The value is known at compile time, even inside a declaration module
+                        Attribute::Import | Attribute::Public => (),
+                        _ => return Err(LangError::parser(&token, ParserErrorKind::InvalidAttribute(attribute.clone()))),
+                    }
+                }
+
+                let (name, constant) = Self::parse_constant(tokens, attributes.clone())?;
+
+                Ok(DeclarationParseAction::Constant(name, constant))
+            },
             TokenKind::Function => {
                 // Definition:  func <name>((<param_name> (type))*) (type) {body}
                 // Declaration: func <name>((<param_name> (type))*) (type)
@@ -140,7 +325,7 @@ impl ModulePreParser {
                 for attribute in attributes as &Vec<Attribute> {
                     match attribute {
                         Attribute::Import => import = true,
-                        Attribute::Custom(_) => (),
+                        Attribute::Public | Attribute::Custom(..) => (),
                         _ => return Err(LangError::parser(&token, ParserErrorKind::InvalidAttribute(attribute.clone()))),
                     }
                 }
@@ -154,12 +339,16 @@ impl ModulePreParser {
             },
             TokenKind::Class => {
                 /*
-                class (data)? ClassName {
+                class (data)? ClassName (: ParentName)? {
                     attr1 int
                     attr2 str
+                    const NAME (type) = [value]
 
                     func method1() (type) ({body})?
+                    static func function1() (type) ({body})?
                 }
+
+                extern class ClassName
                 */
 
                 let mut kind = ClassKind::Normal;
@@ -168,9 +357,10 @@ impl ModulePreParser {
                 for attribute in attributes as &Vec<Attribute> {
                     match attribute {
                         Attribute::Data => kind = ClassKind::Data,
+                        Attribute::Extern => kind = ClassKind::Extern,
                         Attribute::Import => import = true,
-                        Attribute::Custom(_) => (),
-                        // _ => return Err(LangError::parser(&token, ParserErrorKind::InvalidAttribute(attribute.clone()))),
+                        Attribute::Public | Attribute::Custom(..) => (),
+                        Attribute::Static => return Err(LangError::parser(&token, ParserErrorKind::InvalidAttribute(attribute.clone()))),
                     }
                 }
 
@@ -181,6 +371,34 @@ impl ModulePreParser {
                     None => return Err(LangError::new_parser_end_of_file()),
                 };
 
+                // The objects of extern classes are provided by the host, so they don't have a body
+                if let ClassKind::Extern = kind {
+                    if attributes.contains(&Attribute::Data) {
+                        return Err(LangError::parser(&token, ParserErrorKind::InvalidAttribute(Attribute::Data)));
+                    }
+
+                    return Ok(DeclarationParseAction::Class(name.clone(), ParsableClass {
+                        attributes: attributes.clone(),
+                        kind,
+                        name,
+                        module,
+                        parent: None,
+
+                        fields: Vec::new(),
+                        methods: Vec::new(),
+                        constants: Vec::new(),
+                    }));
+                }
+
+                // (: <parent>)?
+                let parent = match tokens.peek() {
+                    Some(Token { kind: TokenKind::Operator(OperatorKind::Colon), .. }) => {
+                        tokens.pop();
+                        Some(preparse_type_error(tokens)?)
+                    },
+                    _ => None,
+                };
+
                 // {
                 expect_open_body!(tokens);
 
@@ -189,6 +407,7 @@ impl ModulePreParser {
                     kind,
                     name.clone(),
                     module,
+                    parent,
                     attributes.clone(),
                     import)?;
 
@@ -197,10 +416,18 @@ impl ModulePreParser {
             TokenKind::Enum => {
                 // enum <name> {
                 //     (<name> Type?,)*
+                //     const NAME (type) = [value]
+                //     (static)? func method1() (type) ({body})?
                 // }
 
-                for attribute in attributes {
-                    return Err(LangError::parser(&token, ParserErrorKind::InvalidAttribute(attribute.clone())));
+                let import = attributes.contains(&Attribute::Import);
+
+                for attribute in attributes as &Vec<Attribute> {
+                    match attribute {
+                        // Enums are only types so importing them changes nothing
+                        Attribute::Import | Attribute::Public => (),
+                        _ => return Err(LangError::parser(&token, ParserErrorKind::InvalidAttribute(attribute.clone()))),
+                    }
                 }
 
                 // <name>
@@ -213,6 +440,9 @@ impl ModulePreParser {
                 expect_open_body!(tokens);
 
                 let mut variants = Vec::new();
+                let mut methods = Vec::new();
+                let mut constants = Vec::new();
+                let mut member_attributes = Vec::new();
 
                 let mut variant_name: Option<String> = None;
                 let mut variant_type: Option<ParsableType> = None;
@@ -228,9 +458,20 @@ impl ModulePreParser {
                     let token = tokens.pop_err()?;
 
                     match token.kind {
-                        TokenKind::Symbol(_) if variant_name.is_some() => {
+                        TokenKind::Symbol(_) if variant_name.is_some() || !member_attributes.is_empty() => {
                             return Err(LangError::new_parser_unexpected_token(&token))
                         },
+                        TokenKind::Attribute(attribute @ (Attribute::Public | Attribute::Static)) if variant_name.is_none() => {
+                            member_attributes.push(attribute);
+                        },
+                        TokenKind::Function if variant_name.is_none() => {
+                            let method = Self::parse_function(tokens, import, std::mem::take(&mut member_attributes))?;
+                            methods.push(method);
+                        },
+                        TokenKind::Constant if variant_name.is_none() && !member_attributes.contains(&Attribute::Static) => {
+                            let constant = Self::parse_constant(tokens, std::mem::take(&mut member_attributes))?;
+                            constants.push(constant);
+                        },
                         TokenKind::Symbol(name) => {
                             variant_name = Some(name);
                         },
@@ -243,7 +484,7 @@ impl ModulePreParser {
                             variants.push((name, variant_type.take().unwrap_or(ParsableType::Nothing)));
                         },
                         TokenKind::NewLine => (),
-                        TokenKind::Parenthesis(ParenthesisKind::Curly, ParenthesisState::Close) => break,
+                        TokenKind::Parenthesis(ParenthesisKind::Curly, ParenthesisState::Close) if member_attributes.is_empty() => break,
                         _ => return Err(LangError::new_parser_unexpected_token(&token)),
                     }
                 }
@@ -251,8 +492,11 @@ impl ModulePreParser {
                 Ok(DeclarationParseAction::Enum(
                     name.clone(),
                     ParsableEnum {
+                        attributes: attributes.clone(),
                         name,
                         variants,
+                        methods,
+                        constants,
                     })
                 )
             },
@@ -265,16 +509,108 @@ impl ModulePreParser {
         }
     }
 
+    /// Parses a custom attribute, it expects the `@` to be already consumed
+    fn parse_custom_attribute(tokens: &mut Tokens) -> Result<Attribute, LangError> {
+        let token = tokens.pop_err()?;
+        let name = match token.kind {
+            TokenKind::Symbol(name) => name,
+            _ => return Err(LangError::parser(&token, ParserErrorKind::UnexpectedToken)),
+        };
+
+        let args = match tokens.peek() {
+            Some(Token { kind: TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Open), .. }) => {
+                tokens.pop();
+                Self::parse_attribute_args(tokens)?
+            },
+            _ => AttributeArgs::default(),
+        };
+
+        Ok(Attribute::Custom(name, Box::new(args)))
+    }
+
+    /// Parses the literal arguments of an attribute, it expects the round bracket to be already consumed
+    fn parse_attribute_args(tokens: &mut Tokens) -> Result<AttributeArgs, LangError> {
+        let mut args = AttributeArgs::default();
+        let mut next_is_arg = true;
+
+        loop {
+            let token = tokens.pop_err()?;
+
+            match &token.kind {
+                TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Close) => break,
+                // <literal>
+                TokenKind::Literal(literal) if next_is_arg => {
+                    // Positional arguments must come before the named ones
+                    if !args.named.is_empty() {
+                        return Err(LangError::new_parser_unexpected_token(&token));
+                    }
+
+                    next_is_arg = false;
+                    args.positional.push(literal.clone());
+                },
+                // <name> = <literal>
+                TokenKind::Symbol(key) if next_is_arg => {
+                    expect_token!(tokens.pop(), TokenKind::Operator(OperatorKind::Assign));
+
+                    let token = tokens.pop_err()?;
+                    let value = match token.kind {
+                        TokenKind::Literal(literal) => literal,
+                        _ => return Err(LangError::new_parser_unexpected_token(&token)),
+                    };
+
+                    next_is_arg = false;
+                    args.named.push((key.clone(), value));
+                },
+                TokenKind::Operator(OperatorKind::Comma) if !next_is_arg => next_is_arg = true,
+                _ => return Err(LangError::new_parser_unexpected_token(&token)),
+            }
+        }
+
+        Ok(args)
+    }
+
+    /// Parses the names of a selective import, it expects the curly bracket to be already consumed
+    fn parse_import_names(tokens: &mut Tokens) -> Result<Vec<(String, Token)>, LangError> {
+        let mut names = Vec::new();
+        let mut next_is_name = true;
+
+        loop {
+            let token = tokens.pop_err()?;
+
+            match &token.kind {
+                TokenKind::Parenthesis(ParenthesisKind::Curly, ParenthesisState::Close) => break,
+                TokenKind::Symbol(name) if next_is_name => {
+                    next_is_name = false;
+                    names.push((name.clone(), token.clone()));
+                },
+                TokenKind::Operator(OperatorKind::Comma) if !next_is_name => next_is_name = true,
+                TokenKind::NewLine => (),
+                _ => return Err(LangError::new_parser_unexpected_token(&token)),
+            }
+        }
+
+        Ok(names)
+    }
+
     fn parse_class_declaration(
         tokens: &mut Tokens,
         kind: ClassKind,
         name: String,
         module: ModuleUID,
+        parent: Option<ParsableType>,
         attributes: Vec<Attribute>,
         import: bool,
     ) -> Result<ParsableClass, LangError> {
         let mut fields = Vec::new();
         let mut methods = Vec::new();
+        let mut constants = Vec::new();
+
+        // Members of classes provided by the host are always public
+        let default_attributes = match import {
+            true => vec![Attribute::Public],
+            false => Vec::new(),
+        };
+        let mut member_attributes = default_attributes.clone();
 
         loop {
             let token = match tokens.pop() {
@@ -283,31 +619,62 @@ impl ModulePreParser {
             };
 
             match token.kind {
+                // pub
+                TokenKind::Attribute(Attribute::Public) => {
+                    member_attributes.push(Attribute::Public);
+                    continue
+                },
+                // static
+                TokenKind::Attribute(Attribute::Static) => {
+                    member_attributes.push(Attribute::Static);
+                    continue
+                },
+                // @<attribute name>((<literal>|<name> = <literal>,)*)?
+                TokenKind::Operator(OperatorKind::At) => {
+                    member_attributes.push(Self::parse_custom_attribute(tokens)?);
+                    continue
+                },
+                TokenKind::Symbol(_) if member_attributes.contains(&Attribute::Static) => {
+                    return Err(LangError::parser(&token, ParserErrorKind::InvalidAttribute(Attribute::Static)))
+                },
+                TokenKind::Constant if member_attributes.contains(&Attribute::Static) => {
+                    return Err(LangError::parser(&token, ParserErrorKind::InvalidAttribute(Attribute::Static)))
+                },
+                TokenKind::Constant => {
+                    constants.push(Self::parse_constant(tokens, member_attributes.clone())?);
+                },
                 TokenKind::Symbol(name) => {
                     // (type)
-                    let type_ = preparse_type_error(tokens)?;
+                    let type_kind = preparse_type_error(tokens)?;
 
-                    fields.push((name, type_));
+                    fields.push((name, ParsableField {
+                        attributes: member_attributes.clone(),
+                        type_kind,
+                    }));
                 },
                 TokenKind::Function => {
-                    if let ClassKind::Data = kind {
+                    let (name, method) = Self::parse_function(tokens, import, member_attributes.clone())?;
+
+                    // The methods of a data class get a copy of the object, there is nothing for a constructor to initialize
+                    if kind == ClassKind::Data && name == CLASS_CONSTRUCTOR_NAME && !method.attributes.contains(&Attribute::Static) {
                         return Err(
                             LangError::parser(
                                 &token,
-                                ParserErrorKind::Unsupported("Methods in data classes are not yet supported".to_string())))
+                                ParserErrorKind::Unsupported("Data classes can't have a constructor".to_string())))
                     }
 
-                    let (name, method) = Self::parse_function(tokens, import, Vec::new())?;
-
                     methods.push((
                         name,
                         method,
                     ));
                 },
-                TokenKind::NewLine => (),
-                TokenKind::Parenthesis(ParenthesisKind::Curly, ParenthesisState::Close) => break,
+                // The attributes can be on their own line
+                TokenKind::NewLine => continue,
+                TokenKind::Parenthesis(ParenthesisKind::Curly, ParenthesisState::Close) if member_attributes == default_attributes => break,
                 _ => return Err(LangError::parser(&token, ParserErrorKind::UnexpectedToken))
             }
+
+            member_attributes = default_attributes.clone();
         }
 
         Ok(ParsableClass {
@@ -315,13 +682,44 @@ impl ModulePreParser {
             kind,
             name,
             module,
+            parent,
 
             fields,
             methods,
+            constants,
         })
     }
 
-    fn parse_variable(tokens: &mut Tokens, attributes: Vec<Attribute>) -> Result<(String, ParsableVariable), LangError> {
+    fn parse_constant(tokens: &mut Tokens, attributes: Vec<Attribute>) -> Result<(String, ParsableConstant), LangError> {
+        let token = tokens.pop_err()?;
+
+        // <name>
+        let name = match token.kind {
+            TokenKind::Symbol(name) => name,
+            _ => return Err(LangError::new_parser_unexpected_token(&token)),
+        };
+
+        // (type)
+        let type_kind = preparse_type_error(tokens)?;
+
+        // =
+        expect_token!(tokens.pop(), TokenKind::Operator(OperatorKind::Assign));
+
+        // [value]
+        let body = tokens.snapshot();
+        Self::pop_until_newline(tokens);
+
+        Ok((
+            name,
+            ParsableConstant {
+                attributes,
+                type_kind,
+                body,
+            },
+        ))
+    }
+
+    fn parse_variable(tokens: &mut Tokens, import: bool, attributes: Vec<Attribute>) -> Result<(String, ParsableVariable), LangError> {
         let token = tokens.pop_err()?;
 
         // <name>
@@ -340,6 +738,8 @@ impl ModulePreParser {
 
         // (= [value])?
         let body = match token.kind {
+            // Imported variables are provided by the host
+            TokenKind::Operator(OperatorKind::Assign) if import => return Err(LangError::new_parser_unexpected_token(&token)),
             TokenKind::Operator(OperatorKind::Assign) => {
                 tokens.pop();
 
@@ -386,6 +786,9 @@ impl ModulePreParser {
         };
 
         let body = match token.kind {
+            // Imported functions are provided by the host
+            TokenKind::Parenthesis(ParenthesisKind::Curly, ParenthesisState::Open) if import
+                => return Err(LangError::new_parser_unexpected_token(&token)),
             TokenKind::Parenthesis(ParenthesisKind::Curly, ParenthesisState::Open) => {
                 tokens.pop();
 
@@ -441,10 +844,11 @@ impl ModulePreParser {
 }
 
 enum DeclarationParseAction {
-    Import(String),
+    Import(ParsableImport),
     Variable(String, ParsableVariable),
     Function(String, ParsableFunction),
     Class(String, ParsableClass),
     Enum(String, ParsableEnum),
+    Constant(String, ParsableConstant),
     Nothing,
 }
@@ -1,4 +1,5 @@
-use common::ast::types::{OperatorKind, ParenthesisKind, ParenthesisState};
+use common::ast::types::{BoolOperatorKind, OperatorKind, ParenthesisKind, ParenthesisState};
+use common::constants::RESULT_TYPE_NAME;
 use common::errors::{LangError, ParserErrorKind};
 use common::tokens::{TokenKind, Token};
 use common::tokens_iterator::Tokens;
@@ -29,9 +30,51 @@ pub fn preparse_type_option(tokens: &mut Tokens) -> Option<ParsableType> {
             tokens.pop();
             Some(ParsableType::from(&tk))
         }
+        TokenKind::Symbol(name) if name == RESULT_TYPE_NAME => {
+            tokens.pop();
+
+            // <
+            match tokens.peek() {
+                Some(Token { kind: TokenKind::BoolOperator(BoolOperatorKind::Smaller), .. }) => tokens.pop(),
+                _ => return Some(ParsableType::Custom(name)),
+            };
+
+            // Ok type
+            let ok_type = preparse_type_option(tokens)?;
+
+            // ,
+            match tokens.pop() {
+                Some(Token { kind: TokenKind::Operator(OperatorKind::Comma), .. }) => (),
+                _ => return None,
+            }
+
+            // Err type
+            let err_type = preparse_type_option(tokens)?;
+
+            // >
+            match tokens.pop() {
+                Some(Token { kind: TokenKind::BoolOperator(BoolOperatorKind::Bigger), .. }) => (),
+                _ => return None,
+            }
+
+            Some(ParsableType::Result(Box::new(ok_type), Box::new(err_type)))
+        },
         TokenKind::Symbol(name) =>{
             tokens.pop();
-            Some(ParsableType::Custom(name))
+
+            // <alias>.<name>
+            match tokens.peek() {
+                Some(Token { kind: TokenKind::Operator(OperatorKind::Dot), .. }) => {
+                    tokens.pop();
+
+                    match tokens.pop() {
+                        Some(Token { kind: TokenKind::Symbol(type_name), .. })
+                            => Some(ParsableType::Custom(format!("{}.{}", name, type_name))),
+                        _ => None,
+                    }
+                },
+                _ => Some(ParsableType::Custom(name)),
+            }
         },
         TokenKind::Parenthesis(ParenthesisKind::Square, ParenthesisState::Open) => {
             tokens.pop();
@@ -45,6 +88,26 @@ pub fn preparse_type_option(tokens: &mut Tokens) -> Option<ParsableType> {
             
             Some(ParsableType::Vector(Box::new(type_)))
         },
+        // (<type>, <type>)
+        TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Open) => {
+            tokens.pop();
+
+            let mut types = vec![preparse_type_option(tokens)?];
+
+            loop {
+                match tokens.pop() {
+                    Some(Token { kind: TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Close), .. }) => break,
+                    Some(Token { kind: TokenKind::Operator(OperatorKind::Comma), .. }) => types.push(preparse_type_option(tokens)?),
+                    _ => return None,
+                }
+            }
+
+            // A single type between parenthesis is not a tuple
+            match types.len() {
+                1 => types.pop(),
+                _ => Some(ParsableType::Tuple(types)),
+            }
+        },
         _ => None,
     }
 }
@@ -1,9 +1,10 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 use common::ast::parsing_types::{ParsableFunctionType, ParsableType};
-use common::ast::types::{ClassType, EnumType, FunctionType, TypeKind};
+use common::ast::types::{ClassType, EnumType, FunctionType, LiteralKind, TypeKind};
 use common::errors::{LangError, ParserErrorKind};
 use common::module::ModuleUID;
+use common::parsable_types::ImportKind;
 use common::tokens::{Token, TokenKind};
 use crate::parser_scope::ParserScope;
 
@@ -11,19 +12,28 @@ pub enum ScopeGetResult {
     Class(ModuleUID, Arc<ClassType>),
     Enum(ModuleUID, Arc<EnumType>),
     Ref(ModuleUID, TypeKind),
+    /// The value of a constant, it replaces the name
+    Const(LiteralKind),
+    /// An imported module alias, its features are accessed with `alias.<name>`
+    Module(ModuleUID),
     None,
 }
 
+#[derive(Clone)]
 pub enum GlobalKind {
     Var(ModuleUID, TypeKind),
     Func(ModuleUID, FunctionType),
     Class(ModuleUID, Arc<ClassType>),
     Enum(ModuleUID, Arc<EnumType>),
+    Const(ModuleUID, LiteralKind),
+    Module(ModuleUID),
 }
 
 pub struct ModuleParserScope {
     pub uid: ModuleUID,
     pub globals: HashMap<String, GlobalKind>,
+    /// Features of the modules imported with an alias
+    pub module_globals: HashMap<ModuleUID, HashMap<String, GlobalKind>>,
 }
 
 impl ModuleParserScope {
@@ -31,20 +41,41 @@ impl ModuleParserScope {
         Self {
             uid: module_uid,
             globals: HashMap::new(),
+            module_globals: HashMap::new(),
         }
     }
 
     pub fn new_child(&self) -> ParserScope {
-        ParserScope::new_module_child(self)
+        ParserScope::new_module_child(self, None)
+    }
+
+    /// Creates the scope of a function body, `return_type` is used by statements like `?`
+    /// that need to know what the function returns
+    pub fn new_function_child(&self, return_type: TypeKind) -> ParserScope<'_> {
+        ParserScope::new_module_child(self, Some(return_type))
     }
 
     pub fn get(&self, name: &String) -> ScopeGetResult {
-        match self.globals.get(name) {
+        Self::get_global(self.globals.get(name))
+    }
+
+    /// Gets a feature of a module imported with an alias
+    pub fn get_module_feature(&self, module: ModuleUID, name: &String) -> ScopeGetResult {
+        match self.module_globals.get(&module) {
+            Some(globals) => Self::get_global(globals.get(name)),
+            None => ScopeGetResult::None,
+        }
+    }
+
+    fn get_global(global: Option<&GlobalKind>) -> ScopeGetResult {
+        match global {
             Some(GlobalKind::Var(uid, type_)) => ScopeGetResult::Ref(*uid, type_.clone()),
             Some(GlobalKind::Func(uid, type_))
                 => ScopeGetResult::Ref(*uid, TypeKind::Function(type_.clone())),
             Some(GlobalKind::Class(uid, type_)) => ScopeGetResult::Class(*uid, type_.clone()),
             Some(GlobalKind::Enum(uid, type_)) => ScopeGetResult::Enum(*uid, type_.clone()),
+            Some(GlobalKind::Const(_, value)) => ScopeGetResult::Const(value.clone()),
+            Some(GlobalKind::Module(uid)) => ScopeGetResult::Module(*uid),
             None => ScopeGetResult::None,
         }
     }
@@ -79,10 +110,35 @@ impl ModuleParserScope {
                     params_types,
                     Box::new(self.convert_parsable_type(return_type)?)))
             },
+            ParsableType::Result(ok, err) => {
+                TypeKind::Enum(Arc::new(EnumType::new_result(
+                    self.convert_parsable_type(ok)?,
+                    self.convert_parsable_type(err)?)))
+            },
+            ParsableType::Tuple(types) => {
+                let mut tuple_types = Vec::new();
+
+                for type_ in types {
+                    tuple_types.push(self.convert_parsable_type(type_)?);
+                }
+
+                TypeKind::Tuple(tuple_types)
+            },
             ParsableType::Custom(name) => {
                 // TODO: This need a token position in case of error
 
-                match self.globals.get(name) {
+                // <alias>.<name>
+                let global = match name.split_once('.') {
+                    Some((alias, name)) => match self.globals.get(alias) {
+                        Some(GlobalKind::Module(uid)) => self.module_globals
+                            .get(uid)
+                            .and_then(|globals| globals.get(name)),
+                        _ => None,
+                    },
+                    None => self.globals.get(name),
+                };
+
+                match global {
                     Some(GlobalKind::Class(_, type_)) => TypeKind::Class(type_.clone()),
                     Some(GlobalKind::Enum(_, type_)) => TypeKind::Enum(type_.clone()),
                     _ => return Err(LangError::parser(
@@ -114,6 +170,11 @@ impl ModuleParserScope {
             .insert(name, GlobalKind::Enum(self.uid, enum_type));
     }
 
+    pub fn declare_const(&mut self, name: String, value: LiteralKind) {
+        self.globals
+            .insert(name, GlobalKind::Const(self.uid, value));
+    }
+
     pub fn declare_external_func(&mut self, name: String, module: ModuleUID, func_type: FunctionType) {
         self.globals
             .insert(name, GlobalKind::Func(module, func_type));
@@ -134,6 +195,29 @@ impl ModuleParserScope {
             .insert(name, GlobalKind::Enum(module, enum_type));
     }
 
+    /// Declares a feature of an imported module, depending on how the module is imported
+    /// it can be directly accessible, accessible through an alias or not accessible at all
+    pub fn declare_import(&mut self, import: &ImportKind, module: ModuleUID, name: String, global: GlobalKind) {
+        match import {
+            ImportKind::All => {
+                self.globals.insert(name, global);
+            },
+            ImportKind::Alias(alias) => {
+                self.globals.insert(alias.clone(), GlobalKind::Module(module));
+
+                self.module_globals
+                    .entry(module)
+                    .or_default()
+                    .insert(name, global);
+            },
+            ImportKind::Selective(names) => {
+                if names.iter().any(|(n, _)| *n == name) {
+                    self.globals.insert(name, global);
+                }
+            },
+        }
+    }
+
     pub fn get_class(&self, name: &String) -> Result<Arc<ClassType>, LangError> {
         match self.globals.get(name) {
             Some(GlobalKind::Class(_, class_type)) => Ok(class_type.clone()),
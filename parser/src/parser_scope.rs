@@ -5,10 +5,13 @@ use common::tokens::{TokenKind, Token, PrimitiveType};
 use common::{ast::{ASTNode, NodeKind, types::{TypeKind, ParenthesisKind, ParenthesisState, OperatorKind, ReturnKind, FunctionType, LiteralKind}}, errors::LangError, constants::SCOPE_SIZE};
 use smallvec::SmallVec;
 use common::ast::ElseType;
-use common::constants::CLASS_CONSTRUCTOR_NAME;
+use common::ast::ASTBody;
+use common::ast::types::{BoolOperatorKind, ClassKind, ClassType, EnumType};
+use common::constants::{ASSERT_DEFAULT_MESSAGE, CLASS_CONSTRUCTOR_NAME, CLASS_PARENT_REFERENCE, CLASS_SELF_REFERENCE, RESULT_ERR_VARIANT, RESULT_OK_VARIANT};
 use common::module::ModuleUID;
 use common::tokens_iterator::Tokens;
-use crate::utils::{parse_type_option, TokensExtensions};
+use std::sync::Arc;
+use crate::utils::TokensExtensions;
 use crate::{expect_token, errors::ParsingErrorHelper, expect_open_body};
 use crate::parser_module_scope::{ModuleParserScope, ScopeGetResult};
 
@@ -21,6 +24,7 @@ pub enum ScopeParent<'a> {
 pub struct ParserScope<'a> {
     parent: ScopeParent<'a>,
     pub eval_type: RefCell<TypeKind>,
+    return_type: Option<TypeKind>,
     module_uid: ModuleUID,
     
     names: RefCell<SmallVec<[String; SCOPE_SIZE]>>,
@@ -28,10 +32,11 @@ pub struct ParserScope<'a> {
 }
 
 impl<'a> ParserScope<'a> {
-    pub fn new_module_child(module: &'a ModuleParserScope) -> Self {
+    pub fn new_module_child(module: &'a ModuleParserScope, return_type: Option<TypeKind>) -> Self {
         Self {
             parent: ScopeParent::Module(module),
             eval_type: RefCell::new(TypeKind::Nothing),
+            return_type,
             module_uid: module.uid,
 
             names: RefCell::new(SmallVec::new()),
@@ -43,6 +48,7 @@ impl<'a> ParserScope<'a> {
         Self {
             parent: ScopeParent::Scope(self),
             eval_type: RefCell::new(TypeKind::Nothing),
+            return_type: self.return_type.clone(),
             module_uid: self.module_uid,
 
             names: RefCell::new(SmallVec::new()),
@@ -69,6 +75,13 @@ impl<'a> ParserScope<'a> {
         }
     }
     
+    pub fn module_scope(&self) -> &ModuleParserScope {
+        match self.parent {
+            ScopeParent::Module(module) => module,
+            ScopeParent::Scope(scope) => scope.module_scope(),
+        }
+    }
+
     pub fn declare(&self, name: String, type_kind: TypeKind) {
         self.names.borrow_mut().push(name);
         self.types.borrow_mut().push(type_kind);
@@ -83,6 +96,9 @@ impl<'a> ParserScope<'a> {
         let token = token.unwrap();
 
         let result = match &token.kind {
+            TokenKind::Variable if matches!(tokens.peek(), Some(Token { kind: TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Open), .. })) => {
+                self.parse_tuple_decl(&token, tokens)?
+            },
             TokenKind::Variable => {
                 // name
                 let name = tokens.pop_err()?;
@@ -93,17 +109,19 @@ impl<'a> ParserScope<'a> {
                 };
 
                 // ?(type)
-                let assign_type = parse_type_option(tokens);
+                let assign_type = self.parse_type_option(tokens)?;
 
                 // =
                 expect_token!(tokens.pop(), TokenKind::Operator(OperatorKind::Assign));
 
                 // value
-                let value = self.parse_statement(tokens)?;
+                let mut value = self.parse_statement(tokens)?;
                 
                 let eval_type = match assign_type {
                     Some(type_kind) => {
-                        if !type_kind.is_compatible(&value.eval_type) {
+                        value = Self::coerce_result(value, &type_kind);
+
+                        if !value.eval_type.is_compatible(&type_kind) {
                             return Err(LangError::wrong_type(&token, &type_kind, &value.eval_type))
                         }
                         type_kind
@@ -115,21 +133,68 @@ impl<'a> ParserScope<'a> {
 
                 ASTNode::new(NodeKind::new_variable_decl(name, value), eval_type)
             },
+            TokenKind::Symbol(name) if name == CLASS_PARENT_REFERENCE => self.parse_parent_invok(&token, tokens)?,
             TokenKind::Symbol(name) => {
-                match self.get(name) {
+                let (name, result) = match self.get(name) {
+                    // <alias>.<name>
+                    ScopeGetResult::Module(uid) => {
+                        expect_token!(tokens.pop(), TokenKind::Operator(OperatorKind::Dot));
+
+                        let token = tokens.pop_err()?;
+                        let name = match &token.kind {
+                            TokenKind::Symbol(name) => name,
+                            _ => return Err(LangError::new_parser_unexpected_token(&token)),
+                        };
+
+                        match self.module_scope().get_module_feature(uid, name) {
+                            ScopeGetResult::None => return Err(LangError::parser(&token, ParserErrorKind::FeatureNotFound(name.clone()))),
+                            result => (name.clone(), result),
+                        }
+                    },
+                    result => (name.clone(), result),
+                };
+
+                match result {
+                    ScopeGetResult::Class(_, class_type) if matches!(tokens.peek(), Some(Token { kind: TokenKind::Operator(OperatorKind::Dot), .. })) => {
+                        // Class.<constant> / Class.<static method>
+                        tokens.pop();
+
+                        let token = tokens.pop_err()?;
+                        let member_name = match &token.kind {
+                            TokenKind::Symbol(name) => name,
+                            _ => return Err(LangError::new_parser_unexpected_token(&token)),
+                        };
+
+                        if let Some((owner, value)) = class_type.find_constant(member_name) {
+                            self.check_member_access(&token, owner.module, owner.is_public(member_name), member_name)?;
+
+                            Self::new_constant(value)
+                        } else if let Some((owner, func_type)) = class_type.find_static_method(member_name) {
+                            self.check_member_access(&token, owner.module, owner.is_public(member_name), member_name)?;
+
+                            ASTNode::new(
+                                NodeKind::new_variable_ref(owner.module, format!("{}::{}", owner.name, member_name)),
+                                TypeKind::Function(func_type))
+                        } else {
+                            return Err(LangError::parser(&token, ParserErrorKind::FieldDoesntExist))
+                        }
+                    },
                     ScopeGetResult::Class(_, class_type) => {
+                        if let ClassKind::Extern = class_type.kind {
+                            return Err(LangError::parser(&token, ParserErrorKind::ExternConstruct(class_type.name.clone())))
+                        }
+
                         expect_token!(tokens.pop(), TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Open));
 
                         let parameters = self.parse_parameter_values(tokens)?;
 
-                        // TODO: Make this a bit better
-                        let constructor = class_type.methods
-                            .borrow()
-                            .iter()
-                            .find(|(name, _)| name == CLASS_CONSTRUCTOR_NAME)
-                            .cloned();
+                        // A class without a constructor uses the one of its parent
+                        let constructor = class_type.find_method(CLASS_CONSTRUCTOR_NAME);
 
                         match constructor {
+                            Some((owner, _)) if owner.module != self.module_uid && !owner.is_public(CLASS_CONSTRUCTOR_NAME) => {
+                                return Err(LangError::parser(&token, ParserErrorKind::PrivateMember(CLASS_CONSTRUCTOR_NAME.to_string())))
+                            },
                             Some((_, constructor)) => {
                                 // Check parameters types
                                 if parameters.len() != constructor.0.len() {
@@ -154,7 +219,7 @@ impl<'a> ParserScope<'a> {
                             TypeKind::Class(class_type.clone()))
                     },
                     ScopeGetResult::Enum(_, type_) => {
-                        // EnumType.Variant (({value}))?
+                        // EnumType.Variant (({value}))? / EnumType.<constant> / EnumType.<static method>
 
                         // .
                         expect_token!(tokens.pop(), TokenKind::Operator(OperatorKind::Dot));
@@ -166,6 +231,24 @@ impl<'a> ParserScope<'a> {
                             _ => return Err(LangError::new_parser_unexpected_token(&token)),
                         };
 
+                        if type_.get_variant(variant_name).is_none() {
+                            if let Some(value) = type_.find_constant(variant_name) {
+                                self.check_member_access(&token, type_.module, type_.is_public(variant_name), variant_name)?;
+
+                                return self.parse_infixes(Self::new_constant(value), tokens)
+                            }
+
+                            if let Some(func_type) = type_.find_static_method(variant_name) {
+                                self.check_member_access(&token, type_.module, type_.is_public(variant_name), variant_name)?;
+
+                                let node = ASTNode::new(
+                                    NodeKind::new_variable_ref(type_.module, format!("{}::{}", type_.name, variant_name)),
+                                    TypeKind::Function(func_type));
+
+                                return self.parse_infixes(node, tokens)
+                            }
+                        }
+
                         let variants = type_.variants.borrow();
                         let variant = variants
                             .iter()
@@ -221,10 +304,46 @@ impl<'a> ParserScope<'a> {
                             TypeKind::Enum(type_.clone()),
                         )
                     },
+                    ScopeGetResult::Const(value) => Self::new_constant(value),
                     ScopeGetResult::Ref(uid, type_) => {
                         let var_ref = NodeKind::new_variable_ref(uid, name.clone());
                         ASTNode::new(var_ref, type_)
                     },
+                    ScopeGetResult::None if name == RESULT_OK_VARIANT || name == RESULT_ERR_VARIANT => {
+                        // Ok(({value})?) / Err(({value})?)
+
+                        // (
+                        expect_token!(tokens.pop(), TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Open));
+
+                        let mut parameters = self.parse_parameter_values(tokens)?;
+
+                        let value = match parameters.len() {
+                            0 => ASTNode::new(NodeKind::new_literal(LiteralKind::Nothing), TypeKind::Nothing),
+                            1 => parameters.remove(0),
+                            _ => return Err(LangError::parser(&token, ParserErrorKind::InvalidArgCount(1))),
+                        };
+
+                        // The other side of the result is unknown until it's checked against an expected type
+                        let result_type = if name == RESULT_OK_VARIANT {
+                            EnumType::new_result(value.eval_type.clone(), TypeKind::Unknown)
+                        } else {
+                            EnumType::new_result(TypeKind::Unknown, value.eval_type.clone())
+                        };
+
+                        let (variant_id, variant_type) = result_type.get_variant(&name).unwrap();
+                        let result_type = Arc::new(result_type);
+
+                        ASTNode::new(
+                            NodeKind::new_construct_enum_variant(
+                                value,
+                                variant_type,
+                                variant_id,
+                                result_type.clone(),
+                            ),
+                            TypeKind::Enum(result_type),
+                        )
+                    },
+                    ScopeGetResult::Module(_) => return Err(LangError::new_parser_unexpected_token(&token)),
                     ScopeGetResult::None => return Err(LangError::parser(&token, ParserErrorKind::VarNotFound)),
                 }
             }
@@ -232,14 +351,24 @@ impl<'a> ParserScope<'a> {
             TokenKind::Parenthesis(kind, state) => {
                 match (kind, state) {
                     (ParenthesisKind::Round, ParenthesisState::Open) => {
-                        let result = self.parse_statement(tokens);
+                        let result = self.parse_statement(tokens)?;
                         
                         match tokens.pop_err()?.kind {
-                            TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Close) => (),
+                            TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Close) => result,
+                            // (<value>, (<value>,)*)
+                            TokenKind::Operator(OperatorKind::Comma) => {
+                                let mut values = vec![result];
+                                values.extend(self.parse_parameter_values(tokens)?);
+
+                                let types = values
+                                    .iter()
+                                    .map(|value| value.eval_type.clone())
+                                    .collect();
+
+                                ASTNode::new(NodeKind::new_tuple_literal(values), TypeKind::Tuple(types))
+                            },
                             _ => return Err(LangError::new_parser_unexpected_token(&token)),
                         }
-                        
-                        result?
                     },
                     (ParenthesisKind::Square, ParenthesisState::Open) => {
                         let (vector_type, values) = self.parse_vector_values(tokens)?;
@@ -258,7 +387,12 @@ impl<'a> ParserScope<'a> {
                         None
                     },
                     Some(_) => {
-                        Some(self.parse_statement(tokens)?)
+                        let value = self.parse_statement(tokens)?;
+
+                        match (&token.kind, &self.return_type) {
+                            (TokenKind::Return, Some(return_type)) => Some(Self::coerce_result(value, return_type)),
+                            _ => Some(value),
+                        }
                     },
                 };
                 
@@ -276,6 +410,63 @@ impl<'a> ParserScope<'a> {
 
                 ASTNode::new(NodeKind::new_return_statement(value, kind), TypeKind::Nothing)
             },
+            TokenKind::Panic => {
+                // panic({message})
+
+                // (
+                expect_token!(tokens.pop(), TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Open));
+
+                // {message}
+                let message = self.parse_statement(tokens)?;
+
+                // )
+                expect_token!(tokens.pop(), TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Close));
+
+                if !message.eval_type.is_compatible(&TypeKind::String) {
+                    return Err(LangError::wrong_type(&token, &TypeKind::String, &message.eval_type))
+                }
+
+                ASTNode::new(NodeKind::new_return_statement(Some(message), ReturnKind::Panic), TypeKind::Nothing)
+            },
+            TokenKind::Assert => {
+                // assert({condition}(, {message})?)
+
+                // (
+                expect_token!(tokens.pop(), TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Open));
+
+                let mut parameters = self.parse_parameter_values(tokens)?;
+
+                let message = match parameters.len() {
+                    1 => ASTNode::new(
+                        NodeKind::new_literal(LiteralKind::String(ASSERT_DEFAULT_MESSAGE.to_string())),
+                        TypeKind::String),
+                    2 => parameters.pop().unwrap(),
+                    _ => return Err(LangError::parser(&token, ParserErrorKind::InvalidArgCount(2))),
+                };
+                let condition = parameters.pop().unwrap();
+
+                if !condition.eval_type.is_compatible(&TypeKind::Bool) {
+                    return Err(LangError::wrong_type(&token, &TypeKind::Bool, &condition.eval_type))
+                }
+
+                if !message.eval_type.is_compatible(&TypeKind::String) {
+                    return Err(LangError::wrong_type(&token, &TypeKind::String, &message.eval_type))
+                }
+
+                // Assertions are just a shorthand for "if {condition} == false { panic({message}) }"
+                let condition = ASTNode::new(
+                    NodeKind::new_bool_operation(
+                        BoolOperatorKind::Equal,
+                        condition,
+                        ASTNode::new(NodeKind::new_literal(LiteralKind::Bool(false)), TypeKind::Bool)),
+                    TypeKind::Bool);
+
+                let panic = ASTNode::new(
+                    NodeKind::new_return_statement(Some(message), ReturnKind::Panic),
+                    TypeKind::Nothing);
+
+                ASTNode::new(NodeKind::new_if_statement(condition, vec![panic], ElseType::None), TypeKind::Nothing)
+            },
             TokenKind::If => {
                 // condition
                 let condition = self.parse_statement(tokens)?;
@@ -336,14 +527,17 @@ impl<'a> ParserScope<'a> {
             TokenKind::Import |
             TokenKind::Class |
             TokenKind::Enum |
+            TokenKind::Constant |
             TokenKind::Function |
             TokenKind::Else |
             TokenKind::Attribute(_) => return Err(LangError::new_parser_unexpected_token(&token)),
         };
         
 
-        let mut node = result;
-        
+        self.parse_infixes(result, tokens)
+    }
+
+    fn parse_infixes(&self, mut node: ASTNode, tokens: &mut Tokens) -> Result<ASTNode, LangError> {
         Ok(loop {
             let res = self.parse_infix(node, tokens)?; 
             if res.1 {
@@ -394,8 +588,20 @@ impl<'a> ParserScope<'a> {
 
         match infix.kind {
             TokenKind::MathOperator(operator) => {
-                tokens.pop();
+                let token = tokens.pop().unwrap();
                 let right = self.parse_statement(tokens)?;
+
+                // The operators of a class are implemented by its methods
+                if let TypeKind::Class(class_type) = &node.eval_type {
+                    let class_type = class_type.clone();
+                    let method = operator.method_name();
+
+                    if class_type.find_method(method).is_none() {
+                        return Err(LangError::parser(&token, ParserErrorKind::OperatorNotImplemented(class_type.name.clone(), method.to_string())))
+                    }
+
+                    return Ok((self.new_operator_call(&token, class_type, method, node, right)?, true))
+                }
                 
                 let eval_type = Self::predict_math_result(operator.clone(), &node.eval_type, &right.eval_type);
                 
@@ -408,8 +614,56 @@ impl<'a> ParserScope<'a> {
                 ))
             },
             TokenKind::BoolOperator(operator) => {
-                tokens.pop();
+                let token = tokens.pop().unwrap();
                 let right = self.parse_statement(tokens)?;
+
+                if let TypeKind::Class(class_type) = &node.eval_type {
+                    let class_type = class_type.clone();
+                    let method = operator.method_name();
+
+                    match (class_type.find_method(method), &operator) {
+                        // {left}.eq({right}) / {left}.eq({right}) == false
+                        (Some(_), BoolOperatorKind::Equal | BoolOperatorKind::Different) => {
+                            let call = self.new_operator_call(&token, class_type, method, node, right)?;
+
+                            if !call.eval_type.is_compatible(&TypeKind::Bool) {
+                                return Err(LangError::wrong_type(&token, &TypeKind::Bool, &call.eval_type))
+                            }
+
+                            let call = match operator {
+                                BoolOperatorKind::Equal => call,
+                                _ => ASTNode::new(
+                                    NodeKind::new_bool_operation(
+                                        BoolOperatorKind::Equal,
+                                        call,
+                                        ASTNode::new(NodeKind::new_literal(LiteralKind::Bool(false)), TypeKind::Bool)),
+                                    TypeKind::Bool),
+                            };
+
+                            return Ok((call, true))
+                        },
+                        // {left}.cmp({right}) {operator} 0
+                        (Some(_), _) => {
+                            let call = self.new_operator_call(&token, class_type, method, node, right)?;
+
+                            if !call.eval_type.is_compatible(&TypeKind::Int) {
+                                return Err(LangError::wrong_type(&token, &TypeKind::Int, &call.eval_type))
+                            }
+
+                            return Ok((
+                                ASTNode::new(
+                                    NodeKind::new_bool_operation(
+                                        operator.clone(),
+                                        call,
+                                        ASTNode::new(NodeKind::new_literal(LiteralKind::Int(0)), TypeKind::Int)),
+                                    TypeKind::Bool),
+                                true))
+                        },
+                        // Objects without `eq` are compared by reference
+                        (None, BoolOperatorKind::Equal | BoolOperatorKind::Different) => (),
+                        (None, _) => return Err(LangError::parser(&token, ParserErrorKind::OperatorNotImplemented(class_type.name.clone(), method.to_string()))),
+                    }
+                }
                 
                 Ok((
                     ASTNode::new(
@@ -448,16 +702,7 @@ impl<'a> ParserScope<'a> {
                     _ => return Err(LangError::parser(&token, ParserErrorKind::NotCallable)),
                 };
 
-                // Check parameters types
-                if parameters.len() != arg_types.len() {
-                    return Err(LangError::parser(&token, ParserErrorKind::InvalidArgCount(arg_types.len())))
-                }
-                
-                for i in 0..parameters.len() {
-                    if !parameters[i].eval_type.is_compatible(&arg_types[i]) {
-                        return Err(LangError::wrong_type(&token, &arg_types[i], &parameters[i].eval_type))
-                    }
-                }
+                let parameters = Self::check_arguments(&token, parameters, arg_types)?;
                 
                 let ret_type = ret_type.as_ref().clone();
 
@@ -480,17 +725,22 @@ impl<'a> ParserScope<'a> {
                 
                 match &node.eval_type {
                     TypeKind::Class(class_type) => {
-                        let field_type = match class_type.fields.borrow().iter().find(|(name, _)| name == field_name) {
-                            Some((_, t)) => t.clone(),
+                        let (owner, field_type) = match class_type.find_field(field_name) {
+                            Some(field) => field,
                             None => {
                                 // If the field doesn't exist search for a method
-                                match class_type.methods.borrow().iter().find(|(name, _)| name == field_name) {
-                                    Some((_, ft)) => TypeKind::Function(ft.clone()),
+                                match class_type.find_method(field_name) {
+                                    Some((owner, ft)) => (owner, TypeKind::Function(ft)),
                                     None => return Err(LangError::parser(&token, ParserErrorKind::FieldDoesntExist)),
                                 }
                             }
                         };
 
+                        // Private members are only accessible inside the module of the class declaring them
+                        if owner.module != self.module_uid && !owner.is_public(field_name) {
+                            return Err(LangError::parser(&token, ParserErrorKind::PrivateMember(field_name.clone())))
+                        }
+
                         let class_type = class_type.clone();
 
                         Ok((
@@ -498,20 +748,92 @@ impl<'a> ParserScope<'a> {
                                 NodeKind::new_field_access(node, class_type, field_name.clone()),
                                 field_type),
                             true))
-                    }
+                    },
+                    TypeKind::Enum(enum_type) => {
+                        // {enum}.<method>((<param>,)*)
+                        let FunctionType(arg_types, ret_type) = match enum_type.find_method(field_name) {
+                            Some(method) => method,
+                            None => return Err(LangError::parser(&token, ParserErrorKind::FieldDoesntExist)),
+                        };
+
+                        self.check_member_access(&token, enum_type.module, enum_type.is_public(field_name), field_name)?;
+
+                        // (
+                        expect_token!(tokens.pop(), TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Open));
+
+                        let parameters = self.parse_parameter_values(tokens)?;
+                        let mut parameters = Self::check_arguments(&token, parameters, &arg_types)?;
+
+                        // The enum value is given as the first parameter, like `this` in the methods of a class
+                        let mut method_args = vec![node.eval_type.clone()];
+                        method_args.extend(arg_types);
+
+                        let method = ASTNode::new(
+                            NodeKind::new_variable_ref(enum_type.module, format!("{}::{}", enum_type.name, field_name)),
+                            TypeKind::Function(FunctionType(method_args, ret_type.clone())));
+
+                        parameters.insert(0, node);
+
+                        Ok((
+                            ASTNode::new(
+                                NodeKind::new_function_invok(method, parameters),
+                                ret_type.as_ref().clone()),
+                            true))
+                    },
                     _ => return Err(LangError::parser(&token, ParserErrorKind::InvalidFieldAccess)),
                 }
             },
+            TokenKind::Operator(OperatorKind::Question) => {
+                // {result}?
+                let token = tokens.pop().unwrap();
+
+                let ok_type = match &node.eval_type {
+                    TypeKind::Enum(enum_type) => match enum_type.result_types() {
+                        Some((ok_type, _)) => ok_type,
+                        None => return Err(LangError::parser(&token, ParserErrorKind::InvalidPropagation)),
+                    },
+                    _ => return Err(LangError::parser(&token, ParserErrorKind::InvalidPropagation)),
+                };
+
+                // The error gets returned as is so the function needs to return a compatible result
+                let result_type = match &self.return_type {
+                    Some(TypeKind::Enum(return_type)) => match (return_type.result_types(), &node.eval_type) {
+                        (Some((_, return_err)), TypeKind::Enum(enum_type)) => {
+                            let (_, err) = enum_type.result_types().unwrap();
+
+                            if !err.is_compatible(&return_err) {
+                                return Err(LangError::wrong_type(&token, &return_err, &err))
+                            }
+
+                            return_type.clone()
+                        },
+                        _ => return Err(LangError::parser(&token, ParserErrorKind::InvalidPropagation)),
+                    },
+                    _ => return Err(LangError::parser(&token, ParserErrorKind::InvalidPropagation)),
+                };
+
+                Ok((
+                    ASTNode::new(
+                        NodeKind::new_result_propagation(node, result_type),
+                        ok_type),
+                    true))
+            },
             TokenKind::Operator(OperatorKind::Assign) => {
                 tokens.pop();
 
                 let value = self.parse_statement(tokens)?;
 
+                // The methods of data classes and enums get a copy of `this`, the changes would be lost
+                if Self::is_value_this(&node) {
+                    return Err(LangError::parser(&infix, ParserErrorKind::Unsupported(
+                        "`this` can't be modified by the methods of data classes and enums".to_string())))
+                }
+
                 match *node.kind {
-                    NodeKind::VariableRef { module: _, name } => {
+                    NodeKind::VariableRef { module, name } => {
                         Ok((
                             ASTNode::new(
-                                NodeKind::new_variable_asgn(name, value),
+                                NodeKind::new_variable_asgn(module, name, value),
                                 TypeKind::Nothing),
                             true))
                     },
@@ -536,4 +858,193 @@ impl<'a> ParserScope<'a> {
             _ => Ok((node, false)),
         }
     }
+
+    /// Returns true if the node is `this` or one of its fields when `this` is a value
+    fn is_value_this(node: &ASTNode) -> bool {
+        match node.kind.as_ref() {
+            NodeKind::VariableRef { name, .. } if name == CLASS_SELF_REFERENCE => match &node.eval_type {
+                TypeKind::Class(class_type) => class_type.kind == ClassKind::Data,
+                TypeKind::Enum(_) => true,
+                _ => false,
+            },
+            // The fields of the other classes are in the memory
+            NodeKind::FieldAccess { variable, class_type, .. } => class_type.kind == ClassKind::Data && Self::is_value_this(variable),
+            _ => false,
+        }
+    }
+
+    /// var (<name>, (<name>,)*) (type)? = <value>
+    fn parse_tuple_decl(&self, token: &Token, tokens: &mut Tokens) -> Result<ASTNode, LangError> {
+        // (
+        tokens.pop();
+
+        let mut names = Vec::new();
+
+        loop {
+            let name_token = tokens.pop_err()?;
+
+            match name_token.kind {
+                TokenKind::Symbol(name) => names.push(name),
+                _ => return Err(LangError::new_parser_unexpected_token(&name_token)),
+            }
+
+            let separator = tokens.pop_err()?;
+
+            match separator.kind {
+                TokenKind::Operator(OperatorKind::Comma) => (),
+                TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Close) => break,
+                _ => return Err(LangError::new_parser_unexpected_token(&separator)),
+            }
+        }
+
+        // ?(type)
+        let assign_type = self.parse_type_option(tokens)?;
+
+        // =
+        expect_token!(tokens.pop(), TokenKind::Operator(OperatorKind::Assign));
+
+        // value
+        let mut value = self.parse_statement(tokens)?;
+
+        if let Some(type_kind) = &assign_type {
+            value = Self::coerce_result(value, type_kind);
+
+            if !value.eval_type.is_compatible(type_kind) {
+                return Err(LangError::wrong_type(token, type_kind, &value.eval_type))
+            }
+        }
+
+        let types = match assign_type.as_ref().unwrap_or(&value.eval_type) {
+            TypeKind::Tuple(types) if types.len() == names.len() => types.clone(),
+            type_ => return Err(LangError::wrong_type(
+                token,
+                &TypeKind::Tuple(vec![TypeKind::Unknown; names.len()]),
+                type_)),
+        };
+
+        for (name, type_) in names.iter().zip(types) {
+            self.declare(name.clone(), type_);
+        }
+
+        Ok(ASTNode::new(NodeKind::new_tuple_decl(names, value), TypeKind::Nothing))
+    }
+
+    /// super.<method>((<param>,)*)
+    fn parse_parent_invok(&self, token: &Token, tokens: &mut Tokens) -> Result<ASTNode, LangError> {
+        // Only the methods of a class with a parent can use it
+        let parent = match self.get(&CLASS_SELF_REFERENCE.to_string()) {
+            ScopeGetResult::Ref(_, TypeKind::Class(class_type)) => class_type.parent(),
+            _ => None,
+        };
+
+        let parent = match parent {
+            Some(parent) => parent,
+            None => return Err(LangError::parser(token, ParserErrorKind::VarNotFound)),
+        };
+
+        // .
+        expect_token!(tokens.pop(), TokenKind::Operator(OperatorKind::Dot));
+
+        // <method>
+        let token = tokens.pop_err()?;
+        let method_name = match &token.kind {
+            TokenKind::Symbol(name) => name.clone(),
+            _ => return Err(LangError::new_parser_unexpected_token(&token)),
+        };
+
+        let (owner, FunctionType(arg_types, ret_type)) = match parent.find_method(&method_name) {
+            Some(method) => method,
+            None => return Err(LangError::parser(&token, ParserErrorKind::FieldDoesntExist)),
+        };
+
+        if owner.module != self.module_uid && !owner.is_public(&method_name) {
+            return Err(LangError::parser(&token, ParserErrorKind::PrivateMember(method_name)))
+        }
+
+        // (
+        expect_token!(tokens.pop(), TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Open));
+
+        let parameters = self.parse_parameter_values(tokens)?;
+        let parameters = Self::check_arguments(&token, parameters, &arg_types)?;
+
+        Ok(ASTNode::new(
+            NodeKind::new_parent_invok(owner, method_name, parameters),
+            ret_type.as_ref().clone()))
+    }
+
+    /// {left}.<method>({right})
+    fn new_operator_call(&self, token: &Token, class_type: Arc<ClassType>, method: &str, left: ASTNode, right: ASTNode) -> Result<ASTNode, LangError> {
+        let (owner, method_type) = class_type.find_method(method).unwrap();
+
+        self.check_member_access(token, owner.module, owner.is_public(method), method)?;
+
+        let parameters = Self::check_arguments(token, vec![right], &method_type.0)?;
+        let ret_type = method_type.1.as_ref().clone();
+
+        let method = ASTNode::new(
+            NodeKind::new_field_access(left, class_type, method.to_string()),
+            TypeKind::Function(method_type));
+
+        Ok(ASTNode::new(NodeKind::new_function_invok(method, parameters), ret_type))
+    }
+
+    /// Private members are only accessible inside the module declaring them
+    fn check_member_access(&self, token: &Token, owner: ModuleUID, is_public: bool, name: &str) -> Result<(), LangError> {
+        if owner != self.module_uid && !is_public {
+            return Err(LangError::parser(token, ParserErrorKind::PrivateMember(name.to_string())))
+        }
+
+        Ok(())
+    }
+
+    fn new_constant(value: LiteralKind) -> ASTNode {
+        let type_ = TypeKind::from(&value);
+
+        ASTNode::new(NodeKind::new_literal(value), type_)
+    }
+
+    /// Checks the count and the types of the arguments of a call
+    fn check_arguments(token: &Token, parameters: ASTBody, arg_types: &[TypeKind]) -> Result<ASTBody, LangError> {
+        if parameters.len() != arg_types.len() {
+            return Err(LangError::parser(token, ParserErrorKind::InvalidArgCount(arg_types.len())))
+        }
+
+        let parameters: ASTBody = parameters
+            .into_iter()
+            .zip(arg_types.iter())
+            .map(|(param, arg_type)| Self::coerce_result(param, arg_type))
+            .collect();
+
+        for i in 0..parameters.len() {
+            if !parameters[i].eval_type.is_compatible(&arg_types[i]) {
+                return Err(LangError::wrong_type(token, &arg_types[i], &parameters[i].eval_type))
+            }
+        }
+
+        Ok(parameters)
+    }
+
+    /// `Ok` and `Err` literals only know one side of their result type,
+    /// this completes it using the type the value is expected to be
+    fn coerce_result(node: ASTNode, expected: &TypeKind) -> ASTNode {
+        let expected_enum = match expected {
+            TypeKind::Enum(enum_type) if enum_type.result_types().is_some() => enum_type,
+            _ => return node,
+        };
+
+        if !node.eval_type.is_compatible(expected) {
+            return node
+        }
+
+        match *node.kind {
+            NodeKind::ConstructEnumVariant { value, variant_type: _, variant_id, enum_type: _ } => {
+                let variant_type = expected_enum.variants.borrow()[variant_id as usize].1.as_ref().clone();
+
+                ASTNode::new(
+                    NodeKind::new_construct_enum_variant(value, variant_type, variant_id, expected_enum.clone()),
+                    expected.clone())
+            },
+            kind => ASTNode::new(kind, node.eval_type),
+        }
+    }
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
-use common::{ast::{ASTBody, ASTNode, types::{ParenthesisKind, ParenthesisState, OperatorKind, TypeKind, MathOperatorKind}}, errors::{LangError, ParserErrorKind}, tokens::{Token, TokenKind}};
+use common::{ast::{ASTBody, ASTNode, Span, types::{ParenthesisKind, ParenthesisState, OperatorKind, TypeKind, MathOperatorKind}}, errors::{LangError, ParserErrorKind}, tokens::{Token, TokenKind}};
 use common::tokens_iterator::Tokens;
 use crate::{errors::ParsingErrorHelper, parser_scope::ParserScope};
+use crate::modules::preparsing_utils::preparse_type_option;
 
 #[macro_export]
 macro_rules! expect_token {
@@ -113,8 +114,10 @@ impl<'a> ParserScope<'a> {
                 TokenKind::NewLine => { tokens.pop(); continue },
                 _ => self.parse_statement(tokens)?,
             };
-            
-            body.push(result);
+
+            let end = tokens.previous().map_or(token.end, |last| last.end);
+
+            body.push(result.with_span(Span { start: token.start, end }));
         }
         
         // Popping the last }
@@ -210,6 +213,14 @@ impl<'a> ParserScope<'a> {
         Ok(body)
     }
 
+    pub fn parse_type_option(&self, tokens: &mut Tokens) -> Result<Option<TypeKind>, LangError> {
+        // type
+        match preparse_type_option(tokens) {
+            Some(type_) => Ok(Some(self.module_scope().convert_parsable_type(&type_)?)),
+            None => Ok(None),
+        }
+    }
+
     pub fn predict_math_result(kind: MathOperatorKind, type_a: &TypeKind, type_b: &TypeKind) -> TypeKind {
         match kind {
             MathOperatorKind::Plus => {
@@ -311,14 +322,3 @@ impl<'a> ParserScope<'a> {
         }
     }
 }
-
-pub fn parse_type_option(tokens: &mut Tokens) -> Option<TypeKind> {
-    // type
-    match tokens.peek() {
-        Some(Token { kind: TokenKind::Type(tk), start: _, end: _ }) => {
-            tokens.pop();
-            Some(TypeKind::from(&tk))
-        },
-        _ => None
-    }
-}
\ No newline at end of file
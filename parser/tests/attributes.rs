@@ -0,0 +1,170 @@
+mod utils;
+
+use std::sync::{Arc, Mutex};
+use common::ast::types::{Attribute, AttributeArgs, LiteralKind};
+use common::errors::{BuildErrorKind, LangError};
+use common::module::ModuleFeature;
+use parser::modules::attribute_processor::{AttributeContext, AttributeProcessor};
+use parser::modules::module_loader::ModuleLoader;
+use utils::{load, load_with};
+
+const SOURCE: &str = "
+@Route(\"/home\", 2, cached = true, ratio = 0.5)
+func home() Int {
+    return 1
+}
+
+class Widget {
+    @Bind(\"width\")
+    size Int
+
+    @Route(\"/widget\")
+    func render() Int {
+        return this.size
+    }
+}
+";
+
+/// The feature, the member and the arguments of every use of an attribute
+type Uses = Arc<Mutex<Vec<(String, Option<String>, AttributeArgs)>>>;
+
+/// Records where the attribute is used
+struct Recorder {
+    name: String,
+    uses: Uses,
+}
+
+impl AttributeProcessor for Recorder {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn process(&self, context: AttributeContext, _: &mut ModuleFeature) -> Result<(), LangError> {
+        self.uses.lock().unwrap().push((
+            context.feature_name.to_string(),
+            context.member_name.map(|name| name.to_string()),
+            context.args.clone(),
+        ));
+
+        Ok(())
+    }
+}
+
+/// Makes the marked features public
+struct Expose;
+
+impl AttributeProcessor for Expose {
+    fn name(&self) -> &str {
+        "Expose"
+    }
+
+    fn process(&self, _: AttributeContext, feature: &mut ModuleFeature) -> Result<(), LangError> {
+        if let ModuleFeature::Function(def) = feature {
+            def.attributes.push(Attribute::Public);
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks that the routes have a path
+struct CheckRoute;
+
+impl AttributeProcessor for CheckRoute {
+    fn name(&self) -> &str {
+        "Route"
+    }
+
+    fn process(&self, context: AttributeContext, _: &mut ModuleFeature) -> Result<(), LangError> {
+        match context.args.positional.first() {
+            Some(LiteralKind::String(_)) => Ok(()),
+            _ => Err(LangError::build(BuildErrorKind::InvalidAttributeArgs("Route".to_string()))),
+        }
+    }
+}
+
+fn recorder(name: &str) -> (Recorder, Uses) {
+    let uses = Arc::new(Mutex::new(Vec::new()));
+
+    (Recorder { name: name.to_string(), uses: uses.clone() }, uses)
+}
+
+#[test]
+fn attributes_keep_their_arguments() {
+    let module = load(SOURCE).unwrap();
+
+    let args = AttributeArgs {
+        positional: vec![LiteralKind::String("/home".to_string()), LiteralKind::Int(2)],
+        named: vec![("cached".to_string(), LiteralKind::Bool(true)), ("ratio".to_string(), LiteralKind::Float(0.5))],
+    };
+
+    assert_eq!(module.features["home"].attributes(), &vec![Attribute::Custom("Route".to_string(), Box::new(args))]);
+}
+
+#[test]
+fn processors_are_called_for_features_and_members() {
+    let (routes, route_uses) = recorder("Route");
+    let (binds, bind_uses) = recorder("Bind");
+
+    let mut loader = ModuleLoader::new();
+    loader.register_attribute_processor(Box::new(routes));
+    loader.register_attribute_processor(Box::new(binds));
+    load_with(&mut loader, SOURCE).unwrap();
+
+    let mut route_uses = route_uses.lock().unwrap().clone();
+    route_uses.sort_by(|a, b| a.0.cmp(&b.0));
+
+    assert_eq!(route_uses.len(), 2);
+    assert_eq!((route_uses[0].0.as_str(), route_uses[0].1.as_deref()), ("Widget", Some("render")));
+    assert_eq!(route_uses[0].2.positional, vec![LiteralKind::String("/widget".to_string())]);
+    assert_eq!((route_uses[1].0.as_str(), route_uses[1].1.as_deref()), ("home", None));
+    assert_eq!(route_uses[1].2.get_named("cached"), Some(&LiteralKind::Bool(true)));
+
+    let bind_uses = bind_uses.lock().unwrap();
+    assert_eq!(bind_uses.len(), 1);
+    assert_eq!((bind_uses[0].0.as_str(), bind_uses[0].1.as_deref()), ("Widget", Some("size")));
+}
+
+#[test]
+fn processors_can_transform_features() {
+    let mut loader = ModuleLoader::new();
+    loader.register_attribute_processor(Box::new(Expose));
+
+    let module = load_with(&mut loader, "
+@Expose
+func shown() Int {
+    return 1
+}
+").unwrap();
+
+    assert!(module.features["shown"].attributes().contains(&Attribute::Public));
+}
+
+#[test]
+fn processors_can_reject_features() {
+    let mut loader = ModuleLoader::new();
+    loader.register_attribute_processor(Box::new(CheckRoute));
+    load_with(&mut loader, SOURCE).unwrap();
+
+    let mut loader = ModuleLoader::new();
+    loader.register_attribute_processor(Box::new(CheckRoute));
+
+    let result = load_with(&mut loader, "
+@Route(cached = true)
+func home() Int {
+    return 1
+}
+");
+
+    assert!(result.err().unwrap().to_string().contains("Invalid arguments for the attribute @Route"));
+}
+
+#[test]
+fn attribute_arguments_must_be_literals() {
+    assert!(load("
+@Route(home)
+func home() Int {
+    return 1
+}
+").is_err());
+}
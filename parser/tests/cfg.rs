@@ -0,0 +1,88 @@
+mod utils;
+
+use common::constants::{CFG_TARGET_INTERPRETER, CFG_TARGET_WASM};
+use parser::modules::module_loader::ModuleLoader;
+use utils::load_with;
+
+const SOURCE: &str = "
+@Cfg(target = \"wasm\")
+func backend() Int {
+    return 1
+}
+
+@Cfg(target = \"interpreter\")
+func backend() Int {
+    return 2
+}
+
+@Cfg(target = \"wasm\", feature = \"debug\")
+@Export
+func trace() Int {
+    return 3
+}
+
+func run() Int {
+    var n = backend()
+    @Cfg(feature = \"debug\")
+    n = n + trace()
+    return n
+}
+";
+
+fn loader(target: &str, features: &[&str]) -> ModuleLoader {
+    let mut loader = ModuleLoader::new();
+    loader.cfg_mut().set_target(target);
+
+    for feature in features {
+        loader.cfg_mut().enable_feature(*feature);
+    }
+
+    loader
+}
+
+#[test]
+fn declarations_of_other_targets_are_removed() {
+    let module = load_with(&mut loader(CFG_TARGET_WASM, &[]), SOURCE).unwrap();
+    assert!(module.features.contains_key("backend"));
+    assert!(!module.features.contains_key("trace"));
+
+    let module = load_with(&mut loader(CFG_TARGET_INTERPRETER, &[]), SOURCE).unwrap();
+    assert!(module.features.contains_key("backend"));
+}
+
+#[test]
+fn every_pair_of_a_cfg_must_be_set() {
+    let module = load_with(&mut loader(CFG_TARGET_WASM, &["debug"]), SOURCE).unwrap();
+    assert!(module.features.contains_key("trace"));
+
+    // `trace` is removed but it is still called by `run`
+    assert!(load_with(&mut loader(CFG_TARGET_INTERPRETER, &["debug"]), SOURCE).is_err());
+}
+
+#[test]
+fn disabled_statements_are_removed() {
+    // `trace` doesn't exist without the feature, the statement calling it must be removed too
+    load_with(&mut loader(CFG_TARGET_WASM, &[]), SOURCE).unwrap();
+}
+
+#[test]
+fn the_other_attributes_of_a_removed_declaration_are_removed() {
+    let module = load_with(&mut loader(CFG_TARGET_WASM, &[]), SOURCE).unwrap();
+
+    // The `@Export` of `trace` would go to `run` otherwise
+    assert!(!module.features["run"].attributes().iter().any(|attribute| attribute.is_custom("Export")));
+}
+
+#[test]
+fn cfg_arguments_are_named_strings() {
+    for cfg in ["@Cfg", "@Cfg(\"wasm\")", "@Cfg(target = 1)"] {
+        let source = format!("
+{}
+func f() Int {{
+    return 1
+}}
+", cfg);
+
+        assert!(load_with(&mut loader(CFG_TARGET_WASM, &[]), &source).is_err(), "{} is accepted", cfg);
+    }
+}
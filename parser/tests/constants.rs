@@ -0,0 +1,121 @@
+mod utils;
+
+use common::ast::types::LiteralKind;
+use common::module::ModuleFeature;
+use utils::{load, load_project};
+
+fn constant(type_: &str, value: &str) -> Option<LiteralKind> {
+    let module = load(&format!("const VALUE {} = {}", type_, value)).ok()?;
+
+    match module.features.get("VALUE") {
+        Some(ModuleFeature::Constant(constant)) => Some(constant.data.clone()),
+        _ => None,
+    }
+}
+
+#[test]
+fn integers_wrap_on_overflow() {
+    assert_eq!(constant("Int", "2147483647 + 1"), Some(LiteralKind::Int(i32::MIN)));
+    assert_eq!(constant("Int", "(0 - 2147483647) - 2"), Some(LiteralKind::Int(i32::MAX)));
+    assert_eq!(constant("Int", "65536 * 65536"), Some(LiteralKind::Int(0)));
+}
+
+#[test]
+fn trapping_divisions_are_not_constants() {
+    assert_eq!(constant("Int", "7 / 0"), None);
+    assert_eq!(constant("Int", "7 % 0"), None);
+    assert_eq!(constant("Int", "((0 - 2147483647) - 1) / (0 - 1)"), None);
+
+    // The divisions truncate like at runtime
+    assert_eq!(constant("Int", "(0 - 7) / 2"), Some(LiteralKind::Int(-3)));
+    assert_eq!(constant("Int", "(0 - 7) % 2"), Some(LiteralKind::Int(-1)));
+}
+
+#[test]
+fn powers_are_not_constants() {
+    // The backend has no power operator, the optimizer doesn't fold them either
+    assert_eq!(constant("Int", "2 ^ 3"), None);
+    assert_eq!(constant("Int", "2 ^ (0 - 1)"), None);
+    assert_eq!(constant("Float", "2.0 ^ 0.5"), None);
+}
+
+#[test]
+fn float_operations() {
+    assert_eq!(constant("Float", "1 / 0.0"), Some(LiteralKind::Float(f32::INFINITY)));
+    assert_eq!(constant("Float", "5.5 % 2.0"), None);
+}
+
+#[test]
+fn constants_use_other_constants() {
+    let module = load("
+const WIDTH Int = 4
+const AREA Int = WIDTH * WIDTH
+").unwrap();
+
+    assert!(matches!(module.features.get("AREA"), Some(ModuleFeature::Constant(constant)) if constant.data == LiteralKind::Int(16)));
+}
+
+#[test]
+fn values_known_at_runtime_are_not_constants() {
+    let err = load("const WIDTHS [Int] = [1, 2]").err().unwrap();
+
+    assert!(err.to_string().contains("The value of the constant WIDTHS can't be computed at compile time"), "{}", err);
+}
+
+#[test]
+fn members_are_accessed_on_the_type() {
+    load("
+class Shape {
+    const SIDES Int = 4
+
+    static func square() Int {
+        return Shape.SIDES * Shape.SIDES
+    }
+}
+
+enum Unit {
+    Meter,
+
+    static func default() Unit {
+        return Unit.Meter
+    }
+}
+
+func use() Int {
+    var unit = Unit.default()
+    return Shape.square() + Shape.SIDES
+}
+").unwrap();
+
+    let err = load("
+class Shape {
+    const SIDES Int = 4
+}
+
+func use() Int {
+    return Shape.CORNERS
+}
+").err().unwrap();
+
+    assert!(err.to_string().contains("Field doesn't exist"), "{}", err);
+}
+
+#[test]
+fn private_members_are_not_accessed_from_other_modules() {
+    let err = load_project(&[
+        ("shape", "
+pub class Shape {
+    const SIDES Int = 4
+}
+"),
+        ("main", "
+import \"shape\"
+
+func use() Int {
+    return Shape.SIDES
+}
+"),
+    ]).err().unwrap();
+
+    assert!(err.to_string().contains("Member SIDES is private to the module of its class"), "{}", err);
+}
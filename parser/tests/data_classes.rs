@@ -0,0 +1,40 @@
+mod utils;
+
+use utils::load;
+
+fn counter(method: &str) -> String {
+    format!("
+data class Counter {{
+    n Int
+    items [Int]
+
+    {}
+}}
+", method)
+}
+
+#[test]
+fn copies_of_this_can_be_modified() {
+    load(&counter("func bumped() Counter {
+        var c = this
+        c.n = c.n + 1
+        return c
+    }")).unwrap();
+
+    // The vector is shared with the object of the caller
+    load(&counter("func clear() {
+        this.items[0] = 0
+    }")).unwrap();
+}
+
+#[test]
+fn this_is_not_modified_by_data_class_methods() {
+    assert!(load(&counter("func bump() Int {
+        this.n = this.n + 1
+        return this.n
+    }")).is_err());
+
+    assert!(load(&counter("func reset() {
+        this = Counter()
+    }")).is_err());
+}
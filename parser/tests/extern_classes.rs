@@ -0,0 +1,50 @@
+mod utils;
+
+use utils::load;
+
+#[test]
+fn extern_objects_are_passed_to_and_from_imports() {
+    load("
+extern class Node
+
+import func createNode(tag String) Node
+import func append(parent Node, child Node)
+
+class Tree {
+    root Node
+
+    func init(root Node) {
+        this.root = root
+    }
+}
+
+func build() Tree {
+    var root = createNode(\"div\")
+    var children = [createNode(\"p\"), createNode(\"span\")]
+    append(root, children[1])
+    return Tree(root)
+}
+").unwrap();
+}
+
+#[test]
+fn extern_objects_are_not_constructed() {
+    let err = load("
+extern class Node
+
+func build() Node {
+    return Node()
+}
+").err().unwrap();
+
+    assert!(err.to_string().contains("Objects of the extern class Node can only be created by the host"), "{}", err);
+}
+
+#[test]
+fn extern_classes_are_not_data_classes() {
+    let err = load("
+data extern class Node
+").err().unwrap();
+
+    assert!(err.to_string().contains("Invalid attribute (Data)"), "{}", err);
+}
@@ -0,0 +1,94 @@
+mod utils;
+
+use utils::load_project;
+
+const GEOM: &str = "
+pub var count Int = 5
+
+pub func value() Int {
+    return count
+}
+
+pub func double(n Int) Int {
+    return n * 2
+}
+
+pub class Vec2 {
+    pub x Int
+    pub y Int
+}
+";
+
+fn main_with(source: &str) -> anyhow::Result<()> {
+    load_project(&[("main", source), ("geom", GEOM)]).map(|_| ())
+}
+
+#[test]
+fn aliased_features_are_accessed_through_the_alias() {
+    main_with("
+import \"geom\" as g
+
+func value() Int {
+    return 100
+}
+
+func run() Int {
+    var v = g.Vec2()
+    v.x = g.double(g.count)
+    return v.x + g.value() + value()
+}
+").unwrap();
+}
+
+#[test]
+fn aliased_features_are_not_in_the_scope() {
+    assert!(main_with("
+import \"geom\" as g
+
+func run() Int {
+    return double(2)
+}
+").unwrap_err().to_string().contains("Variable not found"));
+}
+
+#[test]
+fn unknown_aliased_features_are_rejected() {
+    assert!(main_with("
+import \"geom\" as g
+
+func run() Int {
+    return g.triple(2)
+}
+").unwrap_err().to_string().contains("Imported module has no feature named triple"));
+}
+
+#[test]
+fn only_the_selected_features_are_imported() {
+    main_with("
+import { double, Vec2 } from \"geom\"
+
+func run() Int {
+    var v = Vec2()
+    return double(v.x)
+}
+").unwrap();
+
+    assert!(main_with("
+import { double } from \"geom\"
+
+func run() Int {
+    return value()
+}
+").unwrap_err().to_string().contains("Variable not found"));
+}
+
+#[test]
+fn unknown_selected_features_are_rejected() {
+    assert!(main_with("
+import { double, triple } from \"geom\"
+
+func run() Int {
+    return double(2)
+}
+").unwrap_err().to_string().contains("Imported module has no feature named triple"));
+}
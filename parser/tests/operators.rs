@@ -0,0 +1,121 @@
+mod utils;
+
+use utils::{load, load_project};
+
+fn money(methods: &str, body: &str) -> anyhow::Result<()> {
+    load(&format!("
+class Money {{
+    cents Int
+
+    func init(cents Int) {{
+        this.cents = cents
+    }}
+
+    {}
+}}
+
+func use() {{
+    var a = Money(1)
+    var b = Money(2)
+    {}
+}}
+", methods, body)).map(|_| ())
+}
+
+fn error(result: anyhow::Result<()>) -> String {
+    result.err().unwrap().to_string()
+}
+
+#[test]
+fn operators_are_implemented_by_methods() {
+    money("
+    func add(other Money) Money {
+        return Money(this.cents + other.cents)
+    }
+
+    func cmp(other Money) Int {
+        return this.cents - other.cents
+    }
+", "
+    var sum Money = a + b
+    var smaller Bool = a < b
+    var same Bool = a == b
+").unwrap();
+}
+
+#[test]
+fn missing_operator_methods_are_reported() {
+    let err = error(money("", "var sum = a + b"));
+    assert!(err.contains("The class Money has no add method implementing the operator"), "{}", err);
+
+    let err = error(money("", "var smaller = a < b"));
+    assert!(err.contains("The class Money has no cmp method implementing the operator"), "{}", err);
+
+    // Objects are compared by reference without eq
+    money("", "var same = a == b").unwrap();
+}
+
+#[test]
+fn operands_are_type_checked() {
+    let add = "
+    func add(other Money) Money {
+        return Money(this.cents + other.cents)
+    }
+";
+
+    let err = error(money(add, "var sum = a + 1"));
+    assert!(err.contains("Expected type Class (Money), instead found Int"), "{}", err);
+
+    let err = error(money(add, "var sum Int = a + b"));
+    assert!(err.contains("Expected type Int, instead found Class (Money)"), "{}", err);
+}
+
+#[test]
+fn comparison_methods_return_the_expected_type() {
+    let err = error(money("
+    func eq(other Money) Int {
+        return 0
+    }
+", "var same = a == b"));
+    assert!(err.contains("Expected type Bool, instead found Int"), "{}", err);
+
+    let err = error(money("
+    func cmp(other Money) Bool {
+        return true
+    }
+", "var smaller = a < b"));
+    assert!(err.contains("Expected type Int, instead found Bool"), "{}", err);
+}
+
+#[test]
+fn private_operator_methods_are_not_used_from_other_modules() {
+    let err = load_project(&[
+        ("money", "
+pub class Money {
+    cents Int
+
+    func init(cents Int) {
+        this.cents = cents
+    }
+
+    func add(other Money) Money {
+        return this
+    }
+}
+
+pub func cents(n Int) Money {
+    return Money(n)
+}
+"),
+        ("main", "
+import \"money\"
+
+func use() Money {
+    return cents(1) + cents(2)
+}
+"),
+    ]).err().unwrap();
+
+    assert!(err.to_string().contains("Member add is private to the module of its class"), "{}", err);
+}
+
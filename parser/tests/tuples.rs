@@ -0,0 +1,89 @@
+mod utils;
+
+use common::ast::types::TypeKind;
+use common::module::ModuleFeature;
+use utils::load;
+
+#[test]
+fn functions_return_tuples() {
+    let module = load("
+func divmod(a Int, b Int) (Int, Float) {
+    return (a - b, b * 0.5)
+}
+").unwrap();
+
+    let return_type = match module.features.get("divmod") {
+        Some(ModuleFeature::Function(function)) => function.metadata.1.clone(),
+        _ => panic!("divmod is not a function"),
+    };
+
+    assert!(matches!(return_type.as_ref(), TypeKind::Tuple(types) if matches!(types.as_slice(), [TypeKind::Int, TypeKind::Float])));
+
+    assert!(load("
+func pair() (Int, Int) {
+    return (1, \"two\")
+}
+").is_err());
+}
+
+#[test]
+fn destructured_variables_get_the_types_of_the_elements() {
+    load("
+func f() Float {
+    var (a, b) = (1, 2.5)
+    return b
+}
+").unwrap();
+
+    assert!(load("
+func f() String {
+    var (a, b) = (1, 2.5)
+    return b
+}
+").is_err());
+
+    // With a type the value is checked against it
+    load("
+func f() Int {
+    var (a, b) (Int, Int) = (1, 2)
+    return a + b
+}
+").unwrap();
+
+    assert!(load("
+func f() Int {
+    var (a, b) (Int, String) = (1, 2)
+    return a
+}
+").is_err());
+}
+
+#[test]
+fn destructuring_needs_one_name_per_element() {
+    assert!(load("
+func f() {
+    var (a, b, c) = (1, 2)
+}
+").is_err());
+
+    assert!(load("
+func f() {
+    var (a, b) = 3
+}
+").is_err());
+}
+
+#[test]
+fn tuples_are_passed_to_functions() {
+    load("
+func swap(t (Int, Float)) (Float, Int) {
+    var (a, b) = t
+    return (b, a)
+}
+
+func f() Float {
+    var (x, y) = swap((1, 2.5))
+    return x
+}
+").unwrap();
+}
@@ -0,0 +1,57 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use common::module::{Module, ModuleIdentifier, ModuleUID};
+use parser::modules::module_importer::ModuleImporter;
+use parser::modules::module_loader::ModuleLoader;
+
+pub struct NoImporter;
+
+impl ModuleImporter for NoImporter {
+    fn get_unique_identifier(&self, identifier: &ModuleIdentifier) -> Option<ModuleUID> {
+        Some(ModuleUID::from_string(identifier.0.clone()))
+    }
+
+    fn load_module(&self, _: &ModuleIdentifier) -> Option<String> {
+        None
+    }
+}
+
+/// The modules of a project by name
+pub struct Sources(HashMap<String, String>);
+
+impl ModuleImporter for Sources {
+    fn get_unique_identifier(&self, identifier: &ModuleIdentifier) -> Option<ModuleUID> {
+        Some(ModuleUID::from_string(identifier.0.clone()))
+    }
+
+    fn load_module(&self, identifier: &ModuleIdentifier) -> Option<String> {
+        self.0.get(&identifier.0).cloned()
+    }
+}
+
+pub fn load(source: &str) -> anyhow::Result<Arc<Module>> {
+    load_with(&mut ModuleLoader::new(), source)
+}
+
+/// Loads the module `main` with a configured loader
+pub fn load_with(loader: &mut ModuleLoader, source: &str) -> anyhow::Result<Arc<Module>> {
+    let id = ModuleIdentifier("main".to_string());
+    let uid = ModuleUID::from_string(id.0.clone());
+
+    loader
+        .load_module_with_source(id, uid, &source.to_string(), &NoImporter)
+        .map(|(module, _)| module)
+}
+
+/// Loads the module `main` of a project, the other modules are loaded when they are imported
+pub fn load_project(modules: &[(&str, &str)]) -> anyhow::Result<Arc<Module>> {
+    let sources = Sources(modules.iter().map(|(name, source)| (name.to_string(), source.to_string())).collect());
+    let id = ModuleIdentifier("main".to_string());
+    let uid = ModuleUID::from_string(id.0.clone());
+
+    ModuleLoader::new()
+        .load_module_with_source(id.clone(), uid, &sources.0["main"], &sources)
+        .map(|(module, _)| module)
+}
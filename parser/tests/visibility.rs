@@ -0,0 +1,83 @@
+mod utils;
+
+use utils::load_project;
+
+const GEO: &str = "
+pub const UNIT Int = 10
+const SECRET Int = 3
+
+pub func area(side Int) Int {
+    return side * side
+}
+
+func helper() Int {
+    return 1
+}
+
+pub class Grid {
+    pub size Int
+    cells Int
+
+    pub func count() Int {
+        return this.cells
+    }
+
+    func reset() {
+        this.cells = 0
+    }
+}
+
+class Hidden {
+    n Int
+}
+";
+
+fn main_with(import: &str, body: &str) -> anyhow::Result<()> {
+    let main = format!("
+{}
+
+func run() Int {{
+    {}
+}}
+", import, body);
+
+    load_project(&[("main", &main), ("geo", GEO)]).map(|_| ())
+}
+
+/// The message of the error
+fn error(result: anyhow::Result<()>) -> String {
+    result.unwrap_err().to_string()
+}
+
+#[test]
+fn public_features_and_members_are_accessible() {
+    main_with("import \"geo\"", "return area(UNIT)").unwrap();
+    main_with("import { area, UNIT } from \"geo\"", "return area(UNIT)").unwrap();
+    main_with("import \"geo\"", "var grid = Grid()
+    grid.size = 2
+    return grid.size + grid.count()").unwrap();
+}
+
+#[test]
+fn private_features_are_not_imported() {
+    assert!(error(main_with("import \"geo\"", "return helper()")).contains("Variable not found"));
+    assert!(error(main_with("import \"geo\"", "return SECRET")).contains("Variable not found"));
+    assert!(main_with("import \"geo\"", "var hidden = Hidden()
+    return 0").is_err());
+}
+
+#[test]
+fn private_features_are_rejected_when_selected() {
+    assert!(error(main_with("import { helper } from \"geo\"", "return 0")).contains("helper is private to its module"));
+    assert!(error(main_with("import { SECRET } from \"geo\"", "return 0")).contains("SECRET is private to its module"));
+    assert!(error(main_with("import { Hidden } from \"geo\"", "return 0")).contains("Hidden is private to its module"));
+}
+
+#[test]
+fn private_members_are_rejected() {
+    assert!(error(main_with("import \"geo\"", "var grid = Grid()
+    return grid.cells")).contains("Member cells is private to the module of its class"));
+    assert!(error(main_with("import \"geo\"", "var grid = Grid()
+    grid.reset()
+    return 0")).contains("Member reset is private to the module of its class"));
+}
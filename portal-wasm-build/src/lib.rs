@@ -86,4 +86,8 @@ impl ModuleImporter for PanicImporter {
     fn load_module(&self, _: &ModuleIdentifier) -> Option<String> {
         panic!("PanicImporter::load_module");
     }
+
+    fn load_declaration_module(&self, _: &ModuleIdentifier) -> Option<String> {
+        panic!("PanicImporter::load_declaration_module");
+    }
 }
\ No newline at end of file
@@ -9,10 +9,43 @@ pub struct Args {
     /// Module config file path
     #[clap(short, long, default_value="./portal.json")]
     pub module: String,
+
+    /// Output format of the build
+    #[clap(long, arg_enum, default_value="wasm")]
+    pub emit: Emit,
+
+    /// Environment the module is built for
+    #[clap(long, arg_enum, default_value="host")]
+    pub target: Target,
+
+    /// Build a WebAssembly component, its WIT world is written next to it
+    #[clap(long)]
+    pub component: bool,
+
+    /// Generate the bindings for a Rust host, used by `bindgen`
+    #[clap(long)]
+    pub rust: bool,
 }
 
 #[derive(ArgEnum, Clone, Debug)]
 pub enum Task {
     Init,
     Build,
-}
\ No newline at end of file
+    Bindgen,
+}
+
+#[derive(ArgEnum, Clone, Debug)]
+pub enum Emit {
+    /// Binary module written to the build path
+    Wasm,
+    /// Annotated text format printed to the standard output
+    Wat,
+}
+
+#[derive(ArgEnum, Clone, Debug)]
+pub enum Target {
+    /// The imports are provided by the host
+    Host,
+    /// A WASI command with a `_start` function, the `std` module is available
+    Wasi,
+}
@@ -0,0 +1,27 @@
+use anyhow::anyhow;
+use std::env;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use wasm::engine::BuildTarget;
+use crate::Args;
+use crate::build::{load_config, load_engine};
+
+pub fn bindgen(args: Args) -> anyhow::Result<()> {
+    if !args.rust {
+        return Err(anyhow!("No bindings language given, use --rust"));
+    }
+
+    let config = load_config(&args.module)?;
+    let (engine, module) = load_engine(&config, BuildTarget::Host)?;
+
+    let bindings = engine.build_rust_bindings(module, &config.name)?;
+
+    let path = env::current_dir()?.join(Path::new(&config.build_path).with_extension("rs"));
+    let mut file = File::create(&path)?;
+    file.write_all(bindings.as_bytes())?;
+
+    println!("Bindings generated! Output file at {}", path.to_str().unwrap());
+
+    Ok(())
+}
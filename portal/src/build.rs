@@ -1,19 +1,119 @@
+use anyhow::anyhow;
 use std::env;
 use std::fs::{File, read_to_string};
 use std::io::Write;
-use std::path::PathBuf;
-use common::constants::CORE_MODULE_ID;
+use std::path::{Component, Path, PathBuf};
+use common::constants::{CORE_MODULE_ID, STD_MODULE_ID};
 use common::module::{ModuleIdentifier, ModuleUID};
-use wasm::engine::WasmEngine;
-use crate::{Args, Engine, EngineBuildSource, ReplImporter};
+use wasm::engine::{BuildTarget, WasmEngine};
+use ir::optimize::OptLevel;
+use crate::{Args, Engine, ReplImporter};
+use crate::args::{Emit, Target};
 use crate::config::Config;
 
 pub fn build(args: Args) -> anyhow::Result<()> {
-    let config_str = read_to_string(args.module)?;
-    let config = serde_json::from_str::<Config>(config_str.as_str())?;
+    let config = load_config(&args.module)?;
 
-    // Creating the engine
+    let target = match (args.target, args.component) {
+        (Target::Host, false) => BuildTarget::Host,
+        (Target::Host, true) => BuildTarget::Component,
+        (Target::Wasi, false) => BuildTarget::Wasi,
+        (Target::Wasi, true) => return Err(anyhow!("A component can't be built for the WASI target")),
+    };
+
+    let (engine, module) = load_engine(&config, target)?;
+
+    if let Emit::Wat = args.emit {
+        let text = engine.build_module_text(module)?;
+        print!("{}", text);
+
+        return Ok(())
+    }
+
+    if let BuildTarget::Component = target {
+        return build_component(&config, &engine, module)
+    }
+
+    // The source map is written next to the module
+    let map_path = format!("{}.map", config.build_path);
+    let map_name = Path::new(&map_path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+
+    let (wasm, source_map) = engine.build_module_with_source_map(module, map_name)?;
+
+    // The sources are relative to the source map
+    let depth = Path::new(&config.build_path)
+        .parent()
+        .map_or(0, |parent| parent.components().filter(|c| matches!(c, Component::Normal(_))).count());
+    let source_root = "../".repeat(depth) + &config.src_dir;
+
+    let mut file = File::create(env::current_dir()?.join(&map_path))?;
+    file.write_all(source_map.to_json(&source_root).as_bytes())?;
+
+    // WASI modules are run by the runtimes, the others get a loader next to them
+    if let BuildTarget::Host = target {
+        let bindings = engine.build_js_bindings(module)?;
+        let build_path = Path::new(&config.build_path);
+
+        let mut file = File::create(env::current_dir()?.join(build_path.with_extension("js")))?;
+        file.write_all(bindings.js.as_bytes())?;
+
+        let mut file = File::create(env::current_dir()?.join(build_path.with_extension("d.ts")))?;
+        file.write_all(bindings.dts.as_bytes())?;
+    }
+
+    let path = env::current_dir()?.join(&config.build_path);
+    let mut file = File::create(&path)?;
+    file.write_all(wasm.as_slice())?;
+
+    println!("Build successful! Output file at {}", path.to_str().unwrap());
+
+    Ok(())
+}
+
+/// The component replaces the module, the source map would not match it
+fn build_component(config: &Config, engine: &WasmEngine, module: ModuleUID) -> anyhow::Result<()> {
+    let (component, wit) = engine.build_component(module, &config.name)?;
+
+    let mut file = File::create(env::current_dir()?.join(Path::new(&config.build_path).with_extension("wit")))?;
+    file.write_all(wit.as_bytes())?;
+
+    let path = env::current_dir()?.join(&config.build_path);
+    let mut file = File::create(&path)?;
+    file.write_all(component.as_slice())?;
+
+    println!("Build successful! Component file at {}", path.to_str().unwrap());
+
+    Ok(())
+}
+
+pub(crate) fn load_config(path: &String) -> anyhow::Result<Config> {
+    let config_str = read_to_string(path)?;
+
+    Ok(serde_json::from_str::<Config>(config_str.as_str())?)
+}
+
+/// Creates the engine and loads the main module of the project with its dependencies
+pub(crate) fn load_engine(config: &Config, target: BuildTarget) -> anyhow::Result<(WasmEngine, ModuleUID)> {
     let mut engine = WasmEngine::new();
+    engine.set_memory_config(config.memory.clone().into());
+
+    let opt_level = OptLevel::from_level(config.opt_level)
+        .ok_or(anyhow!("Unknown optimization level {}, the levels go from 0 to 2", config.opt_level))?;
+    engine.set_opt_level(opt_level);
+
+    // Before anything is loaded, the target is used by `@Cfg`
+    engine.set_target(target);
+
+    for feature in &config.features {
+        engine.module_loader().cfg_mut().enable_feature(feature.clone());
+    }
+
+    for (key, value) in &config.cfg {
+        engine.set_cfg(key.clone(), value.clone());
+    }
 
     let importer = ReplImporter {
         src_dir: PathBuf::from(&config.src_dir),
@@ -28,17 +128,26 @@ pub fn build(args: Args) -> anyhow::Result<()> {
             &importer,
         )?;
 
-    // Creating the module from the source file
-    let module = engine
-        .load_module(config.main, &importer)?;
+    // The standard library of WASI is implemented by the builder
+    if let BuildTarget::Wasi = target {
+        engine.module_loader()
+            .load_declaration_module_with_source(
+                ModuleIdentifier(STD_MODULE_ID.to_string()),
+                ModuleUID::from_string(STD_MODULE_ID.to_string()),
+                STD_MODULE_ID.to_string(),
+                &include_str!("../../core_lib/std.d.rn").to_string(),
+                &importer,
+            )?;
+    }
 
-    let wasm = engine.build_module_source(module)?;
-
-    let path = env::current_dir()?.join(config.build_path);
-    let mut file = File::create(&path)?;
-    file.write_all(wasm.as_slice())?;
+    // Loading the declaration modules of the host
+    for (declaration, import_module) in &config.declarations {
+        engine.load_declaration_module(declaration.clone(), import_module.clone(), &importer)?;
+    }
 
-    println!("Build successful! Output file at {}", path.to_str().unwrap());
+    // Creating the module from the source file
+    let module = engine
+        .load_module(config.main.clone(), &importer)?;
 
-    Ok(())
-}
\ No newline at end of file
+    Ok((engine, module))
+}
@@ -1,4 +1,6 @@
-use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use serde::{Deserialize, Deserializer, Serialize};
+use wasm::memory::{MemoryConfig, MemoryImport};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Config {
@@ -8,6 +10,91 @@ pub struct Config {
     #[serde(default)]
     pub main: String,
     pub build_path: String,
+    /// Declaration modules to load, mapped to the host module providing them
+    #[serde(default)]
+    pub declarations: HashMap<String, String>,
+    /// Features enabled for `@Cfg(feature = "...")`
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// Other `key = "value"` pairs used by `@Cfg`
+    #[serde(default)]
+    pub cfg: HashMap<String, String>,
+    #[serde(default)]
+    pub memory: MemorySettings,
+    /// Level of optimization, from 0 to 2 like `-O0` to `-O2`
+    #[serde(default)]
+    pub opt_level: u8,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MemorySettings {
+    #[serde(default = "default_initial_pages")]
+    pub initial_pages: u64,
+    #[serde(default)]
+    pub max_pages: Option<u64>,
+    /// Imports the memory from the host instead of defining it
+    #[serde(default)]
+    pub import: Option<MemoryImportSettings>,
+    #[serde(default)]
+    pub shared: bool,
+    /// Name under which the memory is exported, `null` to not export it.
+    /// When missing a defined memory is exported as "mem" and an imported one is not exported
+    #[serde(default, deserialize_with = "deserialize_explicit", skip_serializing_if = "Option::is_none")]
+    pub export: Option<Option<String>>,
+    /// Address of the first data segment
+    #[serde(default)]
+    pub data_base: u32,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MemoryImportSettings {
+    pub module: String,
+    pub name: String,
+}
+
+fn default_initial_pages() -> u64 {
+    MemoryConfig::default().initial_pages
+}
+
+/// Keeps an explicit `null` apart from a missing value
+fn deserialize_explicit<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Option<String>>, D::Error> {
+    Option::deserialize(deserializer).map(Some)
+}
+
+impl Default for MemorySettings {
+    fn default() -> Self {
+        Self {
+            initial_pages: default_initial_pages(),
+            max_pages: None,
+            import: None,
+            shared: false,
+            export: None,
+            data_base: 0,
+        }
+    }
+}
+
+impl From<MemorySettings> for MemoryConfig {
+    fn from(settings: MemorySettings) -> Self {
+        // The host already has the memory it provides
+        let export_name = match settings.export {
+            Some(export) => export,
+            None if settings.import.is_some() => None,
+            None => MemoryConfig::default().export_name,
+        };
+
+        Self {
+            initial_pages: settings.initial_pages,
+            max_pages: settings.max_pages,
+            import: settings.import.map(|import| MemoryImport {
+                module: import.module,
+                name: import.name,
+            }),
+            shared: settings.shared,
+            export_name,
+            data_base: settings.data_base,
+        }
+    }
 }
 
 impl Default for Config {
@@ -17,6 +104,11 @@ impl Default for Config {
             src_dir: "src".to_string(),
             main: "main".to_string(),
             build_path: "output.wasm".to_string(),
+            declarations: HashMap::new(),
+            features: Vec::new(),
+            cfg: HashMap::new(),
+            memory: MemorySettings::default(),
+            opt_level: 0,
         }
     }
 }
\ No newline at end of file
@@ -33,7 +33,7 @@ pub fn init(args: Args) -> anyhow::Result<()> {
     main_file.write_all(br#"
 import func print(s String)
 
-func main() {
+pub func main() {
     print("Hello, web!")
 }
 "#)?;
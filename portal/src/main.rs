@@ -2,12 +2,14 @@ mod args;
 mod config;
 mod build;
 mod init;
+mod bindgen;
 
 use std::fs;
 use std::path::PathBuf;
 use build::build;
 use init::init;
-use core::{Engine, parser::ModuleImporter, EngineBuildSource};
+use bindgen::bindgen;
+use core::{Engine, parser::ModuleImporter};
 use clap::Parser;
 use common::module::{ModuleIdentifier, ModuleUID};
 use crate::args::{Args, Task};
@@ -22,6 +24,9 @@ fn main() -> anyhow::Result<()> {
         Task::Build => {
             build(args)
         },
+        Task::Bindgen => {
+            bindgen(args)
+        },
     }
 }
 
@@ -31,13 +36,22 @@ struct ReplImporter {
 
 impl ReplImporter {
     fn get_path(&self, identifier: &ModuleIdentifier) -> PathBuf {
+        self.get_path_with_extension(identifier, ".rn")
+    }
+
+    fn get_path_with_extension(&self, identifier: &ModuleIdentifier, extension: &str) -> PathBuf {
+        self.src_dir.join(Self::get_relative_path(identifier, extension))
+    }
+
+    /// The path of a module inside the source directory
+    fn get_relative_path(identifier: &ModuleIdentifier, extension: &str) -> String {
         let mut path = identifier.0.clone();
         if path.starts_with("/") {
             path.remove(0);
         }
-        path += ".rn";
+        path += extension;
 
-        self.src_dir.join(path)
+        path
     }
 }
 
@@ -60,4 +74,19 @@ impl ModuleImporter for ReplImporter {
 
         fs::read_to_string(path.to_str()?).ok()
     }
+
+    fn load_declaration_module(&self, identifier: &ModuleIdentifier) -> Option<String> {
+        let path = self.get_path_with_extension(identifier, ".d.rn");
+
+        fs::read_to_string(path.to_str()?).ok()
+    }
+
+    fn get_source_path(&self, identifier: &ModuleIdentifier, declaration: bool) -> Option<String> {
+        let extension = match declaration {
+            true => ".d.rn",
+            false => ".rn",
+        };
+
+        Some(Self::get_relative_path(identifier, extension))
+    }
 }
\ No newline at end of file
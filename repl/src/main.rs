@@ -76,4 +76,19 @@ impl ModuleImporter for ReplImporter {
         };
         Some(source)
     }
+
+    fn load_declaration_module(&self, identifier: &ModuleIdentifier) -> Option<String> {
+        let mod_path = match env::current_dir() {
+            Ok(path) => path,
+            Err(_) => return None,
+        };
+        let mod_path = mod_path.join(identifier.0.clone() + ".d.rn");
+
+        let source = std::fs::read_to_string(mod_path);
+        let source = match source {
+            Ok(source) => source,
+            Err(_) => return None,
+        };
+        Some(source)
+    }
 }
\ No newline at end of file
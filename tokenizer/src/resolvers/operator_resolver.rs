@@ -52,6 +52,12 @@ impl Resolver for OperatorResolver {
                 AddResult::Ok
             },
 
+            // `?` is always alone, it's followed by other operators like in `f()?, 1` or `f()?.x`
+            '?' if self.chars.is_empty() => AddResult::End(TokenKind::Operator(OperatorKind::Question)),
+
+            // Nothing is left after a `?`
+            _ if self.chars.is_empty() => AddResult::ChangeWithoutToken(char),
+
             _ => {
                 match self.end_operator() {
                     Ok(token) => AddResult::Change(token, char),
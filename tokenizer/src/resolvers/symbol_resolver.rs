@@ -20,8 +20,11 @@ impl SymbolResolver {
             "class" => TokenKind::Class,
             "enum" => TokenKind::Enum,
             "var" => TokenKind::Variable,
+            "const" => TokenKind::Constant,
             "return" => TokenKind::Return,
             "break" => TokenKind::Break,
+            "panic" => TokenKind::Panic,
+            "assert" => TokenKind::Assert,
             "in" => TokenKind::Operator(OperatorKind::In),
             "if" => TokenKind::If,
             "else" => TokenKind::Else,
@@ -30,6 +33,9 @@ impl SymbolResolver {
             "import" => TokenKind::Import,
 
             "data" => TokenKind::Attribute(Attribute::Data),
+            "extern" => TokenKind::Attribute(Attribute::Extern),
+            "pub" => TokenKind::Attribute(Attribute::Public),
+            "static" => TokenKind::Attribute(Attribute::Static),
 
             "Int" => TokenKind::Type(PrimitiveType::Int),
             "Float" => TokenKind::Type(PrimitiveType::Float),
@@ -0,0 +1,59 @@
+use common::ast::types::{LiteralKind, OperatorKind, ParenthesisKind, ParenthesisState};
+use common::tokens::TokenKind;
+use tokenizer::tokenizer::Tokenizer;
+
+fn tokenize(source: &str) -> Vec<TokenKind> {
+    let mut tokens = Tokenizer::tokenize(&source.to_string()).unwrap();
+    let mut kinds = Vec::new();
+
+    while let Some(token) = tokens.pop() {
+        kinds.push(token.kind);
+    }
+
+    kinds
+}
+
+#[test]
+fn question_before_comma() {
+    let kinds = tokenize("f(g()?, 1)");
+
+    assert!(matches!(kinds.as_slice(), [
+        TokenKind::Symbol(_),
+        TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Open),
+        TokenKind::Symbol(_),
+        TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Open),
+        TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Close),
+        TokenKind::Operator(OperatorKind::Question),
+        TokenKind::Operator(OperatorKind::Comma),
+        TokenKind::Literal(LiteralKind::Int(1)),
+        TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Close),
+        TokenKind::NewLine,
+    ]), "{:?}", kinds);
+}
+
+#[test]
+fn question_before_dot() {
+    let kinds = tokenize("g()?.field");
+
+    assert!(matches!(kinds.as_slice(), [
+        TokenKind::Symbol(_),
+        TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Open),
+        TokenKind::Parenthesis(ParenthesisKind::Round, ParenthesisState::Close),
+        TokenKind::Operator(OperatorKind::Question),
+        TokenKind::Operator(OperatorKind::Dot),
+        TokenKind::Symbol(_),
+        TokenKind::NewLine,
+    ]), "{:?}", kinds);
+}
+
+#[test]
+fn question_after_operator() {
+    let kinds = tokenize("a =?");
+
+    assert!(matches!(kinds.as_slice(), [
+        TokenKind::Symbol(_),
+        TokenKind::Operator(OperatorKind::Assign),
+        TokenKind::Operator(OperatorKind::Question),
+        TokenKind::NewLine,
+    ]), "{:?}", kinds);
+}
@@ -1,48 +1,399 @@
 use std::sync::Arc;
-use wasm_encoder::{CodeSection, DataSection, EntityType, Export, ExportSection, Function, FunctionSection, ImportSection, Instruction, MemorySection, MemoryType, Module, TypeSection, ValType};
-use common::ast::types::{ClassKind, ClassType, EnumType, TypeKind};
-use common::errors::LangError;
+use wasm_encoder::{encoders, CodeSection, CustomSection, DataSection, ElementSection, Elements, IndirectNameMap, NameMap, NameSection, Export, ExportSection, Function, FunctionSection, Instruction, Module, RawSection, SectionId, TableSection, TableType, TypeSection, ValType};
+use common::ast::types::{ClassKind, ClassType, EnumType, LiteralKind, TypeKind};
+use common::constants::{ATTRIB_EXPORT, CLASS_SELF_REFERENCE, INTERNAL_MEMORY_ALLOC, MAIN_FUNCTION_NAME, SOURCE_MAPPING_URL_SECTION};
+use common::errors::{BuildErrorKind, LangError};
+use common::module::{FunctionDefinition, ModuleFeature, ModuleKind};
+use common::parsable_types::ModuleSource;
 use core::parser::ModuleLoader;
-use crate::build_code::{FunctionData, ModuleBuilder, ModuleBuilderResult, ModuleData, ModuleDataKind};
+use wasmparser::{Parser, Payload};
+use crate::build_code::{FunctionData, FunctionExport, FunctionImport, GlobalImport, ModuleBuilder, ModuleBuilderResult, ModuleData, ModuleDataKind};
+use ir::function::FunctionRef;
+use ir::optimize::OptLevel;
+use ir::program::Program;
+use crate::memory::MemoryConfig;
+use crate::build_mem::{align_to, MAX_ALIGNMENT, VTABLE_TABLE};
+use crate::source_map::SourceMap;
+use crate::engine::BuildTarget;
+use crate::wasi::{WASI_MEMORY_EXPORT, WASI_START_FUNCTION};
+use crate::js::{JsBindings, JsGenerator};
+use crate::rust::RustGenerator;
+use crate::component::{component_export_name, encode_component, WitGenerator, COMPONENT_MEMORY_EXPORT};
+
+const IMPORT_KIND_FUNCTION: u8 = 0x00;
+const IMPORT_KIND_MEMORY: u8 = 0x02;
+const IMPORT_KIND_GLOBAL: u8 = 0x03;
+use crate::wat::{to_wat, BuildAnnotations};
 
 pub struct WasmBuilder<'a> {
     module_loader: &'a ModuleLoader,
     module: Arc<common::module::Module>,
     core_module: Arc<common::module::Module>,
+    memory: &'a MemoryConfig,
+    target: BuildTarget,
+    opt_level: OptLevel,
 }
 
 impl<'a> WasmBuilder<'a> {
     pub fn new(
         module_loader: &'a ModuleLoader,
         main_module: Arc<common::module::Module>,
-        core_module: Arc<common::module::Module>) -> Self
+        core_module: Arc<common::module::Module>,
+        memory: &'a MemoryConfig,
+        target: BuildTarget,
+        opt_level: OptLevel) -> Self
     {
         Self {
             module_loader,
             core_module,
             module: main_module,
+            memory,
+            target,
+            opt_level,
         }
     }
 
     pub fn build(self) -> Result<Vec<u8>, LangError> {
-        let mut module_builder = ModuleBuilder::new(&self.module_loader)?;
-        module_builder.insert_module(self.core_module.clone())?;
-        module_builder.insert_module(self.module.clone())?;
+        let (wasm, _, _) = self.build_module(None)?;
 
-        let result = module_builder.build();
+        Ok(wasm)
+    }
+
+    /// Builds the module together with its source map, `source_map_url` is the location
+    /// of the source map relative to the module
+    pub fn build_with_source_map(self, source_map_url: &str) -> Result<(Vec<u8>, SourceMap), LangError> {
+        let (wasm, _, source_map) = self.build_module(Some(source_map_url))?;
+
+        Ok((wasm, source_map))
+    }
+
+    /// Builds the module in the WebAssembly text format
+    pub fn build_text(self) -> Result<String, LangError> {
+        let (wasm, annotations, _) = self.build_module(None)?;
+
+        to_wat(&wasm, &annotations)
+    }
+
+    /// Builds an ES module that instantiates the module and converts the values of its exports
+    /// and imports, together with its TypeScript declarations
+    pub fn build_js_bindings(self) -> Result<JsBindings, LangError> {
+        let result = self.build_result()?;
+        let exports = self.collect_exports(&result)?;
+
+        Ok(JsGenerator::new(self.memory).generate(&exports, &result.function_imports, &result.global_imports))
+    }
+
+    pub fn build_rust_bindings(self, name: &str) -> Result<String, LangError> {
+        let result = self.build_result()?;
+        let exports = self.collect_exports(&result)?;
+
+        RustGenerator::new(self.memory, name).generate(&exports, &result.function_imports, &result.global_imports)
+    }
+
+    /// Builds the component and its world in the WIT format, `name` is the name of the world
+    pub fn build_component(self, name: &str) -> Result<(Vec<u8>, String), LangError> {
+        if self.memory.import.is_some() {
+            return Err(LangError::build(BuildErrorKind::Unsupported(
+                "A component has to define its memory, it can't be imported".to_string())));
+        }
+
+        let result = self.build_result()?;
+        let exports = self.collect_exports(&result)?;
+        let wit = WitGenerator::new().generate(name, &exports, &result.function_imports)?;
+
+        let (wasm, _, _) = self.encode_module(result, None)?;
+
+        Ok((encode_component(&wasm, &wit)?, wit))
+    }
+
+    /// The exported functions with their Rain types
+    fn collect_exports(&self, result: &ModuleBuilderResult) -> Result<Vec<FunctionExport>, LangError> {
+        let mut exports = Vec::new();
+
+        for func in &result.function_data {
+            let name = match self.get_export_name(func)? {
+                Some(name) => name,
+                None => continue,
+            };
+
+            let module = match self.module_loader.get_module(func.module) {
+                Some(module) => module,
+                None => continue,
+            };
+
+            // The allocator is only used by the bindings themselves
+            match module.get_func_feature(&func.name) {
+                Some(def) if func.name != INTERNAL_MEMORY_ALLOC => exports.push(FunctionExport {
+                    name,
+                    metadata: def.metadata.clone(),
+                    param_names: param_names(&module, &func.name, def.metadata.0.len()),
+                }),
+                _ => (),
+            }
+        }
+
+        Ok(exports)
+    }
+
+    fn build_result(&self) -> Result<ModuleBuilderResult, LangError> {
+        let roots = self.build_roots()?;
+
+        // The allocator is also called by the code built for the objects and the vectors
+        let mut used = roots.clone();
+        used.push(FunctionRef { module: self.core_module.uid, name: INTERNAL_MEMORY_ALLOC.to_string() });
+
+        let mut program = Program::lower(&self.module_loader.modules())?;
+        program.optimize(self.opt_level, &used);
+        let usage = program.usage(&used);
+
+        let mut module_builder = ModuleBuilder::new(self.module_loader, &program, &usage, self.memory.data_base, self.target)?;
+        module_builder.insert_funcs(&roots)?;
+
+        match self.target {
+            BuildTarget::Wasi => module_builder.insert_start_func(self.module.clone())?,
+            BuildTarget::Component => {
+                let mut exports = Vec::new();
+                for func in &module_builder.function_data {
+                    if let Some(name) = self.get_export_name(func)? {
+                        exports.push((func.module, func.name.clone(), name));
+                    }
+                }
+
+                for (module, func_name, name) in &exports {
+                    module_builder.insert_component_export(*module, func_name, name)?;
+                }
+
+                module_builder.insert_component_realloc()?;
+            },
+            BuildTarget::Host => (),
+        }
+
+        Ok(module_builder.build())
+    }
+
+    /// The functions built even if nothing calls them: the functions marked with `@Export` in the main module
+    /// and in all its dependencies, the main function and the allocator used by the host
+    fn build_roots(&self) -> Result<Vec<FunctionRef>, LangError> {
+        let mut roots = Vec::new();
+
+        let mut visited = Vec::new();
+        let mut to_visit = vec![self.module.uid];
+
+        while let Some(uid) = to_visit.pop() {
+            if visited.contains(&uid) { continue }
+            visited.push(uid);
+
+            let module = self.module_loader
+                .get_module(uid)
+                .ok_or(LangError::build(BuildErrorKind::ModuleNotFound(uid)))?;
+
+            for (name, feature) in &module.features {
+                if let ModuleFeature::Function(FunctionDefinition { data: Some(_), attributes, .. }) = feature {
+                    if attributes.iter().any(|attribute| attribute.is_custom(ATTRIB_EXPORT)) {
+                        roots.push(FunctionRef { module: uid, name: name.clone() });
+                    }
+                }
+            }
+
+            to_visit.extend(module.imports.iter().cloned());
+        }
+
+        let main = FunctionRef { module: self.module.uid, name: MAIN_FUNCTION_NAME.to_string() };
+        let has_main = matches!(self.module.get_func_feature(&main.name), Some(FunctionDefinition { data: Some(_), .. }));
+
+        if has_main && !roots.contains(&main) {
+            roots.push(main);
+        }
+
+        if let BuildTarget::Host = self.target {
+            roots.push(FunctionRef { module: self.core_module.uid, name: INTERNAL_MEMORY_ALLOC.to_string() });
+        }
+
+        Ok(roots)
+    }
+
+    fn build_module(&self, source_map_url: Option<&str>) -> Result<(Vec<u8>, BuildAnnotations, SourceMap), LangError> {
+        let result = self.build_result()?;
+        self.encode_module(result, source_map_url)
+    }
+
+    fn encode_module(&self, result: ModuleBuilderResult, source_map_url: Option<&str>) -> Result<(Vec<u8>, BuildAnnotations, SourceMap), LangError> {
+        let annotations = self.build_annotations(&result);
+
+        let data_end = result.data
+            .iter()
+            .map(|data| data.offset as u64 + data.bytes.len() as u64)
+            .max()
+            .unwrap_or(self.memory.data_base as u64);
+        let data_end = align_to(data_end, MAX_ALIGNMENT);
+
+        self.memory.validate(data_end)?;
 
         let mut module = Module::new();
 
         module
             .section(&Self::build_types(&result)?)
-            .section(&Self::build_imports(&result)?)
-            .section(&Self::build_functions(result.function_imports.len() as u32, &result)?)
-            .section(&Self::build_memory(64))
-            .section(&Self::build_exports(&result)?)
-            .section(&self.build_code(result.function_data)?)
-            .section(&Self::build_data(result.data));
+            .section(&RawSection { id: SectionId::Import.into(), data: &self.build_imports(&result) })
+            .section(&Self::build_functions(result.function_imports.len() as u32, &result)?);
+
+        if result.externref_table || !result.function_table.is_empty() {
+            module.section(&Self::build_tables(&result.function_table));
+        }
+
+        // An imported memory is defined by the host
+        if self.memory.import.is_none() {
+            module.section(&RawSection { id: SectionId::Memory.into(), data: &self.build_memory() });
+        }
+
+        module.section(&self.build_exports(&result)?);
+
+        if !result.function_table.is_empty() {
+            module.section(&Self::build_elements(&result.function_table));
+        }
+
+        module
+            .section(&self.build_code(&result.function_data)?)
+            .section(&Self::build_data(result.data, data_end))
+            .section(&self.build_names(&result.function_imports, &result.function_data, &result.global_imports));
+
+        // The url section is after the code so the offsets of the source map stay the same
+        let source_map = self.build_source_map(module.as_slice(), &result.function_data)?;
+
+        if let Some(url) = source_map_url {
+            module.section(&CustomSection {
+                name: SOURCE_MAPPING_URL_SECTION,
+                data: url.as_bytes(),
+            });
+        }
+
+        Ok((module.finish(), annotations, source_map))
+    }
+
+    fn build_names(&self, function_imports: &[FunctionImport], function_data: &[FunctionData], global_imports: &[GlobalImport]) -> NameSection {
+        let mut names = NameSection::new();
+        names.module(self.module.id.0.as_ref());
+
+        let mut functions = NameMap::new();
+        let mut locals = IndirectNameMap::new();
+
+        for (i, func) in function_imports.iter().enumerate() {
+            functions.append(i as u32, func.name.as_ref());
+        }
+
+        let offset = function_imports.len() as u32;
+
+        for (i, func) in function_data.iter().enumerate() {
+            let module = self.module_loader
+                .get_module(func.module)
+                .map(|module| module.id.0.clone())
+                .unwrap_or_default();
+
+            functions.append(offset + i as u32, format!("{}::{}", module, func.name).as_ref());
+
+            let mut func_locals = NameMap::new();
+            for (id, name) in &func.local_names {
+                func_locals.append(*id, name.as_ref());
+            }
+
+            locals.append(offset + i as u32, &func_locals);
+        }
+
+        let mut globals = NameMap::new();
+
+        for (i, global) in global_imports.iter().enumerate() {
+            globals.append(i as u32, global.name.as_ref());
+        }
+
+        names.functions(&functions);
+        names.locals(&locals);
+        names.globals(&globals);
+
+        names
+    }
+
+    /// Maps the first instruction of every statement to the line of the statement
+    fn build_source_map(&self, wasm: &[u8], functions: &[FunctionData]) -> Result<SourceMap, LangError> {
+        let mut bodies = Vec::new();
+
+        for payload in Parser::new(0).parse_all(wasm) {
+            match payload {
+                Ok(Payload::CodeSectionEntry(body)) => bodies.push(body),
+                Ok(_) => (),
+                Err(err) => return Err(LangError::build(BuildErrorKind::UnexpectedError(format!("build_source_map: {}", err)))),
+            }
+        }
+
+        let mut source_map = SourceMap::new();
+
+        for (func, body) in functions.iter().zip(bodies) {
+            if func.spans.is_empty() { continue }
+
+            let module = match self.module_loader.get_module(func.module) {
+                Some(module) => module,
+                None => return Err(LangError::build(BuildErrorKind::ModuleNotFound(func.module))),
+            };
+
+            let offsets = body.get_operators_reader()
+                .and_then(|reader| reader
+                    .into_iter_with_offsets()
+                    .map(|operator| operator.map(|(_, offset)| offset))
+                    .collect::<Result<Vec<usize>, _>>());
+
+            let offsets = match offsets {
+                Ok(offsets) => offsets,
+                Err(err) => return Err(LangError::build(BuildErrorKind::UnexpectedError(format!("build_source_map: {}", err)))),
+            };
+
+            let source = match &module.parsable_module.source {
+                ModuleSource::Path(path) => source_map.add_source(path.clone(), None),
+                ModuleSource::Code(code) => {
+                    let extension = match module.parsable_module.kind {
+                        ModuleKind::Declaration { .. } => "d.rn",
+                        ModuleKind::Definition => "rn",
+                    };
+
+                    source_map.add_source(format!("{}.{}", module.id.0.trim_start_matches('/'), extension), Some(code.clone()))
+                },
+            };
+
+            for (instruction, span) in &func.spans {
+                if let Some(offset) = offsets.get(*instruction) {
+                    source_map.add_mapping(*offset, source, module.parsable_module.lines.line(span.start));
+                }
+            }
+        }
+
+        Ok(source_map)
+    }
+
+    fn build_annotations(&self, result: &ModuleBuilderResult) -> BuildAnnotations {
+        let mut functions = Vec::new();
 
-        Ok(module.finish())
+        for func in &result.function_imports {
+            functions.push(format!("{} imported from \"{}\"", func.name, func.module_name));
+        }
+
+        for func in &result.function_data {
+            let module = self.module_loader
+                .get_module(func.module)
+                .map(|module| module.id.0.clone())
+                .unwrap_or_default();
+
+            functions.push(format!("{} from module \"{}\"", func.name, module));
+        }
+
+        let data = result.data
+            .iter()
+            .map(|data| match &data.kind {
+                ModuleDataKind::String(literal) => Some(literal.clone()),
+                _ => None,
+            })
+            .collect();
+
+        BuildAnnotations {
+            functions,
+            data,
+        }
     }
 
     fn build_types(result: &ModuleBuilderResult) -> Result<TypeSection, LangError> {
@@ -65,18 +416,39 @@ impl<'a> WasmBuilder<'a> {
         Ok(types)
     }
 
-    fn build_imports(result: &ModuleBuilderResult) -> Result<ImportSection, LangError> {
-        let mut imports = ImportSection::new();
+    /// The section is encoded by hand since the memory type of `wasm_encoder` can't be shared
+    fn build_imports(&self, result: &ModuleBuilderResult) -> Vec<u8> {
+        let count = result.function_imports.len()
+            + result.global_imports.len()
+            + self.memory.import.iter().len();
+
+        let mut imports = Vec::new();
+        imports.extend(encoders::u32(count as u32));
 
         for (i, func) in result.function_imports.iter().enumerate() {
-            imports.import(
-                func.module_name.as_ref(),
-                Some(func.name.as_ref()),
-                EntityType::Function(i as u32),
-            );
+            imports.extend(encoders::str(func.module_name.as_ref()));
+            imports.extend(encoders::str(func.name.as_ref()));
+            imports.push(IMPORT_KIND_FUNCTION);
+            imports.extend(encoders::u32(i as u32));
+        }
+
+        for global in &result.global_imports {
+            imports.extend(encoders::str(global.module_name.as_ref()));
+            imports.extend(encoders::str(global.name.as_ref()));
+            imports.push(IMPORT_KIND_GLOBAL);
+            imports.push(global.val_type.into());
+            // Immutable
+            imports.push(0);
+        }
+
+        if let Some(import) = &self.memory.import {
+            imports.extend(encoders::str(import.module.as_ref()));
+            imports.extend(encoders::str(import.name.as_ref()));
+            imports.push(IMPORT_KIND_MEMORY);
+            self.memory.encode_type(&mut imports);
         }
 
-        Ok(imports)
+        imports
     }
 
     fn build_functions(offset: u32, result: &ModuleBuilderResult) -> Result<FunctionSection, LangError> {
@@ -89,36 +461,124 @@ impl<'a> WasmBuilder<'a> {
         Ok(functions)
     }
 
-    fn build_memory(size: u64) -> MemorySection {
-        let mut memory = MemorySection::new();
-        memory.memory(MemoryType {
-            minimum: size,
+    /// The table holding the extern objects placed in the memory, see `EXTERNREF_TABLE`, followed by
+    /// the functions of the vtables if there are any, see `VTABLE_TABLE`
+    fn build_tables(function_table: &[u32]) -> TableSection {
+        let mut tables = TableSection::new();
+        tables.table(TableType {
+            element_type: ValType::ExternRef,
+            minimum: 1,
             maximum: None,
-            memory64: false,
         });
+
+        if !function_table.is_empty() {
+            tables.table(TableType {
+                element_type: ValType::FuncRef,
+                minimum: function_table.len() as u32,
+                maximum: Some(function_table.len() as u32),
+            });
+        }
+
+        tables
+    }
+
+    fn build_elements(function_table: &[u32]) -> ElementSection {
+        let mut elements = ElementSection::new();
+        elements.active(
+            Some(VTABLE_TABLE),
+            &Instruction::I32Const(0),
+            ValType::FuncRef,
+            Elements::Functions(function_table),
+        );
+
+        elements
+    }
+
+    fn build_memory(&self) -> Vec<u8> {
+        let mut memory = Vec::new();
+        memory.extend(encoders::u32(1));
+        self.memory.encode_type(&mut memory);
+
         memory
     }
 
-    fn build_exports(result: &ModuleBuilderResult) -> Result<ExportSection, LangError> {
+    fn build_exports(&self, result: &ModuleBuilderResult) -> Result<ExportSection, LangError> {
         let mut exports = ExportSection::new();
 
         let offset = result.function_imports.len() as u32;
 
+        let memory_export = match self.target {
+            BuildTarget::Host => self.memory.export_name.as_deref(),
+            BuildTarget::Wasi => Some(WASI_MEMORY_EXPORT),
+            BuildTarget::Component => Some(COMPONENT_MEMORY_EXPORT),
+        };
+
+        // The functions can't take the name of the memory either
+        let mut names: Vec<String> = memory_export.iter().map(|name| name.to_string()).collect();
+
+        // Only the functions marked with `@Export`, the main function of the entry module, `_start` and the allocator are exported
         for (i, func) in result.function_data.iter().enumerate() {
-            exports.export(func.name.as_ref(), Export::Function(offset + i as u32));
+            // The Rain functions of a component are exported through their adapters
+            let name = match self.target {
+                BuildTarget::Component => component_export_name(func),
+                _ => self.get_export_name(func)?,
+            };
+
+            let name = match name {
+                Some(name) => name,
+                None => continue,
+            };
+
+            if names.contains(&name) {
+                return Err(LangError::build(BuildErrorKind::DuplicateExport(name)));
+            }
+
+            exports.export(name.as_ref(), Export::Function(offset + i as u32));
+            names.push(name);
         }
 
-        exports.export("mem", Export::Memory(0));
+        if let Some(name) = memory_export {
+            exports.export(name, Export::Memory(0));
+        }
 
         Ok(exports)
     }
 
-    fn build_code(&self, functions: Vec<FunctionData>) -> Result<CodeSection, LangError> {
+    /// Returns the name under which the function is exported, if it is exported at all
+    fn get_export_name(&self, func: &FunctionData) -> Result<Option<String>, LangError> {
+        let module = match self.module_loader.get_module(func.module) {
+            Some(module) => module,
+            None => return Err(LangError::build(BuildErrorKind::ModuleNotFound(func.module))),
+        };
+
+        let args = module
+            .get_func_feature(&func.name)
+            .and_then(|def| def.attributes.iter().find_map(|attribute| attribute.custom_args(ATTRIB_EXPORT)));
+
+        match args.map(|args| (args.positional.as_slice(), args.named.as_slice())) {
+            // @Export
+            Some(([], [])) => Ok(Some(func.name.clone())),
+            // @Export("name")
+            Some(([LiteralKind::String(name)], [])) => Ok(Some(name.clone())),
+            // @Export(name = "name")
+            Some(([], [(key, LiteralKind::String(name))])) if key == "name" => Ok(Some(name.clone())),
+            Some(_) => Err(LangError::build(BuildErrorKind::InvalidAttributeArgs(ATTRIB_EXPORT.to_string()))),
+            None if func.module == self.module.uid && func.name == MAIN_FUNCTION_NAME => Ok(Some(func.name.clone())),
+            None if func.module == self.module.uid && func.name == WASI_START_FUNCTION => Ok(Some(func.name.clone())),
+            // The host needs the allocator to pass strings and vectors
+            None if func.module == self.core_module.uid && func.name == INTERNAL_MEMORY_ALLOC
+                && matches!(self.target, BuildTarget::Host) => Ok(Some(func.name.clone())),
+            None => Ok(None),
+        }
+    }
+
+    fn build_code(&self, functions: &Vec<FunctionData>) -> Result<CodeSection, LangError> {
         let mut codes = CodeSection::new();
 
         for func in functions {
             let locals: Vec<(u32, ValType)> = func.locals
-                .into_iter()
+                .iter()
+                .cloned()
                 .skip(func.params.len())
                 .map(|local| (1u32, local))
                 .collect();
@@ -135,19 +595,16 @@ impl<'a> WasmBuilder<'a> {
         Ok(codes)
     }
 
-    fn build_data(result: Vec<ModuleData>) -> DataSection {
+    /// The heap starts at `data_end`, right after the data and aligned to `MAX_ALIGNMENT`
+    fn build_data(result: Vec<ModuleData>, data_end: u64) -> DataSection {
         let mut data_sec = DataSection::new();
 
-        let mut total_size = 0;
-        for data in &result {
-            total_size += data.bytes.len();
-        }
-
         for data in result {
             let offset = data.offset as i32;
             let data = match data.kind {
-                ModuleDataKind::Standard => data.bytes,
-                ModuleDataKind::StaticMemoryOffset => i32::to_le_bytes(total_size as i32).to_vec(),
+                ModuleDataKind::Standard |
+                ModuleDataKind::String(_) => data.bytes,
+                ModuleDataKind::StaticMemoryOffset => i32::to_le_bytes(data_end as i32).to_vec(),
             };
 
             data_sec.active(0, &Instruction::I32Const(offset), data);
@@ -178,18 +635,19 @@ pub(crate) fn convert_type(type_: &TypeKind) -> Vec<ValType> {
         TypeKind::Function(_) => todo!(),
         TypeKind::Class(obj) => convert_class(obj),
         TypeKind::Enum(enum_type) => convert_enum(enum_type),
+        TypeKind::Tuple(types) => convert_types(types),
     }
 }
 
 pub(crate) fn convert_class(class_type: &Arc<ClassType>) -> Vec<ValType> {
-    if let ClassKind::Data = class_type.kind {
-        convert_types(&class_type.fields
+    match class_type.kind {
+        ClassKind::Data => convert_types(&class_type.fields
             .borrow()
             .iter()
             .map(|(_, type_)| type_.clone())
-            .collect())
-    } else {
-        vec![ValType::I32]
+            .collect()),
+        ClassKind::Extern => vec![ValType::ExternRef],
+        ClassKind::Normal => vec![ValType::I32],
     }
 }
 
@@ -207,4 +665,41 @@ pub(crate) fn convert_enum(enum_type: &EnumType) -> Vec<ValType> {
     }
 
     return vec![ValType::I32; max_size + 1];
-}
\ No newline at end of file
+}
+
+/// The Rain names of the parameters of a function or of a method (`Class::method`), the methods start with `this`.
+/// The parameters are numbered when the names can't be found
+pub(crate) fn param_names(module: &common::module::Module, name: &str, count: usize) -> Vec<String> {
+    let parsable = &module.parsable_module;
+
+    let names = match name.split_once("::") {
+        Some((class_name, method_name)) => parsable.classes
+            .iter()
+            .find(|(name, _)| name == class_name)
+            .and_then(|(_, class)| class.methods.iter().find(|(name, _)| name == method_name))
+            .map(|(_, method)| [CLASS_SELF_REFERENCE.to_string()].into_iter().chain(method.params.iter().cloned()).collect()),
+        None => parsable.functions
+            .iter()
+            .find(|(func_name, _)| func_name == name)
+            .map(|(_, func)| func.params.clone()),
+    };
+
+    match names {
+        Some(names) if names.len() == count => names,
+        // Static methods don't have `this`
+        Some(names) if names.len() == count + 1 => names[1..].to_vec(),
+        _ => (0..count).map(|i| format!("arg{}", i)).collect(),
+    }
+}
+
+/// The names of the wasm values of the parameters, a parameter made of several values numbers them
+pub(crate) fn value_names(names: &[String], types: &[TypeKind]) -> Vec<String> {
+    names
+        .iter()
+        .zip(types)
+        .flat_map(|(name, type_)| match convert_type(type_).len() {
+            1 => vec![name.clone()],
+            count => (0..count).map(|i| format!("{}_{}", name, i)).collect(),
+        })
+        .collect()
+}
@@ -1,29 +1,44 @@
 use std::ops::Index;
-use wasm_encoder::{BlockType, Instruction, ValType, MemArg};
-use common::ast::{ASTBody, ASTNode, ElseType, NodeKind};
-use common::ast::types::{LiteralKind, FunctionType, Function, TypeKind, ClassKind, ClassType};
+use std::sync::Arc;
+use wasm_encoder::{Instruction, MemArg, ValType};
+use common::ast::Span;
+use common::ast::types::{LiteralKind, FunctionType, TypeKind, ClassKind, ClassType};
 use common::errors::{LangError, BuildErrorKind};
-use common::module::{ModuleUID, Module, FunctionDefinition, ModuleFeature, VariableDefinition};
+use common::module::{Module, ModuleUID, FunctionDefinition, ModuleFeature, VariableDefinition};
 use core::parser::ModuleLoader;
-use std::sync::Arc;
-use crate::build::{convert_class, convert_enum, convert_type, convert_types};
-use common::constants::{ATTRIB_STATIC_MEMORY, CLASS_CONSTRUCTOR_NAME, CLASS_SELF_REFERENCE, INTERNAL_ALLOC_LOCATION};
-
-// TODO: Right now memory alignment is at 0 so it's 1 byte, better alignment would be cool (probably 2)
+use ir::flow::ControlFlow;
+use ir::function::{BlockId, Function, FunctionRef, GlobalRef, InstructionKind, LocalId, Operand, Terminator, Value};
+use ir::liveness::Interference;
+use ir::program::{FunctionSymbol, GlobalSymbol, Program, Usage};
+use crate::build::{convert_class, convert_enum, convert_type, convert_types, param_names};
+use crate::build_mem::{align_to, class_layout, field_layout, type_layout, VECTOR_HEADER_SIZE, VTABLE_TABLE};
+use crate::engine::BuildTarget;
+use crate::wasi::WasiData;
+use common::constants::ATTRIB_STATIC_MEMORY;
+
+/// Strings start with their length as an `i32`
+const STRING_ALIGNMENT: u64 = 4;
 
 pub enum ModuleDataKind {
     Standard,
+    /// The bytes of a string literal, the literal is kept for the text output
+    String(String),
     StaticMemoryOffset,
 }
 
 pub struct FunctionData {
     pub name: String,
+    pub module: ModuleUID,
 
     pub params: Vec<ValType>,
     pub ret: Vec<ValType>,
 
     pub locals: Vec<ValType>,
+    /// Name of every wasm local, the locals that hold a part of a value are suffixed with the part index
+    pub local_names: Vec<(u32, String)>,
     pub instructions: Vec<Instruction<'static>>,
+    /// Index of the first instruction of every statement with its position in the source
+    pub spans: Vec<(usize, Span)>,
 }
 
 pub struct FunctionImport {
@@ -32,6 +47,25 @@ pub struct FunctionImport {
 
     pub params: Vec<ValType>,
     pub ret: Vec<ValType>,
+    /// The Rain type of the function, the functions imported by the builder itself don't have one
+    pub metadata: Option<FunctionType>,
+    /// The Rain names of the parameters, used by the bindings
+    pub param_names: Vec<String>,
+}
+
+/// A function exported by the module with its Rain type, used by the bindings
+pub struct FunctionExport {
+    pub name: String,
+    pub metadata: FunctionType,
+    pub param_names: Vec<String>,
+}
+
+pub struct GlobalImport {
+    pub module_name: String,
+    pub name: String,
+
+    pub val_type: ValType,
+    pub metadata: TypeKind,
 }
 
 pub struct ModuleData {
@@ -43,61 +77,107 @@ pub struct ModuleData {
 pub struct ModuleBuilderResult {
     pub function_data: Vec<FunctionData>,
     pub function_imports: Vec<FunctionImport>,
-    pub data: Vec<ModuleData>
+    pub global_imports: Vec<GlobalImport>,
+    pub data: Vec<ModuleData>,
+    pub externref_table: bool,
+    /// The functions of `VTABLE_TABLE`
+    pub function_table: Vec<u32>,
 }
 
 pub struct ModuleBuilder<'a> {
     module_loader: &'a ModuleLoader,
-    function_names: Vec<String>,
-    functions: Vec<(Vec<TypeKind>, TypeKind)>,
-    global_names: Vec<String>,
+    program: &'a Program,
+    pub(crate) target: BuildTarget,
+    /// Functions are identified by their module, so that features with the same name can coexist
+    pub(crate) function_names: Vec<(ModuleUID, String)>,
+    pub(crate) functions: Vec<(Vec<TypeKind>, TypeKind)>,
+    /// The global variables placed in the data, with their offset
+    global_names: Vec<(ModuleUID, String)>,
     globals: Vec<(TypeKind, u32)>,
+    global_import_names: Vec<(ModuleUID, String)>,
+    global_import_types: Vec<TypeKind>,
 
     data_offset_accumulator: u32,
     data: Vec<ModuleData>,
-    function_data: Vec<FunctionData>,
-    function_imports: Vec<FunctionImport>,
+    pub(crate) function_data: Vec<FunctionData>,
+    pub(crate) function_imports: Vec<FunctionImport>,
+    global_imports: Vec<GlobalImport>,
+    /// Created the first time a WASI function needs it
+    pub(crate) wasi_data: Option<WasiData>,
+    /// Set the first time an extern object is placed in the memory
+    pub(crate) externref_table: bool,
+    /// The address of the vtable of every class whose objects are created, `None` if the class has no slot
+    vtables: Vec<(Arc<ClassType>, Option<u32>)>,
+    function_table: Vec<u32>,
 }
 
 impl<'a> ModuleBuilder<'a> {
-    pub fn new(module_loader: &'a ModuleLoader) -> Result<Self, LangError> {
+    /// The data is placed starting from `data_base`, the imports are limited to the functions
+    /// and the variables in `usage`
+    pub fn new(module_loader: &'a ModuleLoader, program: &'a Program, usage: &Usage, data_base: u32, target: BuildTarget) -> Result<Self, LangError> {
         let mut builder = Self {
             module_loader,
+            program,
+            target,
             function_names: Vec::new(),
             functions: Vec::new(),
             global_names: Vec::new(),
             globals: Vec::new(),
+            global_import_names: Vec::new(),
+            global_import_types: Vec::new(),
 
-            data_offset_accumulator: 0,
+            data_offset_accumulator: data_base,
             data: Vec::new(),
             function_data: Vec::new(),
             function_imports: Vec::new(),
+            global_imports: Vec::new(),
+            wasi_data: None,
+            externref_table: false,
+            vtables: Vec::new(),
+            function_table: Vec::new(),
         };
 
-        // TODO: This is not how it should be done
-        // This loads all the declaration ad imports
+        // Only the declarations used by the built functions are imported
         for module in module_loader.modules() {
 
             for (name, feature) in &module.features {
                 match feature {
+                    ModuleFeature::Function(FunctionDefinition { data: None, .. }) if !usage.uses_function(module.uid, name) => (),
+                    // Implemented by the builder when they are used
+                    ModuleFeature::Function(FunctionDefinition { data: None, .. }) if builder.is_wasi_func(&module) => (),
+                    ModuleFeature::Function(func @ FunctionDefinition { data: None, .. }) if builder.is_component() => {
+                        builder.insert_component_import(&module, name, &func.metadata)?;
+                    },
                     ModuleFeature::Function(func @ FunctionDefinition { data: None, .. }) => {
-                        builder.function_names.push(name.clone());
+                        builder.function_names.push((module.uid, name.clone()));
                         builder.functions.push((func.metadata.0.clone(), (*func.metadata.1).clone()));
 
-                        builder.insert_imported_func(module.id.0.as_ref(), name.as_ref(), &func.metadata)?;
+                        builder.insert_imported_func(&module, name.as_ref(), &func.metadata)?;
+                    },
+                    ModuleFeature::Variable(VariableDefinition { data: None, .. }) if !usage.uses_global(module.uid, name) => (),
+                    ModuleFeature::Variable(VariableDefinition { data: None, .. }) if builder.is_component() => {
+                        return Err(LangError::build(BuildErrorKind::Unsupported(
+                            format!("A component can't import the variable `{}`", name))));
+                    },
+                    ModuleFeature::Variable(var @ VariableDefinition { data: None, .. }) => {
+                        builder.insert_imported_var(module.uid, module.import_module_name(), name.as_ref(), &var.metadata)?;
                     },
-                    ModuleFeature::Variable(VariableDefinition { data: None, .. })
-                        => return Err(LangError::build(BuildErrorKind::Unsupported("Variable declaration not yet supported".to_string()))),
                     ModuleFeature::Class(class) => {
                         for (method_name, method) in &class.data.methods {
                             if let Some(_) = method.data { continue }
 
                             let name = format!("{}::{}", class.metadata.name, method_name);
+                            if !usage.uses_function(module.uid, &name) { continue }
 
-                            builder.function_names.push(name.clone());
+                            if builder.is_component() {
+                                return Err(LangError::build(BuildErrorKind::Unsupported(
+                                    format!("A component can't import the class `{}`", class.metadata.name))));
+                            }
+
+                            builder.function_names.push((module.uid, name.clone()));
                             builder.functions.push((method.metadata.0.clone(), (*method.metadata.1).clone()));
 
-                            builder.insert_imported_func(module.id.0.as_ref(), name.as_ref(), &method.metadata)?;
+                            builder.insert_imported_func(&module, name.as_ref(), &method.metadata)?;
                         }
                     },
                     _ => (),
@@ -105,66 +185,65 @@ impl<'a> ModuleBuilder<'a> {
             }
         }
 
+        if let BuildTarget::Wasi = target {
+            builder.insert_wasi_imports();
+        }
+
         Ok(builder)
     }
 
-    pub fn insert_module(&mut self, module: Arc<Module>) -> Result<(), LangError> {
-
-        // TODO: This is momentary, but it should be done in a better way, anyway globals will gonna be removed
-        for (name, feature) in &module.features {
-            match feature {
-                ModuleFeature::Variable(var @ VariableDefinition { data: Some(ref data), .. }) => {
-                    self.insert_var(name, var, data)?;
-                }
-                _ => (),
-            }
+    /// Inserts the functions built even if nothing calls them, the others are inserted by their first call
+    pub fn insert_funcs(&mut self, functions: &[FunctionRef]) -> Result<(), LangError> {
+        for function in functions {
+            self.get_func(function.module, &function.name)?;
         }
 
-        for (name, feature) in &module.features {
-            match feature {
-                ModuleFeature::Function(func @ FunctionDefinition { data: Some(ref data), .. }) => {
-                    let contains_func = self.function_names
-                        .iter()
-                        .any(|n| n == name);
-
-                    if contains_func {
-                        continue
-                    }
+        Ok(())
+    }
 
-                    self.insert_func(name, &func.metadata, data)?;
+    pub fn insert_imported_func(&mut self, module: &Module, name: &str, func_type: &FunctionType) -> Result<(), LangError> {
+        self.function_imports.push(FunctionImport {
+            module_name: module.import_module_name().to_string(),
+            name: name.to_string(),
 
-                    self.function_names.push(name.clone());
-                    self.functions.push((func.metadata.0.clone(), (*func.metadata.1).clone()));
-                }
-                ModuleFeature::Class(_) => {}
-                _ => (),
-            }
-        }
+            params: convert_types(&func_type.0),
+            ret: convert_type(func_type.1.as_ref()),
+            metadata: Some(func_type.clone()),
+            param_names: param_names(module, name, func_type.0.len()),
+        });
 
         Ok(())
     }
 
-    pub fn insert_imported_func(&mut self, module_name: &str, name: &str, func_type: &FunctionType) -> Result<(), LangError> {
-        self.function_imports.push(FunctionImport {
+    /// Imported variables are immutable wasm globals, so only types made of a single value are supported
+    pub fn insert_imported_var(&mut self, module_uid: ModuleUID, module_name: &str, name: &str, type_: &TypeKind) -> Result<(), LangError> {
+        let val_type = match convert_type(type_).as_slice() {
+            [val_type] => *val_type,
+            _ => return Err(LangError::build(BuildErrorKind::Unsupported(
+                format!("Imported variable `{}` must have a single value type", name)))),
+        };
+
+        self.global_import_names.push((module_uid, name.to_string()));
+        self.global_import_types.push(type_.clone());
+        self.global_imports.push(GlobalImport {
             module_name: module_name.to_string(),
             name: name.to_string(),
 
-            params: convert_types(&func_type.0),
-            ret: convert_type(func_type.1.as_ref()),
+            val_type,
+            metadata: type_.clone(),
         });
 
         Ok(())
     }
 
-    fn insert_var(&mut self, name: &str, var: &VariableDefinition, literal: &LiteralKind) -> Result<(), LangError> {
+    fn insert_var(&mut self, global: &GlobalRef, var: &GlobalSymbol, literal: &LiteralKind) -> u32 {
         let data = match literal {
             LiteralKind::Nothing => Vec::new(),
             LiteralKind::Int(i) => i.to_le_bytes().to_vec(),
             LiteralKind::Float(f) => f.to_le_bytes().to_vec(),
             LiteralKind::Bool(b) => (if *b { 1u32 } else { 0u32 }).to_le_bytes().to_vec(),
             LiteralKind::String(s) => {
-                let data = FunctionBuilder::string_to_bytes(s.clone());
-                let offset = self.push_data(data, ModuleDataKind::Standard);
+                let offset = self.push_string(s);
 
                 offset.to_le_bytes().to_vec()
             },
@@ -175,45 +254,30 @@ impl<'a> ModuleBuilder<'a> {
             false => ModuleDataKind::Standard,
         };
 
-        let offset = self.push_data(data, kind);
+        let offset = self.push_data(data, kind, type_layout(&var.type_).align);
 
-        self.global_names.push(name.to_string());
-        self.globals.push((var.metadata.clone(), offset));
-
-        Ok(())
-    }
-
-    fn insert_func(&mut self, name: &str, func_type: &FunctionType, func: &Arc<Function>) -> Result<(), LangError> {
-        let mut code_builder = FunctionBuilder::new(
-            self,
-            name.to_string(),
-            func_type.0.clone(),
-            func.parameters.clone(),
-            *func_type.1.clone(),
-            func.method.clone());
-
-        for node in &func.body {
-            code_builder.build_statement(&node)?;
-        }
+        self.global_names.push((global.module, global.name.clone()));
+        self.globals.push((var.type_.clone(), offset));
 
-        let result = code_builder.build();
-
-        self.function_data.push(result);
-
-        Ok(())
+        offset
     }
 
     pub fn build(self) -> ModuleBuilderResult {
         ModuleBuilderResult {
             function_data: self.function_data,
             function_imports: self.function_imports,
+            global_imports: self.global_imports,
             data: self.data,
+            externref_table: self.externref_table,
+            function_table: self.function_table,
         }
     }
 
-    fn push_data(&mut self, data: Vec<u8>, kind: ModuleDataKind) -> u32 {
+    /// Places the data right after the previous one, at the next multiple of `align`
+    pub(crate) fn push_data(&mut self, data: Vec<u8>, kind: ModuleDataKind, align: u64) -> u32 {
         let data_len = data.len() as u32;
 
+        self.data_offset_accumulator = align_to(self.data_offset_accumulator as u64, align) as u32;
         let offset = self.data_offset_accumulator;
 
         self.data.push(ModuleData {
@@ -227,926 +291,896 @@ impl<'a> ModuleBuilder<'a> {
         offset
     }
 
-    pub(crate) fn get_func(&mut self, module_uid: ModuleUID, name: &String) -> Result<(u32, &Vec<TypeKind>, &TypeKind), LangError> {
-        let func_id = self.function_names
-            .iter()
-            .position(|n| n == name);
-
-        match func_id {
-            Some(func_id) => {
-                let (params, ret) = self.functions.index(func_id);
-                Ok((
-                    func_id as u32,
-                    params,
-                    ret,
-                ))
-            },
-            None => {
-                let module = self.module_loader
-                    .get_module(module_uid)
-                    .ok_or(LangError::build(BuildErrorKind::ModuleNotFound(module_uid)))?;
+    /// Places a string literal in the data, prefixed by its length
+    pub(crate) fn push_string(&mut self, string: &String) -> u32 {
+        let mut data = (string.len() as u32).to_le_bytes().to_vec();
+        data.extend(string.as_bytes());
 
-                let func = match module.get_func_feature(name) {
-                    Some(f) => f,
-                    None => return Err(LangError::build(BuildErrorKind::FuncNotFound(name.clone()))),
-                };
+        self.push_data(data, ModuleDataKind::String(string.clone()), STRING_ALIGNMENT)
+    }
 
-                self.load_func(func, name)
-            },
+    /// Places the vtable of a class in the data, each slot holds the index of a function in `VTABLE_TABLE`.
+    /// Returns `None` if the class has no slot, the data may start at address 0 so it can't mark a missing vtable
+    pub(crate) fn get_vtable(&mut self, class_type: &Arc<ClassType>) -> Result<Option<u32>, LangError> {
+        if let Some((_, offset)) = self.vtables.iter().find(|(class, _)| Arc::ptr_eq(class, class_type)) {
+            return Ok(*offset)
+        }
+
+        let vtable = self.program.classes.vtable(class_type);
+        let mut func_ids = Vec::with_capacity(vtable.methods.len());
+
+        for function in vtable.functions() {
+            let (func_id, _, _) = self.get_func(function.module, &function.name)?;
+            func_ids.push(func_id);
+        }
+
+        // Building the methods may have placed the vtable already
+        if let Some((_, offset)) = self.vtables.iter().find(|(class, _)| Arc::ptr_eq(class, class_type)) {
+            return Ok(*offset)
         }
-    }
 
-    fn get_method(&mut self, module_uid: ModuleUID, class_name: &String, name: &String) -> Result<(u32, &Vec<TypeKind>, &TypeKind), LangError> {
-        let func_name = format!("{}::{}", class_name, name);
+        let offset = match func_ids.is_empty() {
+            true => None,
+            false => {
+                let mut data = Vec::with_capacity(func_ids.len() * 4);
 
+                for func_id in func_ids {
+                    data.extend((self.function_table.len() as u32).to_le_bytes());
+                    self.function_table.push(func_id);
+                }
+
+                Some(self.push_data(data, ModuleDataKind::Standard, 4))
+            },
+        };
+
+        self.vtables.push((class_type.clone(), offset));
+
+        Ok(offset)
+    }
+
+    pub(crate) fn get_func(&mut self, module_uid: ModuleUID, name: &String) -> Result<(u32, &Vec<TypeKind>, &TypeKind), LangError> {
         let func_id = self.function_names
             .iter()
-            .position(|n| n == &func_name);
-
-        match func_id {
-            Some(func_id) => {
-                let (params, ret) = self.functions.index(func_id);
-                Ok((
-                    func_id as u32,
-                    params,
-                    ret,
-                ))
-            },
+            .position(|(uid, n)| *uid == module_uid && n == name);
+
+        let func_id = match func_id {
+            Some(func_id) => func_id as u32,
             None => {
                 let module = self.module_loader
                     .get_module(module_uid)
                     .ok_or(LangError::build(BuildErrorKind::ModuleNotFound(module_uid)))?;
 
-                let class = match module.get_class_feature(class_name) {
-                    Some(f) => f,
-                    None => return Err(LangError::build(BuildErrorKind::ClassNotFound(class_name.clone()))),
-                };
+                match module.get_func_feature(name) {
+                    Some(func) if func.data.is_none() && self.is_wasi_func(&module) => return self.load_wasi_func(module_uid, func, name),
+                    Some(func) if func.data.is_none() && self.is_component() => return self.load_component_func(module_uid, func, name),
+                    _ => (),
+                }
 
-                let func = match class.get_method_def(name) {
-                    Some(f) => f,
-                    None => return Err(LangError::build(BuildErrorKind::FuncNotFound(func_name.clone()))),
-                };
+                let symbol = self.program
+                    .get_module(module_uid)
+                    .and_then(|table| table.get_function(name))
+                    .ok_or(LangError::build(BuildErrorKind::FuncNotFound(name.clone())))?;
 
-                self.load_func(&func, &func_name)
+                self.load_func(module_uid, symbol)?
             },
-        }
+        };
+
+        let (params, ret) = self.functions.index(func_id as usize);
+
+        Ok((func_id, params, ret))
     }
 
-    fn load_func(&mut self, func: &FunctionDefinition, name: &String) -> Result<(u32, &Vec<TypeKind>, &TypeKind), LangError> {
-        let data = match &func.data {
-            Some(data) => data,
+    fn load_func(&mut self, module_uid: ModuleUID, symbol: &'a FunctionSymbol) -> Result<u32, LangError> {
+        let function = match &symbol.body {
+            Some(function) => function,
             None => return Err(LangError::build(BuildErrorKind::UnexpectedError("load_func: Tried to load a function with no data".to_string()))),
         };
 
-        self.insert_func(name.as_ref(), &func.metadata, data)?;
+        // The index is taken before building the body, so that the function can call itself
+        let func_id = self.function_names.len() as u32;
+        let data_id = self.function_data.len();
+
+        self.function_names.push((module_uid, symbol.name.clone()));
+        self.functions.push((symbol.type_.0.clone(), *symbol.type_.1.clone()));
+        self.function_data.push(FunctionData {
+            name: symbol.name.clone(),
+            module: module_uid,
+            params: Vec::new(),
+            ret: Vec::new(),
+            locals: Vec::new(),
+            local_names: Vec::new(),
+            instructions: Vec::new(),
+            spans: Vec::new(),
+        });
+
+        let function_data = FunctionBuilder::new(self, function).build()?;
+        self.function_data[data_id] = function_data;
 
-        self.function_names.push(name.clone());
-        self.functions.push((
-            func.metadata.0.clone(),
-            *func.metadata.1.clone(),
-        ));
+        Ok(func_id)
+    }
 
-        let (params, ret) = self.functions
-            .last()
-            .unwrap();
+    /// The type of a global variable and where it is, the variables with a value are placed in the data when first used
+    fn get_global(&mut self, global: &GlobalRef) -> Result<(TypeKind, GlobalKind), LangError> {
+        let id = self.global_names
+            .iter()
+            .position(|(uid, name)| *uid == global.module && *name == global.name);
 
-        Ok((
-            self.functions.len() as u32 - 1,
-            params,
-            ret
-        ))
+        if let Some(id) = id {
+            let (type_, offset) = self.globals.index(id);
+
+            return Ok((type_.clone(), GlobalKind::Data(*offset)))
+        }
+
+        let id = self.global_import_names
+            .iter()
+            .position(|(uid, name)| *uid == global.module && *name == global.name);
+
+        if let Some(id) = id {
+            return Ok((self.global_import_types.index(id).clone(), GlobalKind::Import(id as u32)))
+        }
+
+        let symbol = self.program
+            .get_global(global)
+            .ok_or(LangError::build(BuildErrorKind::UnexpectedError(
+                format!("get_global: Variable `{}` not found", global.name))))?;
+
+        match &symbol.value {
+            Some(value) => {
+                let offset = self.insert_var(global, symbol, value);
+
+                Ok((symbol.type_.clone(), GlobalKind::Data(offset)))
+            },
+            None => Err(LangError::build(BuildErrorKind::UnexpectedError(
+                format!("get_global: The imported variable `{}` was not inserted", global.name)))),
+        }
     }
 }
 
-enum VarKind {
-    Local(Vec<u32>),
-    Global(u32),
+enum GlobalKind {
+    /// The offset of the variable in the data
+    Data(u32),
+    /// An immutable wasm global imported from the host
+    Import(u32),
 }
 
 pub struct FunctionBuilder<'a, 'b> {
     pub(crate) module_builder: &'a mut ModuleBuilder<'b>,
+    pub(crate) function: &'b Function,
 
-    /// This is like locals but is kept untouched
-    params: Vec<TypeKind>,
-    ret: TypeKind,
-
-    name: String,
-    id_accumulator: u32,
-    locals: Vec<TypeKind>,
-    local_names: Vec<String>,
+    /// The wasm locals of every IR local, the inlined locals have none and the
+    /// locals never holding a value at the same time share them
     local_ids: Vec<Vec<u32>>,
+    locals: Vec<ValType>,
+    local_names: Vec<(u32, String)>,
+    /// The locals used while building a single value that can be taken again
+    free_scratch: Vec<u32>,
+    /// The locals computed right before their only use are left on the stack instead
+    inlined: Vec<bool>,
+    deferred: Vec<Option<&'b Value>>,
 
-    type_stack: Vec<TypeKind>,
     pub(crate) instructions: Vec<Instruction<'static>>,
+    spans: Vec<(usize, Span)>,
 }
 
 impl<'a, 'b> FunctionBuilder<'a, 'b> {
-    pub fn new(
-        module_builder: &'a mut ModuleBuilder<'b>,
-        name: String,
-        mut params: Vec<TypeKind>,
-        mut param_names: Vec<String>,
-        ret: TypeKind,
-        method: Option<Arc<ClassType>>,
-    ) -> Self {
-        let mut id_accumulator = 0u32;
-        let mut local_ids = Vec::with_capacity(params.len());
-
-        if let Some(method) = method {
-            param_names.insert(0, CLASS_SELF_REFERENCE.to_string());
-            params.insert(0, TypeKind::Class(method));
-        }
+    pub fn new(module_builder: &'a mut ModuleBuilder<'b>, function: &'b Function) -> Self {
+        let inlined = Self::find_inlined(function);
 
-        for param in &params {
-            let len = convert_type(param).len() as u32;
-            let ids = (id_accumulator..id_accumulator + len).collect();
-            local_ids.push(ids);
-
-            id_accumulator += len;
-        }
-
-        Self {
+        let mut builder = Self {
             module_builder,
+            function,
 
-            params: params.clone(),
-            ret,
-
-            name,
-            id_accumulator,
-            locals: params,
-            local_names: param_names,
-            local_ids,
+            local_ids: Vec::with_capacity(function.locals.len()),
+            locals: Vec::new(),
+            local_names: Vec::new(),
+            free_scratch: Vec::new(),
+            inlined,
+            deferred: vec![None; function.locals.len()],
 
-            type_stack: Vec::new(),
             instructions: Vec::new(),
-        }
-    }
+            spans: Vec::new(),
+        };
 
-    pub fn build(mut self) -> FunctionData {
-        self.instructions.push(Instruction::End);
+        let interference = Interference::new(function, &ControlFlow::new(function));
 
-        FunctionData {
-            name: self.name,
+        // Every slot is a group of wasm locals with the IR locals stored in it
+        let mut slots: Vec<(Vec<u32>, Vec<LocalId>)> = Vec::new();
 
-            params: convert_types(&self.params),
-            ret: convert_type(&self.ret),
+        for (i, local) in function.locals.iter().enumerate() {
+            let id = LocalId(i as u32);
 
-            locals: convert_types(&self.locals),
-            instructions: self.instructions,
-        }
-    }
+            if builder.inlined[i] {
+                builder.local_ids.push(Vec::new());
+                continue
+            }
 
-    pub fn push_local(&mut self, name: String, type_: TypeKind) -> &Vec<u32> {
-        let len = convert_type(&type_).len() as u32;
-        let ids = (self.id_accumulator..self.id_accumulator + len).collect();
-        self.local_ids.push(ids);
-        self.id_accumulator += len;
+            let types = convert_type(&local.type_);
+
+            let shared = slots
+                .iter_mut()
+                .find(|(ids, holders)| ids.iter().map(|id| builder.locals[*id as usize]).eq(types.iter().cloned())
+                    && holders.iter().all(|holder| !interference.interferes(*holder, id)));
+
+            let ids = match shared {
+                // The parameters have their own wasm locals, in the order of the signature
+                Some((ids, holders)) if i >= function.param_count as usize => {
+                    holders.push(id);
+                    ids.clone()
+                },
+                _ => {
+                    let ids = builder.push_locals(&types);
+                    slots.push((ids.clone(), vec![id]));
+                    ids
+                },
+            };
+
+            if let Some(name) = &local.name {
+                builder.name_locals(&ids, name);
+            }
 
-        self.locals.push(type_);
-        self.local_names.push(name);
+            builder.local_ids.push(ids);
+        }
 
-        &self.local_ids.last().unwrap()
+        builder
     }
 
-    pub fn build_statement(&mut self, node: &ASTNode) -> Result<(), LangError> {
-        match node.kind.as_ref() {
-            NodeKind::VariableDecl { name, value } => {
-                // Build value
-                self.build_statement(value)?;
+    /// A temporary is inlined if it is used once, by the instruction right after the one that computes it.
+    /// The enum values and the objects of data classes are read in place, so they stay in a local
+    fn find_inlined(function: &Function) -> Vec<bool> {
+        let uses = function.local_uses();
+        let assignments = function.local_assignments();
 
-                // Remove "value" from the stack and add it's type to the locals
-                let type_ = self.type_stack.pop().unwrap();
+        let mut inlined = vec![false; function.locals.len()];
 
-                let ids = self.push_local(name.clone(), type_).clone();
-
-                for id in ids.iter().rev() {
-                    self.instructions.push(Instruction::LocalSet(*id));
-                }
-            },
-            NodeKind::VariableRef { module: _, name } => {
-                let (var_type, var_kind) = match self.get_var(name) {
-                    Some((vr, vk)) => (vr, vk),
-                    None => return Err(LangError::build(BuildErrorKind::UnexpectedError(
-                        "build_statement(VariableRef): Variable not found".to_string(),
-                    ))),
+        for block in &function.blocks {
+            for (i, instruction) in block.instructions.iter().enumerate() {
+                let local = match &instruction.kind {
+                    InstructionKind::Assign { local, .. } => *local,
+                    _ => continue,
                 };
 
-                self.type_stack.push(var_type.clone());
+                let id = local.0 as usize;
+                if function.local(local).name.is_some() || uses[id] != 1 || assignments[id] != 1 { continue }
 
-                match var_kind {
-                    VarKind::Local(ids) => {
-                        for id in ids {
-                            self.instructions.push(Instruction::LocalGet(id));
-                        }
-                    },
-                    VarKind::Global(offset) => {
-                        self.instructions.push(Instruction::I32Const(offset as i32));
-                        self.build_mem_load(&var_type, MemArg {
-                            memory_index: 0,
-                            offset: 0,
-                            align: 0,
-                        });
-                    },
-                };
-            },
-            NodeKind::VariableAsgn { name, value } => {
-                let (local_type, var_kind) = match self.get_var(name) {
-                    Some((lt, vk)) => (lt, vk),
-                    None => return Err(LangError::build(BuildErrorKind::UnexpectedError("build_statement(VariableAsgn): Variable not found".to_string()))),
+                let operands = match block.instructions.get(i + 1) {
+                    Some(next) => Self::stack_operands(&next.kind),
+                    None => block.terminator.operands(),
                 };
 
-                match var_kind {
-                    VarKind::Local(ids) => {
-                        self.build_statement(value)?;
-                        for id in ids {
-                            self.instructions.push(Instruction::LocalSet(id));
-                        }
-                    },
-                    VarKind::Global(offset) => {
-                        self.instructions.push(Instruction::I32Const(offset as i32));
+                inlined[id] = operands.contains(&&Operand::Local(local));
+            }
+        }
 
-                        self.build_statement(value)?;
+        inlined
+    }
 
-                        self.build_mem_store(&local_type, MemArg {
-                            align: 0,
-                            memory_index: 0,
-                            offset: 0,
-                        });
-                    },
-                }
+    /// The operands that are read from the stack
+    fn stack_operands(kind: &InstructionKind) -> Vec<&Operand> {
+        match kind {
+            InstructionKind::Assign { value, .. } |
+            InstructionKind::Eval(value) => match value {
+                Value::Field { class_type, .. } if class_type.kind == ClassKind::Data => Vec::new(),
+                Value::Discriminant(_) |
+                Value::TupleElement { .. } |
+                Value::Payload { .. } => Vec::new(),
+                value => value.operands(),
+            },
+            InstructionKind::SetField { class_type, value, .. } if class_type.kind == ClassKind::Data => vec![value],
+            kind => kind.operands(),
+        }
+    }
 
-                let type_ = self.type_stack.pop().unwrap();
+    fn push_locals(&mut self, types: &[ValType]) -> Vec<u32> {
+        let first = self.locals.len() as u32;
+        self.locals.extend(types);
 
-                Self::assert_type(&type_, &local_type)?;
-            },
-            NodeKind::FunctionInvok { variable, parameters } => {
-                // TODO: Support for other kinds of invocations
-                let (func_id, param_types, ret_type) = match variable.kind.as_ref() {
-                    NodeKind::VariableRef { name, module } => {
-                        for param in parameters {
-                            self.build_statement(param)?;
-                        }
+        (first..first + types.len() as u32).collect()
+    }
 
-                        self.module_builder.get_func(*module, name)?
-                    },
-                    NodeKind::FieldAccess { variable, class_type, field_name } => {
-                        self.build_statement(variable)?;
+    /// The locals holding several variables are named after all of them
+    fn name_locals(&mut self, ids: &[u32], name: &str) {
+        for (i, id) in ids.iter().enumerate() {
+            let name = match ids.len() {
+                1 => name.to_string(),
+                _ => format!("{}.{}", name, i),
+            };
+
+            match self.local_names.iter_mut().find(|(local, _)| local == id) {
+                Some((_, names)) => {
+                    names.push('/');
+                    names.push_str(&name);
+                },
+                None => self.local_names.push((*id, name)),
+            }
+        }
+    }
 
-                        for param in parameters {
-                            self.build_statement(param)?;
-                        }
+    /// A local for the code of a single value, it has to be released once the value is built
+    pub(crate) fn take_scratch(&mut self, type_: ValType) -> u32 {
+        let free = self.free_scratch
+            .iter()
+            .position(|id| self.locals[*id as usize] == type_);
 
-                        self.module_builder.get_method(class_type.module, &class_type.name, field_name)?
-                    },
-                    _ => return Err(LangError::build(BuildErrorKind::Unsupported("Not static function call".to_string()))),
-                };
+        match free {
+            Some(i) => self.free_scratch.swap_remove(i),
+            None => self.push_locals(&[type_])[0],
+        }
+    }
 
-                for param in param_types.iter().skip(1) {
-                    let type_ = self.type_stack.pop().unwrap();
+    pub(crate) fn release_scratch(&mut self, id: u32) {
+        self.free_scratch.push(id);
+    }
 
-                    Self::assert_type(&type_, &param)?;
-                }
+    pub fn build(mut self) -> Result<FunctionData, LangError> {
+        let flow = ControlFlow::new(self.function);
 
-                self.type_stack.push(ret_type.clone());
+        if let Some(entry) = flow.order().first() {
+            self.build_tree(&flow, *entry, &mut Vec::new())?;
+        }
 
-                self.instructions.push(Instruction::Call(func_id));
-            },
-            NodeKind::Literal { value } => {
-                match value {
-                    LiteralKind::Nothing => {
-                        self.type_stack.push(TypeKind::Nothing);
-                    },
-                    LiteralKind::Int(i) => {
-                        self.instructions.push(Instruction::I32Const(*i));
-                        self.type_stack.push(TypeKind::Int);
-                    },
-                    LiteralKind::Float(f) => {
-                        self.instructions.push(Instruction::F32Const(*f));
-                        self.type_stack.push(TypeKind::Float);
-                    },
-                    LiteralKind::Bool(b) => {
-                        let value = if *b { 1 } else { 0 };
+        let ret = convert_type(&self.function.ret);
 
-                        self.instructions.push(Instruction::I32Const(value));
-                        self.type_stack.push(TypeKind::Bool);
-                    },
-                    LiteralKind::String(string) => {
-                        let data = Self::string_to_bytes(string.clone());
+        // The values left on the stack at the end are the results
+        if let Some(Instruction::Return) = self.instructions.last() {
+            self.instructions.pop();
+        } else if !ret.is_empty() && !matches!(self.instructions.last(), Some(Instruction::Unreachable)) {
+            self.instructions.push(Instruction::Unreachable);
+        }
+
+        self.instructions.push(Instruction::End);
 
-                        let offset = self.module_builder.push_data(data, ModuleDataKind::Standard);
+        let params = self.function
+            .params()
+            .iter()
+            .flat_map(|param| convert_type(&param.type_))
+            .collect();
 
-                        self.instructions.push(Instruction::I32Const(offset as i32));
-                        self.type_stack.push(TypeKind::String);
-                    },
-                };
-            },
-            NodeKind::MathOperation { operation, left, right } => {
-                self.build_statement(left)?;
-                self.build_statement(right)?;
+        Ok(FunctionData {
+            name: self.function.name.clone(),
+            module: self.function.module,
 
-                let right = self.type_stack.pop().unwrap();
-                let left = self.type_stack.pop().unwrap();
+            params,
+            ret,
 
-                let left_convert = convert_type(&left);
-                let right_convert = convert_type(&right);
+            locals: self.locals,
+            local_names: self.local_names,
+            instructions: self.instructions,
+            spans: self.spans,
+        })
+    }
 
-                self.type_stack.push(left);
+    fn push_span(&mut self, span: Option<Span>) {
+        let span = match span {
+            Some(span) => span,
+            None => return,
+        };
 
-                match (left_convert.as_slice(), right_convert.as_slice()) {
-                    ([left], [right]) => {
-                        self.build_math_op(operation, left.clone(), right.clone());
-                    }
-                    _ => return Err(LangError::build(BuildErrorKind::InvalidStackType)),
-                }
-            },
-            NodeKind::BoolOperation { operation, left, right } => {
-                self.build_statement(left)?;
-                self.build_statement(right)?;
+        match self.spans.last() {
+            Some((_, last)) if *last == span => (),
+            _ => self.spans.push((self.instructions.len(), span)),
+        }
+    }
 
-                let right = self.type_stack.pop().unwrap();
-                let left = self.type_stack.pop().unwrap();
+    /// Builds the instructions of a block, the terminator is built by the caller
+    pub(crate) fn build_block(&mut self, block: BlockId) -> Result<(), LangError> {
+        let function = self.function;
 
-                let left_convert = convert_type(&left);
-                let right_convert = convert_type(&right);
+        for instruction in &function.block(block).instructions {
+            self.push_span(instruction.span);
+            self.build_instruction(&instruction.kind)?;
+        }
 
-                self.type_stack.push(left);
+        self.push_span(function.block(block).terminator_span);
 
-                match (left_convert.as_slice(), right_convert.as_slice()) {
-                    ([left], [right]) => {
-                        self.build_bool_op(operation, left.clone(), right.clone());
-                    }
-                    _ => return Err(LangError::build(BuildErrorKind::InvalidStackType)),
-                }
-            },
-            NodeKind::ReturnStatement { kind: _ , value } => {
-                match value {
-                    Some(value) => {
-                        self.build_statement(value)?;
-                    }
-                    None => ()
+        Ok(())
+    }
+
+    fn build_instruction(&mut self, kind: &'b InstructionKind) -> Result<(), LangError> {
+        match kind {
+            InstructionKind::Assign { local, value } => {
+                if self.inlined[local.0 as usize] {
+                    self.deferred[local.0 as usize] = Some(value);
+                    return Ok(())
                 }
 
-                self.type_stack.pop();
+                let type_ = self.build_value(value)?;
+                self.adapt(&type_, &self.function.local(*local).type_);
 
-                self.instructions.push(Instruction::Return);
+                for id in self.local_ids[local.0 as usize].clone().iter().rev() {
+                    self.instructions.push(Instruction::LocalSet(*id));
+                }
             },
-            NodeKind::IfStatement { condition, body, else_ } => {
-                self.build_if_statement(condition, body, else_)?;
+            InstructionKind::Eval(value) => {
+                let type_ = self.build_value(value)?;
+
+                for _ in convert_type(&type_) {
+                    self.instructions.push(Instruction::Drop);
+                }
             },
-            NodeKind::ForStatement { iter_name, left, right, body } => {
-                self.build_statement(left)?;
+            InstructionKind::SetGlobal { global, value } => {
+                let (type_, kind) = self.module_builder.get_global(global)?;
 
-                let type_ = self.type_stack.pop().unwrap();
-                Self::assert_type(&type_, &TypeKind::Int)?;
+                match kind {
+                    GlobalKind::Data(offset) => {
+                        self.instructions.push(Instruction::I32Const(offset as i32));
+                        self.build_operand_as(value, &type_)?;
+                        self.build_mem_store(&type_, 0);
+                    },
+                    GlobalKind::Import(_) => return Err(LangError::build(BuildErrorKind::Unsupported(
+                        format!("Cannot assign to the imported variable `{}`", global.name)))),
+                }
+            },
+            InstructionKind::SetField { object, class_type, field, value } => {
+                match &class_type.kind {
+                    ClassKind::Normal => {
+                        let (field_type, offset) = Self::get_field_layout(class_type, field)?;
 
-                // Add "iter_name" to the locals
-                let ids = self.push_local(iter_name.clone(), TypeKind::Int);
+                        self.build_operand(object)?;
+                        self.build_operand_as(value, &field_type)?;
+                        self.build_mem_store(&field_type, offset);
+                    },
+                    ClassKind::Data => {
+                        let (field_type, ids) = self.get_data_field(object, class_type, field)?;
 
-                // Ids should always be 1 long
-                let id = ids.first().unwrap().clone();
+                        self.build_operand_as(value, &field_type)?;
 
-                // Setting "iter_name" to "left"
-                self.instructions.push(Instruction::LocalSet(id));
+                        for id in ids.iter().rev() {
+                            self.instructions.push(Instruction::LocalSet(*id));
+                        }
+                    },
+                    ClassKind::Extern => return Err(LangError::build(BuildErrorKind::UnexpectedError(
+                        format!("build_instruction(SetField): The extern class `{}` has no fields", class_type.name)))),
+                }
+            },
+            InstructionKind::SetElement { vector, index, value } => {
+                let element_type = self.build_element_address(vector, index)?;
 
-                // Open loop
-                self.instructions.push(Instruction::Loop(BlockType::Empty));
+                self.build_operand_as(value, &element_type)?;
+                self.build_mem_store(&element_type, 0);
+            },
+        }
 
-                // "iter_name" < "right"
-                self.instructions.push(Instruction::LocalGet(id));
-                self.build_statement(right)?;
-                self.instructions.push(Instruction::I32LtS);
+        Ok(())
+    }
 
-                let stack_size = self.type_stack.len();
+    pub(crate) fn build_terminator(&mut self, terminator: &'b Terminator) -> Result<(), LangError> {
+        match terminator {
+            Terminator::Return(value) => {
+                let ret = self.function.ret.clone();
 
-                // Open if
-                self.instructions.push(Instruction::If(BlockType::Empty));
+                self.build_operand_as(value, &ret)?;
+                self.instructions.push(Instruction::Return);
+            },
+            Terminator::Unreachable => self.instructions.push(Instruction::Unreachable),
+            // Built with the structure of the function
+            Terminator::Jump(_) |
+            Terminator::Branch { .. } => (),
+        }
 
-                // Building body
-                for node in body {
-                    self.build_statement(node)?;
-                }
+        Ok(())
+    }
 
-                // Add 1 to "iter_name"
-                self.instructions.push(Instruction::LocalGet(id));
-                self.instructions.push(Instruction::I32Const(1));
-                self.instructions.push(Instruction::I32Add);
-                self.instructions.push(Instruction::LocalSet(id));
+    /// Builds a value and returns its type
+    fn build_value(&mut self, value: &'b Value) -> Result<TypeKind, LangError> {
+        let type_ = match value {
+            Value::Use(operand) => self.build_operand(operand)?,
+            Value::Math { operation, left, right } => {
+                let left_type = self.operand_type(left);
+                let right_type = self.operand_type(right);
+
+                let type_ = match (&left_type, &right_type) {
+                    (TypeKind::Int, TypeKind::Int) => TypeKind::Int,
+                    (TypeKind::Int | TypeKind::Float, TypeKind::Int | TypeKind::Float) => TypeKind::Float,
+                    _ => return Err(LangError::build(BuildErrorKind::Unsupported(
+                        format!("Math operations between {:?} and {:?}", left_type, right_type)))),
+                };
 
-                // Goto block
-                self.instructions.push(Instruction::Br(1));
+                let val_type = Self::single_val_type(&type_)?;
 
-                // Close if
-                self.instructions.push(Instruction::End);
+                self.build_operand(left)?;
+                self.build_convert(Self::single_val_type(&left_type)?, val_type);
+                self.build_operand(right)?;
+                self.build_convert(Self::single_val_type(&right_type)?, val_type);
 
-                // Close loop
-                self.instructions.push(Instruction::End);
+                self.build_math_op(operation, val_type)?;
 
-                self.assert_stack_size(stack_size)?;
+                type_
             },
-            NodeKind::WhileStatement { condition, body} => {
+            Value::Compare { operation, left, right } => {
+                let left_type = Self::single_val_type(&self.operand_type(left))?;
+                let right_type = Self::single_val_type(&self.operand_type(right))?;
+
+                // Integers are compared as floats with floats
+                let val_type = match (left_type, right_type) {
+                    (ValType::F32, ValType::I32) | (ValType::I32, ValType::F32) => ValType::F32,
+                    _ => left_type,
+                };
 
-                // Open loop
-                self.instructions.push(Instruction::Loop(BlockType::Empty));
+                self.build_operand(left)?;
+                self.build_convert(left_type, val_type);
+                self.build_operand(right)?;
+                self.build_convert(right_type, val_type);
 
-                let condition_stack_size = self.type_stack.len();
+                self.build_bool_op(operation, val_type)?;
 
-                // Building condition
-                self.build_statement(condition)?;
+                TypeKind::Bool
+            },
+            Value::Call { function, args } => {
+                let (_, params, _) = self.module_builder.get_func(function.module, &function.name)?;
+                let params = params.clone();
+
+                for (i, arg) in args.iter().enumerate() {
+                    match params.get(i) {
+                        Some(param) if params.len() == args.len() => self.build_operand_as(arg, param)?,
+                        _ => self.build_operand(arg).map(|_| ())?,
+                    }
+                }
 
-                self.assert_stack_size(condition_stack_size + 1)?;
+                // The function is loaded by now, the index doesn't change
+                let (func_id, _, ret) = self.module_builder.get_func(function.module, &function.name)?;
+                let ret = ret.clone();
 
-                let stack_size = self.type_stack.len();
+                self.instructions.push(Instruction::Call(func_id));
 
-                // Open if
-                self.instructions.push(Instruction::If(BlockType::Empty));
+                ret
+            },
+            Value::CallVirtual { class_type, method, function, args } => {
+                let (_, params, _) = self.module_builder.get_func(function.module, &function.name)?;
+                let params = params.clone();
+
+                let slot = self.module_builder.program.classes
+                    .vtable(class_type)
+                    .slot(method)
+                    .ok_or(LangError::build(BuildErrorKind::UnexpectedError(
+                        format!("build_value(CallVirtual): No slot for the method `{}` of `{}`", method, class_type.name))))?;
+
+                // The object is kept to find the function once the arguments are pushed
+                let object = self.take_scratch(ValType::I32);
+
+                for (i, arg) in args.iter().enumerate() {
+                    match params.get(i) {
+                        Some(param) if params.len() == args.len() => self.build_operand_as(arg, param)?,
+                        _ => self.build_operand(arg).map(|_| ())?,
+                    }
 
-                // Building body
-                for node in body {
-                    self.build_statement(node)?;
+                    if i == 0 {
+                        self.instructions.push(Instruction::LocalTee(object));
+                    }
                 }
 
-                // Goto block
-                self.instructions.push(Instruction::Br(1));
-
-                // Close if
-                self.instructions.push(Instruction::End);
+                self.instructions.push(Instruction::LocalGet(object));
+                self.instructions.push(Instruction::I32Load(MemArg {
+                    offset: 0,
+                    align: 2,
+                    memory_index: 0,
+                }));
+                self.instructions.push(Instruction::I32Load(MemArg {
+                    offset: slot as u64 * 4,
+                    align: 2,
+                    memory_index: 0,
+                }));
+                self.release_scratch(object);
+
+                // Every function has its own type, the one of the method found statically has the same signature
+                let (func_id, _, ret) = self.module_builder.get_func(function.module, &function.name)?;
+                let ret = ret.clone();
+
+                self.instructions.push(Instruction::CallIndirect { ty: func_id, table: VTABLE_TABLE });
+
+                ret
+            },
+            Value::Global(global) => {
+                let (type_, kind) = self.module_builder.get_global(global)?;
 
-                self.assert_stack_size(stack_size)?;
+                match kind {
+                    GlobalKind::Data(offset) => {
+                        self.instructions.push(Instruction::I32Const(offset as i32));
+                        self.build_mem_load(&type_, 0);
+                    },
+                    GlobalKind::Import(id) => self.instructions.push(Instruction::GlobalGet(id)),
+                }
 
-                // Close loop
-                self.instructions.push(Instruction::End);
-            }
-            NodeKind::FieldAccess { variable, class_type, field_name } => {
+                type_
+            },
+            Value::Field { object, class_type, field } => {
                 match &class_type.kind {
                     ClassKind::Normal => {
-                        self.build_statement(variable)?;
-
-                        let class_type = match self.type_stack.pop().unwrap() {
-                            TypeKind::Class(obj) => obj,
-                            _ => return Err(LangError::build(
-                                BuildErrorKind::UnexpectedError(
-                                    format!("Expected object type, got {:?}", self.type_stack.pop().unwrap())))),
-                        };
-
-                        let mut field_type = TypeKind::Unknown;
-
-                        let mut offset = 0;
-                        for (name, type_) in class_type.fields.borrow().iter() {
-                            if field_name == name {
-                                field_type = type_.clone();
-                                break
-                            }
-                            offset += Self::get_type_byte_size(type_) as u64;
-                        }
+                        let (field_type, offset) = Self::get_field_layout(class_type, field)?;
 
-                        if matches!(field_type, TypeKind::Unknown) {
-                            return Err(LangError::build(BuildErrorKind::UnexpectedError(
-                                format!("Field {} not found in class {}", field_name, class_type.name))));
-                        }
+                        self.build_operand(object)?;
+                        self.build_mem_load(&field_type, offset);
 
-                        self.build_mem_load(&field_type, MemArg {
-                            offset,
-                            align: 0,
-                            memory_index: 0,
-                        });
-
-                        self.type_stack.push(field_type);
-                    }
+                        field_type
+                    },
                     ClassKind::Data => {
-                        match variable.kind.as_ref() {
-                            NodeKind::VariableRef { module: _, name } => {
-                                let (var_type, var_kind) = match self.get_var(name) {
-                                    Some(var) => var,
-                                    None => return Err(LangError::build(
-                                        BuildErrorKind::UnexpectedError("Variable not found".to_string()))),
-                                };
-
-                                match var_kind {
-                                    VarKind::Local(ids) => {
-                                        let mut field_type = TypeKind::Nothing;
-                                        let mut idx_offset = 0u32;
-
-                                        for (name, type_) in class_type.fields.borrow().iter() {
-                                            if name == field_name {
-                                                field_type = type_.clone();
-                                                break
-                                            }
-
-                                            idx_offset += convert_type(type_).len() as u32;
-                                        }
-
-                                        let start_id = ids.index(0) + idx_offset;
-                                        let idx_count = convert_type(&field_type).len() as u32;
-
-                                        for i in start_id..(start_id + idx_count) {
-                                            self.instructions.push(Instruction::LocalGet(i));
-                                        }
-
-                                        self.type_stack.push(field_type);
-                                    },
-                                    VarKind::Global(location) => {
-                                        self.instructions.push(Instruction::I32Const(location as i32));
-
-                                        let mut field_type = TypeKind::Nothing;
-                                        let mut offset = 0;
-
-                                        for (name, type_) in class_type.fields.borrow().iter() {
-                                            if name == field_name {
-                                                field_type = type_.clone();
-                                                break
-                                            }
-
-                                            offset += Self::get_type_byte_size(type_) as u64;
-                                        }
-
-                                        self.build_mem_load(&var_type, MemArg {
-                                            offset,
-                                            align: 0,
-                                            memory_index: 0
-                                        });
-
-                                        self.type_stack.push(field_type);
-                                    },
-                                }
-                            },
-                            _ => todo!(),
+                        let (field_type, ids) = self.get_data_field(object, class_type, field)?;
+
+                        for id in ids {
+                            self.instructions.push(Instruction::LocalGet(id));
                         }
-                    }
+
+                        field_type
+                    },
+                    ClassKind::Extern => return Err(LangError::build(BuildErrorKind::UnexpectedError(
+                        format!("build_value(Field): The extern class `{}` has no fields", class_type.name)))),
                 }
             },
-            NodeKind::FieldAsgn { variable, class_type, field_name, value } => {
-                // TODO: Future me please fix this shit, this is just a copy of the thing above
+            Value::Element { vector, index } => {
+                let element_type = self.build_element_address(vector, index)?;
+                self.build_mem_load(&element_type, 0);
 
+                element_type
+            },
+            Value::NewObject(class_type) => {
                 match &class_type.kind {
                     ClassKind::Normal => {
-                        self.build_statement(variable)?;
+                        let (_, layout) = class_layout(class_type);
+                        let vtable = self.module_builder.get_vtable(class_type)?;
 
-                        let class_type = match self.type_stack.pop().unwrap() {
-                            TypeKind::Class(obj) => obj,
-                            _ => return Err(LangError::build(BuildErrorKind::UnexpectedError("Expected object type".to_string()))),
-                        };
+                        self.build_memory_alloc(layout.size as i32)?;
 
-                        let mut field_type = TypeKind::Unknown;
+                        if let Some(vtable) = vtable {
+                            let id = self.take_scratch(ValType::I32);
 
-                        let mut offset = 0;
-                        for (name, type_) in class_type.fields.borrow().iter() {
-                            if field_name == name {
-                                field_type = type_.clone();
-                                break
-                            }
-                            offset += Self::get_type_byte_size(type_) as u64;
-                        }
-
-                        self.build_statement(value)?;
-
-                        if field_type != self.type_stack.pop().unwrap() {
-                            return Err(LangError::build(BuildErrorKind::InvalidStackType));
+                            self.instructions.push(Instruction::LocalTee(id));
+                            self.instructions.push(Instruction::I32Const(vtable as i32));
+                            self.instructions.push(Instruction::I32Store(MemArg {
+                                offset: 0,
+                                align: 2,
+                                memory_index: 0,
+                            }));
+                            self.instructions.push(Instruction::LocalGet(id));
+                            self.release_scratch(id);
                         }
-
-                        self.build_mem_store(&field_type, MemArg {
-                            offset,
-                            align: 0,
-                            memory_index: 0,
-                        });
                     },
                     ClassKind::Data => {
-                        match variable.kind.as_ref() {
-                            NodeKind::VariableRef { module: _, name } => {
-                                let (var_type, var_kind) = match self.get_var(name) {
-                                    Some(var) => var,
-                                    None => return Err(LangError::build(
-                                        BuildErrorKind::UnexpectedError("build_statement(FieldAsgn, Data): Variable not found".to_string()))),
-                                };
-
-                                match var_kind {
-                                    VarKind::Local(ids) => {
-                                        let mut field_type = TypeKind::Nothing;
-                                        let mut idx_offset = 0u32;
-
-                                        for (name, type_) in class_type.fields.borrow().iter() {
-                                            if name == field_name {
-                                                field_type = type_.clone();
-                                                break
-                                            }
-
-                                            idx_offset += convert_type(type_).len() as u32;
-                                        }
-
-                                        let start_id = ids.index(0) + idx_offset;
-                                        let idx_count = convert_type(&field_type).len() as u32;
-
-                                        self.build_statement(value)?;
-
-                                        for i in start_id..(start_id + idx_count) {
-                                            self.instructions.push(Instruction::LocalSet(i));
-                                        }
-
-                                        self.type_stack.push(field_type);
-                                    },
-                                    VarKind::Global(location) => {
-                                        self.instructions.push(Instruction::I32Const(location as i32));
-
-                                        let mut field_type = TypeKind::Nothing;
-                                        let mut offset = 0;
-
-                                        for (name, type_) in class_type.fields.borrow().iter() {
-                                            if name == field_name {
-                                                field_type = type_.clone();
-                                                break
-                                            }
-
-                                            offset += Self::get_type_byte_size(type_) as u64;
-                                        }
-
-                                        self.build_statement(value)?;
-
-                                        self.build_mem_store(&var_type, MemArg {
-                                            offset,
-                                            align: 0,
-                                            memory_index: 0
-                                        });
-
-                                        self.type_stack.push(field_type);
-                                    },
-                                }
-                            },
-                            _ => todo!(),
+                        for type_ in convert_class(class_type) {
+                            self.build_default_value(type_);
                         }
                     },
+                    ClassKind::Extern => return Err(LangError::build(BuildErrorKind::UnexpectedError(
+                        format!("build_value(NewObject): The extern class `{}` can't be constructed", class_type.name)))),
                 }
-            },
-            NodeKind::VectorLiteral { values } => {
-                // TODO: Make the vector take other types (for now only ints)
 
-                self.build_memory_alloc(values.len() as i32 * 4)?;
+                TypeKind::Class(class_type.clone())
+            },
+            Value::Vector { element, values } => {
+                let stride = type_layout(element).size;
+                self.build_memory_alloc((VECTOR_HEADER_SIZE + values.len() as u64 * stride) as i32)?;
 
-                // TODO: Support multiple allocations in the same method
-                let ids = self.push_local(INTERNAL_ALLOC_LOCATION.to_string(), TypeKind::Int);
-                let id = *ids.index(0);
+                let id = self.take_scratch(ValType::I32);
 
+                // The length is stored right before the elements, so the host can read the vector
                 self.instructions.push(Instruction::LocalTee(id));
-                
-                let mut type_ = None;
+                self.instructions.push(Instruction::I32Const(values.len() as i32));
+                self.instructions.push(Instruction::I32Store(MemArg {
+                    offset: VECTOR_HEADER_SIZE - 4,
+                    align: 2,
+                    memory_index: 0,
+                }));
 
-                for (offset, val) in values.iter().enumerate() {
-                    self.instructions.push(Instruction::LocalGet(id));
+                self.instructions.push(Instruction::LocalGet(id));
+                self.instructions.push(Instruction::I32Const(VECTOR_HEADER_SIZE as i32));
+                self.instructions.push(Instruction::I32Add);
+                self.instructions.push(Instruction::LocalSet(id));
 
-                    self.build_statement(val)?;
-                    
-                    let val_type = match self.type_stack.pop() {
-                        Some(t) => t,
-                        None => panic!(), // FIXME: Remove panic
-                    };
-                    
-                    match type_ {
-                        Some(t) if t != val_type => return Err(LangError::build(BuildErrorKind::InvalidStackType)),
-                        None => type_ = Some(val_type.clone()),
-                        _ => (),
-                    };
-
-                    self.build_mem_store(&val_type, MemArg {
-                        offset: offset as u64 * 4,
-                        align: 0,
-                        memory_index: 0
-                    });
+                for (i, value) in values.iter().enumerate() {
+                    self.instructions.push(Instruction::LocalGet(id));
+                    self.build_operand_as(value, element)?;
+                    self.build_mem_store(element, i as u64 * stride);
                 }
 
-                self.type_stack.push(TypeKind::Vector(Box::new(type_.unwrap_or(TypeKind::Nothing))));
+                self.instructions.push(Instruction::LocalGet(id));
+                self.release_scratch(id);
+
+                TypeKind::Vector(Box::new(element.clone()))
             },
-            NodeKind::ObjectLiteral { .. } => todo!(),
-            NodeKind::FunctionLiteral { .. } => todo!(),
-            NodeKind::ValueFieldAccess { variable, value } => {
-                // Vector position
-                self.build_statement(variable)?;
-                let var_type = self.type_stack.pop().unwrap();
-                
-                // Access index
-                self.build_statement(value)?;
-                
-                // Check index is int
-                match self.type_stack.pop() {
-                    Some(TypeKind::Int) => (),
-                    _ => return Err(LangError::build(BuildErrorKind::InvalidStackType)),
-                } 
-                
-                // Multiply index by 4
-                self.instructions.push(Instruction::I32Const(4));
-                self.instructions.push(Instruction::I32Mul);
-                
-                self.instructions.push(Instruction::I32Add); 
-
-                if let TypeKind::Vector(type_) = var_type {
-                    self.build_mem_load(&type_, MemArg {
-                        align: 0,
-                        offset: 0,
-                        memory_index: 0,
-                    });
-                    
-                    self.type_stack.push((*type_).clone());
+            Value::Variant { enum_type, variant, value } => {
+                self.instructions.push(Instruction::I32Const(*variant as i32));
+
+                let payload = convert_type(&self.build_operand(value)?);
+
+                // The values of the variants share the same i32 locals
+                for (i, val_type) in payload.iter().enumerate().rev() {
+                    match val_type {
+                        ValType::I32 => (),
+                        ValType::F32 if i == payload.len() - 1 => self.instructions.push(Instruction::I32ReinterpretF32),
+                        _ => return Err(LangError::build(BuildErrorKind::Unsupported(
+                            format!("The variant of `{}` can't hold a {:?} value", enum_type.name, val_type)))),
+                    }
+                }
+
+                let enum_size = convert_enum(enum_type).len();
+                for _ in (payload.len() + 1)..enum_size {
+                    self.instructions.push(Instruction::I32Const(0));
                 }
+
+                TypeKind::Enum(enum_type.clone())
             },
-            NodeKind::ValueFieldAssign { variable, offset, asgn_value } => {
-                // TODO: This is the same as above, with some changes
-
-                // Vector position
-                self.build_statement(variable)?;
-                let var_type = self.type_stack.pop().unwrap();
-                
-                // Access index
-                self.build_statement(offset)?;
-                
-                // Check index is int
-                match self.type_stack.pop() {
-                    Some(TypeKind::Int) => (),
-                    _ => return Err(LangError::build(BuildErrorKind::InvalidStackType)),
-                } 
-                
-                // Multiply index by 4
-                self.instructions.push(Instruction::I32Const(4));
-                self.instructions.push(Instruction::I32Mul);
-                
-                self.instructions.push(Instruction::I32Add); 
-                
-                self.build_statement(asgn_value)?;
-                self.type_stack.pop();
-
-                if let TypeKind::Vector(type_) = var_type {
-                    self.build_mem_store(&type_, MemArg {
-                        align: 0,
-                        offset: 0,
-                        memory_index: 0,
-                    });
-                    
-                    self.type_stack.push(TypeKind::Nothing);
+            Value::Tuple(values) => {
+                let mut types = Vec::new();
+
+                for value in values {
+                    types.push(self.build_operand(value)?);
                 }
+
+                TypeKind::Tuple(types)
             },
-            NodeKind::ConstructClass { parameters, class_type } => {
-                match &class_type.kind {
-                    ClassKind::Normal => {
-                        let size = class_type.fields
-                            .borrow()
-                            .iter()
-                            .map(|(_, type_)| Self::get_type_byte_size(type_) as i32) .sum();
+            Value::TupleElement { tuple, index } => {
+                let (element_type, ids) = self.get_tuple_element(tuple, *index)?;
 
-                        self.build_memory_alloc(size)?;
+                for id in ids {
+                    self.instructions.push(Instruction::LocalGet(id));
+                }
 
-                        if let Some((_, _)) = class_type.methods.borrow().iter().find(|(n, _)| n == CLASS_CONSTRUCTOR_NAME) {
-                            // TODO: Support multiple allocations in the same method
-                            let ids = self.push_local(INTERNAL_ALLOC_LOCATION.to_string(), TypeKind::Int);
-                            let id = *ids.index(0);
+                element_type
+            },
+            Value::Discriminant(value) => {
+                let ids = self.get_local_ids(value)?;
+                self.instructions.push(Instruction::LocalGet(ids[0]));
 
-                            self.instructions.push(Instruction::LocalTee(id));
+                TypeKind::Int
+            },
+            Value::Payload { value, variant } => {
+                let variant_type = match self.operand_type(value) {
+                    TypeKind::Enum(enum_type) => enum_type.variants
+                        .borrow()
+                        .get(*variant as usize)
+                        .map(|(_, type_)| type_.as_ref().clone()),
+                    _ => None,
+                };
 
-                            for param in parameters {
-                                self.build_statement(param)?;
-                            }
+                let variant_type = variant_type.ok_or(LangError::build(BuildErrorKind::UnexpectedError(
+                    "build_value(Payload): Variant not found".to_string())))?;
 
-                            let (constructor_id, _, _) = self.module_builder.get_method(
-                                class_type.module,
-                                &class_type.name,
-                                &CLASS_CONSTRUCTOR_NAME.to_string())?;
+                let ids = self.get_local_ids(value)?;
+                for (id, val_type) in ids.iter().skip(1).zip(convert_type(&variant_type)) {
+                    self.instructions.push(Instruction::LocalGet(*id));
 
-                            self.instructions.push(Instruction::Call(constructor_id));
+                    if let ValType::F32 = val_type {
+                        self.instructions.push(Instruction::F32ReinterpretI32);
+                    }
+                }
 
-                            self.instructions.push(Instruction::LocalGet(id));
-                        }
+                variant_type
+            },
+        };
 
-                        self.type_stack.push(TypeKind::Class(class_type.clone()));
-                    },
-                    ClassKind::Data => {
-                        for type_ in convert_class(class_type) {
-                            self.build_default_value(type_);
-                        }
+        Ok(type_)
+    }
 
-                        self.type_stack.push(TypeKind::Class(class_type.clone()));
+    pub(crate) fn build_operand(&mut self, operand: &Operand) -> Result<TypeKind, LangError> {
+        match operand {
+            Operand::Const(literal) => {
+                match literal {
+                    LiteralKind::Nothing => (),
+                    LiteralKind::Int(i) => self.instructions.push(Instruction::I32Const(*i)),
+                    LiteralKind::Float(f) => self.instructions.push(Instruction::F32Const(*f)),
+                    LiteralKind::Bool(b) => self.instructions.push(Instruction::I32Const(if *b { 1 } else { 0 })),
+                    LiteralKind::String(string) => {
+                        let offset = self.module_builder.push_string(string);
+                        self.instructions.push(Instruction::I32Const(offset as i32));
                     },
                 }
-            },
-            NodeKind::ConstructEnumVariant { value, variant_type, enum_type, variant_id } => {
-                self.instructions.push(Instruction::I32Const(*variant_id as i32));
 
-                self.build_statement(value)?;
-
-                let type_ = self.type_stack.pop().unwrap();
-                if !type_.is_compatible(variant_type) {
-                    return Err(LangError::build(BuildErrorKind::InvalidStackType));
+                Ok(literal.into())
+            },
+            Operand::Local(local) => {
+                if let Some(value) = self.deferred[local.0 as usize].take() {
+                    return self.build_value(value)
                 }
 
-                let enum_size = convert_enum(enum_type).len() as i32;
-                let variant_size = convert_type(variant_type).len() as i32;
-                for _ in 0..(enum_size - variant_size -  1) {
-                    self.instructions.push(Instruction::I32Const(0));
+                for id in self.local_ids[local.0 as usize].clone() {
+                    self.instructions.push(Instruction::LocalGet(id));
                 }
 
-                self.type_stack.push(TypeKind::Enum(enum_type.clone()));
+                Ok(self.function.local(*local).type_.clone())
             },
         }
-        Ok(())
     }
 
-    fn build_if_statement(&mut self, condition: &ASTNode, body: &ASTBody, else_: &ElseType) -> Result<(), LangError> {
-        self.build_statement(condition)?;
+    fn build_operand_as(&mut self, operand: &Operand, expected: &TypeKind) -> Result<(), LangError> {
+        let type_ = self.build_operand(operand)?;
+        self.adapt(&type_, expected);
 
-        self.type_stack.pop();
+        Ok(())
+    }
 
-        let stack_size = self.type_stack.len();
+    /// The results built by `Ok` and `Err` only know one side of their type, the values
+    /// of the other variant are added or removed at the end
+    fn adapt(&mut self, type_: &TypeKind, expected: &TypeKind) {
+        if let (TypeKind::Enum(enum_type), TypeKind::Enum(expected)) = (type_, expected) {
+            let size = convert_enum(enum_type).len();
+            let expected_size = convert_enum(expected).len();
 
-        self.instructions.push(Instruction::If(BlockType::Empty));
+            for _ in size..expected_size {
+                self.instructions.push(Instruction::I32Const(0));
+            }
 
-        for node in body {
-            self.build_statement(node)?;
+            for _ in expected_size..size {
+                self.instructions.push(Instruction::Drop);
+            }
         }
+    }
 
-        match else_ {
-            ElseType::None => (),
-            ElseType::ElseIf { condition, body, else_ } => {
-                self.instructions.push(Instruction::Else);
-                self.build_if_statement(condition, body, else_)?;
-            },
-            ElseType::Else { body } => {
-                self.instructions.push(Instruction::Else);
+    fn operand_type(&self, operand: &Operand) -> TypeKind {
+        self.function.operand_type(operand)
+    }
 
-                for node in body {
-                    self.build_statement(node)?;
-                }
-            },
+    fn single_val_type(type_: &TypeKind) -> Result<ValType, LangError> {
+        match convert_type(type_).as_slice() {
+            [val_type] => Ok(*val_type),
+            _ => Err(LangError::build(BuildErrorKind::InvalidStackType)),
         }
+    }
 
-        self.assert_stack_size(stack_size)?;
-
-        self.instructions.push(Instruction::End);
+    /// The enum values and the objects of data classes are read from their locals
+    fn get_local_ids(&self, operand: &Operand) -> Result<Vec<u32>, LangError> {
+        match operand {
+            Operand::Local(local) if !self.inlined[local.0 as usize] => Ok(self.local_ids[local.0 as usize].clone()),
+            _ => Err(LangError::build(BuildErrorKind::UnexpectedError(
+                "get_local_ids: The value is not in a local".to_string()))),
+        }
+    }
 
-        return Ok(())
+    fn get_field_layout(class_type: &ClassType, field: &String) -> Result<(TypeKind, u64), LangError> {
+        field_layout(class_type, field).ok_or(LangError::build(BuildErrorKind::UnexpectedError(
+            format!("Field {} not found in class {}", field, class_type.name))))
     }
 
-    fn string_to_bytes(string: String) -> Vec<u8> {
-        let string_len = string.len() as u32;
+    /// The type of a field of a data class object and the locals holding it
+    fn get_data_field(&self, object: &Operand, class_type: &ClassType, field: &String) -> Result<(TypeKind, Vec<u32>), LangError> {
+        let ids = self.get_local_ids(object)?;
+        let mut start = 0;
 
-        let mut data = string_len.to_le_bytes().to_vec();
-        data.append(&mut string.clone().into_bytes());
+        for (name, type_) in class_type.fields.borrow().iter() {
+            let len = convert_type(type_).len();
 
-        data
-    }
+            if name == field {
+                return Ok((type_.clone(), ids[start..start + len].to_vec()))
+            }
 
-    fn get_var(&self, name: &String) -> Option<(TypeKind, VarKind)> {
-        let id = self.local_names
-            .iter()
-            .position(|n| n == name);
+            start += len;
+        }
 
-        if let Some(id) = id {
-            return Some((
-                self.locals.index(id).clone(),
-                VarKind::Local(self.local_ids.index(id).clone()),
-            ))
+        Err(LangError::build(BuildErrorKind::UnexpectedError(
+            format!("Field {} not found in class {}", field, class_type.name))))
+    }
+
+    /// The type of an element of a tuple value and the locals holding it
+    fn get_tuple_element(&self, tuple: &Operand, index: u32) -> Result<(TypeKind, Vec<u32>), LangError> {
+        let types = match self.operand_type(tuple) {
+            TypeKind::Tuple(types) => types,
+            _ => return Err(LangError::build(BuildErrorKind::InvalidStackType)),
         };
 
-        let id = self.module_builder.global_names
-            .iter()
-            .position(|n| n == name);
+        let element_type = types.get(index as usize).cloned().ok_or(LangError::build(BuildErrorKind::UnexpectedError(
+            format!("get_tuple_element: The tuple has no element {}", index))))?;
 
-        match id {
-            Some(id) => {
-                let (type_, offset) = self.module_builder.globals.index(id);
+        let ids = self.get_local_ids(tuple)?;
+        let start = convert_types(&types[..index as usize].to_vec()).len();
+        let len = convert_type(&element_type).len();
 
-                Some((
-                    type_.clone(),
-                    VarKind::Global(*offset),
-                ))
-            },
-            None => None,
-        }
+        Ok((element_type, ids[start..start + len].to_vec()))
     }
 
-    fn get_type_byte_size(type_: &TypeKind) -> usize {
-        match type_ {
-            TypeKind::Int => 4,
-            TypeKind::Float => 4,
-            TypeKind::String => 4,
-            TypeKind::Bool => 4,
-            TypeKind::Vector(_) => 4,
-            TypeKind::Class(type_) => convert_class(type_).len() * 4,
-            TypeKind::Nothing => 0,
-            TypeKind::Unknown => 0,
-            TypeKind::Function(_) => 4,
-            TypeKind::Enum(type_) => convert_enum(type_).len() * 4,
-        }
-    }
+    /// Pushes the address of an element of a vector and returns the type of the element
+    fn build_element_address(&mut self, vector: &Operand, index: &Operand) -> Result<TypeKind, LangError> {
+        let element_type = match self.build_operand(vector)? {
+            TypeKind::Vector(type_) => type_.as_ref().clone(),
+            _ => return Err(LangError::build(BuildErrorKind::InvalidStackType)),
+        };
 
-    #[inline]
-    fn assert_type(a: &TypeKind, b: &TypeKind) -> Result<(), LangError> {
-        if a == b {
-            Ok(())
-        } else {
-            Err(LangError::build(BuildErrorKind::InvalidStackType))
+        match self.build_operand(index)? {
+            TypeKind::Int => (),
+            _ => return Err(LangError::build(BuildErrorKind::InvalidStackType)),
         }
-    }
 
-    #[inline]
-    fn assert_stack_size(&self, size: usize) -> Result<(), LangError> {
-        if self.type_stack.len() >= size {
-            Ok(())
-        } else {
-            Err(LangError::build(BuildErrorKind::InvalidStackSize(size, self.type_stack.len())))
-        }
+        // Multiply index by the element size
+        self.instructions.push(Instruction::I32Const(type_layout(&element_type).size as i32));
+        self.instructions.push(Instruction::I32Mul);
+        self.instructions.push(Instruction::I32Add);
+
+        Ok(element_type)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,108 @@
+use wasm_encoder::{BlockType, Instruction};
+use common::errors::{BuildErrorKind, LangError};
+use ir::flow::ControlFlow;
+use ir::function::{BlockId, Terminator};
+use crate::build_code::FunctionBuilder;
+
+/// The wasm constructs enclosing the code being built, the innermost is the last one
+pub(crate) enum Frame {
+    If,
+    /// A branch to the loop goes back to its header
+    Loop(BlockId),
+    /// A branch to the block goes to the code right after it, which starts with the given block
+    Block(BlockId),
+}
+
+/// The control flow graph is turned back into structured code following "Beyond Relooper" by Norman Ramsey.
+/// Every block is placed inside the code of its immediate dominator: the blocks reached from a single place
+/// follow the branch that leads to them, the others follow a wasm block that is exited to reach them
+impl<'a, 'b> FunctionBuilder<'a, 'b> {
+    pub(crate) fn build_tree(&mut self, flow: &ControlFlow, block: BlockId, context: &mut Vec<Frame>) -> Result<(), LangError> {
+        // The blocks placed after this one, the last in the order is the outermost
+        let mut merges: Vec<BlockId> = flow.dominated(block)
+            .iter()
+            .filter(|dominated| flow.is_merge(**dominated))
+            .cloned()
+            .collect();
+
+        merges.sort_by_key(|merge| std::cmp::Reverse(flow.position(*merge)));
+
+        if flow.is_loop_header(block) {
+            self.instructions.push(Instruction::Loop(BlockType::Empty));
+            context.push(Frame::Loop(block));
+
+            self.build_within(flow, block, &merges, context)?;
+
+            context.pop();
+            self.instructions.push(Instruction::End);
+        } else {
+            self.build_within(flow, block, &merges, context)?;
+        }
+
+        Ok(())
+    }
+
+    fn build_within(&mut self, flow: &ControlFlow, block: BlockId, merges: &[BlockId], context: &mut Vec<Frame>) -> Result<(), LangError> {
+        match merges.split_first() {
+            Some((merge, inner)) => {
+                self.instructions.push(Instruction::Block(BlockType::Empty));
+                context.push(Frame::Block(*merge));
+
+                self.build_within(flow, block, inner, context)?;
+
+                context.pop();
+                self.instructions.push(Instruction::End);
+
+                self.build_tree(flow, *merge, context)
+            },
+            None => {
+                self.build_block(block)?;
+
+                let function = self.function;
+
+                match &function.block(block).terminator {
+                    Terminator::Jump(target) => self.build_branch(flow, block, *target, context),
+                    Terminator::Branch { condition, then, else_ } => {
+                        self.build_operand(condition)?;
+
+                        self.instructions.push(Instruction::If(BlockType::Empty));
+                        context.push(Frame::If);
+
+                        self.build_branch(flow, block, *then, context)?;
+                        self.instructions.push(Instruction::Else);
+                        self.build_branch(flow, block, *else_, context)?;
+
+                        context.pop();
+                        self.instructions.push(Instruction::End);
+
+                        Ok(())
+                    },
+                    terminator => self.build_terminator(terminator),
+                }
+            },
+        }
+    }
+
+    fn build_branch(&mut self, flow: &ControlFlow, source: BlockId, target: BlockId, context: &mut Vec<Frame>) -> Result<(), LangError> {
+        if flow.is_backward(source, target) || flow.is_merge(target) {
+            let depth = context
+                .iter()
+                .rev()
+                .position(|frame| match frame {
+                    Frame::Loop(header) => *header == target && flow.is_backward(source, target),
+                    Frame::Block(merge) => *merge == target && !flow.is_backward(source, target),
+                    Frame::If => false,
+                });
+
+            match depth {
+                Some(depth) => self.instructions.push(Instruction::Br(depth as u32)),
+                None => return Err(LangError::build(BuildErrorKind::UnexpectedError(
+                    "build_branch: The target of the branch is not enclosing it".to_string()))),
+            }
+
+            Ok(())
+        } else {
+            self.build_tree(flow, target, context)
+        }
+    }
+}
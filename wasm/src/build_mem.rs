@@ -1,16 +1,170 @@
-use wasm_encoder::{Instruction, MemArg, ValType};
-use common::ast::types::TypeKind;
+use wasm_encoder::{BlockType, Instruction, MemArg, ValType};
+use common::ast::types::{ClassKind, ClassType, TypeKind};
 use common::constants::{CORE_MODULE_ID, INTERNAL_MEMORY_ALLOC};
 use common::errors::LangError;
 use common::module::ModuleUID;
 use crate::build::convert_type;
 use crate::build_code::FunctionBuilder;
 
+/// The largest natural alignment of a scalar value, every allocated block and the heap start are aligned to it
+pub(crate) const MAX_ALIGNMENT: u64 = 8;
+
+/// Vectors point to their first element, the header before it holds the length as an `i32` in its last 4 bytes.
+/// Its size keeps the elements aligned to `MAX_ALIGNMENT`
+pub(crate) const VECTOR_HEADER_SIZE: u64 = MAX_ALIGNMENT;
+
+/// Objects of extern classes can't be placed in the memory, they are appended to this table and the memory
+/// holds their index. The first element is always null so that a zeroed memory reads as null.
+/// A slot belongs to the memory location holding its index: copying the value appends another slot and
+/// overwriting it reuses the slot. The memory is never freed, so neither are the slots
+pub(crate) const EXTERNREF_TABLE: u32 = 0;
+
+/// Objects of normal classes start with the address of the vtable of their class, the header is not written if it has no slot.
+/// The fields inherited from a parent follow at the same offsets as in the parent
+pub(crate) const CLASS_HEADER_SIZE: u64 = 4;
+
+/// The functions of the vtables, they are called by their index in this table
+pub(crate) const VTABLE_TABLE: u32 = 1;
+
+/// Placement of a type in memory, every wasm value is stored at its natural alignment
+pub(crate) struct TypeLayout {
+    /// The offset of each value returned by `convert_type`
+    pub offsets: Vec<u64>,
+    /// The size is padded to a multiple of the alignment so the type can be repeated in a vector
+    pub size: u64,
+    pub align: u64,
+}
+
+pub(crate) fn align_to(value: u64, align: u64) -> u64 {
+    value.div_ceil(align) * align
+}
+
+/// The size of a wasm value, which is also its natural alignment
+pub(crate) fn val_type_size(type_: ValType) -> u64 {
+    match type_ {
+        ValType::I32 | ValType::F32 => 4,
+        ValType::I64 | ValType::F64 => 8,
+        ValType::V128 => 16,
+        ValType::FuncRef | ValType::ExternRef => 4,
+    }
+}
+
+/// The alignment hint of a `MemArg` is the base 2 logarithm of the alignment
+fn val_type_align_hint(type_: ValType) -> u32 {
+    val_type_size(type_).trailing_zeros()
+}
+
+pub(crate) fn type_layout(type_: &TypeKind) -> TypeLayout {
+    match type_ {
+        TypeKind::Class(class_type) if matches!(class_type.kind, ClassKind::Data) => {
+            let (_, layout) = class_layout(class_type);
+            layout
+        },
+        _ => values_layout(&convert_type(type_)),
+    }
+}
+
+/// Lays out the wasm values one after the other, each one at its natural alignment
+fn values_layout(val_types: &[ValType]) -> TypeLayout {
+    let mut offsets = Vec::new();
+    let mut size = 0;
+    let mut align = 1;
+
+    for val_type in val_types {
+        let val_size = val_type_size(*val_type);
+
+        size = align_to(size, val_size);
+        offsets.push(size);
+        size += val_size;
+        align = align.max(val_size);
+    }
+
+    TypeLayout { offsets, size: align_to(size, align), align }
+}
+
+/// The fields of a class in memory, the fields of a normal class include the inherited ones
+fn class_fields(class_type: &ClassType) -> Vec<(String, TypeKind)> {
+    match class_type.kind {
+        ClassKind::Normal => class_type.all_fields(),
+        _ => class_type.fields.borrow().clone(),
+    }
+}
+
+/// Lays out the fields of a class one after the other like a C struct, returns the offset of each field
+pub(crate) fn class_layout(class_type: &ClassType) -> (Vec<u64>, TypeLayout) {
+    let mut field_offsets = Vec::new();
+    let mut offsets = Vec::new();
+    let mut size = match class_type.kind {
+        ClassKind::Normal => CLASS_HEADER_SIZE,
+        _ => 0,
+    };
+    let mut align = 1;
+
+    for (_, type_) in class_fields(class_type).iter() {
+        let field_layout = type_layout(type_);
+
+        size = align_to(size, field_layout.align);
+        field_offsets.push(size);
+        offsets.extend(field_layout.offsets.iter().map(|offset| size + offset));
+        size += field_layout.size;
+        align = align.max(field_layout.align);
+    }
+
+    (field_offsets, TypeLayout { offsets, size: align_to(size, align), align })
+}
+
+/// The type and offset of a field inside a class allocated in memory
+pub(crate) fn field_layout(class_type: &ClassType, field_name: &String) -> Option<(TypeKind, u64)> {
+    let (field_offsets, _) = class_layout(class_type);
+
+    class_fields(class_type)
+        .into_iter()
+        .zip(field_offsets)
+        .find(|((name, _), _)| name == field_name)
+        .map(|((_, type_), offset)| (type_, offset))
+}
+
 impl<'a, 'b> FunctionBuilder<'a, 'b> {
 
-    pub(crate) fn build_mem_load(&mut self, type_: &TypeKind, mem_arg: MemArg) {
-        for type_ in convert_type(type_) {
-            self.instructions.push(Self::convert_load_type(type_, mem_arg));
+    /// Loads a value of type `type_` located at `offset` from the address on the stack
+    pub(crate) fn build_mem_load(&mut self, type_: &TypeKind, offset: u64) {
+        let layout = type_layout(type_);
+        let val_types = convert_type(type_);
+
+        // Every value of a data class is loaded from the same address
+        let address = match val_types.len() {
+            0 | 1 => None,
+            _ => {
+                let address = self.take_scratch(ValType::I32);
+                self.instructions.push(Instruction::LocalSet(address));
+
+                Some(address)
+            },
+        };
+
+        for (type_, value_offset) in val_types.into_iter().zip(layout.offsets) {
+            if let Some(address) = address {
+                self.instructions.push(Instruction::LocalGet(address));
+            }
+
+            self.instructions.push(Self::convert_load_type(type_, Self::mem_arg(type_, offset + value_offset)));
+
+            if let ValType::ExternRef = type_ {
+                self.module_builder.externref_table = true;
+                self.instructions.push(Instruction::TableGet { table: EXTERNREF_TABLE });
+            }
+        }
+
+        if let Some(address) = address {
+            self.release_scratch(address);
+        }
+    }
+
+    fn mem_arg(type_: ValType, offset: u64) -> MemArg {
+        MemArg {
+            offset,
+            align: val_type_align_hint(type_),
+            memory_index: 0,
         }
     }
 
@@ -22,16 +176,80 @@ impl<'a, 'b> FunctionBuilder<'a, 'b> {
             ValType::F64 => Instruction::F64Load(mem_arg),
             ValType::V128 => Instruction::V128Load { memarg: mem_arg },
             ValType::FuncRef => Instruction::I32Load(mem_arg), // TODO: Is this right?
+            // The index in `EXTERNREF_TABLE`
             ValType::ExternRef => Instruction::I32Load(mem_arg),
         }
     }
 
-    pub(crate) fn build_mem_store(&mut self, type_: &TypeKind, mem_arg: MemArg) {
-        for type_ in convert_type(type_) {
-            self.instructions.push(Self::convert_store_type(type_, mem_arg));
+    /// Stores a value of type `type_` at `offset` from the address on the stack, the value is above it
+    pub(crate) fn build_mem_store(&mut self, type_: &TypeKind, offset: u64) {
+        let layout = type_layout(type_);
+        let val_types = convert_type(type_);
+
+        // The values of a data class are moved to locals, so that each one is stored after the address.
+        // An extern object needs the address twice, to read the index it replaces
+        let scratch = match val_types.len() {
+            0 => None,
+            1 if val_types[0] != ValType::ExternRef => None,
+            _ => {
+                let values: Vec<u32> = val_types.iter().map(|type_| self.take_scratch(*type_)).collect();
+                let address = self.take_scratch(ValType::I32);
+
+                for value in values.iter().rev() {
+                    self.instructions.push(Instruction::LocalSet(*value));
+                }
+                self.instructions.push(Instruction::LocalSet(address));
+
+                Some((address, values))
+            },
+        };
+
+        for (i, (type_, value_offset)) in val_types.into_iter().zip(layout.offsets).enumerate() {
+            if let Some((address, values)) = &scratch {
+                self.instructions.push(Instruction::LocalGet(*address));
+
+                match type_ {
+                    ValType::ExternRef => self.build_externref_slot(*address, values[i], offset + value_offset),
+                    _ => self.instructions.push(Instruction::LocalGet(values[i])),
+                }
+            }
+
+            self.instructions.push(Self::convert_store_type(type_, Self::mem_arg(type_, offset + value_offset)));
+        }
+
+        if let Some((address, values)) = scratch {
+            self.release_scratch(address);
+
+            for value in values {
+                self.release_scratch(value);
+            }
         }
     }
 
+    /// Pushes the index of the slot holding the extern object `value` stored at `address + offset`,
+    /// the slot of the replaced object is reused, otherwise the object is appended to `EXTERNREF_TABLE`
+    fn build_externref_slot(&mut self, address: u32, value: u32, offset: u64) {
+        self.module_builder.externref_table = true;
+
+        let index = self.take_scratch(ValType::I32);
+
+        self.instructions.push(Instruction::LocalGet(address));
+        self.instructions.push(Instruction::I32Load(Self::mem_arg(ValType::ExternRef, offset)));
+        self.instructions.push(Instruction::LocalTee(index));
+        self.instructions.push(Instruction::If(BlockType::Result(ValType::I32)));
+        self.instructions.push(Instruction::LocalGet(index));
+        self.instructions.push(Instruction::LocalGet(value));
+        self.instructions.push(Instruction::TableSet { table: EXTERNREF_TABLE });
+        self.instructions.push(Instruction::LocalGet(index));
+        self.instructions.push(Instruction::Else);
+        self.instructions.push(Instruction::LocalGet(value));
+        self.instructions.push(Instruction::I32Const(1));
+        self.instructions.push(Instruction::TableGrow { table: EXTERNREF_TABLE });
+        self.instructions.push(Instruction::End);
+
+        self.release_scratch(index);
+    }
+
     pub fn build_default_value(&mut self, type_: ValType) {
         let inst = match type_ {
             ValType::I32 => Instruction::I32Const(0),
@@ -40,7 +258,7 @@ impl<'a, 'b> FunctionBuilder<'a, 'b> {
             ValType::F64 => Instruction::F64Const(0f64),
             ValType::V128 => Instruction::V128Const(0i128),
             ValType::FuncRef => Instruction::RefFunc(0),
-            ValType::ExternRef => todo!(),
+            ValType::ExternRef => Instruction::RefNull(ValType::ExternRef),
         };
 
         self.instructions.push(inst);
@@ -54,10 +272,12 @@ impl<'a, 'b> FunctionBuilder<'a, 'b> {
             ValType::F64 => Instruction::F64Store(mem_arg),
             ValType::V128 => Instruction::V128Store { memarg: mem_arg },
             ValType::FuncRef => Instruction::I32Store(mem_arg), // TODO: Is this right?
+            // The index in `EXTERNREF_TABLE`
             ValType::ExternRef => Instruction::I32Store(mem_arg),
         }
     }
 
+    /// The allocator rounds the size up so every block stays aligned to `MAX_ALIGNMENT`
     pub(crate) fn build_memory_alloc(&mut self, size: i32) -> Result<(), LangError> {
         let (alloc_func_id, _, _) = self.module_builder.get_func(
             ModuleUID::from_string(CORE_MODULE_ID.to_string()),
@@ -68,4 +288,52 @@ impl<'a, 'b> FunctionBuilder<'a, 'b> {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use wasm_encoder::ValType;
+    use super::{align_to, val_type_align_hint, values_layout};
+
+    #[test]
+    fn mixed_size_values_are_naturally_aligned() {
+        let layout = values_layout(&[ValType::I32, ValType::F64, ValType::I32]);
+
+        assert_eq!(layout.offsets, vec![0, 8, 16]);
+        assert_eq!(layout.align, 8);
+        // Padded so the next element of a vector is aligned too
+        assert_eq!(layout.size, 24);
+
+        let layout = values_layout(&[ValType::I64, ValType::I32, ValType::F32, ValType::V128]);
+
+        assert_eq!(layout.offsets, vec![0, 8, 12, 16]);
+        assert_eq!(layout.align, 16);
+        assert_eq!(layout.size, 32);
+    }
+
+    #[test]
+    fn small_values_are_not_padded() {
+        let layout = values_layout(&[ValType::I32, ValType::F32, ValType::I32]);
+
+        assert_eq!(layout.offsets, vec![0, 4, 8]);
+        assert_eq!(layout.align, 4);
+        assert_eq!(layout.size, 12);
+
+        let layout = values_layout(&[]);
+
+        assert!(layout.offsets.is_empty());
+        assert_eq!((layout.size, layout.align), (0, 1));
+    }
+
+    #[test]
+    fn alignment_hints_are_the_log2_of_the_size() {
+        assert_eq!(val_type_align_hint(ValType::I32), 2);
+        assert_eq!(val_type_align_hint(ValType::F32), 2);
+        assert_eq!(val_type_align_hint(ValType::I64), 3);
+        assert_eq!(val_type_align_hint(ValType::F64), 3);
+        assert_eq!(val_type_align_hint(ValType::V128), 4);
+
+        assert_eq!(align_to(13, 8), 16);
+        assert_eq!(align_to(16, 8), 16);
+        assert_eq!(align_to(0, 4), 0);
+    }
+}
@@ -1,36 +1,58 @@
 use common::ast::types::{MathOperatorKind, BoolOperatorKind};
+use common::errors::{BuildErrorKind, LangError};
 use wasm_encoder::{ValType, Instruction};
 use crate::build_code::FunctionBuilder;
 
 impl<'a, 'b> FunctionBuilder<'a, 'b> {
-    pub fn build_math_op(&mut self, op: &MathOperatorKind, left: ValType, right: ValType) {
-        self.check_type_convert(left, right);
+    /// Both operands are already converted to `type_`
+    pub fn build_math_op(&mut self, op: &MathOperatorKind, type_: ValType) -> Result<(), LangError> {
+        Self::check_operands(type_)?;
 
         let inst = match op {
-            MathOperatorKind::Plus => Self::build_add_op(left),
-            MathOperatorKind::Minus => Self::build_sub_op(left),
-            MathOperatorKind::Multiply => Self::build_mul_op(left),
-            MathOperatorKind::Divide => Self::build_div_op(left),
-            MathOperatorKind::Modulus => todo!(),
-            MathOperatorKind::Power => todo!(),
+            MathOperatorKind::Plus => Self::build_add_op(type_),
+            MathOperatorKind::Minus => Self::build_sub_op(type_),
+            MathOperatorKind::Multiply => Self::build_mul_op(type_),
+            MathOperatorKind::Divide => Self::build_div_op(type_),
+            MathOperatorKind::Modulus => match type_ {
+                ValType::I32 => Instruction::I32RemS,
+                ValType::I64 => Instruction::I64RemS,
+                _ => return Err(LangError::build(BuildErrorKind::Unsupported(
+                    "Modulus of floats".to_string()))),
+            },
+            MathOperatorKind::Power => return Err(LangError::build(BuildErrorKind::Unsupported(
+                "Power operator".to_string()))),
         };
 
         self.instructions.push(inst);
+
+        Ok(())
     }
 
-    pub fn build_bool_op(&mut self, op: &BoolOperatorKind, left: ValType, right: ValType) {
-        self.check_type_convert(left, right);
+    /// Both operands are already converted to `type_`
+    pub fn build_bool_op(&mut self, op: &BoolOperatorKind, type_: ValType) -> Result<(), LangError> {
+        Self::check_operands(type_)?;
 
         let inst = match op {
-            BoolOperatorKind::Equal => Self::build_eq_op(left),
-            BoolOperatorKind::Different => Self::build_ne_op(left),
-            BoolOperatorKind::Bigger => Self::build_gt_op(left),
-            BoolOperatorKind::Smaller => Self::build_lt_op(left),
-            BoolOperatorKind::BiggerEq => Self::build_ge_op(left),
-            BoolOperatorKind::SmallerEq => Self::build_le_op(left),
+            BoolOperatorKind::Equal => Self::build_eq_op(type_),
+            BoolOperatorKind::Different => Self::build_ne_op(type_),
+            BoolOperatorKind::Bigger => Self::build_gt_op(type_),
+            BoolOperatorKind::Smaller => Self::build_lt_op(type_),
+            BoolOperatorKind::BiggerEq => Self::build_ge_op(type_),
+            BoolOperatorKind::SmallerEq => Self::build_le_op(type_),
         };
 
         self.instructions.push(inst);
+
+        Ok(())
+    }
+
+    /// Extern objects are opaque, they can't be used in operations
+    fn check_operands(type_: ValType) -> Result<(), LangError> {
+        match type_ {
+            ValType::ExternRef => Err(LangError::build(BuildErrorKind::Unsupported(
+                "Operations on extern objects".to_string()))),
+            _ => Ok(()),
+        }
     }
 
     fn build_le_op(type_: ValType) -> Instruction<'static> {
@@ -41,7 +63,7 @@ impl<'a, 'b> FunctionBuilder<'a, 'b> {
             ValType::F64 => Instruction::F64Le,
             ValType::V128 => todo!(),
             ValType::FuncRef => todo!(),
-            ValType::ExternRef => todo!(),
+            ValType::ExternRef => unreachable!(),
         }
     }
 
@@ -53,7 +75,7 @@ impl<'a, 'b> FunctionBuilder<'a, 'b> {
             ValType::F64 => Instruction::F64Ge,
             ValType::V128 => todo!(),
             ValType::FuncRef => todo!(),
-            ValType::ExternRef => todo!(),
+            ValType::ExternRef => unreachable!(),
         }
     }
 
@@ -65,7 +87,7 @@ impl<'a, 'b> FunctionBuilder<'a, 'b> {
             ValType::F64 => Instruction::F64Lt,
             ValType::V128 => todo!(),
             ValType::FuncRef => todo!(),
-            ValType::ExternRef => todo!(),
+            ValType::ExternRef => unreachable!(),
         }
     }
 
@@ -77,7 +99,7 @@ impl<'a, 'b> FunctionBuilder<'a, 'b> {
             ValType::F64 => Instruction::F64Gt,
             ValType::V128 => todo!(),
             ValType::FuncRef => todo!(),
-            ValType::ExternRef => todo!(),
+            ValType::ExternRef => unreachable!(),
         }
     }
 
@@ -89,7 +111,7 @@ impl<'a, 'b> FunctionBuilder<'a, 'b> {
             ValType::F64 => Instruction::F64Ne,
             ValType::V128 => todo!(),
             ValType::FuncRef => todo!(),
-            ValType::ExternRef => todo!(),
+            ValType::ExternRef => unreachable!(),
         }
     }
 
@@ -101,15 +123,15 @@ impl<'a, 'b> FunctionBuilder<'a, 'b> {
             ValType::F64 => Instruction::F64Eq,
             ValType::V128 => todo!(),
             ValType::FuncRef => todo!(),
-            ValType::ExternRef => todo!(),
+            ValType::ExternRef => unreachable!(),
         }
     }
 
-    fn check_type_convert(&mut self, left: ValType, right: ValType) {
-        if left != right {
-            match Self::convert_op(left, right) {
-                Some(op) => self.instructions.push(op),
-                None => (),
+    /// Converts the value on the stack from `from` to `to`
+    pub(crate) fn build_convert(&mut self, from: ValType, to: ValType) {
+        if from != to {
+            if let Some(op) = Self::convert_op(to, from) {
+                self.instructions.push(op);
             }
         }
     }
@@ -124,7 +146,7 @@ impl<'a, 'b> FunctionBuilder<'a, 'b> {
                     ValType::F64 => Some(Instruction::I32TruncF64S),
                     ValType::V128 => todo!(),
                     ValType::FuncRef => todo!(),
-                    ValType::ExternRef => todo!(),
+                    ValType::ExternRef => unreachable!(),
                 }
             },
             ValType::I64 => {
@@ -135,7 +157,7 @@ impl<'a, 'b> FunctionBuilder<'a, 'b> {
                     ValType::F64 => Some(Instruction::I64TruncF64S),
                     ValType::V128 => todo!(),
                     ValType::FuncRef => todo!(),
-                    ValType::ExternRef => todo!(),
+                    ValType::ExternRef => unreachable!(),
                 }
             },
             ValType::F32 => {
@@ -146,7 +168,7 @@ impl<'a, 'b> FunctionBuilder<'a, 'b> {
                     ValType::F64 => Some(Instruction::F32DemoteF64),
                     ValType::V128 => todo!(),
                     ValType::FuncRef => todo!(),
-                    ValType::ExternRef => todo!(),
+                    ValType::ExternRef => unreachable!(),
                 }
             },
             ValType::F64 => {
@@ -157,12 +179,12 @@ impl<'a, 'b> FunctionBuilder<'a, 'b> {
                     ValType::F64 => None,
                     ValType::V128 => todo!(),
                     ValType::FuncRef => todo!(),
-                    ValType::ExternRef => todo!(),
+                    ValType::ExternRef => unreachable!(),
                 }
             },
             ValType::V128 => todo!(),
             ValType::FuncRef => todo!(),
-            ValType::ExternRef => todo!(),
+            ValType::ExternRef => unreachable!(),
         }
     }
 
@@ -174,7 +196,7 @@ impl<'a, 'b> FunctionBuilder<'a, 'b> {
             ValType::F64 => Instruction::F64Sub,
             ValType::V128 => todo!(),
             ValType::FuncRef => todo!(),
-            ValType::ExternRef => todo!(),
+            ValType::ExternRef => unreachable!(),
         }
     }
 
@@ -186,7 +208,7 @@ impl<'a, 'b> FunctionBuilder<'a, 'b> {
             ValType::F64 => Instruction::F64Mul,
             ValType::V128 => todo!(),
             ValType::FuncRef => todo!(),
-            ValType::ExternRef => todo!(),
+            ValType::ExternRef => unreachable!(),
         }
     }
 
@@ -198,7 +220,7 @@ impl<'a, 'b> FunctionBuilder<'a, 'b> {
             ValType::F64 => Instruction::F64Div,
             ValType::V128 => todo!(),
             ValType::FuncRef => todo!(),
-            ValType::ExternRef => todo!(),
+            ValType::ExternRef => unreachable!(),
         }
     }
 
@@ -210,7 +232,7 @@ impl<'a, 'b> FunctionBuilder<'a, 'b> {
             ValType::F64 => Instruction::F64Add,
             ValType::V128 => todo!(),
             ValType::FuncRef => todo!(),
-            ValType::ExternRef => todo!(),
+            ValType::ExternRef => unreachable!(),
         }
     }
 }
\ No newline at end of file
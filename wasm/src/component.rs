@@ -0,0 +1,886 @@
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::Arc;
+use wasm_encoder::{BlockType, Instruction, MemArg, ValType};
+use wit_component::{ComponentEncoder, StringEncoding};
+use wit_parser::{Resolve, UnresolvedPackage};
+use core::reexport::anyhow::Error;
+use common::ast::types::{ClassKind, ClassType, EnumType, FunctionType, TypeKind};
+use common::constants::{CORE_MODULE_ID, INTERNAL_MEMORY_ALLOC, INTERNAL_PANIC};
+use common::errors::{BuildErrorKind, LangError};
+use common::module::{FunctionDefinition, Module, ModuleUID};
+use crate::build::{convert_type, convert_types, param_names};
+use crate::build_code::{FunctionData, FunctionExport, FunctionImport, ModuleBuilder, ModuleDataKind};
+use crate::build_mem::{align_to, VECTOR_HEADER_SIZE};
+use crate::engine::BuildTarget;
+
+/// Allocator used by the host to pass strings and lists to the component
+pub const COMPONENT_REALLOC: &str = "cabi_realloc";
+/// The component model expects the memory under this name
+pub const COMPONENT_MEMORY_EXPORT: &str = "memory";
+/// Core module of the functions imported by the world itself
+const COMPONENT_IMPORT_MODULE: &str = "$root";
+/// Prefix of the canonical functions in the function names, a Rain name can't contain it
+const CANONICAL_PREFIX: &str = "[canonical]";
+
+/// Past these limits the canonical ABI passes the values through the memory
+const MAX_FLAT_PARAMS: usize = 16;
+const MAX_FLAT_RESULTS: usize = 1;
+
+const WIT_KEYWORDS: [&str; 39] = [
+    "as", "bool", "borrow", "char", "constructor", "enum", "export", "f32", "f64", "flags", "float32", "float64",
+    "from", "func", "future", "import", "include", "interface", "list", "option", "own", "package", "record",
+    "resource", "result", "s16", "s32", "s64", "s8", "static", "stream", "string", "tuple", "type", "u16",
+    "u32", "u64", "u8", "use",
+];
+
+impl<'a> ModuleBuilder<'a> {
+    /// Imported functions are called through an adapter that lowers the Rain values to the canonical ABI,
+    /// the canonical function is imported by the world itself
+    pub(crate) fn insert_component_import(&mut self, module: &Module, name: &String, func_type: &FunctionType) -> Result<(), LangError> {
+        // A component can't import the panic handler, it traps instead
+        if module.import_module_name() == CORE_MODULE_ID {
+            return Ok(())
+        }
+
+        for type_ in func_type.0.iter().chain([func_type.1.as_ref()]) {
+            check_type(type_)?;
+        }
+
+        let (params, ret) = canonical_signature(name, func_type, true)?;
+
+        self.function_names.push((module.uid, format!("{}{}", CANONICAL_PREFIX, name)));
+        self.functions.push((Vec::new(), TypeKind::Nothing));
+
+        self.function_imports.push(FunctionImport {
+            module_name: COMPONENT_IMPORT_MODULE.to_string(),
+            name: wit_name(name),
+
+            params,
+            ret,
+            metadata: Some(func_type.clone()),
+            param_names: param_names(module, name, func_type.0.len()),
+        });
+
+        Ok(())
+    }
+
+    pub(crate) fn load_component_func(&mut self, module_uid: ModuleUID, func: &FunctionDefinition, name: &String) -> Result<(u32, &Vec<TypeKind>, &TypeKind), LangError> {
+        let params = convert_types(&func.metadata.0);
+        let mut builder = ComponentFunctionBuilder::new(self, params.clone());
+
+        match module_uid == ModuleUID::from_string(CORE_MODULE_ID.to_string()) && name == INTERNAL_PANIC {
+            true => builder.instructions.push(Instruction::Unreachable),
+            false => builder.build_import_adapter(module_uid, name, &func.metadata)?,
+        }
+
+        let function_data = builder.build(name.clone(), module_uid, params, convert_type(&func.metadata.1));
+        self.function_data.push(function_data);
+
+        self.function_names.push((module_uid, name.clone()));
+        self.functions.push((func.metadata.0.clone(), *func.metadata.1.clone()));
+
+        let (params, ret) = self.functions
+            .last()
+            .unwrap();
+
+        Ok((
+            self.functions.len() as u32 - 1,
+            params,
+            ret
+        ))
+    }
+
+    /// Inserts the adapter of an exported function, it lifts the canonical values to the Rain values
+    /// and is exported as `export_name`
+    pub(crate) fn insert_component_export(&mut self, module_uid: ModuleUID, func_name: &String, export_name: &String) -> Result<(), LangError> {
+        let (func_id, params, ret) = self.get_func(module_uid, func_name)?;
+        let func_type = FunctionType(params.clone(), Box::new(ret.clone()));
+
+        for type_ in func_type.0.iter().chain([func_type.1.as_ref()]) {
+            check_type(type_)?;
+        }
+
+        let (canonical_params, canonical_ret) = canonical_signature(export_name, &func_type, false)?;
+        let mut builder = ComponentFunctionBuilder::new(self, canonical_params.clone());
+
+        let mut values = 0..canonical_params.len() as u32;
+        for type_ in &func_type.0 {
+            builder.lift_flat(type_, &mut values);
+        }
+
+        builder.instructions.push(Instruction::Call(func_id));
+
+        let results = builder.push_locals(&convert_type(&func_type.1));
+        for id in results.iter().rev() {
+            builder.instructions.push(Instruction::LocalSet(*id));
+        }
+
+        match flat_types(&func_type.1).len() > MAX_FLAT_RESULTS {
+            // The results are returned through a return area
+            true => {
+                let area = builder.return_area(&func_type.1);
+                builder.lower_memory(&func_type.1, &mut results.into_iter(), area, 0);
+                builder.instructions.push(Instruction::LocalGet(area));
+            },
+            false => builder.lower_flat(&func_type.1, &mut results.into_iter()),
+        }
+
+        let name = format!("{}{}", CANONICAL_PREFIX, wit_name(export_name));
+        let function_data = builder.build(name.clone(), module_uid, canonical_params.clone(), canonical_ret.clone());
+        self.function_data.push(function_data);
+
+        self.function_names.push((module_uid, name));
+        self.functions.push((Vec::new(), TypeKind::Nothing));
+
+        Ok(())
+    }
+
+    /// `cabi_realloc(old_ptr, old_size, align, new_size)`, the canonical ABI only uses it to allocate
+    /// so the old block is never copied. Every block has room for the length header of the strings and vectors,
+    /// so that they are used as they are
+    pub(crate) fn insert_component_realloc(&mut self) -> Result<(), LangError> {
+        let core_uid = ModuleUID::from_string(CORE_MODULE_ID.to_string());
+        let (alloc_id, _, _) = self.get_func(core_uid, &INTERNAL_MEMORY_ALLOC.to_string())?;
+
+        let params = vec![ValType::I32; 4];
+        let mut builder = ComponentFunctionBuilder::new(self, params.clone());
+
+        builder.instructions.push(Instruction::LocalGet(3));
+        builder.instructions.push(Instruction::I32Const(VECTOR_HEADER_SIZE as i32));
+        builder.instructions.push(Instruction::I32Add);
+        builder.instructions.push(Instruction::Call(alloc_id));
+        builder.instructions.push(Instruction::I32Const(VECTOR_HEADER_SIZE as i32));
+        builder.instructions.push(Instruction::I32Add);
+
+        let function_data = builder.build(COMPONENT_REALLOC.to_string(), core_uid, params, vec![ValType::I32]);
+        self.function_data.push(function_data);
+
+        self.function_names.push((core_uid, COMPONENT_REALLOC.to_string()));
+        self.functions.push((Vec::new(), TypeKind::Nothing));
+
+        Ok(())
+    }
+
+    pub(crate) fn is_component(&self) -> bool {
+        matches!(self.target, BuildTarget::Component)
+    }
+}
+
+/// The name under which a function of the core module is exported to the component
+pub(crate) fn component_export_name(func: &FunctionData) -> Option<String> {
+    match func.name.strip_prefix(CANONICAL_PREFIX) {
+        Some(name) => Some(name.to_string()),
+        None if func.name == COMPONENT_REALLOC => Some(func.name.clone()),
+        None => None,
+    }
+}
+
+/// The flat core signature of a function, following the canonical ABI
+fn canonical_signature(name: &String, func_type: &FunctionType, import: bool) -> Result<(Vec<ValType>, Vec<ValType>), LangError> {
+    let mut params: Vec<ValType> = func_type.0
+        .iter()
+        .flat_map(flat_types)
+        .collect();
+
+    if params.len() > MAX_FLAT_PARAMS {
+        return Err(LangError::build(BuildErrorKind::Unsupported(
+            format!("`{}` has too many parameters for a component", name))));
+    }
+
+    let ret = flat_types(&func_type.1);
+
+    // The results are passed through a return area, an import writes them where the last parameter points
+    Ok(match (ret.len() > MAX_FLAT_RESULTS, import) {
+        (true, true) => {
+            params.push(ValType::I32);
+            (params, Vec::new())
+        },
+        (true, false) => (params, vec![ValType::I32]),
+        (false, _) => (params, ret),
+    })
+}
+
+/// Only the types that have the same flat representation in Rain and in the component model
+/// can cross the component boundary, apart from strings and vectors which are converted
+fn check_type(type_: &TypeKind) -> Result<(), LangError> {
+    let unsupported = |description: String| Err(LangError::build(BuildErrorKind::Unsupported(
+        format!("{} can't be used by a component", description))));
+
+    match type_ {
+        TypeKind::Int | TypeKind::Float | TypeKind::Bool | TypeKind::String | TypeKind::Nothing => Ok(()),
+        TypeKind::Vector(element) => match element.as_ref() {
+            TypeKind::Int | TypeKind::Float => Ok(()),
+            _ => unsupported(format!("A vector of {:?}", element)),
+        },
+        TypeKind::Class(class_type) => match class_type.kind {
+            ClassKind::Data => {
+                for (_, field) in class_type.fields.borrow().iter() {
+                    check_type(field)?;
+                }
+
+                Ok(())
+            },
+            ClassKind::Normal | ClassKind::Extern => unsupported(format!("The class `{}`", class_type.name)),
+        },
+        TypeKind::Enum(enum_type) => {
+            let variants = enum_type.variants.borrow();
+
+            let supported = |type_: &TypeKind| matches!(type_, TypeKind::Nothing | TypeKind::Int | TypeKind::Float | TypeKind::String);
+
+            match variants.len() <= u8::MAX as usize && variants.iter().all(|(_, type_)| supported(type_)) {
+                true => Ok(()),
+                false => unsupported(format!("The enum `{}`, only `Int`, `Float` and `String` values are supported in its variants,", enum_type.name)),
+            }
+        },
+        _ => unsupported(format!("{:?}", type_)),
+    }
+}
+
+fn flat_types(type_: &TypeKind) -> Vec<ValType> {
+    match type_ {
+        TypeKind::String | TypeKind::Vector(_) => vec![ValType::I32, ValType::I32],
+        TypeKind::Class(class_type) => class_type.fields
+            .borrow()
+            .iter()
+            .flat_map(|(_, field)| flat_types(field))
+            .collect(),
+        TypeKind::Enum(enum_type) => [ValType::I32]
+            .into_iter()
+            .chain(variant_payload(enum_type))
+            .collect(),
+        _ => convert_type(type_),
+    }
+}
+
+/// The flat payload of a variant joins the flat values of its cases,
+/// a value that is an `f32` in a case and an `i32` in another one is an `i32`
+fn variant_payload(enum_type: &EnumType) -> Vec<ValType> {
+    let mut payload: Vec<ValType> = Vec::new();
+
+    for (_, type_) in enum_type.variants.borrow().iter() {
+        for (i, value) in flat_types(type_).into_iter().enumerate() {
+            match payload.get(i) {
+                Some(joined) if *joined == value => (),
+                Some(_) => payload[i] = ValType::I32,
+                None => payload.push(value),
+            }
+        }
+    }
+
+    payload
+}
+
+fn has_payload(enum_type: &EnumType) -> bool {
+    enum_type.variants
+        .borrow()
+        .iter()
+        .any(|(_, type_)| !matches!(type_.as_ref(), TypeKind::Nothing))
+}
+
+/// Size and alignment of a value stored in the memory by the canonical ABI
+fn canonical_layout(type_: &TypeKind) -> (u64, u64) {
+    match type_ {
+        TypeKind::Bool => (1, 1),
+        TypeKind::Int | TypeKind::Float => (4, 4),
+        TypeKind::String | TypeKind::Vector(_) => (8, 4),
+        TypeKind::Class(class_type) => {
+            let (_, size, align) = record_layout(class_type);
+            (size, align)
+        },
+        TypeKind::Enum(enum_type) => {
+            let (_, size, align) = variant_layout(enum_type);
+            (size, align)
+        },
+        _ => (0, 1),
+    }
+}
+
+/// The offsets of the fields, the size and the alignment of a record
+fn record_layout(class_type: &ClassType) -> (Vec<u64>, u64, u64) {
+    let mut offsets = Vec::new();
+    let mut size = 0;
+    let mut align = 1;
+
+    for (_, field) in class_type.fields.borrow().iter() {
+        let (field_size, field_align) = canonical_layout(field);
+
+        size = align_to(size, field_align);
+        offsets.push(size);
+        size += field_size;
+        align = align.max(field_align);
+    }
+
+    (offsets, align_to(size, align), align)
+}
+
+/// The offset of the payload, the size and the alignment of a variant, the payload is after its `u8` discriminant
+fn variant_layout(enum_type: &EnumType) -> (u64, u64, u64) {
+    let (size, align) = enum_type.variants
+        .borrow()
+        .iter()
+        .map(|(_, type_)| canonical_layout(type_))
+        .fold((0, 1), |(size, align), (case_size, case_align)| (size.max(case_size), align.max(case_align)));
+
+    let payload_offset = align_to(1, align);
+
+    (payload_offset, align_to(payload_offset + size, align), align)
+}
+
+/// Strings point to their length, vectors to their first element
+fn data_offset(type_: &TypeKind) -> i32 {
+    match type_ {
+        TypeKind::String => 4,
+        _ => 0,
+    }
+}
+
+/// Writes the adapters between the canonical ABI and the Rain functions
+struct ComponentFunctionBuilder<'a, 'b> {
+    module_builder: &'a mut ModuleBuilder<'b>,
+    locals: Vec<ValType>,
+    instructions: Vec<Instruction<'static>>,
+}
+
+impl<'a, 'b> ComponentFunctionBuilder<'a, 'b> {
+    fn new(module_builder: &'a mut ModuleBuilder<'b>, params: Vec<ValType>) -> Self {
+        Self {
+            module_builder,
+            locals: params,
+            instructions: Vec::new(),
+        }
+    }
+
+    fn build(mut self, name: String, module: ModuleUID, params: Vec<ValType>, ret: Vec<ValType>) -> FunctionData {
+        self.instructions.push(Instruction::End);
+
+        FunctionData {
+            name,
+            module,
+
+            params,
+            ret,
+
+            locals: self.locals,
+            local_names: Vec::new(),
+            instructions: self.instructions,
+            spans: Vec::new(),
+        }
+    }
+
+    fn push_local(&mut self, type_: ValType) -> u32 {
+        self.locals.push(type_);
+        self.locals.len() as u32 - 1
+    }
+
+    fn push_locals(&mut self, types: &[ValType]) -> Vec<u32> {
+        types
+            .iter()
+            .map(|type_| self.push_local(*type_))
+            .collect()
+    }
+
+    fn mem_arg(offset: u64, align: u32) -> MemArg {
+        MemArg {
+            offset,
+            align,
+            memory_index: 0,
+        }
+    }
+
+    /// A local pointing to a static area that holds the results passed through the memory
+    fn return_area(&mut self, type_: &TypeKind) -> u32 {
+        let (size, align) = canonical_layout(type_);
+        let offset = self.module_builder.push_data(vec![0; size as usize], ModuleDataKind::Standard, align);
+
+        let area = self.push_local(ValType::I32);
+        self.instructions.push(Instruction::I32Const(offset as i32));
+        self.instructions.push(Instruction::LocalSet(area));
+
+        area
+    }
+
+    /// Calls the canonical import of `name` with the Rain values of the parameters
+    fn build_import_adapter(&mut self, module_uid: ModuleUID, name: &String, func_type: &FunctionType) -> Result<(), LangError> {
+        let canonical_name = format!("{}{}", CANONICAL_PREFIX, name);
+        let (import_id, _, _) = self.module_builder.get_func(module_uid, &canonical_name)?;
+
+        let mut values = 0..self.locals.len() as u32;
+        for type_ in &func_type.0 {
+            self.lower_flat(type_, &mut values);
+        }
+
+        match flat_types(&func_type.1).len() > MAX_FLAT_RESULTS {
+            // The host writes the results to the return area
+            true => {
+                let area = self.return_area(&func_type.1);
+                self.instructions.push(Instruction::LocalGet(area));
+                self.instructions.push(Instruction::Call(import_id));
+                self.lift_memory(&func_type.1, area, 0);
+            },
+            // A single value is the same in Rain
+            false => self.instructions.push(Instruction::Call(import_id)),
+        }
+
+        Ok(())
+    }
+
+    /// Runs `build` for every case with a payload, when `discriminant` is the index of the case.
+    /// A Rain enum keeps the payload in a single `i32`, the `Float` values are reinterpreted
+    fn build_cases(&mut self, enum_type: &EnumType, discriminant: u32, mut build: impl FnMut(&mut Self, &TypeKind)) {
+        let variants = enum_type.variants.borrow().clone();
+
+        for (i, (_, type_)) in variants.iter().enumerate() {
+            if let TypeKind::Nothing = type_.as_ref() { continue }
+
+            self.instructions.push(Instruction::LocalGet(discriminant));
+            self.instructions.push(Instruction::I32Const(i as i32));
+            self.instructions.push(Instruction::I32Eq);
+            self.instructions.push(Instruction::If(BlockType::Empty));
+            build(self, type_);
+            self.instructions.push(Instruction::End);
+        }
+    }
+
+    /// Pushes the Rain values of `type_` from the canonical flat values in the locals `values`
+    fn lift_flat(&mut self, type_: &TypeKind, values: &mut impl Iterator<Item=u32>) {
+        match type_ {
+            TypeKind::String | TypeKind::Vector(_) => {
+                let ptr = values.next().unwrap();
+                let len = values.next().unwrap();
+
+                self.lift_sequence(type_, ptr, len);
+            },
+            TypeKind::Class(class_type) => {
+                for (_, field) in class_type.fields.borrow().iter() {
+                    self.lift_flat(field, values);
+                }
+            },
+            TypeKind::Enum(enum_type) if has_payload(enum_type) => {
+                let discriminant = values.next().unwrap();
+                let payload: Vec<u32> = variant_payload(enum_type)
+                    .iter()
+                    .map(|_| values.next().unwrap())
+                    .collect();
+                let value = self.push_local(ValType::I32);
+
+                self.build_cases(enum_type, discriminant, |builder, case| {
+                    match case {
+                        TypeKind::String => builder.lift_sequence(case, payload[0], payload[1]),
+                        _ => {
+                            builder.instructions.push(Instruction::LocalGet(payload[0]));
+                            if builder.locals[payload[0] as usize] == ValType::F32 {
+                                builder.instructions.push(Instruction::I32ReinterpretF32);
+                            }
+                        },
+                    }
+
+                    builder.instructions.push(Instruction::LocalSet(value));
+                });
+
+                self.instructions.push(Instruction::LocalGet(discriminant));
+                self.instructions.push(Instruction::LocalGet(value));
+            },
+            _ => for _ in flat_types(type_) {
+                self.instructions.push(Instruction::LocalGet(values.next().unwrap()));
+            },
+        }
+    }
+
+    /// The blocks given by the host come from `cabi_realloc`, so the length is written before the data
+    fn lift_sequence(&mut self, type_: &TypeKind, ptr: u32, len: u32) {
+        self.instructions.push(Instruction::LocalGet(ptr));
+        self.instructions.push(Instruction::I32Const(4));
+        self.instructions.push(Instruction::I32Sub);
+        self.instructions.push(Instruction::LocalGet(len));
+        self.instructions.push(Instruction::I32Store(Self::mem_arg(0, 2)));
+
+        self.instructions.push(Instruction::LocalGet(ptr));
+        self.instructions.push(Instruction::I32Const(data_offset(type_)));
+        self.instructions.push(Instruction::I32Sub);
+    }
+
+    /// Pushes the Rain values of `type_` from the canonical value stored at `base + offset`
+    fn lift_memory(&mut self, type_: &TypeKind, base: u32, offset: u64) {
+        match type_ {
+            TypeKind::Int => {
+                self.instructions.push(Instruction::LocalGet(base));
+                self.instructions.push(Instruction::I32Load(Self::mem_arg(offset, 2)));
+            },
+            TypeKind::Float => {
+                self.instructions.push(Instruction::LocalGet(base));
+                self.instructions.push(Instruction::F32Load(Self::mem_arg(offset, 2)));
+            },
+            TypeKind::Bool => {
+                self.instructions.push(Instruction::LocalGet(base));
+                self.instructions.push(Instruction::I32Load8_U(Self::mem_arg(offset, 0)));
+            },
+            TypeKind::String | TypeKind::Vector(_) => {
+                let ptr = self.push_local(ValType::I32);
+                let len = self.push_local(ValType::I32);
+
+                self.instructions.push(Instruction::LocalGet(base));
+                self.instructions.push(Instruction::I32Load(Self::mem_arg(offset, 2)));
+                self.instructions.push(Instruction::LocalSet(ptr));
+                self.instructions.push(Instruction::LocalGet(base));
+                self.instructions.push(Instruction::I32Load(Self::mem_arg(offset + 4, 2)));
+                self.instructions.push(Instruction::LocalSet(len));
+
+                self.lift_sequence(type_, ptr, len);
+            },
+            TypeKind::Class(class_type) => {
+                let (offsets, _, _) = record_layout(class_type);
+
+                for ((_, field), field_offset) in class_type.fields.borrow().iter().zip(offsets) {
+                    self.lift_memory(field, base, offset + field_offset);
+                }
+            },
+            TypeKind::Enum(enum_type) => {
+                self.instructions.push(Instruction::LocalGet(base));
+                self.instructions.push(Instruction::I32Load8_U(Self::mem_arg(offset, 0)));
+
+                if has_payload(enum_type) {
+                    let (payload_offset, _, _) = variant_layout(enum_type);
+                    let discriminant = self.push_local(ValType::I32);
+                    let value = self.push_local(ValType::I32);
+                    self.instructions.push(Instruction::LocalTee(discriminant));
+
+                    self.build_cases(enum_type, discriminant, |builder, case| {
+                        match case {
+                            TypeKind::Float => {
+                                builder.instructions.push(Instruction::LocalGet(base));
+                                builder.instructions.push(Instruction::I32Load(Self::mem_arg(offset + payload_offset, 2)));
+                            },
+                            _ => builder.lift_memory(case, base, offset + payload_offset),
+                        }
+
+                        builder.instructions.push(Instruction::LocalSet(value));
+                    });
+
+                    self.instructions.push(Instruction::LocalGet(value));
+                }
+            },
+            _ => (),
+        }
+    }
+
+    /// Pushes the canonical flat values of the Rain values in the locals `values`
+    fn lower_flat(&mut self, type_: &TypeKind, values: &mut impl Iterator<Item=u32>) {
+        match type_ {
+            TypeKind::String | TypeKind::Vector(_) => {
+                let value = values.next().unwrap();
+
+                self.push_data_ptr(type_, value);
+                self.push_len(type_, value);
+            },
+            TypeKind::Class(class_type) => {
+                for (_, field) in class_type.fields.borrow().iter() {
+                    self.lower_flat(field, values);
+                }
+            },
+            TypeKind::Enum(enum_type) if has_payload(enum_type) => {
+                let discriminant = values.next().unwrap();
+                let value = values.next().unwrap();
+                let payload_types = variant_payload(enum_type);
+                let payload = self.push_locals(&payload_types);
+
+                self.build_cases(enum_type, discriminant, |builder, case| {
+                    match case {
+                        TypeKind::String => {
+                            builder.push_data_ptr(case, value);
+                            builder.instructions.push(Instruction::LocalSet(payload[0]));
+                            builder.push_len(case, value);
+                            builder.instructions.push(Instruction::LocalSet(payload[1]));
+                        },
+                        _ => {
+                            builder.instructions.push(Instruction::LocalGet(value));
+                            if payload_types[0] == ValType::F32 {
+                                builder.instructions.push(Instruction::F32ReinterpretI32);
+                            }
+                            builder.instructions.push(Instruction::LocalSet(payload[0]));
+                        },
+                    }
+                });
+
+                self.instructions.push(Instruction::LocalGet(discriminant));
+                for id in payload {
+                    self.instructions.push(Instruction::LocalGet(id));
+                }
+            },
+            _ => for _ in convert_type(type_) {
+                self.instructions.push(Instruction::LocalGet(values.next().unwrap()));
+            },
+        }
+    }
+
+    fn push_data_ptr(&mut self, type_: &TypeKind, value: u32) {
+        self.instructions.push(Instruction::LocalGet(value));
+        self.instructions.push(Instruction::I32Const(data_offset(type_)));
+        self.instructions.push(Instruction::I32Add);
+    }
+
+    /// The length is right before the data
+    fn push_len(&mut self, type_: &TypeKind, value: u32) {
+        self.push_data_ptr(type_, value);
+        self.instructions.push(Instruction::I32Const(4));
+        self.instructions.push(Instruction::I32Sub);
+        self.instructions.push(Instruction::I32Load(Self::mem_arg(0, 2)));
+    }
+
+    /// Stores the canonical value of the Rain values in the locals `values` at `base + offset`
+    fn lower_memory(&mut self, type_: &TypeKind, values: &mut impl Iterator<Item=u32>, base: u32, offset: u64) {
+        match type_ {
+            TypeKind::Int => {
+                self.instructions.push(Instruction::LocalGet(base));
+                self.instructions.push(Instruction::LocalGet(values.next().unwrap()));
+                self.instructions.push(Instruction::I32Store(Self::mem_arg(offset, 2)));
+            },
+            TypeKind::Float => {
+                self.instructions.push(Instruction::LocalGet(base));
+                self.instructions.push(Instruction::LocalGet(values.next().unwrap()));
+                self.instructions.push(Instruction::F32Store(Self::mem_arg(offset, 2)));
+            },
+            TypeKind::Bool => {
+                self.instructions.push(Instruction::LocalGet(base));
+                self.instructions.push(Instruction::LocalGet(values.next().unwrap()));
+                self.instructions.push(Instruction::I32Store8(Self::mem_arg(offset, 0)));
+            },
+            TypeKind::String | TypeKind::Vector(_) => {
+                let value = values.next().unwrap();
+
+                self.instructions.push(Instruction::LocalGet(base));
+                self.push_data_ptr(type_, value);
+                self.instructions.push(Instruction::I32Store(Self::mem_arg(offset, 2)));
+
+                self.instructions.push(Instruction::LocalGet(base));
+                self.push_len(type_, value);
+                self.instructions.push(Instruction::I32Store(Self::mem_arg(offset + 4, 2)));
+            },
+            TypeKind::Class(class_type) => {
+                let (offsets, _, _) = record_layout(class_type);
+
+                for ((_, field), field_offset) in class_type.fields.borrow().iter().zip(offsets) {
+                    self.lower_memory(field, values, base, offset + field_offset);
+                }
+            },
+            TypeKind::Enum(enum_type) => {
+                let discriminant = values.next().unwrap();
+
+                self.instructions.push(Instruction::LocalGet(base));
+                self.instructions.push(Instruction::LocalGet(discriminant));
+                self.instructions.push(Instruction::I32Store8(Self::mem_arg(offset, 0)));
+
+                if has_payload(enum_type) {
+                    let (payload_offset, _, _) = variant_layout(enum_type);
+                    let value = values.next().unwrap();
+
+                    self.build_cases(enum_type, discriminant, |builder, case| {
+                        match case {
+                            TypeKind::Float => {
+                                builder.instructions.push(Instruction::LocalGet(base));
+                                builder.instructions.push(Instruction::LocalGet(value));
+                                builder.instructions.push(Instruction::I32Store(Self::mem_arg(offset + payload_offset, 2)));
+                            },
+                            _ => builder.lower_memory(case, &mut [value].into_iter(), base, offset + payload_offset),
+                        }
+                    });
+                }
+            },
+            _ => (),
+        }
+    }
+}
+
+/// Writes the WIT world of a component, the data classes and enums are defined in the world
+pub(crate) struct WitGenerator {
+    definitions: Vec<String>,
+    defined: Vec<String>,
+}
+
+impl WitGenerator {
+    pub(crate) fn new() -> Self {
+        Self {
+            definitions: Vec::new(),
+            defined: Vec::new(),
+        }
+    }
+
+    pub(crate) fn generate(mut self, name: &str, exports: &[FunctionExport], function_imports: &[FunctionImport]) -> Result<String, LangError> {
+        let mut functions = Vec::new();
+
+        for func in function_imports {
+            if let (Some(func_type), COMPONENT_IMPORT_MODULE) = (&func.metadata, func.module_name.as_str()) {
+                let signature = self.signature(func_type, &func.param_names)?;
+                functions.push(format!("import {}: {};", func.name, signature));
+            }
+        }
+
+        for func in exports {
+            let signature = self.signature(&func.metadata, &func.param_names)?;
+            functions.push(format!("export {}: {};", wit_name(&func.name), signature));
+        }
+
+        let name = wit_name(name);
+        let mut wit = String::new();
+
+        writeln!(wit, "package rain:{};\n", name).unwrap();
+        writeln!(wit, "world {} {{", name).unwrap();
+
+        for definition in &self.definitions {
+            for line in definition.lines() {
+                writeln!(wit, "    {}", line).unwrap();
+            }
+            wit.push('\n');
+        }
+
+        for func in &functions {
+            writeln!(wit, "    {}", func).unwrap();
+        }
+        wit.push_str("}\n");
+
+        Ok(wit)
+    }
+
+    fn signature(&mut self, func_type: &FunctionType, names: &[String]) -> Result<String, LangError> {
+        let mut params = Vec::new();
+
+        for (type_, name) in func_type.0.iter().zip(names) {
+            check_type(type_)?;
+            params.push(format!("{}: {}", wit_name(name), self.wit_type(type_)));
+        }
+
+        check_type(&func_type.1)?;
+
+        Ok(match func_type.1.as_ref() {
+            TypeKind::Nothing => format!("func({})", params.join(", ")),
+            ret => format!("func({}) -> {}", params.join(", "), self.wit_type(ret)),
+        })
+    }
+
+    fn wit_type(&mut self, type_: &TypeKind) -> String {
+        match type_ {
+            TypeKind::Int => "s32".to_string(),
+            TypeKind::Float => "f32".to_string(),
+            TypeKind::Bool => "bool".to_string(),
+            TypeKind::String => "string".to_string(),
+            TypeKind::Vector(element) => format!("list<{}>", self.wit_type(element)),
+            TypeKind::Class(class_type) => self.define_record(class_type),
+            TypeKind::Enum(enum_type) => match enum_type.result_types() {
+                Some(types) => self.result_type(types),
+                None => self.define_enum(enum_type),
+            },
+            _ => "_".to_string(),
+        }
+    }
+
+    /// The builtin `Result<T, E>` is the `result` of WIT, they have the same representation
+    fn result_type(&mut self, (ok, err): (TypeKind, TypeKind)) -> String {
+        match (ok, err) {
+            (TypeKind::Nothing, TypeKind::Nothing) => "result".to_string(),
+            (ok, TypeKind::Nothing) => format!("result<{}>", self.wit_type(&ok)),
+            (ok, err) => format!("result<{}, {}>", self.wit_type(&ok), self.wit_type(&err)),
+        }
+    }
+
+    fn define_record(&mut self, class_type: &Arc<ClassType>) -> String {
+        let name = wit_name(&class_type.name);
+        if self.defined.contains(&name) { return name }
+        self.defined.push(name.clone());
+
+        // The types of the fields are defined first
+        let fields: Vec<String> = class_type.fields
+            .borrow()
+            .iter()
+            .map(|(field, type_)| format!("    {}: {},", wit_name(field), self.wit_type(type_)))
+            .collect();
+
+        self.definitions.push(format!("record {} {{\n{}\n}}", name, fields.join("\n")));
+
+        name
+    }
+
+    fn define_enum(&mut self, enum_type: &Arc<EnumType>) -> String {
+        let name = wit_name(&enum_type.name);
+        if self.defined.contains(&name) { return name }
+        self.defined.push(name.clone());
+
+        let kind = match has_payload(enum_type) {
+            true => "variant",
+            false => "enum",
+        };
+
+        let cases: Vec<String> = enum_type.variants
+            .borrow()
+            .iter()
+            .map(|(variant, type_)| match type_.as_ref() {
+                TypeKind::Nothing => format!("    {},", wit_name(variant)),
+                type_ => format!("    {}({}),", wit_name(variant), self.wit_type(type_)),
+            })
+            .collect();
+
+        self.definitions.push(format!("{} {} {{\n{}\n}}", kind, name, cases.join("\n")));
+
+        name
+    }
+}
+
+/// WIT names are kebab case, the keywords are escaped with `%`
+pub(crate) fn wit_name(name: &str) -> String {
+    let mut words: Vec<String> = Vec::new();
+    let mut previous: Option<char> = None;
+
+    // A word ends at a separator or before an upper case letter that follows a lower case one
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            previous = None;
+            continue
+        }
+
+        let boundary = match previous {
+            Some(p) => c.is_ascii_uppercase() && (p.is_ascii_lowercase() || p.is_ascii_digit()),
+            None => true,
+        };
+
+        if boundary {
+            words.push(String::new());
+        }
+
+        words.last_mut().unwrap().push(c.to_ascii_lowercase());
+        previous = Some(c);
+    }
+
+    // Every word starts with a letter
+    let wit = words
+        .iter()
+        .map(|word| match word.starts_with(|c: char| c.is_ascii_digit()) {
+            true => format!("n{}", word),
+            false => word.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join("-");
+
+    match WIT_KEYWORDS.contains(&wit.as_str()) {
+        true => format!("%{}", wit),
+        false => wit,
+    }
+}
+
+/// Wraps a core module built for the component target into a component of the `wit` world
+pub(crate) fn encode_component(module: &[u8], wit: &str) -> Result<Vec<u8>, LangError> {
+    let error = |err: Error| LangError::build(BuildErrorKind::UnexpectedError(format!("encode_component: {:#}", err)));
+
+    let mut resolve = Resolve::default();
+    let package = UnresolvedPackage::parse(Path::new("component.wit"), wit).map_err(error)?;
+    let package = resolve.push(package).map_err(error)?;
+    let world = resolve.select_world(package, None).map_err(error)?;
+
+    let mut module = module.to_vec();
+    wit_component::embed_component_metadata(&mut module, &resolve, world, StringEncoding::UTF8).map_err(error)?;
+
+    ComponentEncoder::default()
+        .validate(true)
+        .module(&module)
+        .map_err(error)?
+        .encode()
+        .map_err(error)
+}
@@ -1,13 +1,31 @@
 use core::{Engine, EngineBuildSource, parser::{ModuleLoader, ModuleImporter}, LangError};
 use std::sync::Arc;
 use common::{module::{Module, ModuleUID, ModuleIdentifier}, errors::BuildErrorKind};
-use common::constants::CORE_MODULE_ID;
+use common::constants::{CFG_TARGET_KEY, CFG_TARGET_WASI, CFG_TARGET_WASM, CORE_MODULE_ID};
 use core::reexport::anyhow::Result;
 use crate::module::WasmModule;
 use crate::build::WasmBuilder;
+use crate::memory::MemoryConfig;
+use crate::source_map::SourceMap;
+use crate::js::JsBindings;
+use ir::optimize::OptLevel;
+
+/// Environment in which the built modules run
+#[derive(Clone, Copy, Debug)]
+pub enum BuildTarget {
+    /// The host provides the imported functions, usually through JavaScript
+    Host,
+    /// The module is a WASI command, the standard library is built on top of `wasi_snapshot_preview1`
+    Wasi,
+    /// The module is wrapped into a component, its imports and exports follow the canonical ABI
+    Component,
+}
 
 pub struct WasmEngine {
     module_loader: ModuleLoader,
+    memory: MemoryConfig,
+    target: BuildTarget,
+    opt_level: OptLevel,
 }
 
 impl Engine for WasmEngine {
@@ -21,6 +39,14 @@ impl Engine for WasmEngine {
         Ok(module.uid)
     }
 
+    fn load_declaration_module(&mut self, identifier: impl Into<String>, import_module: impl Into<String>, importer: &impl ModuleImporter) -> Result<ModuleUID> {
+        let (module, _) = self
+            .module_loader()
+            .load_declaration_module(&ModuleIdentifier(identifier.into()), import_module.into(), importer)?;
+
+        Ok(module.uid)
+    }
+
     fn insert_module(&mut self, _module: Arc<Module>) -> Result<()> {
         Ok(())
     }
@@ -30,14 +56,73 @@ impl Engine for WasmEngine {
     }
 
     fn new() -> Self {
+        let mut module_loader = ModuleLoader::new();
+        module_loader.cfg_mut().set_target(CFG_TARGET_WASM);
+
         Self {
-            module_loader: ModuleLoader::new(),
+            module_loader,
+            memory: MemoryConfig::default(),
+            target: BuildTarget::Host,
+            opt_level: OptLevel::default(),
         }
     }
 }
 
-impl EngineBuildSource for WasmEngine {
-    fn build_module_source(&self, uid: ModuleUID) -> Result<Vec<u8>, LangError> {
+impl WasmEngine {
+    /// Sets the memory layout of the modules built afterwards
+    pub fn set_memory_config(&mut self, memory: MemoryConfig) {
+        self.memory = memory;
+    }
+
+    /// Sets the optimizations run on the modules built afterwards
+    pub fn set_opt_level(&mut self, opt_level: OptLevel) {
+        self.opt_level = opt_level;
+    }
+
+    /// Sets the environment of the modules built afterwards, this also sets the target used by `@Cfg`
+    /// so it has to be called before loading the modules.
+    /// WASI modules are still wasm modules, both `wasm` and `wasi` are set for them
+    pub fn set_target(&mut self, target: BuildTarget) {
+        let cfg = self.module_loader.cfg_mut();
+        cfg.set_target(CFG_TARGET_WASM);
+
+        if let BuildTarget::Wasi = target {
+            cfg.set(CFG_TARGET_KEY, CFG_TARGET_WASI);
+        }
+
+        self.target = target;
+    }
+
+    /// Builds the module in the WebAssembly text format, annotated with the Rain
+    /// names of the functions and the string literals of the data segments
+    pub fn build_module_text(&self, uid: ModuleUID) -> Result<String, LangError> {
+        self.create_builder(uid)?.build_text()
+    }
+
+    /// Builds the module and a source map that maps its instructions to the lines of the Rain sources,
+    /// `source_map_url` is the location of the source map relative to the module
+    pub fn build_module_with_source_map(&self, uid: ModuleUID, source_map_url: &str) -> Result<(Vec<u8>, SourceMap), LangError> {
+        self.create_builder(uid)?.build_with_source_map(source_map_url)
+    }
+
+    /// Builds an ES module with TypeScript declarations that loads the module and converts the values
+    /// passed to its exports and imports
+    pub fn build_js_bindings(&self, uid: ModuleUID) -> Result<JsBindings, LangError> {
+        self.create_builder(uid)?.build_js_bindings()
+    }
+
+    /// A Rust struct named `name` that instantiates the module with wasmtime
+    pub fn build_rust_bindings(&self, uid: ModuleUID, name: &str) -> Result<String, LangError> {
+        self.create_builder(uid)?.build_rust_bindings(name)
+    }
+
+    /// Builds a component whose world is derived from the exports and the imports of the module,
+    /// together with the world in the WIT format, the target has to be `BuildTarget::Component`
+    pub fn build_component(&self, uid: ModuleUID, name: &str) -> Result<(Vec<u8>, String), LangError> {
+        self.create_builder(uid)?.build_component(name)
+    }
+
+    fn create_builder(&self, uid: ModuleUID) -> Result<WasmBuilder<'_>, LangError> {
         let module = match self.module_loader.get_module(uid) {
             Some(module) => module,
             None => return Err(LangError::build(BuildErrorKind::UnexpectedError("build_module_source: Module not found".to_string()))),
@@ -48,7 +133,12 @@ impl EngineBuildSource for WasmEngine {
             None => return Err(LangError::build(BuildErrorKind::UnexpectedError("build_module_source: Core module not found".to_string()))),
         };
 
-        let builder = WasmBuilder::new(&self.module_loader, module, core_module);
-        builder.build()
+        Ok(WasmBuilder::new(&self.module_loader, module, core_module, &self.memory, self.target, self.opt_level))
+    }
+}
+
+impl EngineBuildSource for WasmEngine {
+    fn build_module_source(&self, uid: ModuleUID) -> Result<Vec<u8>, LangError> {
+        self.create_builder(uid)?.build()
     }
 }
\ No newline at end of file
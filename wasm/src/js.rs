@@ -0,0 +1,509 @@
+use std::fmt::Write;
+use wasm_encoder::ValType;
+use common::ast::types::{ClassKind, EnumType, FunctionType, TypeKind};
+use common::constants::{CORE_MODULE_ID, INTERNAL_MEMORY_ALLOC, INTERNAL_PANIC};
+use crate::build::{convert_type, convert_types, value_names};
+use crate::build_code::{FunctionExport, FunctionImport, GlobalImport};
+use crate::build_mem::VECTOR_HEADER_SIZE;
+use crate::memory::MemoryConfig;
+
+/// An ES module that instantiates a built module, and its TypeScript declarations
+pub struct JsBindings {
+    pub js: String,
+    pub dts: String,
+}
+
+const HEADER: &str = "// Generated by portal from the Rain sources, do not edit\n";
+
+/// An imported value with its TypeScript declaration, the optional imports have a default
+struct JsImport {
+    name: String,
+    value: String,
+    decl: String,
+    optional: bool,
+}
+
+/// Converts the values between JavaScript and the module, data classes become objects, tuples become arrays
+/// and the enums without payloads become the names of their variants. The types that can't be converted
+/// (functions and enums with payloads) are passed as their wasm values
+pub(crate) struct JsGenerator<'a> {
+    memory: &'a MemoryConfig,
+    /// The imports grouped by module
+    imports: Vec<(String, Vec<JsImport>)>,
+    /// The TypeScript declarations of the data classes and enums, by name
+    types: Vec<(String, String)>,
+}
+
+impl<'a> JsGenerator<'a> {
+    pub(crate) fn new(memory: &'a MemoryConfig) -> Self {
+        Self {
+            memory,
+            imports: Vec::new(),
+            types: Vec::new(),
+        }
+    }
+
+    pub(crate) fn generate(
+        mut self,
+        exports: &[FunctionExport],
+        function_imports: &[FunctionImport],
+        global_imports: &[GlobalImport],
+    ) -> JsBindings {
+        for func in function_imports {
+            let func_type = match &func.metadata {
+                Some(func_type) => func_type,
+                None => continue,
+            };
+
+            // The module doesn't have to handle its panics, they throw by default
+            let optional = func.module_name == CORE_MODULE_ID && func.name == INTERNAL_PANIC;
+            let import = match optional {
+                true => format!("(imports[{}]?.[{}] ?? panic)", quote(&func.module_name), quote(&func.name)),
+                false => format!("imports[{}][{}]", quote(&func.module_name), quote(&func.name)),
+            };
+
+            let names = js_params(&func.param_names);
+            let (value, signature) = match self.convert_import(&import, func_type, &names) {
+                Some(converted) => converted,
+                None => (import, wasm_signature(&value_names(&names, &func_type.0), &func.params, &func.ret)),
+            };
+            let decl = format!("{}{}{}", quote(&func.name), if optional { "?" } else { "" }, signature);
+
+            self.push_import(&func.module_name, JsImport { name: func.name.clone(), value, decl, optional });
+        }
+
+        // The globals are given before the instantiation, so only the numbers are converted
+        for global in global_imports {
+            let import = format!("imports[{}][{}]", quote(&global.module_name), quote(&global.name));
+            let (value, decl) = match &global.metadata {
+                TypeKind::Bool => (format!("({} ? 1 : 0)", import), "boolean"),
+                _ => (import, "number"),
+            };
+
+            let decl = format!("{}: {}", quote(&global.name), decl);
+            self.push_import(&global.module_name, JsImport { name: global.name.clone(), value, decl, optional: false });
+        }
+
+        if let Some(import) = &self.memory.import {
+            let value = format!("imports[{}][{}]", quote(&import.module), quote(&import.name));
+            let decl = format!("{}: WebAssembly.Memory", quote(&import.name));
+
+            self.push_import(&import.module, JsImport { name: import.name.clone(), value, decl, optional: false });
+        }
+
+        let exports: Vec<(String, String, String)> = exports
+            .iter()
+            .map(|func| {
+                let names = js_params(&func.param_names);
+
+                match self.convert_export(&func.name, &func.metadata, &names) {
+                    Some((value, signature)) => (func.name.clone(), value, signature),
+                    None => {
+                        let params = convert_types(&func.metadata.0);
+                        let ret = convert_type(&func.metadata.1);
+                        let signature = wasm_signature(&value_names(&names, &func.metadata.0), &params, &ret);
+
+                        (func.name.clone(), format!("exports[{}]", quote(&func.name)), signature)
+                    },
+                }
+            })
+            .collect();
+
+        JsBindings {
+            js: self.generate_js(&exports),
+            dts: self.generate_dts(&exports),
+        }
+    }
+
+    fn push_import(&mut self, module: &str, import: JsImport) {
+        match self.imports.iter_mut().find(|(m, _)| m == module) {
+            Some((_, imports)) => imports.push(import),
+            None => self.imports.push((module.to_string(), vec![import])),
+        }
+    }
+
+    /// The function given to the module, it converts the arguments for the imported JavaScript function
+    fn convert_import(&mut self, import: &str, func_type: &FunctionType, names: &[String]) -> Option<(String, String)> {
+        let values = value_names(names, &func_type.0);
+        let mut iter = values.iter().cloned();
+
+        let lifted = func_type.0
+            .iter()
+            .map(|type_| lift(type_, &mut iter))
+            .collect::<Option<Vec<_>>>()?;
+
+        let call = format!("{}({})", import, lifted.join(", "));
+        let body = match convert_type(&func_type.1).len() {
+            0 => call,
+            1 => lower(&func_type.1, &call)?.remove(0),
+            // The results of the function are returned as an array
+            _ => format!("{{ const result = {}; return [{}]; }}", call, lower(&func_type.1, "result")?.join(", ")),
+        };
+
+        let value = format!("({}) => {}", values.join(", "), body);
+
+        Some((value, self.ts_signature(func_type, names)?))
+    }
+
+    /// The function returned to JavaScript, it converts the arguments for the exported function
+    fn convert_export(&mut self, name: &str, func_type: &FunctionType, names: &[String]) -> Option<(String, String)> {
+        let mut lowered = Vec::new();
+        for (type_, name) in func_type.0.iter().zip(names) {
+            lowered.extend(lower(type_, name)?);
+        }
+
+        let call = format!("exports[{}]({})", quote(name), lowered.join(", "));
+        let body = match convert_type(&func_type.1).len() {
+            0 => call,
+            1 => lift(&func_type.1, &mut [call].into_iter())?,
+            // The results of the function are returned as an array
+            count => format!("{{ const result = {}; return {}; }}", call, lift(&func_type.1, &mut (0..count).map(|i| format!("result[{}]", i)))?),
+        };
+
+        let value = format!("({}) => {}", names.join(", "), body);
+
+        Some((value, self.ts_signature(func_type, names)?))
+    }
+
+    fn ts_signature(&mut self, func_type: &FunctionType, names: &[String]) -> Option<String> {
+        let params = func_type.0
+            .iter()
+            .zip(names)
+            .map(|(type_, name)| Some(format!("{}: {}", name, self.ts_type(type_)?)))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(format!("({}): {}", params.join(", "), self.ts_type(&func_type.1)?))
+    }
+
+    fn ts_type(&mut self, type_: &TypeKind) -> Option<String> {
+        match type_ {
+            TypeKind::Int | TypeKind::Float => Some("number".to_string()),
+            TypeKind::Bool => Some("boolean".to_string()),
+            TypeKind::String => Some("string".to_string()),
+            TypeKind::Nothing => Some("void".to_string()),
+            TypeKind::Vector(type_) => {
+                vector_kind(type_)?;
+                Some(format!("Array<{}>", self.ts_type(type_)?))
+            },
+            TypeKind::Class(class_type) => match class_type.kind {
+                // Objects are pointers to the memory
+                ClassKind::Normal => Some("number".to_string()),
+                // Extern objects are passed as they are
+                ClassKind::Extern => Some("unknown".to_string()),
+                ClassKind::Data => {
+                    let mut fields = Vec::new();
+                    for (name, type_) in class_type.fields.borrow().iter() {
+                        fields.push(format!("    {}: {};\n", name, self.ts_type(type_)?));
+                    }
+
+                    let decl = format!("export interface {} {{\n{}}}\n", class_type.name, fields.concat());
+                    self.push_type(&class_type.name, decl);
+
+                    Some(class_type.name.clone())
+                },
+            },
+            TypeKind::Enum(enum_type) => {
+                let variants = enum_variants(enum_type)?;
+                let decl = format!("export type {} = {};\n", enum_type.name, variants.iter().map(|name| quote(name)).collect::<Vec<_>>().join(" | "));
+                self.push_type(&enum_type.name, decl);
+
+                Some(enum_type.name.clone())
+            },
+            TypeKind::Tuple(types) => {
+                let mut converted = Vec::new();
+
+                for type_ in types {
+                    converted.push(self.ts_type(type_)?);
+                }
+
+                Some(format!("[{}]", converted.join(", ")))
+            },
+            _ => None,
+        }
+    }
+
+    fn push_type(&mut self, name: &str, decl: String) {
+        if !self.types.iter().any(|(n, _)| n == name) {
+            self.types.push((name.to_string(), decl));
+        }
+    }
+
+    fn generate_js(&self, exports: &[(String, String, String)]) -> String {
+        let mut js = String::new();
+
+        js.push_str(HEADER);
+        js.push_str(JS_RUNTIME.replace("VECTOR_HEADER_SIZE", &VECTOR_HEADER_SIZE.to_string()).as_str());
+
+        js.push_str("    const wasmImports = {\n");
+        for (module, imports) in &self.imports {
+            writeln!(js, "        {}: {{", quote(module)).unwrap();
+            for import in imports {
+                writeln!(js, "            {}: {},", quote(&import.name), import.value).unwrap();
+            }
+            js.push_str("        },\n");
+        }
+        js.push_str("    };\n\n");
+
+        js.push_str("    const result = await WebAssembly.instantiate(source, wasmImports);\n");
+        js.push_str("    const instance = result instanceof WebAssembly.Instance ? result : result.instance;\n");
+        js.push_str("    const exports = instance.exports;\n\n");
+
+        match (&self.memory.import, &self.memory.export_name) {
+            (Some(import), _) => writeln!(js, "    memory = imports[{}][{}];", quote(&import.module), quote(&import.name)).unwrap(),
+            (None, Some(name)) => writeln!(js, "    memory = exports[{}];", quote(name)).unwrap(),
+            // Strings and vectors can't be converted
+            (None, None) => (),
+        }
+        writeln!(js, "    alloc = exports[{}];\n", quote(INTERNAL_MEMORY_ALLOC)).unwrap();
+
+        js.push_str("    return {\n");
+        for (name, value, _) in exports {
+            writeln!(js, "        {}: {},", quote(name), value).unwrap();
+        }
+        js.push_str("    };\n");
+        js.push_str("}\n");
+
+        js
+    }
+
+    fn generate_dts(&self, exports: &[(String, String, String)]) -> String {
+        let mut dts = String::new();
+
+        dts.push_str(HEADER);
+        dts.push('\n');
+
+        for (_, decl) in &self.types {
+            dts.push_str(decl);
+            dts.push('\n');
+        }
+
+        dts.push_str("export interface Imports {\n");
+        for (module, imports) in &self.imports {
+            let optional = imports.iter().all(|import| import.optional);
+
+            writeln!(dts, "    {}{}: {{", quote(module), if optional { "?" } else { "" }).unwrap();
+            for import in imports {
+                writeln!(dts, "        {};", import.decl).unwrap();
+            }
+            dts.push_str("    };\n");
+        }
+        dts.push_str("}\n\n");
+
+        dts.push_str("export interface Exports {\n");
+        for (name, _, signature) in exports {
+            writeln!(dts, "    {}{};", quote(name), signature).unwrap();
+        }
+        dts.push_str("}\n\n");
+
+        let imports_param = match self.imports.iter().all(|(_, imports)| imports.iter().all(|import| import.optional)) {
+            true => "imports?: Imports",
+            false => "imports: Imports",
+        };
+        writeln!(dts, "export function instantiate(source: BufferSource | WebAssembly.Module, {}): Promise<Exports>;", imports_param).unwrap();
+
+        dts
+    }
+}
+
+const JS_RUNTIME: &str = r#"
+const encoder = new TextEncoder();
+const decoder = new TextDecoder();
+
+export async function instantiate(source, imports = {}) {
+    let memory;
+    let alloc;
+
+    const panic = (message) => {
+        throw new Error(message);
+    };
+
+    const view = () => new DataView(memory.buffer);
+
+    // Strings start with their length
+    const liftString = (ptr) => {
+        const len = view().getUint32(ptr, true);
+        return decoder.decode(new Uint8Array(memory.buffer, ptr + 4, len));
+    };
+
+    const lowerString = (value) => {
+        const bytes = encoder.encode(value);
+        const ptr = alloc(4 + bytes.length);
+        view().setUint32(ptr, bytes.length, true);
+        new Uint8Array(memory.buffer, ptr + 4, bytes.length).set(bytes);
+        return ptr;
+    };
+
+    // Vectors point to their first element, the length is right before it
+    const liftVector = (ptr, kind, liftElement) => {
+        const len = view().getUint32(ptr - 4, true);
+        const values = [];
+        for (let i = 0; i < len; i++) {
+            const value = kind === "f32" ? view().getFloat32(ptr + i * 4, true) : view().getInt32(ptr + i * 4, true);
+            values.push(liftElement(value));
+        }
+        return values;
+    };
+
+    const lowerVector = (values, kind, lowerElement) => {
+        // The elements are converted first as they can allocate too
+        const elements = values.map(lowerElement);
+        const ptr = alloc(VECTOR_HEADER_SIZE + elements.length * 4) + VECTOR_HEADER_SIZE;
+        view().setUint32(ptr - 4, elements.length, true);
+        elements.forEach((value, i) => kind === "f32"
+            ? view().setFloat32(ptr + i * 4, value, true)
+            : view().setInt32(ptr + i * 4, value, true));
+        return ptr;
+    };
+
+"#;
+
+fn quote(name: &str) -> String {
+    format!("{:?}", name)
+}
+
+/// The element of a vector has to be a single 4 bytes value
+fn vector_kind(type_: &TypeKind) -> Option<&'static str> {
+    match convert_type(type_).as_slice() {
+        [ValType::I32] => Some("i32"),
+        [ValType::F32] => Some("f32"),
+        _ => None,
+    }
+}
+
+/// The names of the variants of an enum, the enums with payloads can't be converted
+fn enum_variants(enum_type: &EnumType) -> Option<Vec<String>> {
+    enum_type.variants
+        .borrow()
+        .iter()
+        .map(|(name, type_)| match type_.as_ref() {
+            TypeKind::Nothing => Some(name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Converts the module values taken from `values` to a JavaScript value
+fn lift(type_: &TypeKind, values: &mut impl Iterator<Item=String>) -> Option<String> {
+    match type_ {
+        TypeKind::Int | TypeKind::Float => values.next(),
+        TypeKind::Nothing => Some("undefined".to_string()),
+        TypeKind::Bool => Some(format!("({} !== 0)", values.next()?)),
+        TypeKind::String => Some(format!("liftString({})", values.next()?)),
+        TypeKind::Vector(type_) => {
+            let kind = vector_kind(type_)?;
+            Some(format!("liftVector({}, \"{}\", (e) => {})", values.next()?, kind, lift(type_, &mut ["e".to_string()].into_iter())?))
+        },
+        TypeKind::Class(class_type) => match class_type.kind {
+            ClassKind::Normal | ClassKind::Extern => values.next(),
+            ClassKind::Data => {
+                let mut fields = Vec::new();
+
+                for (name, type_) in class_type.fields.borrow().iter() {
+                    fields.push(format!("{}: {}", name, lift(type_, values)?));
+                }
+
+                Some(format!("{{ {} }}", fields.join(", ")))
+            },
+        },
+        TypeKind::Enum(enum_type) => {
+            let variants = enum_variants(enum_type)?;
+            Some(format!("[{}][{}]", variants.iter().map(|name| quote(name)).collect::<Vec<_>>().join(", "), values.next()?))
+        },
+        TypeKind::Tuple(types) => {
+            let mut elements = Vec::new();
+
+            for type_ in types {
+                elements.push(lift(type_, values)?);
+            }
+
+            Some(format!("[{}]", elements.join(", ")))
+        },
+        _ => None,
+    }
+}
+
+/// Converts the JavaScript value `value` to module values
+fn lower(type_: &TypeKind, value: &str) -> Option<Vec<String>> {
+    match type_ {
+        TypeKind::Int | TypeKind::Float => Some(vec![value.to_string()]),
+        TypeKind::Nothing => Some(Vec::new()),
+        TypeKind::Bool => Some(vec![format!("({} ? 1 : 0)", value)]),
+        TypeKind::String => Some(vec![format!("lowerString({})", value)]),
+        TypeKind::Vector(type_) => {
+            let kind = vector_kind(type_)?;
+            let element = lower(type_, "e")?;
+            Some(vec![format!("lowerVector({}, \"{}\", (e) => {})", value, kind, element.first()?)])
+        },
+        TypeKind::Class(class_type) => match class_type.kind {
+            ClassKind::Normal | ClassKind::Extern => Some(vec![value.to_string()]),
+            ClassKind::Data => {
+                let mut values = Vec::new();
+
+                for (name, type_) in class_type.fields.borrow().iter() {
+                    values.extend(lower(type_, &format!("{}.{}", value, name))?);
+                }
+
+                Some(values)
+            },
+        },
+        TypeKind::Enum(enum_type) => {
+            let variants = enum_variants(enum_type)?;
+            Some(vec![format!("[{}].indexOf({})", variants.iter().map(|name| quote(name)).collect::<Vec<_>>().join(", "), value)])
+        },
+        TypeKind::Tuple(types) => {
+            let mut values = Vec::new();
+
+            for (i, type_) in types.iter().enumerate() {
+                values.extend(lower(type_, &format!("{}[{}]", value, i))?);
+            }
+
+            Some(values)
+        },
+        _ => None,
+    }
+}
+
+/// The signature of a function that takes and returns its wasm values
+fn wasm_signature(names: &[String], params: &[ValType], ret: &[ValType]) -> String {
+    let params: Vec<String> = names
+        .iter()
+        .zip(params)
+        .map(|(name, type_)| format!("{}: {}", name, wasm_ts_type(type_)))
+        .collect();
+
+    let ret = match ret {
+        [] => "void".to_string(),
+        [type_] => wasm_ts_type(type_).to_string(),
+        types => format!("[{}]", types.iter().map(wasm_ts_type).collect::<Vec<_>>().join(", ")),
+    };
+
+    format!("({}): {}", params.join(", "), ret)
+}
+
+fn wasm_ts_type(type_: &ValType) -> &'static str {
+    match type_ {
+        ValType::I64 => "bigint",
+        ValType::I32 | ValType::F32 | ValType::F64 => "number",
+        _ => "unknown",
+    }
+}
+
+const JS_RESERVED: [&str; 57] = [
+    "arguments", "await", "break", "case", "catch", "class", "const", "continue", "debugger", "default", "delete",
+    "do", "e", "else", "enum", "eval", "export", "exports", "extends", "false", "finally", "for", "function", "if",
+    "implements", "import", "imports", "in", "instanceof", "interface", "let", "liftString", "liftVector",
+    "lowerString", "lowerVector", "new", "null", "package", "panic", "private", "protected", "public", "result",
+    "return", "static", "super", "switch", "this", "throw", "true", "try", "typeof", "var", "void", "while",
+    "with", "yield",
+];
+
+/// The parameters get a `_` suffix when they are reserved words or clash with the generated code
+fn js_params(names: &[String]) -> Vec<String> {
+    names
+        .iter()
+        .map(|name| match JS_RESERVED.contains(&name.as_str()) {
+            true => format!("{}_", name),
+            false => name.clone(),
+        })
+        .collect()
+}
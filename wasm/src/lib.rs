@@ -2,5 +2,13 @@ pub mod engine;
 pub mod module;
 mod build;
 mod build_code;
+mod build_flow;
 mod build_op;
-mod build_mem;
\ No newline at end of file
+mod build_mem;
+mod wat;
+pub mod source_map;
+pub mod memory;
+pub mod wasi;
+pub mod js;
+pub mod component;
+mod rust;
\ No newline at end of file
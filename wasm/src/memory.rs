@@ -0,0 +1,94 @@
+use wasm_encoder::encoders;
+use common::errors::{BuildErrorKind, LangError};
+
+const PAGE_SIZE: u64 = 65536;
+/// A 32 bit memory can't address more than 4 GiB
+const MAX_PAGES: u64 = 65536;
+
+/// Host module and name from which the memory is imported
+#[derive(Clone, Debug)]
+pub struct MemoryImport {
+    pub module: String,
+    pub name: String,
+}
+
+/// Layout of the linear memory of the built module.
+/// The data segments start at `data_base` and the heap starts right after them
+#[derive(Clone, Debug)]
+pub struct MemoryConfig {
+    pub initial_pages: u64,
+    pub max_pages: Option<u64>,
+    /// When set the memory is provided by the host instead of being defined by the module
+    pub import: Option<MemoryImport>,
+    /// A shared memory needs a maximum size
+    pub shared: bool,
+    /// Name under which the memory is exported, if it is exported at all.
+    /// An imported memory is re-exported only when this is set
+    pub export_name: Option<String>,
+    pub data_base: u32,
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            initial_pages: 64,
+            max_pages: None,
+            import: None,
+            shared: false,
+            export_name: Some("mem".to_string()),
+            data_base: 0,
+        }
+    }
+}
+
+impl MemoryConfig {
+    /// Checks that the settings are consistent and that the data (ending at `data_end`) fits in the initial pages
+    pub(crate) fn validate(&self, data_end: u64) -> Result<(), LangError> {
+        if self.initial_pages > MAX_PAGES {
+            return Err(LangError::build(BuildErrorKind::InvalidMemoryConfig(
+                format!("the initial pages ({}) are more than the limit of {} pages", self.initial_pages, MAX_PAGES))))
+        }
+
+        if let Some(max_pages) = self.max_pages.filter(|max_pages| *max_pages > MAX_PAGES) {
+            return Err(LangError::build(BuildErrorKind::InvalidMemoryConfig(
+                format!("the maximum pages ({}) are more than the limit of {} pages", max_pages, MAX_PAGES))))
+        }
+
+        if let Some(max_pages) = self.max_pages {
+            if max_pages < self.initial_pages {
+                return Err(LangError::build(BuildErrorKind::InvalidMemoryConfig(
+                    format!("the maximum pages ({}) are less than the initial pages ({})", max_pages, self.initial_pages))))
+            }
+        }
+
+        if self.shared && self.max_pages.is_none() {
+            return Err(LangError::build(BuildErrorKind::InvalidMemoryConfig(
+                "a shared memory needs a maximum number of pages".to_string())))
+        }
+
+        if data_end > self.initial_pages * PAGE_SIZE {
+            return Err(LangError::build(BuildErrorKind::InvalidMemoryConfig(
+                format!("the data ends at {} which is out of the initial pages", data_end))))
+        }
+
+        Ok(())
+    }
+
+    /// Encodes the memory type (the memory type of `wasm_encoder` can't be shared)
+    pub(crate) fn encode_type(&self, bytes: &mut Vec<u8>) {
+        let mut flags = 0;
+        if self.max_pages.is_some() {
+            flags |= 0b01;
+        }
+        if self.shared {
+            flags |= 0b10;
+        }
+
+        bytes.push(flags);
+        bytes.extend(encoders::u64(self.initial_pages));
+
+        if let Some(max_pages) = self.max_pages {
+            bytes.extend(encoders::u64(max_pages));
+        }
+    }
+}
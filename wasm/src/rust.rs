@@ -0,0 +1,727 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::sync::Arc;
+use wasm_encoder::ValType;
+use common::ast::types::{ClassKind, ClassType, FunctionType, TypeKind};
+use common::constants::{CORE_MODULE_ID, INTERNAL_MEMORY_ALLOC, INTERNAL_PANIC};
+use common::errors::{BuildErrorKind, LangError};
+use crate::build::{convert_type, convert_types, value_names};
+use crate::build_code::{FunctionExport, FunctionImport, GlobalImport};
+use crate::build_mem::VECTOR_HEADER_SIZE;
+use crate::memory::MemoryConfig;
+
+const HEADER: &str = "// Generated by portal from the Rain sources, do not edit\n// The bindings use the `wasmtime` crate\n";
+
+/// Generates a Rust struct with a method for every export of a module and a trait for its imports,
+/// the values that can't be converted are passed as their wasm values
+pub(crate) struct RustGenerator<'a> {
+    memory: &'a MemoryConfig,
+    name: String,
+    data_classes: Vec<Arc<ClassType>>,
+}
+
+/// The statements that convert the values of a call
+struct Body {
+    lines: Vec<String>,
+    depth: usize,
+    temps: usize,
+}
+
+impl Body {
+    fn new(depth: usize) -> Self {
+        Self {
+            lines: Vec::new(),
+            depth,
+            temps: 0,
+        }
+    }
+
+    fn line(&mut self, line: impl AsRef<str>) {
+        self.lines.push(format!("{}{}", "    ".repeat(self.depth), line.as_ref()));
+    }
+
+    fn temp(&mut self) -> String {
+        self.temps += 1;
+        format!("v{}", self.temps)
+    }
+
+    fn uses(&self, name: &str) -> bool {
+        self.lines.iter().any(|line| line.contains(name))
+    }
+}
+
+impl<'a> RustGenerator<'a> {
+    pub(crate) fn new(memory: &'a MemoryConfig, name: &str) -> Self {
+        Self {
+            memory,
+            name: pascal_case(name),
+            data_classes: Vec::new(),
+        }
+    }
+
+    pub(crate) fn generate(
+        mut self,
+        exports: &[FunctionExport],
+        function_imports: &[FunctionImport],
+        global_imports: &[GlobalImport],
+    ) -> Result<String, LangError> {
+        let memory_export = match (&self.memory.import, &self.memory.export_name) {
+            (Some(_), _) if self.memory.shared => return Err(LangError::build(BuildErrorKind::Unsupported(
+                "The Rust bindings don't support a shared imported memory".to_string()))),
+            (Some(_), _) => None,
+            (None, Some(name)) => Some(name.clone()),
+            (None, None) => return Err(LangError::build(BuildErrorKind::Unsupported(
+                "The Rust bindings need the memory to be exported or imported".to_string()))),
+        };
+
+        let mut trait_methods = Vec::new();
+        let mut linker = Vec::new();
+
+        for func in function_imports {
+            let func_type = match &func.metadata {
+                Some(func_type) => func_type,
+                None => continue,
+            };
+
+            let (method, closure) = self.generate_import(func, func_type, &memory_export)?;
+            trait_methods.extend(method);
+            linker.push(closure);
+        }
+
+        for global in global_imports {
+            let (method, define) = self.generate_global(global);
+            trait_methods.push(method);
+            linker.push(define);
+        }
+
+        let mut methods = Vec::new();
+        for func in exports {
+            methods.push(self.generate_export(func)?);
+        }
+
+        let mut rs = String::new();
+
+        for class_type in self.data_classes.clone() {
+            writeln!(rs, "\n/// The data class `{}`", class_type.name).unwrap();
+            rs.push_str("#[derive(Clone, Debug, Default, PartialEq)]\n");
+            writeln!(rs, "pub struct {} {{", class_type.name).unwrap();
+            for (name, type_) in class_type.fields.borrow().iter() {
+                writeln!(rs, "    pub {}: {},", rust_ident(name), self.rust_type(type_).unwrap()).unwrap();
+            }
+            rs.push_str("}\n");
+        }
+
+        writeln!(rs, "\n/// The functions and variables imported by the module, implemented by the host").unwrap();
+        writeln!(rs, "pub trait {}Imports {{", self.name).unwrap();
+        for method in &trait_methods {
+            writeln!(rs, "    {}", method).unwrap();
+        }
+        rs.push_str("}\n");
+
+        writeln!(rs, "\npub struct {}<H: {}Imports> {{", self.name, self.name).unwrap();
+        rs.push_str("    store: Store<H>,\n");
+        rs.push_str("    instance: Instance,\n");
+        rs.push_str("    memory: Memory,\n");
+        rs.push_str("    alloc: TypedFunc<i32, i32>,\n");
+        rs.push_str("}\n");
+
+        writeln!(rs, "\nimpl<H: {}Imports + 'static> {}<H> {{", self.name, self.name).unwrap();
+        rs.push_str("    pub fn new(engine: &Engine, wasm: &[u8], imports: H) -> wasmtime::Result<Self> {\n");
+        rs.push_str("        let module = Module::new(engine, wasm)?;\n");
+        rs.push_str("        let mut store = Store::new(engine, imports);\n");
+        rs.push_str("        let mut linker = Linker::new(engine);\n");
+
+        if let Some(import) = &self.memory.import {
+            let max = match self.memory.max_pages {
+                Some(max) => format!("Some({})", max),
+                None => "None".to_string(),
+            };
+
+            writeln!(rs, "        let memory = Memory::new(&mut store, MemoryType::new({}, {}))?;", self.memory.initial_pages, max).unwrap();
+            writeln!(rs, "        linker.define(&store, {:?}, {:?}, memory)?;", import.module, import.name).unwrap();
+        }
+
+        for item in &linker {
+            rs.push('\n');
+            rs.push_str(item);
+        }
+
+        rs.push_str("\n        let instance = linker.instantiate(&mut store, &module)?;\n");
+        if let Some(name) = &memory_export {
+            writeln!(rs, "        let memory = instance.get_memory(&mut store, {:?})", name).unwrap();
+            rs.push_str("            .ok_or_else(|| wasmtime::Error::msg(\"The module does not export its memory\"))?;\n");
+        }
+        writeln!(rs, "        let alloc = instance.get_typed_func::<i32, i32>(&mut store, {:?})?;\n", INTERNAL_MEMORY_ALLOC).unwrap();
+        rs.push_str("        Ok(Self { store, instance, memory, alloc })\n");
+        rs.push_str("    }\n\n");
+
+        rs.push_str("    pub fn imports(&self) -> &H {\n        self.store.data()\n    }\n\n");
+        rs.push_str("    pub fn imports_mut(&mut self) -> &mut H {\n        self.store.data_mut()\n    }\n");
+
+        for method in &methods {
+            rs.push('\n');
+            rs.push_str(method);
+        }
+        rs.push_str("}\n");
+
+        rs.push_str(RUST_RUNTIME.replace("VECTOR_HEADER_SIZE", &VECTOR_HEADER_SIZE.to_string()).as_str());
+
+        if let Some(name) = &memory_export {
+            rs.push_str(&format!(r#"
+#[allow(dead_code)]
+fn caller_memory<T>(caller: &mut Caller<'_, T>) -> wasmtime::Result<Memory> {{
+    caller.get_export({:?})
+        .and_then(|export| export.into_memory())
+        .ok_or_else(|| wasmtime::Error::msg("The module does not export its memory"))
+}}
+"#, name));
+        }
+
+        // Only the items that are used are imported
+        let words: HashSet<&str> = rs.split(|c: char| !c.is_alphanumeric() && c != '_').collect();
+        let items: Vec<&str> = WASMTIME_ITEMS
+            .iter()
+            .copied()
+            .filter(|item| words.contains(item))
+            .collect();
+
+        Ok(format!("{}\nuse wasmtime::{{{}}};\n{}", HEADER, items.join(", "), rs))
+    }
+
+    /// The trait method and the function given to the linker
+    fn generate_import(&mut self, func: &FunctionImport, func_type: &FunctionType, memory_export: &Option<String>) -> Result<(Option<String>, String), LangError> {
+        let params = wasm_types(&func.params)?;
+        let ret = wasm_results(&func.ret)?;
+        let method = rust_ident(&func.name);
+
+        let names: Vec<String> = func.param_names.iter().map(|name| rust_param(name)).collect();
+        let value_names = value_names(&names, &func_type.0);
+        let closure_params: String = value_names
+            .iter()
+            .zip(&params)
+            .map(|(name, type_)| format!(", {}: {}", name, type_))
+            .collect();
+
+        let mut body = Body::new(3);
+        let mut values = value_names.iter().cloned();
+
+        let is_panic = func.module_name == CORE_MODULE_ID && func.name == INTERNAL_PANIC;
+
+        // The arguments converted to Rust, or the wasm values when they can't be converted
+        let converted = func_type.0
+            .iter()
+            .map(|type_| self.lift(&mut body, type_, &mut values))
+            .collect::<Option<Vec<_>>>();
+        let typed = converted.is_some() && self.rust_type(&func_type.1).is_some();
+
+        let trait_method = match (is_panic, typed) {
+            // A panic stops the call of the export
+            (true, _) => {
+                let message = converted.and_then(|args| args.first().cloned()).unwrap_or(value_names.join(", "));
+                body.line(format!("Err(wasmtime::Error::msg(format!(\"panic: {{}}\", {})))", message));
+                None
+            },
+            (false, true) => {
+                let args = converted.unwrap();
+                let call = format!("ctx.data_mut().{}({})", method, args.join(", "));
+
+                let results = match &*func_type.1 {
+                    TypeKind::Nothing => {
+                        body.line(format!("{};", call));
+                        Vec::new()
+                    },
+                    type_ => {
+                        let result = body.temp();
+                        body.line(format!("let {} = {};", result, call));
+                        self.lower(&mut body, type_, &result).unwrap()
+                    },
+                };
+                body.line(format!("Ok({})", tuple(&results)));
+
+                let params: Vec<String> = names
+                    .iter()
+                    .zip(&func_type.0)
+                    .map(|(name, type_)| format!(", {}: {}", name, self.rust_type(type_).unwrap()))
+                    .collect();
+
+                Some(format!("fn {}(&mut self{}){};", method, params.concat(), rust_return(&self.rust_type(&func_type.1).unwrap())))
+            },
+            (false, false) => {
+                body = Body::new(3);
+                body.line(format!("Ok(ctx.data_mut().{}({}))", method, value_names.join(", ")));
+
+                Some(format!("fn {}(&mut self{}){};", method, closure_params, rust_return(&ret)))
+            },
+        };
+
+        let mut closure = String::new();
+        writeln!(closure, "        linker.func_wrap({:?}, {:?}, move |mut caller: Caller<'_, H>{}| -> wasmtime::Result<{}> {{",
+            func.module_name, func.name, closure_params, ret).unwrap();
+
+        if body.uses("&memory") && memory_export.is_some() {
+            closure.push_str("            let memory = caller_memory(&mut caller)?;\n");
+        }
+        if body.uses("&alloc") {
+            writeln!(closure, "            let alloc = caller.get_export({:?}).and_then(|export| export.into_func())", INTERNAL_MEMORY_ALLOC).unwrap();
+            closure.push_str("                .ok_or_else(|| wasmtime::Error::msg(\"The module does not export its allocator\"))?\n");
+            closure.push_str("                .typed::<i32, i32>(&caller)?;\n");
+        }
+        closure.push_str("            let ctx = &mut caller;\n");
+
+        for line in &body.lines {
+            writeln!(closure, "{}", line).unwrap();
+        }
+        closure.push_str("        })?;\n");
+
+        Ok((trait_method, closure))
+    }
+
+    /// Imported variables are given once, when the module is instantiated
+    fn generate_global(&mut self, global: &GlobalImport) -> (String, String) {
+        let method = rust_ident(&global.name);
+
+        let (rust_type, val_type, value) = match (&global.metadata, global.val_type) {
+            (TypeKind::Bool, _) => ("bool", "I32", "Val::I32(value as i32)"),
+            (_, ValType::F32) => ("f32", "F32", "Val::F32(value.to_bits())"),
+            (_, ValType::F64) => ("f64", "F64", "Val::F64(value.to_bits())"),
+            (_, ValType::I64) => ("i64", "I64", "Val::I64(value)"),
+            _ => ("i32", "I32", "Val::I32(value)"),
+        };
+
+        let mut define = String::new();
+        writeln!(define, "        let value = store.data().{}();", method).unwrap();
+        writeln!(define, "        let global = Global::new(&mut store, GlobalType::new(ValType::{}, Mutability::Const), {})?;", val_type, value).unwrap();
+        writeln!(define, "        linker.define(&store, {:?}, {:?}, global)?;", global.module_name, global.name).unwrap();
+
+        (format!("fn {}(&self) -> {};", method, rust_type), define)
+    }
+
+    fn generate_export(&mut self, func: &FunctionExport) -> Result<String, LangError> {
+        let func_type = &func.metadata;
+        let params = wasm_types(&convert_types(&func_type.0))?;
+        let results = convert_type(&func_type.1);
+        let ret = wasm_results(&results)?;
+        let method = rust_ident(&func.name);
+        let names: Vec<String> = func.param_names.iter().map(|name| rust_param(name)).collect();
+
+        let mut body = Body::new(2);
+        let mut args = Vec::new();
+        let mut values = Vec::new();
+
+        let typed = func_type.0.iter().all(|type_| self.rust_type(type_).is_some())
+            && self.rust_type(&func_type.1).is_some();
+
+        let rust_ret = match typed {
+            true => {
+                for (name, type_) in names.iter().zip(&func_type.0) {
+                    args.push(format!(", {}: {}", name, self.rust_type(type_).unwrap()));
+                    values.extend(self.lower(&mut body, type_, name).unwrap());
+                }
+
+                self.rust_type(&func_type.1).unwrap()
+            },
+            false => {
+                for (name, type_) in value_names(&names, &func_type.0).into_iter().zip(&params) {
+                    args.push(format!(", {}: {}", name, type_));
+                    values.push(name);
+                }
+
+                ret.clone()
+            },
+        };
+
+        let call = format!("func.call(&mut *ctx, {})?", param_tuple(&values));
+
+        // Nothing is returned
+        match results.len() {
+            0 => body.line(format!("{};", call)),
+            _ => body.line(format!("let result = {};", call)),
+        }
+
+        let result = match (typed, results.len()) {
+            (_, 0) => "()".to_string(),
+            (false, _) => "result".to_string(),
+            (true, 1) => self.lift(&mut body, &func_type.1, &mut vec!["result".to_string()].into_iter()).unwrap(),
+            (true, count) => self.lift(&mut body, &func_type.1, &mut (0..count).map(|i| format!("result.{}", i))).unwrap(),
+        };
+        body.line(format!("Ok({})", result));
+
+        let mut method_str = String::new();
+        writeln!(method_str, "    pub fn {}(&mut self{}) -> wasmtime::Result<{}> {{", method, args.concat(), rust_ret).unwrap();
+        if body.uses("&memory") {
+            method_str.push_str("        let memory = self.memory;\n");
+        }
+        if body.uses("&alloc") {
+            method_str.push_str("        let alloc = self.alloc.clone();\n");
+        }
+        method_str.push_str("        let ctx = &mut self.store;\n");
+        writeln!(method_str, "        let func = self.instance.get_typed_func::<{}, {}>(&mut *ctx, {:?})?;", param_tuple(&params), ret, func.name).unwrap();
+        for line in &body.lines {
+            writeln!(method_str, "{}", line).unwrap();
+        }
+        method_str.push_str("    }\n");
+
+        Ok(method_str)
+    }
+
+    fn rust_type(&mut self, type_: &TypeKind) -> Option<String> {
+        match type_ {
+            TypeKind::Int => Some("i32".to_string()),
+            TypeKind::Float => Some("f32".to_string()),
+            TypeKind::Bool => Some("bool".to_string()),
+            TypeKind::String => Some("String".to_string()),
+            TypeKind::Nothing => Some("()".to_string()),
+            TypeKind::Vector(type_) => {
+                vector_kind(type_)?;
+                Some(format!("Vec<{}>", self.rust_type(type_)?))
+            },
+            TypeKind::Class(class_type) => match class_type.kind {
+                // Objects are pointers to the memory
+                ClassKind::Normal => Some("i32".to_string()),
+                // TODO: Map extern objects to the rooted references of the host
+                ClassKind::Extern => None,
+                ClassKind::Data => {
+                    for (_, type_) in class_type.fields.borrow().iter() {
+                        self.rust_type(type_)?;
+                    }
+
+                    if !self.data_classes.iter().any(|c| c.name == class_type.name) {
+                        self.data_classes.push(class_type.clone());
+                    }
+
+                    Some(class_type.name.clone())
+                },
+            },
+            TypeKind::Tuple(types) => {
+                let mut converted = Vec::new();
+
+                for type_ in types {
+                    converted.push(self.rust_type(type_)?);
+                }
+
+                Some(tuple(&converted))
+            },
+            _ => None,
+        }
+    }
+
+    /// Converts the wasm values taken from `values` to a Rust value
+    fn lift(&mut self, body: &mut Body, type_: &TypeKind, values: &mut impl Iterator<Item=String>) -> Option<String> {
+        match type_ {
+            TypeKind::Int | TypeKind::Float => values.next(),
+            TypeKind::Nothing => Some("()".to_string()),
+            TypeKind::Bool => Some(format!("{} != 0", values.next()?)),
+            TypeKind::String => {
+                let temp = body.temp();
+                body.line(format!("let {} = lift_string(&memory, &mut *ctx, {})?;", temp, values.next()?));
+                Some(temp)
+            },
+            TypeKind::Vector(element) => {
+                let kind = vector_kind(element)?;
+                let value = values.next()?;
+                let temp = body.temp();
+                let element_temp = body.temp();
+
+                let start = body.lines.len();
+
+                body.line(format!("let mut {} = Vec::new();", temp));
+                body.line(format!("for {} in lift_vector_{}(&memory, &mut *ctx, {})? {{", element_temp, kind, value));
+                body.depth += 1;
+                let element = self.lift(body, element, &mut vec![element_temp.clone()].into_iter())?;
+                body.depth -= 1;
+
+                // Numbers don't need to be converted
+                if element == element_temp && body.lines.len() == start + 2 {
+                    body.lines.truncate(start);
+                    body.line(format!("let {} = lift_vector_{}(&memory, &mut *ctx, {})?;", temp, kind, value));
+
+                    return Some(temp)
+                }
+
+                body.depth += 1;
+                body.line(format!("{}.push({});", temp, element));
+                body.depth -= 1;
+                body.line("}");
+
+                Some(temp)
+            },
+            TypeKind::Class(class_type) => match class_type.kind {
+                ClassKind::Normal => values.next(),
+                ClassKind::Extern => None,
+                ClassKind::Data => {
+                    let fields = class_type.fields.borrow();
+                    let mut converted = Vec::new();
+
+                    for (name, type_) in fields.iter() {
+                        converted.push(format!("{}: {}", rust_ident(name), self.lift(body, type_, values)?));
+                    }
+
+                    Some(format!("{} {{ {} }}", class_type.name, converted.join(", ")))
+                },
+            },
+            TypeKind::Tuple(types) => {
+                let mut converted = Vec::new();
+
+                for type_ in types {
+                    converted.push(self.lift(body, type_, values)?);
+                }
+
+                Some(tuple(&converted))
+            },
+            _ => None,
+        }
+    }
+
+    /// Converts the Rust value `value` to wasm values
+    fn lower(&mut self, body: &mut Body, type_: &TypeKind, value: &str) -> Option<Vec<String>> {
+        match type_ {
+            TypeKind::Int | TypeKind::Float => Some(vec![value.to_string()]),
+            TypeKind::Nothing => Some(Vec::new()),
+            TypeKind::Bool => Some(vec![format!("{} as i32", value)]),
+            TypeKind::String => {
+                let temp = body.temp();
+                body.line(format!("let {} = lower_string(&memory, &alloc, &mut *ctx, &{})?;", temp, value));
+                Some(vec![temp])
+            },
+            TypeKind::Vector(element) => {
+                let kind = vector_kind(element)?;
+                let elements = body.temp();
+                let element_temp = body.temp();
+                let temp = body.temp();
+
+                let start = body.lines.len();
+
+                // The elements are converted first as they can allocate too
+                body.line(format!("let mut {} = Vec::new();", elements));
+                body.line(format!("for {} in {} {{", element_temp, value));
+                body.depth += 1;
+                let element = self.lower(body, element, &element_temp)?;
+                body.depth -= 1;
+
+                if element.first()? == &element_temp && body.lines.len() == start + 2 {
+                    body.lines.truncate(start);
+                    body.line(format!("let {} = lower_vector_{}(&memory, &alloc, &mut *ctx, &{})?;", temp, kind, value));
+
+                    return Some(vec![temp])
+                }
+
+                body.depth += 1;
+                body.line(format!("{}.push({});", elements, element.first()?));
+                body.depth -= 1;
+                body.line("}");
+                body.line(format!("let {} = lower_vector_{}(&memory, &alloc, &mut *ctx, &{})?;", temp, kind, elements));
+
+                Some(vec![temp])
+            },
+            TypeKind::Class(class_type) => match class_type.kind {
+                ClassKind::Normal => Some(vec![value.to_string()]),
+                ClassKind::Extern => None,
+                ClassKind::Data => {
+                    let fields = class_type.fields.borrow();
+                    let mut values = Vec::new();
+
+                    for (name, type_) in fields.iter() {
+                        values.extend(self.lower(body, type_, &format!("{}.{}", value, rust_ident(name)))?);
+                    }
+
+                    Some(values)
+                },
+            },
+            TypeKind::Tuple(types) => {
+                let mut values = Vec::new();
+
+                for (i, type_) in types.iter().enumerate() {
+                    values.extend(self.lower(body, type_, &format!("{}.{}", value, i))?);
+                }
+
+                Some(values)
+            },
+            _ => None,
+        }
+    }
+}
+
+const WASMTIME_ITEMS: [&str; 16] = [
+    "AsContext", "AsContextMut", "Caller", "Engine", "Global", "GlobalType", "Instance", "Linker",
+    "Memory", "MemoryType", "Module", "Mutability", "Store", "TypedFunc", "Val", "ValType",
+];
+
+const RUST_RUNTIME: &str = r#"
+const VECTOR_HEADER: usize = VECTOR_HEADER_SIZE;
+
+fn out_of_bounds() -> wasmtime::Error {
+    wasmtime::Error::msg("Out of bounds memory access")
+}
+
+fn read_u32(data: &[u8], offset: usize) -> wasmtime::Result<u32> {
+    let bytes = data.get(offset..offset + 4).ok_or_else(out_of_bounds)?;
+    Ok(u32::from_le_bytes(bytes.try_into()?))
+}
+
+/// Strings start with their length
+#[allow(dead_code)]
+fn lift_string(memory: &Memory, ctx: impl AsContext, ptr: i32) -> wasmtime::Result<String> {
+    let data = memory.data(&ctx);
+    let len = read_u32(data, ptr as usize)? as usize;
+    let bytes = data.get(ptr as usize + 4..ptr as usize + 4 + len).ok_or_else(out_of_bounds)?;
+    Ok(String::from_utf8(bytes.to_vec())?)
+}
+
+#[allow(dead_code)]
+fn lower_string(memory: &Memory, alloc: &TypedFunc<i32, i32>, mut ctx: impl AsContextMut, value: &str) -> wasmtime::Result<i32> {
+    let ptr = alloc.call(&mut ctx, 4 + value.len() as i32)?;
+    memory.write(&mut ctx, ptr as usize, &(value.len() as u32).to_le_bytes())?;
+    memory.write(&mut ctx, ptr as usize + 4, value.as_bytes())?;
+    Ok(ptr)
+}
+
+/// Vectors point to their first element, the length is right before it
+#[allow(dead_code)]
+fn lift_vector_i32(memory: &Memory, ctx: impl AsContext, ptr: i32) -> wasmtime::Result<Vec<i32>> {
+    let data = memory.data(&ctx);
+    let len = read_u32(data, (ptr as usize).checked_sub(4).ok_or_else(out_of_bounds)?)? as usize;
+    (0..len).map(|i| read_u32(data, ptr as usize + i * 4).map(|value| value as i32)).collect()
+}
+
+#[allow(dead_code)]
+fn lift_vector_f32(memory: &Memory, ctx: impl AsContext, ptr: i32) -> wasmtime::Result<Vec<f32>> {
+    let data = memory.data(&ctx);
+    let len = read_u32(data, (ptr as usize).checked_sub(4).ok_or_else(out_of_bounds)?)? as usize;
+    (0..len).map(|i| read_u32(data, ptr as usize + i * 4).map(f32::from_bits)).collect()
+}
+
+#[allow(dead_code)]
+fn lower_vector_i32(memory: &Memory, alloc: &TypedFunc<i32, i32>, mut ctx: impl AsContextMut, values: &[i32]) -> wasmtime::Result<i32> {
+    let ptr = alloc.call(&mut ctx, (VECTOR_HEADER + values.len() * 4) as i32)? as usize + VECTOR_HEADER;
+    memory.write(&mut ctx, ptr - 4, &(values.len() as u32).to_le_bytes())?;
+    for (i, value) in values.iter().enumerate() {
+        memory.write(&mut ctx, ptr + i * 4, &value.to_le_bytes())?;
+    }
+    Ok(ptr as i32)
+}
+
+#[allow(dead_code)]
+fn lower_vector_f32(memory: &Memory, alloc: &TypedFunc<i32, i32>, mut ctx: impl AsContextMut, values: &[f32]) -> wasmtime::Result<i32> {
+    let ptr = alloc.call(&mut ctx, (VECTOR_HEADER + values.len() * 4) as i32)? as usize + VECTOR_HEADER;
+    memory.write(&mut ctx, ptr - 4, &(values.len() as u32).to_le_bytes())?;
+    for (i, value) in values.iter().enumerate() {
+        memory.write(&mut ctx, ptr + i * 4, &value.to_le_bytes())?;
+    }
+    Ok(ptr as i32)
+}
+"#;
+
+/// The element of a vector has to be a single 4 bytes value
+fn vector_kind(type_: &TypeKind) -> Option<&'static str> {
+    match convert_type(type_).as_slice() {
+        [ValType::I32] => Some("i32"),
+        [ValType::F32] => Some("f32"),
+        _ => None,
+    }
+}
+
+fn wasm_types(types: &[ValType]) -> Result<Vec<String>, LangError> {
+    types
+        .iter()
+        .map(|type_| match type_ {
+            ValType::I32 => Ok("i32".to_string()),
+            ValType::I64 => Ok("i64".to_string()),
+            ValType::F32 => Ok("f32".to_string()),
+            ValType::F64 => Ok("f64".to_string()),
+            _ => Err(LangError::build(BuildErrorKind::Unsupported(
+                format!("The Rust bindings don't support the wasm type {:?}", type_)))),
+        })
+        .collect()
+}
+
+fn wasm_results(types: &[ValType]) -> Result<String, LangError> {
+    let types = wasm_types(types)?;
+
+    Ok(match types.as_slice() {
+        [type_] => type_.clone(),
+        types => format!("({})", types.join(", ")),
+    })
+}
+
+/// The parameters of a `TypedFunc` are a tuple
+fn param_tuple(values: &[String]) -> String {
+    match values {
+        [value] => format!("({},)", value),
+        values => format!("({})", values.join(", ")),
+    }
+}
+
+fn tuple(values: &[String]) -> String {
+    match values {
+        [value] => value.clone(),
+        values => format!("({})", values.join(", ")),
+    }
+}
+
+fn rust_return(type_: &str) -> String {
+    match type_ {
+        "()" => String::new(),
+        type_ => format!(" -> {}", type_),
+    }
+}
+
+const RUST_KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+];
+
+/// Rain names are camel case, `::` separates a class from its method
+fn rust_ident(name: &str) -> String {
+    let mut ident = String::new();
+
+    for (i, c) in name.replace("::", "_").chars().enumerate() {
+        if c.is_uppercase() && i > 0 && !ident.ends_with('_') {
+            ident.push('_');
+        }
+
+        match c.is_alphanumeric() {
+            true => ident.extend(c.to_lowercase()),
+            false => ident.push('_'),
+        }
+    }
+
+    match RUST_KEYWORDS.contains(&ident.as_str()) {
+        true => format!("r#{}", ident),
+        false => ident,
+    }
+}
+
+/// The names used by the generated code, the parameters can't shadow them
+const RUST_RESERVED: [&str; 15] = [
+    "alloc", "caller", "caller_memory", "ctx", "func", "lift_string", "lift_vector_f32", "lift_vector_i32",
+    "lower_string", "lower_vector_f32", "lower_vector_i32", "memory", "out_of_bounds", "read_u32", "result",
+];
+
+/// A parameter gets a `_` suffix when it would clash with the generated code, `self` can't be a raw identifier
+fn rust_param(name: &str) -> String {
+    let ident = rust_ident(name);
+    let is_temp = ident.strip_prefix('v').is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+
+    match ident.strip_prefix("r#") {
+        Some(keyword @ ("self" | "Self" | "super" | "crate")) => format!("{}_", keyword),
+        _ if is_temp || RUST_RESERVED.contains(&ident.as_str()) => format!("{}_", ident),
+        _ => ident,
+    }
+}
+
+fn pascal_case(name: &str) -> String {
+    let ident: String = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map_or(String::new(), |first| first.to_uppercase().chain(chars).collect())
+        })
+        .collect();
+
+    match ident.chars().next() {
+        Some(c) if c.is_alphabetic() => ident,
+        _ => format!("Module{}", ident),
+    }
+}
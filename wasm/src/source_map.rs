@@ -0,0 +1,119 @@
+const BASE64_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// A source map (version 3) that maps the offsets of the instructions inside the
+/// wasm module to the lines of the Rain sources
+pub struct SourceMap {
+    /// Name of every source with its code, when it's not a file
+    sources: Vec<(String, Option<String>)>,
+    /// Instruction offset, index of the source and line (starting from 0)
+    mappings: Vec<(usize, usize, usize)>,
+}
+
+impl SourceMap {
+    pub(crate) fn new() -> Self {
+        Self {
+            sources: Vec::new(),
+            mappings: Vec::new(),
+        }
+    }
+
+    pub(crate) fn add_source(&mut self, source: String, content: Option<String>) -> usize {
+        match self.sources.iter().position(|(s, _)| *s == source) {
+            Some(index) => index,
+            None => {
+                self.sources.push((source, content));
+                self.sources.len() - 1
+            },
+        }
+    }
+
+    /// Maps an instruction offset to a line (starting from 1) of a source
+    pub(crate) fn add_mapping(&mut self, offset: usize, source: usize, line: usize) {
+        self.mappings.push((offset, source, line.saturating_sub(1)));
+    }
+
+    /// Creates the json of the source map, `source_root` is the path of the sources relative to the map
+    pub fn to_json(&self, source_root: &str) -> String {
+        let mut mappings = self.mappings.clone();
+        mappings.sort_by_key(|(offset, _, _)| *offset);
+
+        // Every segment is relative to the previous one
+        let mut encoded = String::new();
+        let (mut last_offset, mut last_source, mut last_line) = (0, 0, 0);
+
+        for (i, (offset, source, line)) in mappings.into_iter().enumerate() {
+            if i > 0 {
+                encoded.push(',');
+            }
+
+            encode_vlq(offset as i64 - last_offset as i64, &mut encoded);
+            encode_vlq(source as i64 - last_source as i64, &mut encoded);
+            encode_vlq(line as i64 - last_line as i64, &mut encoded);
+            encode_vlq(0, &mut encoded);
+
+            (last_offset, last_source, last_line) = (offset, source, line);
+        }
+
+        let sources: Vec<String> = self.sources
+            .iter()
+            .map(|(source, _)| json_string(source))
+            .collect();
+
+        // The sources without a file have their code embedded
+        let contents = match self.sources.iter().any(|(_, content)| content.is_some()) {
+            true => {
+                let contents: Vec<String> = self.sources
+                    .iter()
+                    .map(|(_, content)| content.as_deref().map_or("null".to_string(), json_string))
+                    .collect();
+
+                format!(",\"sourcesContent\":[{}]", contents.join(","))
+            },
+            false => String::new(),
+        };
+
+        format!(
+            "{{\"version\":3,\"sourceRoot\":{},\"sources\":[{}]{},\"names\":[],\"mappings\":{}}}",
+            json_string(source_root),
+            sources.join(","),
+            contents,
+            json_string(&encoded))
+    }
+}
+
+fn encode_vlq(value: i64, result: &mut String) {
+    let mut value = match value < 0 {
+        true => ((-value) << 1) | 1,
+        false => value << 1,
+    };
+
+    loop {
+        let mut digit = value & 0b11111;
+        value >>= 5;
+
+        if value > 0 {
+            digit |= 0b100000;
+        }
+
+        result.push(BASE64_CHARS[digit as usize] as char);
+
+        if value == 0 { break }
+    }
+}
+
+fn json_string(string: &str) -> String {
+    let mut result = String::with_capacity(string.len() + 2);
+    result.push('"');
+
+    for c in string.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+
+    result.push('"');
+    result
+}
@@ -0,0 +1,518 @@
+use std::sync::Arc;
+use wasm_encoder::{BlockType, Instruction, MemArg, ValType};
+use common::ast::types::{FunctionType, TypeKind};
+use common::constants::{CORE_MODULE_ID, INTERNAL_MEMORY_ALLOC, INTERNAL_PANIC, MAIN_FUNCTION_NAME, STD_MODULE_ID};
+use common::errors::{BuildErrorKind, LangError};
+use common::module::{FunctionDefinition, Module, ModuleUID};
+use crate::build::{convert_type, convert_types};
+use crate::build_code::{FunctionData, FunctionImport, ModuleBuilder, ModuleDataKind};
+use crate::engine::BuildTarget;
+
+/// Host module of the WASI functions
+pub const WASI_IMPORT_MODULE: &str = "wasi_snapshot_preview1";
+/// Entry point called by the WASI runtimes
+pub const WASI_START_FUNCTION: &str = "_start";
+/// WASI runtimes expect the memory under this name
+pub const WASI_MEMORY_EXPORT: &str = "memory";
+
+const FD_STDIN: i32 = 0;
+const FD_STDOUT: i32 = 1;
+const FD_STDERR: i32 = 2;
+const CLOCK_MONOTONIC: i32 = 1;
+const PANIC_EXIT_CODE: i32 = 101;
+/// Longest line returned by `readLine`, the rest of the line is left for the next call
+const LINE_CAPACITY: i32 = 1024;
+
+/// The scratch area holds the arguments and results of the WASI calls, it starts with up to 4 io vectors
+const SCRATCH_SIZE: usize = 48;
+const SCRATCH_RESULT: u64 = 32;
+const SCRATCH_RESULT_2: u64 = 40;
+
+/// (name, params, results) of the imported WASI functions
+const WASI_IMPORTS: [(&str, &[ValType], &[ValType]); 6] = [
+    ("fd_write", &[ValType::I32, ValType::I32, ValType::I32, ValType::I32], &[ValType::I32]),
+    ("fd_read", &[ValType::I32, ValType::I32, ValType::I32, ValType::I32], &[ValType::I32]),
+    ("args_sizes_get", &[ValType::I32, ValType::I32], &[ValType::I32]),
+    ("args_get", &[ValType::I32, ValType::I32], &[ValType::I32]),
+    ("proc_exit", &[ValType::I32], &[]),
+    ("clock_time_get", &[ValType::I32, ValType::I64, ValType::I32], &[ValType::I32]),
+];
+
+/// Data segments shared by the WASI functions
+pub(crate) struct WasiData {
+    scratch: u32,
+    newline: u32,
+    panic_prefix: (u32, i32),
+}
+
+impl<'a> ModuleBuilder<'a> {
+    /// The functions declared by the standard library and the panic handler of the core module
+    /// are implemented by the builder on top of the WASI imports
+    pub(crate) fn is_wasi_func(&self, module: &Module) -> bool {
+        match self.target {
+            BuildTarget::Wasi => [STD_MODULE_ID, CORE_MODULE_ID].contains(&module.import_module_name()),
+            BuildTarget::Host | BuildTarget::Component => false,
+        }
+    }
+
+    pub(crate) fn insert_wasi_imports(&mut self) {
+        for (name, params, ret) in WASI_IMPORTS {
+            // They can't be called from Rain, so they have no Rain type
+            self.function_names.push((ModuleUID::from_string(WASI_IMPORT_MODULE.to_string()), name.to_string()));
+            self.functions.push((Vec::new(), TypeKind::Nothing));
+
+            self.function_imports.push(FunctionImport {
+                module_name: WASI_IMPORT_MODULE.to_string(),
+                name: name.to_string(),
+
+                params: params.to_vec(),
+                ret: ret.to_vec(),
+                metadata: None,
+                param_names: Vec::new(),
+            });
+        }
+    }
+
+    pub(crate) fn load_wasi_func(&mut self, module_uid: ModuleUID, func: &FunctionDefinition, name: &String) -> Result<(u32, &Vec<TypeKind>, &TypeKind), LangError> {
+        let mut builder = WasiFunctionBuilder::new(self, &func.metadata)?;
+
+        match name.as_str() {
+            "print" => builder.build_print()?,
+            "readLine" => builder.build_read_line()?,
+            "argCount" => builder.build_arg_count()?,
+            "arg" => builder.build_arg()?,
+            "exit" => builder.build_exit()?,
+            "clock" => builder.build_clock()?,
+            INTERNAL_PANIC => builder.build_panic()?,
+            _ => return Err(LangError::build(BuildErrorKind::Unsupported(
+                format!("`{}` is not available on the WASI target", name)))),
+        }
+
+        let function_data = builder.build(name, module_uid, &func.metadata);
+        self.function_data.push(function_data);
+
+        self.function_names.push((module_uid, name.clone()));
+        self.functions.push((func.metadata.0.clone(), *func.metadata.1.clone()));
+
+        let (params, ret) = self.functions
+            .last()
+            .unwrap();
+
+        Ok((
+            self.functions.len() as u32 - 1,
+            params,
+            ret
+        ))
+    }
+
+    /// Inserts the `_start` function, it calls the main function of `module` and exits with its result
+    pub(crate) fn insert_start_func(&mut self, module: Arc<Module>) -> Result<(), LangError> {
+        let (main_id, params, ret) = self.get_func(module.uid, &MAIN_FUNCTION_NAME.to_string())?;
+        let (params, ret) = (params.clone(), ret.clone());
+
+        if !params.is_empty() {
+            return Err(LangError::build(BuildErrorKind::Unsupported(
+                "The main function of a WASI program can't have parameters, use `argCount` and `arg`".to_string())));
+        }
+
+        let start_type = FunctionType(Vec::new(), Box::new(TypeKind::Nothing));
+        let mut builder = WasiFunctionBuilder::new(self, &start_type)?;
+
+        builder.instructions.push(Instruction::Call(main_id));
+
+        match ret {
+            TypeKind::Nothing => (),
+            TypeKind::Int => builder.call_wasi("proc_exit")?,
+            ret => for _ in convert_type(&ret) {
+                builder.instructions.push(Instruction::Drop);
+            },
+        }
+
+        let function_data = builder.build(WASI_START_FUNCTION, module.uid, &start_type);
+        self.function_data.push(function_data);
+
+        self.function_names.push((module.uid, WASI_START_FUNCTION.to_string()));
+        self.functions.push((Vec::new(), TypeKind::Nothing));
+
+        Ok(())
+    }
+
+    fn get_wasi_import(&self, name: &str) -> Result<u32, LangError> {
+        let uid = ModuleUID::from_string(WASI_IMPORT_MODULE.to_string());
+
+        self.function_names
+            .iter()
+            .position(|(module, n)| *module == uid && n == name)
+            .map(|id| id as u32)
+            .ok_or(LangError::build(BuildErrorKind::FuncNotFound(name.to_string())))
+    }
+
+    fn wasi_data(&mut self) -> &WasiData {
+        if self.wasi_data.is_none() {
+            let scratch = self.push_data(vec![0; SCRATCH_SIZE], ModuleDataKind::Standard, 8);
+            let newline = self.push_data(b"\n".to_vec(), ModuleDataKind::Standard, 1);
+
+            let prefix = b"panic: ".to_vec();
+            let prefix_len = prefix.len() as i32;
+            let panic_prefix = self.push_data(prefix, ModuleDataKind::Standard, 1);
+
+            self.wasi_data = Some(WasiData {
+                scratch,
+                newline,
+                panic_prefix: (panic_prefix, prefix_len),
+            });
+        }
+
+        self.wasi_data.as_ref().unwrap()
+    }
+}
+
+/// Writes the body of a function implemented directly in WebAssembly
+struct WasiFunctionBuilder<'a, 'b> {
+    module_builder: &'a mut ModuleBuilder<'b>,
+    locals: Vec<ValType>,
+    instructions: Vec<Instruction<'static>>,
+    scratch: i32,
+}
+
+impl<'a, 'b> WasiFunctionBuilder<'a, 'b> {
+    fn new(module_builder: &'a mut ModuleBuilder<'b>, func_type: &FunctionType) -> Result<Self, LangError> {
+        let scratch = module_builder.wasi_data().scratch as i32;
+
+        Ok(Self {
+            module_builder,
+            locals: convert_types(&func_type.0),
+            instructions: Vec::new(),
+            scratch,
+        })
+    }
+
+    fn build(mut self, name: &str, module: ModuleUID, func_type: &FunctionType) -> FunctionData {
+        self.instructions.push(Instruction::End);
+
+        FunctionData {
+            name: name.to_string(),
+            module,
+
+            params: convert_types(&func_type.0),
+            ret: convert_type(&func_type.1),
+
+            locals: self.locals,
+            local_names: Vec::new(),
+            instructions: self.instructions,
+            spans: Vec::new(),
+        }
+    }
+
+    fn push_local(&mut self) -> u32 {
+        self.locals.push(ValType::I32);
+        self.locals.len() as u32 - 1
+    }
+
+    fn call_wasi(&mut self, name: &str) -> Result<(), LangError> {
+        let id = self.module_builder.get_wasi_import(name)?;
+        self.instructions.push(Instruction::Call(id));
+
+        Ok(())
+    }
+
+    fn call_alloc(&mut self) -> Result<(), LangError> {
+        let (id, _, _) = self.module_builder.get_func(
+            ModuleUID::from_string(CORE_MODULE_ID.to_string()),
+            &INTERNAL_MEMORY_ALLOC.to_string())?;
+        self.instructions.push(Instruction::Call(id));
+
+        Ok(())
+    }
+
+    fn mem_arg(offset: u64, align: u32) -> MemArg {
+        MemArg {
+            offset,
+            align,
+            memory_index: 0,
+        }
+    }
+
+    /// Loads an `i32` result of the last WASI call from the scratch area
+    fn load_result(&mut self, offset: u64) {
+        self.instructions.push(Instruction::I32Const(self.scratch));
+        self.instructions.push(Instruction::I32Load(Self::mem_arg(offset, 2)));
+    }
+
+    /// Stores the io vector at `index` of the scratch area, `buf` and `len` push its fields
+    fn store_iovec(&mut self, index: u64, buf: &[Instruction<'static>], len: &[Instruction<'static>]) {
+        self.instructions.push(Instruction::I32Const(self.scratch));
+        self.instructions.extend_from_slice(buf);
+        self.instructions.push(Instruction::I32Store(Self::mem_arg(index * 8, 2)));
+
+        self.instructions.push(Instruction::I32Const(self.scratch));
+        self.instructions.extend_from_slice(len);
+        self.instructions.push(Instruction::I32Store(Self::mem_arg(index * 8 + 4, 2)));
+    }
+
+    /// The io vector of the bytes of a string, the string starts with its length
+    fn store_string_iovec(&mut self, index: u64, string_local: u32) {
+        self.store_iovec(index, &[
+            Instruction::LocalGet(string_local),
+            Instruction::I32Const(4),
+            Instruction::I32Add,
+        ], &[
+            Instruction::LocalGet(string_local),
+            Instruction::I32Load(Self::mem_arg(0, 2)),
+        ]);
+    }
+
+    fn store_newline_iovec(&mut self, index: u64) {
+        let newline = self.module_builder.wasi_data().newline as i32;
+
+        self.store_iovec(index, &[Instruction::I32Const(newline)], &[Instruction::I32Const(1)]);
+    }
+
+    /// Writes the first `count` io vectors of the scratch area, the errors are ignored
+    fn write_iovecs(&mut self, fd: i32, count: i32) -> Result<(), LangError> {
+        self.instructions.push(Instruction::I32Const(fd));
+        self.instructions.push(Instruction::I32Const(self.scratch));
+        self.instructions.push(Instruction::I32Const(count));
+        self.instructions.push(Instruction::I32Const(self.scratch + SCRATCH_RESULT as i32));
+        self.call_wasi("fd_write")?;
+        self.instructions.push(Instruction::Drop);
+
+        Ok(())
+    }
+
+    /// `print(message String)`: writes the message and a new line to the standard output
+    fn build_print(&mut self) -> Result<(), LangError> {
+        self.store_string_iovec(0, 0);
+        self.store_newline_iovec(1);
+
+        self.write_iovecs(FD_STDOUT, 2)
+    }
+
+    /// `__internal_panic(message String)`: writes the message to the standard error and exits
+    fn build_panic(&mut self) -> Result<(), LangError> {
+        let (prefix, prefix_len) = self.module_builder.wasi_data().panic_prefix;
+
+        self.store_iovec(0, &[Instruction::I32Const(prefix as i32)], &[Instruction::I32Const(prefix_len)]);
+        self.store_string_iovec(1, 0);
+        self.store_newline_iovec(2);
+        self.write_iovecs(FD_STDERR, 3)?;
+
+        self.instructions.push(Instruction::I32Const(PANIC_EXIT_CODE));
+        self.call_wasi("proc_exit")?;
+        self.instructions.push(Instruction::Unreachable);
+
+        Ok(())
+    }
+
+    /// `readLine() String`: reads the standard input one byte at a time until a new line,
+    /// the new line is not part of the result
+    fn build_read_line(&mut self) -> Result<(), LangError> {
+        let string = self.push_local();
+        let len = self.push_local();
+
+        self.instructions.push(Instruction::I32Const(4 + LINE_CAPACITY));
+        self.call_alloc()?;
+        self.instructions.push(Instruction::LocalSet(string));
+
+        self.instructions.push(Instruction::Block(BlockType::Empty));
+        self.instructions.push(Instruction::Loop(BlockType::Empty));
+
+        // The line is full
+        self.instructions.push(Instruction::LocalGet(len));
+        self.instructions.push(Instruction::I32Const(LINE_CAPACITY));
+        self.instructions.push(Instruction::I32GeU);
+        self.instructions.push(Instruction::BrIf(1));
+
+        self.store_iovec(0, &[
+            Instruction::LocalGet(string),
+            Instruction::LocalGet(len),
+            Instruction::I32Add,
+            Instruction::I32Const(4),
+            Instruction::I32Add,
+        ], &[Instruction::I32Const(1)]);
+
+        self.instructions.push(Instruction::I32Const(FD_STDIN));
+        self.instructions.push(Instruction::I32Const(self.scratch));
+        self.instructions.push(Instruction::I32Const(1));
+        self.instructions.push(Instruction::I32Const(self.scratch + SCRATCH_RESULT as i32));
+        self.call_wasi("fd_read")?;
+
+        // An error or the end of the input
+        self.instructions.push(Instruction::BrIf(1));
+        self.load_result(SCRATCH_RESULT);
+        self.instructions.push(Instruction::I32Eqz);
+        self.instructions.push(Instruction::BrIf(1));
+
+        // The end of the line
+        self.instructions.push(Instruction::LocalGet(string));
+        self.instructions.push(Instruction::LocalGet(len));
+        self.instructions.push(Instruction::I32Add);
+        self.instructions.push(Instruction::I32Load8_U(Self::mem_arg(4, 0)));
+        self.instructions.push(Instruction::I32Const(b'\n' as i32));
+        self.instructions.push(Instruction::I32Eq);
+        self.instructions.push(Instruction::BrIf(1));
+
+        self.instructions.push(Instruction::LocalGet(len));
+        self.instructions.push(Instruction::I32Const(1));
+        self.instructions.push(Instruction::I32Add);
+        self.instructions.push(Instruction::LocalSet(len));
+        self.instructions.push(Instruction::Br(0));
+
+        self.instructions.push(Instruction::End);
+        self.instructions.push(Instruction::End);
+
+        self.instructions.push(Instruction::LocalGet(string));
+        self.instructions.push(Instruction::LocalGet(len));
+        self.instructions.push(Instruction::I32Store(Self::mem_arg(0, 2)));
+        self.instructions.push(Instruction::LocalGet(string));
+
+        Ok(())
+    }
+
+    /// Stores the argument count and the size of the arguments buffer in the scratch area
+    fn get_args_sizes(&mut self) -> Result<(), LangError> {
+        self.instructions.push(Instruction::I32Const(self.scratch + SCRATCH_RESULT as i32));
+        self.instructions.push(Instruction::I32Const(self.scratch + SCRATCH_RESULT_2 as i32));
+        self.call_wasi("args_sizes_get")?;
+        self.instructions.push(Instruction::Drop);
+
+        Ok(())
+    }
+
+    /// `argCount() Int`: the number of command line arguments, including the program name
+    fn build_arg_count(&mut self) -> Result<(), LangError> {
+        self.get_args_sizes()?;
+        self.load_result(SCRATCH_RESULT);
+
+        Ok(())
+    }
+
+    /// `arg(index Int) String`: the command line argument at `index`, or an empty string if there is none
+    fn build_arg(&mut self) -> Result<(), LangError> {
+        let index = 0;
+        let argv = self.push_local();
+        let arg = self.push_local();
+        let len = self.push_local();
+        let string = self.push_local();
+        let i = self.push_local();
+
+        self.get_args_sizes()?;
+
+        // Out of range
+        self.instructions.push(Instruction::LocalGet(index));
+        self.load_result(SCRATCH_RESULT);
+        self.instructions.push(Instruction::I32GeU);
+        self.instructions.push(Instruction::If(BlockType::Empty));
+        self.instructions.push(Instruction::I32Const(4));
+        self.call_alloc()?;
+        self.instructions.push(Instruction::LocalTee(string));
+        self.instructions.push(Instruction::I32Const(0));
+        self.instructions.push(Instruction::I32Store(Self::mem_arg(0, 2)));
+        self.instructions.push(Instruction::LocalGet(string));
+        self.instructions.push(Instruction::Return);
+        self.instructions.push(Instruction::End);
+
+        // The pointers to the arguments are followed by the arguments buffer
+        self.load_result(SCRATCH_RESULT);
+        self.instructions.push(Instruction::I32Const(4));
+        self.instructions.push(Instruction::I32Mul);
+        self.load_result(SCRATCH_RESULT_2);
+        self.instructions.push(Instruction::I32Add);
+        self.call_alloc()?;
+        self.instructions.push(Instruction::LocalTee(argv));
+
+        self.instructions.push(Instruction::LocalGet(argv));
+        self.load_result(SCRATCH_RESULT);
+        self.instructions.push(Instruction::I32Const(4));
+        self.instructions.push(Instruction::I32Mul);
+        self.instructions.push(Instruction::I32Add);
+        self.call_wasi("args_get")?;
+        self.instructions.push(Instruction::Drop);
+
+        self.instructions.push(Instruction::LocalGet(argv));
+        self.instructions.push(Instruction::LocalGet(index));
+        self.instructions.push(Instruction::I32Const(4));
+        self.instructions.push(Instruction::I32Mul);
+        self.instructions.push(Instruction::I32Add);
+        self.instructions.push(Instruction::I32Load(Self::mem_arg(0, 2)));
+        self.instructions.push(Instruction::LocalSet(arg));
+
+        // The arguments are null terminated
+        self.instructions.push(Instruction::Block(BlockType::Empty));
+        self.instructions.push(Instruction::Loop(BlockType::Empty));
+        self.instructions.push(Instruction::LocalGet(arg));
+        self.instructions.push(Instruction::LocalGet(len));
+        self.instructions.push(Instruction::I32Add);
+        self.instructions.push(Instruction::I32Load8_U(Self::mem_arg(0, 0)));
+        self.instructions.push(Instruction::I32Eqz);
+        self.instructions.push(Instruction::BrIf(1));
+        self.instructions.push(Instruction::LocalGet(len));
+        self.instructions.push(Instruction::I32Const(1));
+        self.instructions.push(Instruction::I32Add);
+        self.instructions.push(Instruction::LocalSet(len));
+        self.instructions.push(Instruction::Br(0));
+        self.instructions.push(Instruction::End);
+        self.instructions.push(Instruction::End);
+
+        self.instructions.push(Instruction::LocalGet(len));
+        self.instructions.push(Instruction::I32Const(4));
+        self.instructions.push(Instruction::I32Add);
+        self.call_alloc()?;
+        self.instructions.push(Instruction::LocalTee(string));
+        self.instructions.push(Instruction::LocalGet(len));
+        self.instructions.push(Instruction::I32Store(Self::mem_arg(0, 2)));
+
+        self.instructions.push(Instruction::Block(BlockType::Empty));
+        self.instructions.push(Instruction::Loop(BlockType::Empty));
+        self.instructions.push(Instruction::LocalGet(i));
+        self.instructions.push(Instruction::LocalGet(len));
+        self.instructions.push(Instruction::I32GeU);
+        self.instructions.push(Instruction::BrIf(1));
+        self.instructions.push(Instruction::LocalGet(string));
+        self.instructions.push(Instruction::LocalGet(i));
+        self.instructions.push(Instruction::I32Add);
+        self.instructions.push(Instruction::LocalGet(arg));
+        self.instructions.push(Instruction::LocalGet(i));
+        self.instructions.push(Instruction::I32Add);
+        self.instructions.push(Instruction::I32Load8_U(Self::mem_arg(0, 0)));
+        self.instructions.push(Instruction::I32Store8(Self::mem_arg(4, 0)));
+        self.instructions.push(Instruction::LocalGet(i));
+        self.instructions.push(Instruction::I32Const(1));
+        self.instructions.push(Instruction::I32Add);
+        self.instructions.push(Instruction::LocalSet(i));
+        self.instructions.push(Instruction::Br(0));
+        self.instructions.push(Instruction::End);
+        self.instructions.push(Instruction::End);
+
+        self.instructions.push(Instruction::LocalGet(string));
+
+        Ok(())
+    }
+
+    /// `exit(code Int)`: ends the program with an exit code
+    fn build_exit(&mut self) -> Result<(), LangError> {
+        self.instructions.push(Instruction::LocalGet(0));
+        self.call_wasi("proc_exit")?;
+        self.instructions.push(Instruction::Unreachable);
+
+        Ok(())
+    }
+
+    /// `clock() Int`: milliseconds of a monotonic clock, only the difference between two calls is meaningful
+    fn build_clock(&mut self) -> Result<(), LangError> {
+        self.instructions.push(Instruction::I32Const(CLOCK_MONOTONIC));
+        // Precision in nanoseconds
+        self.instructions.push(Instruction::I64Const(1_000_000));
+        self.instructions.push(Instruction::I32Const(self.scratch + SCRATCH_RESULT as i32));
+        self.call_wasi("clock_time_get")?;
+        self.instructions.push(Instruction::Drop);
+
+        self.instructions.push(Instruction::I32Const(self.scratch));
+        self.instructions.push(Instruction::I64Load(Self::mem_arg(SCRATCH_RESULT, 3)));
+        self.instructions.push(Instruction::I64Const(1_000_000));
+        self.instructions.push(Instruction::I64DivU);
+        self.instructions.push(Instruction::I32WrapI64);
+
+        Ok(())
+    }
+}
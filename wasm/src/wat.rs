@@ -0,0 +1,64 @@
+use common::errors::{BuildErrorKind, LangError};
+
+/// Rain information about the items of a built module, used to annotate its text format
+pub(crate) struct BuildAnnotations {
+    /// Description of every function, in the order of the wasm function indices (imports first)
+    pub functions: Vec<String>,
+    /// The string literal held by every data segment, if it holds one
+    pub data: Vec<Option<String>>,
+}
+
+/// Converts a built module to the WebAssembly text format, every function is preceded by
+/// its Rain name and module and every data segment by the string literal it holds
+pub(crate) fn to_wat(wasm: &[u8], annotations: &BuildAnnotations) -> Result<String, LangError> {
+    let text = match wasmprinter::print_bytes(wasm) {
+        Ok(text) => text,
+        Err(err) => return Err(LangError::build(BuildErrorKind::UnexpectedError(format!("to_wat: {}", err)))),
+    };
+
+    let mut result = String::with_capacity(text.len());
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+
+        let annotation = if let Some(index) = parse_index(trimmed, "(func ") {
+            annotations.functions.get(index).cloned()
+        } else if let Some(index) = parse_index(trimmed, "(data ") {
+            annotations.data
+                .get(index)
+                .cloned()
+                .flatten()
+                .map(|literal| format!("string {:?}", literal))
+        } else {
+            None
+        };
+
+        if let Some(annotation) = annotation {
+            result.push_str(indent);
+            result.push_str(";; ");
+            result.push_str(annotation.as_str());
+            result.push('\n');
+        }
+
+        result.push_str(line);
+        result.push('\n');
+    }
+
+    Ok(result)
+}
+
+/// Parses the index of an item printed as `<prefix>($<name> )?(;<index>;)`
+fn parse_index(line: &str, prefix: &str) -> Option<usize> {
+    let mut rest = line.strip_prefix(prefix)?;
+
+    // Name from the name section
+    if rest.starts_with('$') {
+        rest = &rest[rest.find(' ')? + 1..];
+    }
+
+    let rest = rest.strip_prefix("(;")?;
+    let end = rest.find(';')?;
+
+    rest[..end].parse().ok()
+}
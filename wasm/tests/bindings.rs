@@ -0,0 +1,143 @@
+mod utils;
+
+use wasm::engine::BuildTarget;
+use wasm::js::JsBindings;
+use utils::{engine, load};
+
+const SOURCE: &str = "
+import func twice(n Int) Int
+import func report(point Point, label String)
+
+data class Point {
+    x Int
+    y Float
+}
+
+@Export
+func greet(name String, times Int) String {
+    return name
+}
+
+@Export
+func move(point Point, result Int) Point {
+    report(point, \"moved\")
+    return point
+}
+
+@Export
+func log(message String) {
+}
+
+pub func main() Int {
+    return twice(21)
+}
+";
+
+const TYPES: &str = "
+enum Color {
+    Red,
+    Green,
+}
+
+import func describe(color Color, point Point) String
+
+data class Point {
+    x Int
+    y Float
+}
+
+@Export
+func swap(pair (Int, String), this Bool) (String, Int) {
+    var (a, b) = pair
+    return (b, a)
+}
+
+@Export
+func show(color Color, point Point) String {
+    return describe(color, point)
+}
+
+@Export
+func check(value Int) Int {
+    if value < 0 {
+        panic(\"negative\")
+    }
+    return value
+}
+";
+
+fn rust_bindings(source: &str) -> String {
+    let mut engine = engine(BuildTarget::Host);
+    let module = load(&mut engine, &[("main.rn", source)]).unwrap();
+
+    engine.build_rust_bindings(module, "main").unwrap()
+}
+
+fn js_bindings(source: &str) -> JsBindings {
+    let mut engine = engine(BuildTarget::Host);
+    let module = load(&mut engine, &[("main.rn", source)]).unwrap();
+
+    engine.build_js_bindings(module).unwrap()
+}
+
+#[test]
+fn rust_parameters_are_named_after_the_rain_parameters() {
+    let rs = rust_bindings(SOURCE);
+
+    assert!(rs.contains("pub fn greet(&mut self, name: String, times: i32) -> wasmtime::Result<String> {"));
+    assert!(rs.contains("fn twice(&mut self, n: i32) -> i32;"));
+    assert!(rs.contains("move |mut caller: Caller<'_, H>, n: i32| -> wasmtime::Result<i32> {"));
+    assert!(rs.contains("fn report(&mut self, point: Point, label: String);"));
+    assert!(rs.contains("move |mut caller: Caller<'_, H>, point_0: i32, point_1: f32, label: i32| -> wasmtime::Result<()> {"));
+    assert!(!rs.contains("arg0"));
+    assert!(!rs.contains("p0"));
+}
+
+#[test]
+fn rust_parameters_do_not_shadow_the_generated_names() {
+    let rs = rust_bindings(SOURCE);
+
+    assert!(rs.contains("pub fn r#move(&mut self, point: Point, result_: i32) -> wasmtime::Result<Point> {"));
+    assert!(rs.contains("func.call(&mut *ctx, (point.x, point.y, result_))?;"));
+}
+
+#[test]
+fn rust_functions_returning_nothing_ignore_the_result() {
+    let rs = rust_bindings(SOURCE);
+    let log = &rs[rs.find("pub fn log(").unwrap()..];
+    let log = &log[..log.find("\n    }\n").unwrap()];
+
+    assert!(log.contains("pub fn log(&mut self, message: String) -> wasmtime::Result<()> {"));
+    assert!(log.contains("        func.call(&mut *ctx, (v1,))?;\n        Ok(())"));
+    assert!(!log.contains("let result"));
+}
+
+#[test]
+fn typescript_declares_data_classes_enums_and_tuples() {
+    let dts = js_bindings(TYPES).dts;
+
+    assert!(dts.contains("export type Color = \"Red\" | \"Green\";"));
+    assert!(dts.contains("export interface Point {\n    x: number;\n    y: number;\n}"));
+    assert!(dts.contains("\"describe\"(color: Color, point: Point): string;"));
+    assert!(dts.contains("\"swap\"(pair: [number, string], this_: boolean): [string, number];"));
+    assert!(!dts.contains("unknown"));
+    assert!(!dts.contains("arg0"));
+}
+
+#[test]
+fn javascript_converts_data_classes_enums_and_tuples() {
+    let js = js_bindings(TYPES).js;
+
+    assert!(js.contains("\"describe\": (color, point_0, point_1) => lowerString(imports[\"main\"][\"describe\"]([\"Red\", \"Green\"][color], { x: point_0, y: point_1 })),"));
+    assert!(js.contains("\"show\": (color, point) => liftString(exports[\"show\"]([\"Red\", \"Green\"].indexOf(color), point.x, point.y)),"));
+    assert!(js.contains("\"swap\": (pair, this_) => { const result = exports[\"swap\"](pair[0], lowerString(pair[1]), (this_ ? 1 : 0)); return [liftString(result[0]), result[1]]; },"));
+}
+
+#[test]
+fn panics_throw_when_no_handler_is_given() {
+    let bindings = js_bindings(TYPES);
+
+    assert!(bindings.js.contains("const panic = (message) => {\n        throw new Error(message);\n    };"));
+    assert!(bindings.js.contains("(imports[\"core\"]?.[\"__internal_panic\"] ?? panic)(liftString(message))"));
+    assert!(bindings.dts.contains("\"core\"?: {\n        \"__internal_panic\"?(message: string): void;\n    };"));
+}
@@ -0,0 +1,44 @@
+mod utils;
+
+use core::{Engine, EngineBuildSource};
+use common::constants::CFG_FEATURE_KEY;
+use wasm::engine::BuildTarget;
+use utils::{build, engine, load, Instance};
+
+const SOURCE: &str = "
+@Cfg(target = \"wasm\")
+func backend() Int {
+    return 1
+}
+
+@Cfg(target = \"interpreter\")
+func backend() Int {
+    return 2
+}
+
+@Export
+func run() Int {
+    var n = backend() * 10
+    @Cfg(feature = \"debug\")
+    n = n + 5
+    return n
+}
+";
+
+#[test]
+fn the_wasm_target_is_set() {
+    let mut instance = Instance::new(&build(SOURCE).unwrap()).unwrap();
+
+    assert_eq!(instance.call_i32("run", &[]).unwrap(), 10);
+}
+
+#[test]
+fn statements_are_kept_for_the_enabled_features() {
+    let mut engine = engine(BuildTarget::Host);
+    engine.set_cfg(CFG_FEATURE_KEY, "debug");
+
+    let module = load(&mut engine, &[("main.rn", SOURCE)]).unwrap();
+    let wasm = engine.build_module_source(module).unwrap();
+
+    assert_eq!(Instance::new(&wasm).unwrap().call_i32("run", &[]).unwrap(), 15);
+}
@@ -0,0 +1,84 @@
+mod utils;
+
+use wasm::engine::BuildTarget;
+use wasm::memory::{MemoryConfig, MemoryImport};
+use utils::{build, engine, load, Instance};
+
+const ENTITIES: &str = "
+class Entity {
+    hp Int
+
+    func init(hp Int) {
+        this.hp = hp
+    }
+
+    func kind() Int {
+        return 1
+    }
+
+    func describe() Int {
+        return (this.kind() * 100) + this.hp
+    }
+}
+
+class Player : Entity {
+    func init(hp Int) {
+        super.init(hp)
+    }
+
+    func kind() Int {
+        return 2
+    }
+}
+
+class Boss : Player {
+    func kind() Int {
+        return super.kind() + 10
+    }
+}
+
+@Export
+func entityKind() Int {
+    var e = Entity(5)
+    return e.describe()
+}
+
+@Export
+func playerKind() Int {
+    var e Entity = Player(6)
+    return e.describe()
+}
+
+@Export
+func bossKind() Int {
+    var e Entity = Boss(7)
+    return e.kind()
+}
+";
+
+#[test]
+fn overridden_methods_are_called() {
+    let mut instance = Instance::new(&build(ENTITIES).unwrap()).unwrap();
+
+    assert_eq!(instance.call_i32("entityKind", &[]).unwrap(), 105);
+    assert_eq!(instance.call_i32("playerKind", &[]).unwrap(), 206);
+    assert_eq!(instance.call_i32("bossKind", &[]).unwrap(), 12);
+}
+
+#[test]
+fn vtable_at_address_zero_is_stored() {
+    let mut engine = engine(BuildTarget::Host);
+    engine.set_memory_config(MemoryConfig {
+        import: Some(MemoryImport { module: "env".to_string(), name: "memory".to_string() }),
+        ..MemoryConfig::default()
+    });
+
+    let module = load(&mut engine, &[("main.rn", ENTITIES)]).unwrap();
+    let wasm = core::EngineBuildSource::build_module_source(&engine, module).unwrap();
+
+    // The objects are allocated in memory that was never zeroed
+    let mut instance = Instance::with_memory_fill(&wasm, 0xAA).unwrap();
+
+    assert_eq!(instance.call_i32("entityKind", &[]).unwrap(), 105);
+    assert_eq!(instance.call_i32("playerKind", &[]).unwrap(), 206);
+}
@@ -0,0 +1,79 @@
+mod utils;
+
+use wasm::engine::BuildTarget;
+use utils::{engine, load, validate};
+
+const SOURCE: &str = "
+import func log(message String)
+import func origin() Point
+
+data class Point {
+    x Int
+    y Float
+}
+
+enum Shape {
+    Circle Int,
+    Empty,
+}
+
+@Export
+func greet(userName String) String {
+    log(userName)
+    return userName
+}
+
+@Export
+func shift(point Point, isFast Bool) Point {
+    var o = origin()
+    var q = Point()
+    q.x = point.x + o.x
+    q.y = point.y
+    return q
+}
+
+@Export
+func area(shape Shape) Int {
+    return 3
+}
+
+pub func main() Int {
+    return 42
+}
+";
+
+fn component(source: &str, name: &str) -> (Vec<u8>, String) {
+    let mut engine = engine(BuildTarget::Component);
+    let module = load(&mut engine, &[("main.rn", source)]).unwrap();
+
+    engine.build_component(module, name).unwrap()
+}
+
+#[test]
+fn component_is_valid() {
+    let (wasm, _) = component(SOURCE, "demoApp");
+
+    validate(&wasm).unwrap();
+}
+
+#[test]
+fn wit_world_declares_the_types_and_functions() {
+    let (_, wit) = component(SOURCE, "demoApp");
+
+    assert!(wit.starts_with("package rain:demo-app;\n\nworld demo-app {\n"));
+    assert!(wit.contains("    record point {\n        x: s32,\n        y: f32,\n    }\n"));
+    assert!(wit.contains("    variant shape {\n        circle(s32),\n        empty,\n    }\n"));
+    assert!(wit.contains("    import log: func(message: string);\n"));
+    assert!(wit.contains("    import origin: func() -> point;\n"));
+    assert!(wit.contains("    export area: func(shape: shape) -> s32;\n"));
+    assert!(wit.contains("    export main: func() -> s32;\n"));
+}
+
+#[test]
+fn wit_parameters_are_named_after_the_rain_parameters() {
+    let (_, wit) = component(SOURCE, "demoApp");
+
+    assert!(wit.contains("    export greet: func(user-name: string) -> string;\n"));
+    assert!(wit.contains("    export shift: func(point: point, is-fast: bool) -> point;\n"));
+    assert!(!wit.contains("arg0"));
+}
@@ -0,0 +1,94 @@
+mod utils;
+
+use wasm::engine::BuildTarget;
+use wasmparser::{Name, NameSectionReader, Parser, Payload};
+use utils::{engine, load};
+
+const MAIN: &str = "
+import func log(message String)
+
+@Export
+func greet(name String) String {
+    var greeting = \"hello\"
+    log(greeting)
+    return name
+}
+";
+
+fn build(source: &str) -> (Vec<u8>, String) {
+    let mut engine = engine(BuildTarget::Host);
+    let module = load(&mut engine, &[("main.rn", source)]).unwrap();
+    let (wasm, source_map) = engine.build_module_with_source_map(module, "main.wasm.map").unwrap();
+
+    (wasm, source_map.to_json("src"))
+}
+
+/// The data of the custom section `name`
+fn custom_section<'a>(wasm: &'a [u8], name: &str) -> Option<(&'a [u8], usize)> {
+    Parser::new(0)
+        .parse_all(wasm)
+        .find_map(|payload| match payload.unwrap() {
+            Payload::CustomSection(reader) if reader.name() == name => Some((reader.data(), reader.data_offset())),
+            _ => None,
+        })
+}
+
+type Names = (String, Vec<String>, Vec<(u32, Vec<String>)>);
+
+/// The module name, the function names and the local names of every function
+fn names(wasm: &[u8]) -> Names {
+    let (data, offset) = custom_section(wasm, "name").expect("No name section");
+    let (mut module, mut functions, mut locals) = (String::new(), Vec::new(), Vec::new());
+
+    for name in NameSectionReader::new(data, offset) {
+        match name.unwrap() {
+            Name::Module { name, .. } => module = name.to_string(),
+            Name::Function(map) => functions = map.into_iter().map(|naming| naming.unwrap().name.to_string()).collect(),
+            Name::Local(map) => locals = map
+                .into_iter()
+                .map(|naming| {
+                    let naming = naming.unwrap();
+                    (naming.index, naming.names.into_iter().map(|local| local.unwrap().name.to_string()).collect())
+                })
+                .collect(),
+            _ => (),
+        }
+    }
+
+    (module, functions, locals)
+}
+
+#[test]
+fn name_section_names_the_module_functions_and_locals() {
+    let (wasm, _) = build(MAIN);
+    let (module, functions, locals) = names(&wasm);
+
+    assert_eq!(module, "main");
+    assert!(functions.contains(&"log".to_string()));
+    assert!(functions.contains(&"main::greet".to_string()));
+
+    let greet = functions.iter().position(|name| name == "main::greet").unwrap() as u32;
+    let (_, greet_locals) = locals.iter().find(|(index, _)| *index == greet).unwrap();
+
+    assert!(greet_locals.contains(&"name".to_string()));
+    assert!(greet_locals.contains(&"greeting".to_string()));
+}
+
+#[test]
+fn module_points_to_its_source_map() {
+    let (wasm, _) = build(MAIN);
+    let (url, _) = custom_section(&wasm, "sourceMappingURL").expect("No source mapping url");
+
+    assert_eq!(url, b"main.wasm.map");
+}
+
+#[test]
+fn source_map_embeds_the_sources_without_a_file() {
+    let (_, json) = build(MAIN);
+
+    assert!(json.starts_with("{\"version\":3,\"sourceRoot\":\"src\",\"sources\":["));
+    assert!(json.contains("\"main.rn\""));
+    assert!(json.contains("\"sourcesContent\":["));
+    assert!(json.contains("var greeting = \\\"hello\\\"\\u000a"));
+    assert!(!json.contains("\"mappings\":\"\""));
+}
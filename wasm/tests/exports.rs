@@ -0,0 +1,69 @@
+mod utils;
+
+use common::errors::{BuildErrorKind, LangError};
+use utils::{build, exports, validate};
+
+fn duplicate_export(source: &str) -> Option<String> {
+    match build(source).unwrap_err().downcast::<LangError>() {
+        Ok(LangError::Build { kind: BuildErrorKind::DuplicateExport(name) }) => Some(name),
+        _ => None,
+    }
+}
+
+#[test]
+fn only_marked_functions_are_exported() {
+    let wasm = build("
+@Export
+func add(a Int, b Int) Int {
+    return a + b
+}
+
+@Export(\"times\")
+func multiply(a Int, b Int) Int {
+    return a * b
+}
+
+@Export(name = \"minus\")
+func subtract(a Int, b Int) Int {
+    return a - b
+}
+
+pub func hidden() Int {
+    return add(1, 2)
+}
+
+func main() {
+}
+").unwrap();
+
+    validate(&wasm).unwrap();
+
+    let mut names = exports(&wasm);
+    names.sort();
+    assert_eq!(names, vec!["__internal_memory_alloc", "add", "main", "mem", "minus", "times"]);
+}
+
+#[test]
+fn functions_exported_twice_are_rejected() {
+    assert_eq!(duplicate_export("
+@Export(\"f\")
+func a() Int {
+    return 1
+}
+
+@Export(\"f\")
+func b() Int {
+    return 2
+}
+"), Some("f".to_string()));
+}
+
+#[test]
+fn functions_exported_as_the_memory_are_rejected() {
+    assert_eq!(duplicate_export("
+@Export(\"mem\")
+func a() Int {
+    return 1
+}
+"), Some("mem".to_string()));
+}
@@ -0,0 +1,126 @@
+mod utils;
+
+use wasmi::{Caller, Engine, ExternRef, Linker, Module, Store};
+use wasmparser::{Parser, Payload, TypeRef, ValType};
+use utils::{build, validate};
+
+const NODES: &str = "
+extern class Node
+
+import func createNode(id Int) Node
+import func nodeId(node Node) Int
+
+class Holder {
+    node Node
+
+    func init(node Node) {
+        this.node = node
+    }
+}
+
+@Export
+func testLocal() Int {
+    var node = createNode(7)
+    var same = node
+    return nodeId(same)
+}
+
+@Export
+func testMemory() Int {
+    var holder = Holder(createNode(1))
+    var nodes = [createNode(2), createNode(3)]
+    nodes[0] = createNode(4)
+    holder.node = nodes[1]
+    return (nodeId(holder.node) * 100) + (nodeId(nodes[0]) * 10) + nodeId(nodes[1])
+}
+";
+
+const LOCALS: &str = "
+extern class Node
+
+import func createNode(id Int) Node
+import func nodeId(node Node) Int
+
+@Export
+func testLocal() Int {
+    return nodeId(createNode(7))
+}
+";
+
+/// Runs an export with the host objects being their id
+fn run(wasm: &[u8], name: &str) -> i32 {
+    let engine = Engine::default();
+    let module = Module::new(&engine, wasm).unwrap();
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::<()>::new(&engine);
+
+    linker.func_wrap("main", "createNode", |mut caller: Caller<'_, ()>, id: i32| ExternRef::new(&mut caller, id)).unwrap();
+    linker.func_wrap("main", "nodeId", |caller: Caller<'_, ()>, node: ExternRef| {
+        *node.data(&caller).unwrap().downcast_ref::<i32>().unwrap()
+    }).unwrap();
+
+    let instance = linker.instantiate(&mut store, &module).unwrap().start(&mut store).unwrap();
+    let func = instance.get_typed_func::<(), i32>(&store, name).unwrap();
+
+    func.call(&mut store, ()).unwrap()
+}
+
+/// The type of every imported function, in order
+fn import_types(wasm: &[u8]) -> Vec<(Vec<ValType>, Vec<ValType>)> {
+    let mut types = Vec::new();
+    let mut imports = Vec::new();
+
+    for payload in Parser::new(0).parse_all(wasm) {
+        match payload.unwrap() {
+            Payload::TypeSection(reader) => for group in reader {
+                for sub_type in group.unwrap().into_types() {
+                    let func = sub_type.unwrap_func();
+                    types.push((func.params().to_vec(), func.results().to_vec()));
+                }
+            },
+            Payload::ImportSection(reader) => for import in reader {
+                if let TypeRef::Func(index) = import.unwrap().ty {
+                    imports.push(types[index as usize].clone());
+                }
+            },
+            _ => (),
+        }
+    }
+
+    imports
+}
+
+fn has_table(wasm: &[u8]) -> bool {
+    Parser::new(0)
+        .parse_all(wasm)
+        .any(|payload| matches!(payload, Ok(Payload::TableSection(_))))
+}
+
+#[test]
+fn extern_objects_are_externref_values() {
+    let wasm = build(NODES).unwrap();
+    validate(&wasm).unwrap();
+
+    let types = import_types(&wasm);
+
+    assert!(types.contains(&(vec![ValType::I32], vec![ValType::EXTERNREF])));
+    assert!(types.contains(&(vec![ValType::EXTERNREF], vec![ValType::I32])));
+}
+
+#[test]
+fn extern_objects_are_held_in_locals() {
+    let wasm = build(LOCALS).unwrap();
+
+    assert_eq!(run(&wasm, "testLocal"), 7);
+    // Nothing is placed in the memory
+    assert!(!has_table(&wasm));
+}
+
+#[test]
+fn extern_objects_are_stored_in_the_table() {
+    let wasm = build(NODES).unwrap();
+
+    assert!(has_table(&wasm));
+    assert_eq!(run(&wasm, "testLocal"), 7);
+    assert_eq!(run(&wasm, "testMemory"), 343);
+}
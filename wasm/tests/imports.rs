@@ -0,0 +1,53 @@
+mod utils;
+
+use utils::{build_project, Instance};
+
+const GEOM: &str = "
+pub var count Int = 5
+
+pub func value() Int {
+    return count
+}
+
+pub func double(n Int) Int {
+    return n * 2
+}
+";
+
+#[test]
+fn aliased_features_resolve_to_the_imported_module() {
+    let wasm = build_project(&[("geom.rn", GEOM), ("main.rn", "
+import \"geom\" as g
+
+var count Int = 7
+
+func value() Int {
+    return 100
+}
+
+@Export
+func run() Int {
+    return (g.count * 1000) + ((count * 10) + (g.value() + value()))
+}
+")]).unwrap();
+
+    assert_eq!(Instance::new(&wasm).unwrap().call_i32("run", &[]).unwrap(), 5175);
+}
+
+#[test]
+fn selected_features_resolve_to_the_imported_module() {
+    let wasm = build_project(&[("geom.rn", GEOM), ("main.rn", "
+import { double } from \"geom\"
+
+func value() Int {
+    return 100
+}
+
+@Export
+func run() Int {
+    return double(value())
+}
+")]).unwrap();
+
+    assert_eq!(Instance::new(&wasm).unwrap().call_i32("run", &[]).unwrap(), 200);
+}
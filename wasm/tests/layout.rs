@@ -0,0 +1,89 @@
+mod utils;
+
+use utils::{build, validate, Instance};
+
+const LAYOUT: &str = "
+data class Record {
+    flag Bool
+    ratio Float
+    count Int
+    pair (Int, Float)
+}
+
+class Node {
+    value Int
+    next Record
+
+    func init(value Int) {
+        this.value = value
+    }
+}
+
+func record(n Int) Record {
+    var r = Record()
+    r.flag = true
+    r.ratio = 0.5
+    r.count = n
+    r.pair = (n * 2, 1.5)
+    return r
+}
+
+@Export
+func testRecords() Int {
+    var records = [record(1), record(2), record(3)]
+    var total = 0
+
+    var i = 0
+    while i < 3 {
+        var r = records[i]
+        var (a, b) = r.pair
+        if r.flag {
+            total = total + (r.count + a)
+        }
+        i = i + 1
+    }
+
+    return total
+}
+
+@Export
+func testFields() Int {
+    var node = Node(4)
+    node.next = record(5)
+    var (a, b) = node.next.pair
+    return (node.value * 100) + (node.next.count + a)
+}
+
+@Export
+func allocateBools() [Bool] {
+    return [true]
+}
+
+@Export
+func allocateInts() [Int] {
+    return [1, 2, 3]
+}
+";
+
+#[test]
+fn mixed_fields_are_read_back() {
+    let wasm = build(LAYOUT).unwrap();
+    validate(&wasm).unwrap();
+
+    let mut instance = Instance::new(&wasm).unwrap();
+
+    assert_eq!(instance.call_i32("testRecords", &[]).unwrap(), 18);
+    assert_eq!(instance.call_i32("testFields", &[]).unwrap(), 415);
+}
+
+#[test]
+fn allocated_blocks_are_aligned() {
+    let mut instance = Instance::new(&build(LAYOUT).unwrap()).unwrap();
+
+    // Odd sizes would misalign the following blocks without rounding
+    for func in ["allocateBools", "allocateInts", "allocateBools", "allocateInts"] {
+        let address = instance.call_i32(func, &[]).unwrap();
+
+        assert_eq!(address % 8, 0, "{} returned {}", func, address);
+    }
+}
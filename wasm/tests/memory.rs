@@ -0,0 +1,92 @@
+mod utils;
+
+use common::errors::{BuildErrorKind, LangError};
+use wasm::engine::BuildTarget;
+use wasm::memory::{MemoryConfig, MemoryImport};
+use utils::{engine, exports, load, validate, Instance};
+
+const SOURCE: &str = "
+@Export
+func greet() String {
+    return \"hello\"
+}
+";
+
+fn build(memory: MemoryConfig) -> Result<Vec<u8>, LangError> {
+    let mut engine = engine(BuildTarget::Host);
+    engine.set_memory_config(memory);
+
+    let module = load(&mut engine, &[("main.rn", SOURCE)]).unwrap();
+
+    core::EngineBuildSource::build_module_source(&engine, module)
+}
+
+fn assert_invalid(memory: MemoryConfig, reason: &str) {
+    match build(memory) {
+        Err(LangError::Build { kind: BuildErrorKind::InvalidMemoryConfig(err) }) => assert!(err.contains(reason), "{}", err),
+        Err(err) => panic!("Unexpected error {:?}", err),
+        Ok(_) => panic!("The memory configuration is valid"),
+    }
+}
+
+#[test]
+fn initial_pages_over_the_limit_are_rejected() {
+    assert_invalid(
+        MemoryConfig { initial_pages: 65537, ..MemoryConfig::default() },
+        "the initial pages (65537) are more than the limit of 65536 pages");
+}
+
+#[test]
+fn maximum_pages_over_the_limit_are_rejected() {
+    assert_invalid(
+        MemoryConfig { max_pages: Some(70000), ..MemoryConfig::default() },
+        "the maximum pages (70000) are more than the limit of 65536 pages");
+}
+
+#[test]
+fn maximum_pages_below_the_initial_pages_are_rejected() {
+    assert_invalid(
+        MemoryConfig { initial_pages: 4, max_pages: Some(2), ..MemoryConfig::default() },
+        "the maximum pages (2) are less than the initial pages (4)");
+}
+
+#[test]
+fn shared_memory_without_maximum_is_rejected() {
+    assert_invalid(
+        MemoryConfig { shared: true, ..MemoryConfig::default() },
+        "a shared memory needs a maximum number of pages");
+}
+
+#[test]
+fn data_out_of_the_initial_pages_is_rejected() {
+    assert_invalid(
+        MemoryConfig { initial_pages: 1, data_base: 65535, ..MemoryConfig::default() },
+        "which is out of the initial pages");
+}
+
+#[test]
+fn shared_memory_with_maximum_is_valid() {
+    let wasm = build(MemoryConfig { shared: true, max_pages: Some(128), ..MemoryConfig::default() }).unwrap();
+
+    validate(&wasm).unwrap();
+}
+
+#[test]
+fn imported_memory_is_exported_only_by_name() {
+    let import = MemoryImport { module: "env".to_string(), name: "memory".to_string() };
+
+    let hidden = build(MemoryConfig { import: Some(import.clone()), export_name: None, ..MemoryConfig::default() }).unwrap();
+    let exported = build(MemoryConfig { import: Some(import), export_name: Some("memory".to_string()), ..MemoryConfig::default() }).unwrap();
+
+    validate(&hidden).unwrap();
+    assert!(!exports(&hidden).contains(&"memory".to_string()));
+    assert!(exports(&exported).contains(&"memory".to_string()));
+}
+
+#[test]
+fn data_starts_at_the_data_base() {
+    let wasm = build(MemoryConfig { data_base: 1024, ..MemoryConfig::default() }).unwrap();
+    let mut instance = Instance::new(&wasm).unwrap();
+
+    assert!(instance.call_i32("greet", &[]).unwrap() >= 1024);
+}
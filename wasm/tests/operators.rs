@@ -0,0 +1,121 @@
+mod utils;
+
+use utils::{build, validate, Instance};
+
+const OPERATORS: &str = "
+class Vec2 {
+    x Int
+    y Int
+
+    func init(x Int, y Int) {
+        this.x = x
+        this.y = y
+    }
+
+    func add(other Vec2) Vec2 {
+        return Vec2(this.x + other.x, this.y + other.y)
+    }
+
+    func mul(factor Int) Vec2 {
+        return Vec2(this.x * factor, this.y * factor)
+    }
+
+    func eq(other Vec2) Bool {
+        if this.x != other.x {
+            return false
+        }
+        return this.y == other.y
+    }
+
+    func cmp(other Vec2) Int {
+        return ((this.x * this.x) + (this.y * this.y)) - ((other.x * other.x) + (other.y * other.y))
+    }
+}
+
+class Scaled : Vec2 {
+    func init(x Int, y Int) {
+        super.init(x, y)
+    }
+
+    func mul(factor Int) Vec2 {
+        return Vec2(this.x * (factor * 10), this.y * (factor * 10))
+    }
+}
+
+class Handle {
+    id Int
+
+    func init(id Int) {
+        this.id = id
+    }
+}
+
+func bit(value Bool) Int {
+    if value {
+        return 1
+    }
+    return 0
+}
+
+@Export
+func testMath() Int {
+    var sum = Vec2(1, 2) + (Vec2(3, 4) * 2)
+    return (sum.x * 100) + sum.y
+}
+
+@Export
+func testOverride() Int {
+    var v Vec2 = Scaled(1, 2)
+    var scaled = v * 2
+    return (scaled.x * 100) + scaled.y
+}
+
+@Export
+func testEquality() Int {
+    var a = Vec2(1, 2)
+    var b = Vec2(1, 2)
+    var c = Vec2(2, 1)
+    return (bit(a == b) * 100) + ((bit(a != c) * 10) + bit(a != b))
+}
+
+@Export
+func testOrdering() Int {
+    var small = Vec2(1, 1)
+    var big = Vec2(3, 4)
+    return (bit(small < big) * 1000) + ((bit(big >= small) * 100) + ((bit(small > big) * 10) + bit(small <= Vec2(1, 1))))
+}
+
+@Export
+func testReference() Int {
+    var a = Handle(1)
+    var b = Handle(1)
+    var same = a
+    return (bit(a == same) * 10) + bit(a == b)
+}
+";
+
+#[test]
+fn math_operators_call_the_methods() {
+    let wasm = build(OPERATORS).unwrap();
+    validate(&wasm).unwrap();
+
+    let mut instance = Instance::new(&wasm).unwrap();
+
+    assert_eq!(instance.call_i32("testMath", &[]).unwrap(), 710);
+    assert_eq!(instance.call_i32("testOverride", &[]).unwrap(), 2040);
+}
+
+#[test]
+fn comparisons_call_eq_and_cmp() {
+    let mut instance = Instance::new(&build(OPERATORS).unwrap()).unwrap();
+
+    assert_eq!(instance.call_i32("testEquality", &[]).unwrap(), 110);
+    assert_eq!(instance.call_i32("testOrdering", &[]).unwrap(), 1101);
+}
+
+#[test]
+fn objects_without_eq_are_compared_by_reference() {
+    let mut instance = Instance::new(&build(OPERATORS).unwrap()).unwrap();
+
+    assert_eq!(instance.call_i32("testReference", &[]).unwrap(), 10);
+}
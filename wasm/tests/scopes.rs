@@ -0,0 +1,108 @@
+mod utils;
+
+use utils::{build, validate, Instance};
+
+const SCOPES: &str = "
+class Box {
+    value Int
+
+    func init(value Int) {
+        this.value = value
+    }
+}
+
+@Export
+func testShadowInIf(flag Bool) Int {
+    var x = 1
+    if flag {
+        var x = 10
+        x = x + 5
+    }
+    return x
+}
+
+@Export
+func testShadowInLoop() Int {
+    var total = 0
+    var i = 0
+    while i < 3 {
+        var total = 100
+        i = i + 1
+    }
+    return total + i
+}
+
+@Export
+func testBlockLocals() Int {
+    var sum = 0
+    var i = 0
+    while i < 2 {
+        var a = i + 1
+        sum = sum + a
+        i = i + 1
+    }
+    if sum > 0 {
+        var b = 20
+        sum = sum + b
+    }
+    return sum
+}
+
+@Export
+func testShadowType() Float {
+    var value = 2
+    if value > 1 {
+        var value = 0.5
+        return value
+    }
+    return 1.5
+}
+
+@Export
+func testAllocations() Int {
+    var boxes = [Box(1), Box(2), Box(3)]
+    var first = Box(Box(4).value + Box(5).value)
+    return (boxes[0].value + boxes[2].value) + first.value
+}
+";
+
+#[test]
+fn inner_declarations_shadow_the_outer_ones() {
+    let wasm = build(SCOPES).unwrap();
+    validate(&wasm).unwrap();
+
+    let mut instance = Instance::new(&wasm).unwrap();
+
+    assert_eq!(instance.call_i32("testShadowInIf", &[1]).unwrap(), 1);
+    assert_eq!(instance.call_i32("testShadowInIf", &[0]).unwrap(), 1);
+    assert_eq!(instance.call_i32("testShadowInLoop", &[]).unwrap(), 3);
+    assert!(matches!(instance.call("testShadowType", &[]).unwrap()[0], wasmi::Val::F32(value) if value.to_float() == 0.5));
+}
+
+#[test]
+fn block_locals_are_independent() {
+    let mut instance = Instance::new(&build(SCOPES).unwrap()).unwrap();
+
+    assert_eq!(instance.call_i32("testBlockLocals", &[]).unwrap(), 23);
+}
+
+#[test]
+fn allocations_in_the_same_function_are_distinct() {
+    let mut instance = Instance::new(&build(SCOPES).unwrap()).unwrap();
+
+    assert_eq!(instance.call_i32("testAllocations", &[]).unwrap(), 13);
+}
+
+#[test]
+fn variables_are_not_visible_outside_their_block() {
+    let err = build("
+func leak(flag Bool) Int {
+    if flag {
+        var inner = 1
+    }
+    return inner
+}
+").err().unwrap();
+
+    assert!(err.to_string().contains("not found"), "{}", err);
+}
@@ -0,0 +1,131 @@
+mod utils;
+
+use utils::{build, build_project, validate, Instance};
+
+const STATICS: &str = "
+const BASE Int = 10 * 4
+const SCALE Float = 0.5
+
+class Counter {
+    const START Int = BASE + 2
+
+    count Int
+
+    func init(count Int) {
+        this.count = count
+    }
+
+    static func zero() Counter {
+        return Counter(0)
+    }
+
+    static func twice(n Int) Int {
+        return n * 2
+    }
+}
+
+class Child : Counter {
+    func init(count Int) {
+        super.init(count)
+    }
+}
+
+enum Direction {
+    Up,
+    Down,
+
+    const COUNT Int = 2
+
+    func sign() Int {
+        if this == Direction.Up {
+            return 1
+        }
+        return 0 - 1
+    }
+
+    static func opposite(direction Direction) Direction {
+        if direction == Direction.Up {
+            return Direction.Down
+        }
+        return Direction.Up
+    }
+}
+
+@Export
+func testConstants() Int {
+    return (BASE + Counter.START) + Direction.COUNT
+}
+
+@Export
+func testFloatConstant() Float {
+    return SCALE * 3.0
+}
+
+@Export
+func testStaticMethods() Int {
+    var counter = Counter.zero()
+    return counter.count + Counter.twice(21)
+}
+
+@Export
+func testInherited() Int {
+    return Child.twice(Child.START)
+}
+
+@Export
+func testEnumMethods() Int {
+    var up = Direction.Up
+    return (up.sign() * 10) + Direction.opposite(up).sign()
+}
+";
+
+#[test]
+fn constants_are_inlined() {
+    let wasm = build(STATICS).unwrap();
+    validate(&wasm).unwrap();
+
+    let mut instance = Instance::new(&wasm).unwrap();
+
+    assert_eq!(instance.call_i32("testConstants", &[]).unwrap(), 84);
+    assert!(matches!(instance.call("testFloatConstant", &[]).unwrap()[0], wasmi::Val::F32(value) if value.to_float() == 1.5));
+}
+
+#[test]
+fn static_methods_are_called_on_the_type() {
+    let mut instance = Instance::new(&build(STATICS).unwrap()).unwrap();
+
+    assert_eq!(instance.call_i32("testStaticMethods", &[]).unwrap(), 42);
+    assert_eq!(instance.call_i32("testInherited", &[]).unwrap(), 84);
+}
+
+#[test]
+fn enum_methods_get_the_value_as_this() {
+    let mut instance = Instance::new(&build(STATICS).unwrap()).unwrap();
+
+    assert_eq!(instance.call_i32("testEnumMethods", &[]).unwrap(), 9);
+}
+
+#[test]
+fn public_members_are_accessed_from_other_modules() {
+    let wasm = build_project(&[
+        ("units.rn", "
+pub class Units {
+    pub const METER Int = 100
+
+    pub static func km(n Int) Int {
+        return n * 1000
+    }
+}
+"),
+        ("main.rn", "
+import \"units\"
+
+@Export
+func distance() Int {
+    return Units.km(2) + Units.METER
+}
+"),
+    ]).unwrap();
+
+    assert_eq!(Instance::new(&wasm).unwrap().call_i32("distance", &[]).unwrap(), 2100);
+}
@@ -0,0 +1,86 @@
+mod utils;
+
+use wasm::engine::BuildTarget;
+use utils::{engine, load, validate, Sources};
+
+const GEO: &str = "
+pub func area(side Int) Int {
+    return side * side
+}
+
+pub func perimeter(side Int) Int {
+    return side * 4
+}
+";
+
+const EXTRA: &str = "
+import func extraLog(n Int)
+
+pub func extra() Int {
+    extraLog(1)
+    return 1
+}
+";
+
+const MAIN: &str = "
+import \"geo\"
+
+import func log(n Int)
+import func unusedLog(n Int)
+
+func helper(n Int) Int {
+    log(n)
+    return area(n)
+}
+
+func unused(n Int) Int {
+    unusedLog(n)
+    return perimeter(n)
+}
+
+@Export
+func square(n Int) Int {
+    return helper(n)
+}
+";
+
+/// Builds `main` after loading `extra`, which `main` doesn't import
+fn build_with_extra() -> (Vec<u8>, String) {
+    let mut engine = engine(BuildTarget::Host);
+    let files = [("geo.rn", GEO), ("extra.rn", EXTRA), ("main.rn", MAIN)];
+
+    core::Engine::load_module(&mut engine, "extra", &Sources::new(&files)).unwrap();
+
+    let module = load(&mut engine, &files).unwrap();
+    let wasm = core::EngineBuildSource::build_module_source(&engine, module).unwrap();
+
+    (wasm, engine.build_module_text(module).unwrap())
+}
+
+#[test]
+fn reachable_functions_are_built() {
+    let (wasm, text) = build_with_extra();
+    validate(&wasm).unwrap();
+
+    assert!(text.contains("(func $main::square "));
+    assert!(text.contains("(func $main::helper "));
+    assert!(text.contains("(func $geo::area "));
+    assert!(text.contains("(import \"main\" \"log\" "));
+}
+
+#[test]
+fn unreachable_functions_are_dropped() {
+    let (_, text) = build_with_extra();
+
+    assert!(!text.contains("$main::unused"));
+    assert!(!text.contains("$geo::perimeter"));
+}
+
+#[test]
+fn unused_modules_and_imports_are_dropped() {
+    let (_, text) = build_with_extra();
+
+    assert!(!text.contains("$extra::extra"));
+    assert!(!text.contains("extraLog"));
+    assert!(!text.contains("unusedLog"));
+}
@@ -0,0 +1,130 @@
+mod utils;
+
+use wasmi::Val;
+use utils::{build, validate, Instance};
+
+const TUPLES: &str = "
+data class Vec2 {
+    x Int
+    y Int
+}
+
+func divmod(a Int, b Int) (Int, Int) {
+    return (a - b, a % b)
+}
+
+func mixed(n Int) (Float, Int, Bool) {
+    return (n * 1.5, n + 1, n > 2)
+}
+
+func swap(t (Int, Int)) (Int, Int) {
+    var (a, b) = t
+    return (b, a)
+}
+
+func pair(n Int) (Vec2, Int) {
+    var v = Vec2()
+    v.x = n
+    v.y = n * 2
+    return (v, n * 3)
+}
+
+@Export
+func testDivmod() Int {
+    var (q, r) = divmod(17, 5)
+    return (q * 10) + r
+}
+
+@Export
+func testMixed() Float {
+    var (f, i, b) = mixed(3)
+    if b {
+        return f + i
+    }
+    return 0.0
+}
+
+@Export
+func testSwap() Int {
+    var t = (1, 2)
+    var (a, b) = swap(t)
+    return (a * 10) + b
+}
+
+@Export
+func testNested() Int {
+    var (v, k) = pair(4)
+    return v.x + (v.y + k)
+}
+
+@Export
+func testTyped() Int {
+    var (a, b) (Int, Int) = (5, 6)
+    var t (Int, Int) = (a, b)
+    var (c, d) = t
+    return c * d
+}
+
+@Export
+func testExport(n Int) (Int, Float) {
+    return (n + 1, n * 0.5)
+}
+
+
+class Holder {
+    pair (Int, Float)
+
+    func init() {
+        this.pair = (7, 0.5)
+    }
+}
+
+@Export
+func testMemory() Float {
+    var v = [(1, 2.5), (3, 4.5)]
+    var (a, b) = v[1]
+    var h = Holder()
+    var (c, d) = h.pair
+    h.pair = (a, b)
+    var (e, f) = h.pair
+    return (a + b) + ((c + d) + (e + f))
+}
+";
+
+fn as_f32(value: &Val) -> f32 {
+    match value {
+        Val::F32(value) => value.to_float(),
+        value => panic!("{:?} is not a float", value),
+    }
+}
+
+#[test]
+fn tuples_are_destructured() {
+    let wasm = build(TUPLES).unwrap();
+    validate(&wasm).unwrap();
+
+    let mut instance = Instance::new(&wasm).unwrap();
+
+    assert_eq!(instance.call_i32("testDivmod", &[]).unwrap(), 122);
+    assert_eq!(instance.call_i32("testSwap", &[]).unwrap(), 21);
+    assert_eq!(instance.call_i32("testNested", &[]).unwrap(), 24);
+    assert_eq!(instance.call_i32("testTyped", &[]).unwrap(), 30);
+    assert_eq!(as_f32(&instance.call("testMixed", &[]).unwrap()[0]), 8.5);
+}
+
+#[test]
+fn tuples_are_returned_as_multiple_values() {
+    let mut instance = Instance::new(&build(TUPLES).unwrap()).unwrap();
+
+    let results = instance.call("testExport", &[Val::I32(1)]).unwrap();
+    assert!(matches!(results.as_slice(), [Val::I32(2), Val::F32(_)]));
+    assert_eq!(as_f32(&results[1]), 0.5);
+}
+
+#[test]
+fn tuples_are_stored_in_memory() {
+    let mut instance = Instance::new(&build(TUPLES).unwrap()).unwrap();
+
+    // Elements of vectors and fields of objects
+    assert_eq!(as_f32(&instance.call("testMemory", &[]).unwrap()[0]), 22.5);
+}
@@ -0,0 +1,218 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use core::{Engine, parser::ModuleImporter};
+use core::reexport::anyhow::{anyhow, Result};
+use common::constants::{CORE_MODULE_ID, STD_MODULE_ID};
+use common::module::{ModuleIdentifier, ModuleUID};
+use wasm::engine::{BuildTarget, WasmEngine};
+use wasmi::{Caller, Linker, Memory, Module, Store, Val};
+
+/// The modules of a project, `main.rn` is the main module and `name.d.rn` the declaration module `name`
+pub struct Sources(HashMap<String, String>);
+
+impl Sources {
+    pub fn new(files: &[(&str, &str)]) -> Self {
+        Self(files.iter().map(|(name, source)| (name.to_string(), source.to_string())).collect())
+    }
+}
+
+impl ModuleImporter for Sources {
+    fn get_unique_identifier(&self, identifier: &ModuleIdentifier) -> Option<ModuleUID> {
+        Some(ModuleUID::from_string(identifier.0.clone()))
+    }
+
+    fn load_module(&self, identifier: &ModuleIdentifier) -> Option<String> {
+        self.0.get(&format!("{}.rn", identifier.0)).cloned()
+    }
+
+    fn load_declaration_module(&self, identifier: &ModuleIdentifier) -> Option<String> {
+        self.0.get(&format!("{}.d.rn", identifier.0)).cloned()
+    }
+}
+
+/// Creates an engine for the target with the core library loaded, like portal does
+pub fn engine(target: BuildTarget) -> WasmEngine {
+    let mut engine = WasmEngine::new();
+    engine.set_target(target);
+
+    let sources = Sources(HashMap::new());
+
+    engine.module_loader()
+        .load_module_with_source(
+            ModuleIdentifier(CORE_MODULE_ID.to_string()),
+            ModuleUID::from_string(CORE_MODULE_ID.to_string()),
+            &include_str!("../../../core_lib/lib.rn").to_string(),
+            &sources,
+        )
+        .unwrap();
+
+    if let BuildTarget::Wasi = target {
+        engine.module_loader()
+            .load_declaration_module_with_source(
+                ModuleIdentifier(STD_MODULE_ID.to_string()),
+                ModuleUID::from_string(STD_MODULE_ID.to_string()),
+                STD_MODULE_ID.to_string(),
+                &include_str!("../../../core_lib/std.d.rn").to_string(),
+                &sources,
+            )
+            .unwrap();
+    }
+
+    engine
+}
+
+/// Loads the modules of a project, the declaration modules are imported from the host module of the same name
+pub fn load(engine: &mut WasmEngine, files: &[(&str, &str)]) -> Result<ModuleUID> {
+    let sources = Sources::new(files);
+
+    for (name, _) in files {
+        if let Some(declaration) = name.strip_suffix(".d.rn") {
+            engine.load_declaration_module(declaration, declaration, &sources)?;
+        }
+    }
+
+    engine.load_module("main", &sources)
+}
+
+/// Builds a single module for the host
+pub fn build(source: &str) -> Result<Vec<u8>> {
+    build_project(&[("main.rn", source)])
+}
+
+/// Builds the module `main` of a project for the host
+pub fn build_project(files: &[(&str, &str)]) -> Result<Vec<u8>> {
+    let mut engine = engine(BuildTarget::Host);
+    let module = load(&mut engine, files)?;
+
+    Ok(core::EngineBuildSource::build_module_source(&engine, module)?)
+}
+
+/// Checks the module with the validator of the WebAssembly specification
+pub fn validate(wasm: &[u8]) -> Result<()> {
+    wasmparser::Validator::new_with_features(wasmparser::WasmFeatures::all()).validate_all(wasm)?;
+
+    Ok(())
+}
+
+/// The names of the exports of the module, in order
+pub fn exports(wasm: &[u8]) -> Vec<String> {
+    let mut names = Vec::new();
+
+    for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+        if let Ok(wasmparser::Payload::ExportSection(exports)) = payload {
+            for export in exports {
+                names.push(export.unwrap().name.to_string());
+            }
+        }
+    }
+
+    names
+}
+
+/// An instance of a built module. The imported functions trap when called, except `fd_write` and `proc_exit`
+/// of WASI. An imported memory is created with every byte set to `memory_fill`
+pub struct Instance {
+    /// What the module wrote to the file descriptors
+    store: Store<Vec<u8>>,
+    instance: wasmi::Instance,
+}
+
+impl Instance {
+    pub fn new(wasm: &[u8]) -> Result<Self> {
+        Self::with_memory_fill(wasm, 0)
+    }
+
+    pub fn with_memory_fill(wasm: &[u8], memory_fill: u8) -> Result<Self> {
+        let engine = wasmi::Engine::default();
+        let module = Module::new(&engine, wasm).map_err(|e| anyhow!("{}", e))?;
+        let mut store = Store::new(&engine, Vec::new());
+        let mut linker = Linker::<Vec<u8>>::new(&engine);
+
+        for import in module.imports() {
+            let (module_name, name) = (import.module().to_string(), import.name().to_string());
+
+            if let Some(func_type) = import.ty().func() {
+                match (module_name.as_str(), name.as_str()) {
+                    ("wasi_snapshot_preview1", "fd_write") => linker.func_new(&module_name, &name, func_type.clone(), fd_write),
+                    ("wasi_snapshot_preview1", "proc_exit") => linker.func_new(&module_name, &name, func_type.clone(), |_, params, _| {
+                        Err(wasmi::Error::i32_exit(params[0].i32().unwrap_or_default()))
+                    }),
+                    _ => {
+                        let message = format!("{}.{} was called", module_name, name);
+                        linker.func_new(&module_name, &name, func_type.clone(), move |_, _, _| Err(wasmi::Error::new(message.clone())))
+                    },
+                }.map_err(|e| anyhow!("{}", e))?;
+            } else if let Some(memory_type) = import.ty().memory() {
+                let memory = Memory::new(&mut store, *memory_type).map_err(|e| anyhow!("{}", e))?;
+                memory.data_mut(&mut store).fill(memory_fill);
+
+                linker.define(&module_name, &name, memory).map_err(|e| anyhow!("{}", e))?;
+            }
+        }
+
+        let instance = linker.instantiate(&mut store, &module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(|e| anyhow!("{}", e))?;
+
+        Ok(Self { store, instance })
+    }
+
+    /// Runs a WASI command and returns its exit code
+    pub fn start(&mut self) -> Result<i32> {
+        match self.call("_start", &[]) {
+            Ok(_) => Ok(0),
+            Err(error) => match error.downcast::<wasmi::Error>()?.i32_exit_status() {
+                Some(status) => Ok(status),
+                None => Err(anyhow!("`_start` trapped")),
+            },
+        }
+    }
+
+    pub fn output(&self) -> String {
+        String::from_utf8_lossy(self.store.data()).to_string()
+    }
+
+    pub fn call(&mut self, name: &str, args: &[Val]) -> Result<Vec<Val>> {
+        let func = self.instance.get_func(&self.store, name).ok_or(anyhow!("No export `{}`", name))?;
+        let mut results = vec![Val::I32(0); func.ty(&self.store).results().len()];
+
+        func.call(&mut self.store, args, &mut results)?;
+
+        Ok(results)
+    }
+
+    pub fn call_i32(&mut self, name: &str, args: &[i32]) -> Result<i32> {
+        let args: Vec<_> = args.iter().map(|arg| Val::I32(*arg)).collect();
+
+        match self.call(name, &args)?.as_slice() {
+            [Val::I32(result)] => Ok(*result),
+            results => Err(anyhow!("`{}` returned {:?}", name, results)),
+        }
+    }
+}
+
+/// Appends the buffers to the output whatever the file descriptor
+fn fd_write(mut caller: Caller<'_, Vec<u8>>, params: &[Val], results: &mut [Val]) -> Result<(), wasmi::Error> {
+    let memory = caller.get_export("memory")
+        .and_then(|export| export.into_memory())
+        .ok_or(wasmi::Error::new("The memory is not exported"))?;
+
+    let arg = |i: usize| params[i].i32().unwrap_or_default() as usize;
+    let (iovs, iovs_len, written_ptr) = (arg(1), arg(2), arg(3));
+    let read_u32 = |data: &[u8], address: usize| u32::from_le_bytes(data[address..address + 4].try_into().unwrap()) as usize;
+
+    let mut written = Vec::new();
+    let data = memory.data(&caller);
+
+    for i in 0..iovs_len {
+        let (buffer, length) = (read_u32(data, iovs + i * 8), read_u32(data, iovs + i * 8 + 4));
+        written.extend_from_slice(&data[buffer..buffer + length]);
+    }
+
+    memory.data_mut(&mut caller)[written_ptr..written_ptr + 4].copy_from_slice(&(written.len() as u32).to_le_bytes());
+    caller.data_mut().extend(written);
+    results[0] = Val::I32(0);
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+mod utils;
+
+use common::errors::{BuildErrorKind, LangError};
+use wasm::engine::BuildTarget;
+use utils::{engine, load, validate, Instance};
+
+fn build_wasi(source: &str) -> core::reexport::anyhow::Result<Vec<u8>> {
+    let mut engine = engine(BuildTarget::Wasi);
+    let module = load(&mut engine, &[("main.rn", source)])?;
+
+    Ok(core::EngineBuildSource::build_module_source(&engine, module)?)
+}
+
+#[test]
+fn main_is_run_by_start() {
+    let wasm = build_wasi("
+import \"std\"
+
+pub func main() Int {
+    print(\"hello\")
+    return 3
+}
+").unwrap();
+
+    validate(&wasm).unwrap();
+
+    let mut instance = Instance::new(&wasm).unwrap();
+    assert_eq!(instance.start().unwrap(), 3);
+    assert_eq!(instance.output(), "hello\n");
+}
+
+#[test]
+fn wasm_code_is_kept_for_wasi() {
+    let wasm = build_wasi("
+import \"std\"
+
+@Cfg(target = \"wasm\")
+func platform() String {
+    return \"wasm\"
+}
+
+pub func main() Int {
+    print(platform())
+    @Cfg(target = \"wasi\")
+    print(\"wasi\")
+    @Cfg(target = \"interpreter\")
+    print(\"interpreter\")
+    return 0
+}
+").unwrap();
+
+    let mut instance = Instance::new(&wasm).unwrap();
+    assert_eq!(instance.start().unwrap(), 0);
+    assert_eq!(instance.output(), "wasm\nwasi\n");
+}
+
+#[test]
+fn functions_exported_as_the_memory_are_rejected() {
+    let error = build_wasi("
+import \"std\"
+
+@Export(\"memory\")
+func a() Int {
+    return 1
+}
+
+pub func main() Int {
+    return 0
+}
+").unwrap_err();
+
+    assert!(matches!(error.downcast::<LangError>(), Ok(LangError::Build { kind: BuildErrorKind::DuplicateExport(name) }) if name == "memory"));
+}
@@ -0,0 +1,66 @@
+mod utils;
+
+use wasm::engine::BuildTarget;
+use utils::{engine, load};
+
+const GEO: &str = "
+pub func area(side Int) Int {
+    return side * side
+}
+";
+
+const MAIN: &str = "
+import \"geo\"
+
+@Export
+func greet(name String) String {
+    return \"hello\"
+}
+
+@Export
+func square(n Int) Int {
+    return area(n)
+}
+";
+
+fn text(files: &[(&str, &str)]) -> String {
+    let mut engine = engine(BuildTarget::Host);
+    let module = load(&mut engine, files).unwrap();
+
+    engine.build_module_text(module).unwrap()
+}
+
+/// The line before the first line starting with `start`
+fn annotation<'a>(text: &'a str, start: &str) -> &'a str {
+    let lines: Vec<&str> = text.lines().map(|line| line.trim()).collect();
+    let index = lines.iter().position(|line| line.starts_with(start)).unwrap_or_else(|| panic!("No line starts with {}", start));
+
+    lines[index - 1]
+}
+
+#[test]
+fn text_is_a_module() {
+    let text = text(&[("geo.rn", GEO), ("main.rn", MAIN)]);
+
+    assert!(text.starts_with("(module"));
+    assert!(text.contains("(export \"greet\" (func $main::greet))"));
+    assert!(text.contains("(export \"square\" (func $main::square))"));
+}
+
+#[test]
+fn functions_are_annotated_with_their_module() {
+    let text = text(&[("geo.rn", GEO), ("main.rn", MAIN)]);
+
+    assert_eq!(annotation(&text, "(func $main::greet "), ";; greet from module \"main\"");
+    assert_eq!(annotation(&text, "(func $geo::area "), ";; area from module \"geo\"");
+    assert!(text.contains("(param $side i32) (result i32)"));
+}
+
+#[test]
+fn data_segments_are_annotated_with_their_string() {
+    let text = text(&[("geo.rn", GEO), ("main.rn", MAIN)]);
+    let data = text.lines().find(|line| line.contains("hello\")")).unwrap().trim();
+
+    assert!(data.starts_with("(data "));
+    assert_eq!(annotation(&text, data), ";; string \"hello\"");
+}